@@ -0,0 +1,130 @@
+use super::Backend;
+use bytes::Bytes;
+use dashmap::DashMap;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use tokio::sync::Notify;
+
+/// The registry of the clients blocked on keys, e.g. by `BLPOP`, in the order they
+/// blocked.
+#[derive(Debug, Default)]
+pub(crate) struct Waiters {
+    queues: DashMap<Bytes, VecDeque<Arc<Notify>>>,
+}
+
+/// A client waiting for keys to become ready, unregistered when dropped.
+///
+/// Only the oldest waiter of a key is woken when the key becomes ready. A waiter leaving
+/// while the key is still ready, because it was served, timed out or its client
+/// disconnected, hands the key over to the next waiter, so every waiter is eventually woken
+/// as long as there is something left for it.
+#[derive(Debug)]
+pub struct KeyWaiter {
+    backend: Backend,
+    notify: Arc<Notify>,
+    keys: Vec<Bytes>,
+}
+
+impl Backend {
+    /// Registers a waiter on the given keys, behind the waiters already registered.
+    ///
+    /// The waiter must be registered before checking whether the keys are ready, so that a
+    /// key becoming ready in between is not missed.
+    pub fn wait_for_keys(&self, keys: &[Bytes]) -> KeyWaiter {
+        let notify = Arc::new(Notify::new());
+        let mut keys = keys.to_vec();
+        keys.sort_unstable();
+        keys.dedup();
+        for key in &keys {
+            self.waiters
+                .queues
+                .entry(key.clone())
+                .or_default()
+                .push_back(notify.clone());
+        }
+        KeyWaiter {
+            backend: self.clone(),
+            notify,
+            keys,
+        }
+    }
+
+    /// Wakes the oldest client blocked on `key`, if any.
+    ///
+    /// Called whenever a value is created at `key`.
+    pub(crate) fn signal_key_ready(&self, key: &[u8]) {
+        if let Some(queue) = self.waiters.queues.get(key) {
+            if let Some(notify) = queue.front() {
+                notify.notify_one();
+            }
+        }
+    }
+
+    /// Wakes every client blocked on `key`.
+    ///
+    /// Called whenever entries are added to a stream: reading a stream doesn't consume the
+    /// entries, so all of its readers may be served. Also called when a consumer group is
+    /// destroyed, so that its blocked readers get an error.
+    pub(crate) fn signal_key_updated(&self, key: &[u8]) {
+        if let Some(queue) = self.waiters.queues.get(key) {
+            for notify in queue.iter() {
+                notify.notify_one();
+            }
+        }
+    }
+}
+
+impl KeyWaiter {
+    /// Waits until one of the keys may be ready.
+    ///
+    /// A wake-up is only a hint: the key may have been taken by another client meanwhile,
+    /// so the caller checks the keys again and keeps waiting if needed.
+    pub async fn ready(&self) {
+        self.notify.notified().await
+    }
+}
+
+impl Drop for KeyWaiter {
+    fn drop(&mut self) {
+        let queues = &self.backend.waiters.queues;
+        for key in &self.keys {
+            if let Some(mut queue) = queues.get_mut(key) {
+                queue.retain(|notify| !Arc::ptr_eq(notify, &self.notify));
+            }
+            queues.remove_if(key, |_, queue| queue.is_empty());
+            if self.backend.contains_key(key) {
+                self.backend.signal_key_ready(key);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tokio::time::timeout;
+
+    #[tokio::test]
+    async fn test_waiters_are_woken_in_order() {
+        let backend = Backend::new();
+        let first = backend.wait_for_keys(&["list".into()]);
+        let second = backend.wait_for_keys(&["list".into(), "other".into()]);
+        backend.signal_key_ready(b"list");
+        timeout(Duration::from_secs(5), first.ready())
+            .await
+            .unwrap();
+        assert!(timeout(Duration::from_millis(20), second.ready())
+            .await
+            .is_err());
+
+        // the first waiter leaves while the key is ready, so the second one takes over
+        backend.set("list".into(), "value".into());
+        drop(first);
+        timeout(Duration::from_secs(5), second.ready())
+            .await
+            .unwrap();
+        drop(second);
+        assert!(backend.waiters.queues.is_empty());
+    }
+}
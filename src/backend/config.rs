@@ -0,0 +1,94 @@
+use super::{Backend, KeyspaceEvents};
+use crate::cmd::CommandError;
+use crate::glob::glob_match;
+
+/// The parameters readable with `CONFIG GET` and writable with `CONFIG SET`.
+const PARAMETERS: &[&str] = &["notify-keyspace-events"];
+
+impl Backend {
+    /// Returns the parameters matching the glob-style `pattern` and their values, sorted by
+    /// name.
+    pub fn config_get(&self, pattern: &[u8]) -> Vec<(&'static str, String)> {
+        let pattern = pattern.to_ascii_lowercase();
+        PARAMETERS
+            .iter()
+            .filter(|name| glob_match(&pattern, name.as_bytes()))
+            .map(|&name| (name, self.config_value(name)))
+            .collect()
+    }
+
+    fn config_value(&self, name: &str) -> String {
+        match name {
+            "notify-keyspace-events" => self.notifications.get().to_string(),
+            _ => unreachable!("unknown parameter {name}"),
+        }
+    }
+
+    /// Sets the parameters to the given values, all of them or none if one is invalid.
+    pub fn config_set(&self, params: &[(Vec<u8>, Vec<u8>)]) -> Result<(), CommandError> {
+        let mut events = None;
+        for (name, value) in params {
+            match name.to_ascii_lowercase().as_slice() {
+                b"notify-keyspace-events" => {
+                    events = Some(KeyspaceEvents::parse(value).ok_or_else(|| {
+                        CommandError::InvalidArguments(
+                            "CONFIG SET failed (possibly related to argument \
+                             'notify-keyspace-events') - Invalid event class character. Use \
+                             'Ag$lshzxeKEtmn'."
+                                .to_string(),
+                        )
+                    })?);
+                }
+                _ => {
+                    return Err(CommandError::InvalidArguments(format!(
+                        "Unknown option or number of arguments for CONFIG SET - '{}'",
+                        String::from_utf8_lossy(name)
+                    )))
+                }
+            }
+        }
+        if let Some(events) = events {
+            self.notifications.set(events);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config() {
+        let backend = Backend::new();
+        assert_eq!(
+            backend.config_get(b"notify-*"),
+            vec![("notify-keyspace-events", String::new())]
+        );
+        assert!(backend.config_get(b"maxmemory").is_empty());
+
+        backend
+            .config_set(&[(b"Notify-Keyspace-Events".to_vec(), b"Ex".to_vec())])
+            .unwrap();
+        assert_eq!(
+            backend.config_get(b"NOTIFY-KEYSPACE-EVENTS"),
+            vec![("notify-keyspace-events", "xE".to_string())]
+        );
+
+        // nothing is set if a parameter is invalid
+        let err = backend
+            .config_set(&[
+                (b"notify-keyspace-events".to_vec(), b"KA".to_vec()),
+                (b"maxmemory".to_vec(), b"1".to_vec()),
+            ])
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "ERR Unknown option or number of arguments for CONFIG SET - 'maxmemory'"
+        );
+        assert!(backend
+            .config_set(&[(b"notify-keyspace-events".to_vec(), b"K?".to_vec())])
+            .is_err());
+        assert_eq!(backend.config_get(b"*")[0].1, "xE".to_string());
+    }
+}
@@ -0,0 +1,233 @@
+use super::scan::KeyspaceOrder;
+use super::{Backend, EventClass};
+use bytes::Bytes;
+use dashmap::{mapref::entry::Entry, DashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::task::JoinHandle;
+
+/// How often the active expiry task wakes up.
+const ACTIVE_EXPIRE_INTERVAL: Duration = Duration::from_millis(100);
+/// Number of keys with an expiry sampled per active expiry round.
+const ACTIVE_EXPIRE_SAMPLE: usize = 20;
+/// A round is repeated while more than this many sampled keys were expired.
+const ACTIVE_EXPIRE_REPEAT_THRESHOLD: usize = ACTIVE_EXPIRE_SAMPLE / 4;
+/// Upper bound of rounds per tick, so a burst of expiring keys can't starve the runtime.
+const ACTIVE_EXPIRE_MAX_ROUNDS: usize = 16;
+
+/// The expiry state of a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyExpiry {
+    /// The key does not exist.
+    NotFound,
+    /// The key exists and has no associated expiry.
+    Persistent,
+    /// The key exists and expires at the given unix time in milliseconds.
+    ExpiresAt(u64),
+}
+
+/// Condition under which `EXPIRE` and friends are allowed to change a key's expiry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExpireCondition {
+    #[default]
+    Always,
+    /// Only set the expiry when the key has none.
+    Nx,
+    /// Only set the expiry when the key already has one.
+    Xx,
+    /// Only set the expiry when it is greater than the current one.
+    Gt,
+    /// Only set the expiry when it is less than the current one.
+    Lt,
+}
+
+/// The expiry index: the unix time in milliseconds at which each volatile key expires.
+///
+/// The volatile keys are also kept in hash order, so that the active expiry task walks them
+/// with a cursor, a few per round, instead of scanning the whole index.
+#[derive(Debug, Default)]
+pub(crate) struct Expires {
+    times: DashMap<Bytes, u64>,
+    order: KeyspaceOrder,
+    /// Where the next active expiry round resumes the walk.
+    cursor: AtomicU64,
+}
+
+impl Expires {
+    /// Returns the expiry of a key, `None` if it is persistent.
+    pub(crate) fn get(&self, key: &[u8]) -> Option<u64> {
+        self.times.get(key).map(|at| *at)
+    }
+
+    /// Sets the expiry of a key.
+    pub(crate) fn insert(&self, key: Bytes, at: u64) {
+        match self.times.entry(key) {
+            Entry::Occupied(mut entry) => {
+                entry.insert(at);
+            }
+            Entry::Vacant(entry) => {
+                self.order.insert(entry.key());
+                entry.insert(at);
+            }
+        }
+    }
+
+    /// Removes the expiry of a key and returns it.
+    pub(crate) fn remove(&self, key: &[u8]) -> Option<u64> {
+        self.remove_if(key, |_| true)
+    }
+
+    /// Removes the expiry of a key if `f` returns `true` for it, and returns it.
+    ///
+    /// The key leaves the hash order while its entry is held, so that it can't race with
+    /// an expiry being set again.
+    pub(crate) fn remove_if(&self, key: &[u8], f: impl FnOnce(u64) -> bool) -> Option<u64> {
+        self.times
+            .remove_if(key, |key, at| {
+                let remove = f(*at);
+                if remove {
+                    self.order.remove(key);
+                }
+                remove
+            })
+            .map(|(_, at)| at)
+    }
+
+    /// Returns about `count` volatile keys and their expiry, resuming the walk where the
+    /// previous call left it and starting over once every key was visited.
+    fn sample(&self, count: usize) -> Vec<(Bytes, u64)> {
+        let cursor = self.cursor.load(Ordering::Relaxed);
+        let (next, keys) = self.order.scan(cursor, count);
+        self.cursor.store(next, Ordering::Relaxed);
+        keys.into_iter()
+            .filter_map(|key| self.get(&key).map(|at| (key, at)))
+            .collect()
+    }
+}
+
+/// Returns the current unix time in milliseconds.
+pub(crate) fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+impl Backend {
+    /// Removes the key if its expiry is in the past.
+    ///
+    /// This is the lazy half of the expiry subsystem: every accessor calls it before looking
+    /// at the key, so an expired key is never observed even if the active expiry task has not
+    /// reached it yet. Returns `true` if the key was expired.
+    pub(crate) fn expire_if_needed(&self, key: &[u8]) -> bool {
+        let now = now_ms();
+        if !self.is_expired(key, now) {
+            return false;
+        }
+        let removed = self
+            .keyspace
+            .remove_if(key, |k, _| {
+                let expired = self.is_expired(k, now);
+                if expired {
+                    self.scan_order.remove(k);
+                }
+                expired
+            })
+            .is_some();
+        self.expires.remove_if(key, |at| at <= now);
+        if removed {
+            self.notify_keyspace_event(EventClass::Expired, "expired", key);
+        }
+        true
+    }
+
+    fn is_expired(&self, key: &[u8], now: u64) -> bool {
+        self.expires.get(key).is_some_and(|at| at <= now)
+    }
+
+    /// Sets the expiry of a key to the given unix time in milliseconds.
+    ///
+    /// Returns `false` if the key does not exist or the `condition` is not met. A time in the
+    /// past deletes the key right away, as Redis does.
+    pub fn expire_at(&self, key: &[u8], at: u64, condition: ExpireCondition) -> bool {
+        let current = match self.expiry(key) {
+            KeyExpiry::NotFound => return false,
+            KeyExpiry::Persistent => None,
+            KeyExpiry::ExpiresAt(at) => Some(at),
+        };
+        let allowed = match condition {
+            ExpireCondition::Always => true,
+            ExpireCondition::Nx => current.is_none(),
+            ExpireCondition::Xx => current.is_some(),
+            // a persistent key has an infinite ttl
+            ExpireCondition::Gt => current.is_some_and(|c| at > c),
+            ExpireCondition::Lt => current.is_none_or(|c| at < c),
+        };
+        if !allowed {
+            return false;
+        }
+        if at <= now_ms() {
+            self.remove(key);
+        } else {
+            self.expires.insert(Bytes::copy_from_slice(key), at);
+        }
+        true
+    }
+
+    /// Returns the expiry state of a key.
+    pub fn expiry(&self, key: &[u8]) -> KeyExpiry {
+        if !self.contains_key(key) {
+            return KeyExpiry::NotFound;
+        }
+        match self.expires.get(key) {
+            Some(at) => KeyExpiry::ExpiresAt(at),
+            None => KeyExpiry::Persistent,
+        }
+    }
+
+    /// Removes the expiry of a key, making it persistent.
+    ///
+    /// Returns `true` if the key existed and had an expiry.
+    pub fn persist(&self, key: &[u8]) -> bool {
+        self.contains_key(key) && self.expires.remove(key).is_some()
+    }
+
+    /// Spawns the active expiry task on the current tokio runtime.
+    ///
+    /// Lazy expiry alone never frees keys that are not accessed again, so this task
+    /// periodically samples keys with an expiry and removes the expired ones. Like Redis, a
+    /// round is repeated right away while more than a quarter of the sample was expired. The
+    /// task stops once every other handle to the backend has been dropped.
+    pub fn spawn_active_expire(&self) -> JoinHandle<()> {
+        let inner = Arc::downgrade(&self.0);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(ACTIVE_EXPIRE_INTERVAL);
+            loop {
+                interval.tick().await;
+                let Some(inner) = inner.upgrade() else {
+                    break;
+                };
+                let backend = Backend(inner);
+                for _ in 0..ACTIVE_EXPIRE_MAX_ROUNDS {
+                    if backend.active_expire_round() <= ACTIVE_EXPIRE_REPEAT_THRESHOLD {
+                        break;
+                    }
+                }
+            }
+        })
+    }
+
+    /// Samples about `ACTIVE_EXPIRE_SAMPLE` keys with an expiry, continuing the walk of the
+    /// expiry index where the previous round stopped, and removes the expired ones.
+    ///
+    /// Returns the number of keys removed.
+    pub(crate) fn active_expire_round(&self) -> usize {
+        let now = now_ms();
+        self.expires
+            .sample(ACTIVE_EXPIRE_SAMPLE)
+            .into_iter()
+            .filter(|(key, at)| *at <= now && self.expire_if_needed(key))
+            .count()
+    }
+}
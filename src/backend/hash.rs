@@ -0,0 +1,307 @@
+use super::scan::ScanOrder;
+use super::string::{format_float, parse_i64};
+use super::Backend;
+use crate::cmd::CommandError;
+use bytes::Bytes;
+use rand::seq::IteratorRandom;
+use rand::Rng;
+use std::collections::hash_map::{Iter, Keys, Values};
+use std::collections::HashMap;
+
+/// The fields of a hash and their values.
+///
+/// The fields are also kept in the order `HSCAN` visits them in, so that each call of an
+/// iteration only reads the fields it returns.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HashFields {
+    fields: HashMap<Bytes, Bytes>,
+    order: ScanOrder,
+}
+
+impl HashFields {
+    pub fn get(&self, field: &[u8]) -> Option<&Bytes> {
+        self.fields.get(field)
+    }
+
+    pub fn contains_key(&self, field: &[u8]) -> bool {
+        self.fields.contains_key(field)
+    }
+
+    /// Sets the value of a field, returning its previous value.
+    pub fn insert(&mut self, field: Bytes, value: Bytes) -> Option<Bytes> {
+        if !self.fields.contains_key(&field) {
+            self.order.insert(&field);
+        }
+        self.fields.insert(field, value)
+    }
+
+    /// Removes a field, returning its value.
+    pub fn remove(&mut self, field: &[u8]) -> Option<Bytes> {
+        let value = self.fields.remove(field)?;
+        self.order.remove(field);
+        Some(value)
+    }
+
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    pub fn iter(&self) -> Iter<'_, Bytes, Bytes> {
+        self.fields.iter()
+    }
+
+    pub fn keys(&self) -> Keys<'_, Bytes, Bytes> {
+        self.fields.keys()
+    }
+
+    pub fn values(&self) -> Values<'_, Bytes, Bytes> {
+        self.fields.values()
+    }
+
+    /// Returns the next batch of about `count` field-value pairs of an `HSCAN` iteration
+    /// starting at `cursor`, along with the cursor of the next call.
+    pub(crate) fn scan(&self, cursor: u64, count: usize) -> (u64, Vec<(Bytes, Bytes)>) {
+        let (cursor, fields) = self.order.scan(cursor, count);
+        let pairs = fields
+            .into_iter()
+            .map(|field| {
+                let value = self.fields[&field].clone();
+                (field, value)
+            })
+            .collect();
+        (cursor, pairs)
+    }
+}
+
+impl Backend {
+    /// Get a value from a hash.
+    ///
+    /// This function retrieves the value associated with the given field within the hash
+    /// identified by the provided key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key identifying the hash.
+    /// * `field` - The field within the hash whose value is to be retrieved.
+    ///
+    /// # Returns
+    ///
+    /// An `Option<Bytes>` containing the value if it exists, or `None` if either the key or
+    /// field is not present. If the key holds a value that is not a hash,
+    /// `CommandError::WrongType` is returned.
+    pub fn hget(&self, key: &[u8], field: &[u8]) -> Result<Option<Bytes>, CommandError> {
+        let value = self.read(key, |h: &HashFields| h.get(field).cloned())?;
+        Ok(value.flatten())
+    }
+
+    /// Stores a value in the hash identified by the given key.
+    ///
+    /// The value is associated with the given field within the hash.
+    /// If the key is not found, a new hash is created and the value is stored. If the field is
+    /// not found, it is created and the value is stored.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key identifying the hash in which the value is to be stored.
+    /// * `field` - The field within the hash with which the value is to be associated.
+    /// * `value` - The value to be stored in the hash.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the field was created, `false` if an existing field was updated. If the key
+    /// holds a value that is not a hash, `CommandError::WrongType` is returned.
+    pub fn hset(&self, key: Bytes, field: Bytes, value: Bytes) -> Result<bool, CommandError> {
+        let created = self.write(&key, true, |h: &mut HashFields| {
+            h.insert(field, value).is_none()
+        })?;
+        Ok(created.unwrap_or_default())
+    }
+
+    /// Retrieves all the field-value pairs in the hash identified by the given key.
+    ///
+    /// If the key is found, the hash is cloned and returned. If the key is not found,
+    /// `None` is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key identifying the hash from which all field-value pairs are to be
+    ///   retrieved.
+    ///
+    /// # Returns
+    ///
+    /// An `Option<HashMap>` containing the hash if it exists, or
+    /// `None` if the key is not present. If the key holds a value that is not a hash,
+    /// `CommandError::WrongType` is returned.
+    pub fn hgetall(&self, key: &[u8]) -> Result<Option<HashMap<Bytes, Bytes>>, CommandError> {
+        self.read(key, |h: &HashFields| h.fields.clone())
+    }
+
+    /// Stores several field-value pairs in the hash identified by the given key, creating
+    /// the hash if needed.
+    ///
+    /// # Returns
+    ///
+    /// The number of fields that were created. If the key holds a value that is not a hash,
+    /// `CommandError::WrongType` is returned.
+    pub fn hset_many(&self, key: Bytes, pairs: Vec<(Bytes, Bytes)>) -> Result<usize, CommandError> {
+        let created = self.write(&key, true, |h: &mut HashFields| {
+            pairs
+                .into_iter()
+                .filter(|(field, value)| h.insert(field.clone(), value.clone()).is_none())
+                .count()
+        })?;
+        Ok(created.unwrap_or_default())
+    }
+
+    /// Stores a value in a hash only if the field does not exist yet.
+    ///
+    /// Returns `true` if the field was created.
+    pub fn hsetnx(&self, key: Bytes, field: Bytes, value: Bytes) -> Result<bool, CommandError> {
+        let created = self.write(&key, true, |h: &mut HashFields| {
+            if h.contains_key(&field) {
+                return false;
+            }
+            h.insert(field, value);
+            true
+        })?;
+        Ok(created.unwrap_or_default())
+    }
+
+    /// Removes fields from a hash. The hash is removed from the keyspace once it is empty.
+    ///
+    /// Returns the number of fields that were removed.
+    pub fn hdel(&self, key: &Bytes, fields: &[Bytes]) -> Result<usize, CommandError> {
+        let removed = self.write(key, false, |h: &mut HashFields| {
+            fields
+                .iter()
+                .filter(|field| h.remove(field).is_some())
+                .count()
+        })?;
+        Ok(removed.unwrap_or_default())
+    }
+
+    /// Returns `true` if the field exists in the hash.
+    pub fn hexists(&self, key: &[u8], field: &[u8]) -> Result<bool, CommandError> {
+        let exists = self.read(key, |h: &HashFields| h.contains_key(field))?;
+        Ok(exists.unwrap_or_default())
+    }
+
+    /// Returns the number of fields in the hash, `0` if the key does not exist.
+    pub fn hlen(&self, key: &[u8]) -> Result<usize, CommandError> {
+        let len = self.read(key, |h: &HashFields| h.len())?;
+        Ok(len.unwrap_or_default())
+    }
+
+    /// Returns the fields of the hash.
+    pub fn hkeys(&self, key: &[u8]) -> Result<Vec<Bytes>, CommandError> {
+        let fields = self.read(key, |h: &HashFields| h.keys().cloned().collect())?;
+        Ok(fields.unwrap_or_default())
+    }
+
+    /// Returns the values of the hash.
+    pub fn hvals(&self, key: &[u8]) -> Result<Vec<Bytes>, CommandError> {
+        let values = self.read(key, |h: &HashFields| h.values().cloned().collect())?;
+        Ok(values.unwrap_or_default())
+    }
+
+    /// Returns the values of the given fields, `None` for the fields that do not exist.
+    pub fn hmget(&self, key: &[u8], fields: &[Bytes]) -> Result<Vec<Option<Bytes>>, CommandError> {
+        let values = self.read(key, |h: &HashFields| {
+            fields.iter().map(|field| h.get(field).cloned()).collect()
+        })?;
+        Ok(values.unwrap_or_else(|| vec![None; fields.len()]))
+    }
+
+    /// Returns the length of the value of a field, `0` if the key or field does not exist.
+    pub fn hstrlen(&self, key: &[u8], field: &[u8]) -> Result<usize, CommandError> {
+        let len = self.read(key, |h: &HashFields| {
+            h.get(field).map_or(0, |value| value.len())
+        })?;
+        Ok(len.unwrap_or_default())
+    }
+
+    /// Increments the integer value of a field by `delta`, a missing field counting as `0`.
+    ///
+    /// Returns the new value, or an error if the value is not an integer or the increment
+    /// would overflow.
+    pub fn hincrby(&self, key: Bytes, field: Bytes, delta: i64) -> Result<i64, CommandError> {
+        let result = self.write(&key, true, |h: &mut HashFields| {
+            let current = match h.get(&field) {
+                Some(value) => parse_i64(value).ok_or_else(|| {
+                    CommandError::InvalidArguments("hash value is not an integer".to_string())
+                })?,
+                None => 0,
+            };
+            let value = current.checked_add(delta).ok_or(CommandError::Overflow)?;
+            h.insert(field, value.to_string().into());
+            Ok(value)
+        })?;
+        result.expect("the hash is created when missing")
+    }
+
+    /// Increments the float value of a field by `delta`, a missing field counting as `0`.
+    ///
+    /// Returns the new value as stored, or an error if the value is not a float or the
+    /// result is not finite.
+    pub fn hincrbyfloat(
+        &self,
+        key: Bytes,
+        field: Bytes,
+        delta: f64,
+    ) -> Result<Bytes, CommandError> {
+        let result = self.write(&key, true, |h: &mut HashFields| {
+            let current = match h.get(&field) {
+                Some(value) => std::str::from_utf8(value)
+                    .ok()
+                    .and_then(|value| value.parse::<f64>().ok())
+                    .filter(|value| value.is_finite())
+                    .ok_or_else(|| {
+                        CommandError::InvalidArguments("hash value is not a float".to_string())
+                    })?,
+                None => 0.0,
+            };
+            let value = current + delta;
+            if !value.is_finite() {
+                return Err(CommandError::InvalidArguments(
+                    "increment would produce NaN or Infinity".to_string(),
+                ));
+            }
+            let value = Bytes::from(format_float(value));
+            h.insert(field, value.clone());
+            Ok(value)
+        })?;
+        result.expect("the hash is created when missing")
+    }
+
+    /// Returns random field-value pairs of a hash, as `HRANDFIELD` does.
+    ///
+    /// With a positive `count`, up to `count` distinct fields are returned. With a negative
+    /// `count`, exactly `-count` fields are returned and the same field may appear several
+    /// times.
+    pub fn hrandfield(&self, key: &[u8], count: i64) -> Result<Vec<(Bytes, Bytes)>, CommandError> {
+        let pairs = self.read(key, |h: &HashFields| {
+            let mut rng = rand::rng();
+            if count >= 0 {
+                let count = (count as usize).min(h.len());
+                h.iter()
+                    .choose_multiple(&mut rng, count)
+                    .into_iter()
+                    .map(|(field, value)| (field.clone(), value.clone()))
+                    .collect()
+            } else {
+                let pairs = h.iter().collect::<Vec<_>>();
+                (0..count.unsigned_abs())
+                    .map(|_| {
+                        let (field, value) = pairs[rng.random_range(0..pairs.len())];
+                        (field.clone(), value.clone())
+                    })
+                    .collect()
+            }
+        })?;
+        Ok(pairs.unwrap_or_default())
+    }
+}
@@ -0,0 +1,94 @@
+use super::Backend;
+use crate::cmd::CommandError;
+use bytes::Bytes;
+
+/// Values made of more allocations than this are freed on a background task by `UNLINK`.
+const LAZYFREE_THRESHOLD: usize = 64;
+
+impl Backend {
+    /// Deletes a key, its value and its expiry.
+    ///
+    /// Returns `true` if the key existed.
+    pub fn del(&self, key: &[u8]) -> bool {
+        self.expire_if_needed(key);
+        self.remove(key)
+    }
+
+    /// Deletes a key like `del`, but frees a large value off the request path.
+    ///
+    /// The value is unlinked from the keyspace right away, so the key is gone for every
+    /// client once this returns. Dropping a value with many allocations is left to a
+    /// blocking task when called from within a tokio runtime.
+    pub fn unlink(&self, key: &[u8]) -> bool {
+        self.expire_if_needed(key);
+        self.expires.remove(key);
+        match self.remove_value(key) {
+            Some(value) => {
+                if value.effort() > LAZYFREE_THRESHOLD {
+                    if let Ok(handle) = tokio::runtime::Handle::try_current() {
+                        handle.spawn_blocking(move || drop(value));
+                    }
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the type name of the value stored at `key`, or `None` if the key does not
+    /// exist.
+    pub fn key_type(&self, key: &[u8]) -> Option<&'static str> {
+        self.expire_if_needed(key);
+        self.keyspace.get(key).map(|value| value.type_name())
+    }
+
+    /// Renames `key` to `new_key`, carrying its expiry over.
+    ///
+    /// An existing `new_key` is overwritten, unless `nx` is set in which case nothing
+    /// happens. Returns `true` if the key was renamed, or `CommandError::NoSuchKey` if `key`
+    /// does not exist.
+    pub fn rename(&self, key: &[u8], new_key: Bytes, nx: bool) -> Result<bool, CommandError> {
+        if !self.contains_key(key) {
+            return Err(CommandError::NoSuchKey);
+        }
+        if key == new_key.as_ref() {
+            return Ok(!nx);
+        }
+        if nx && self.contains_key(&new_key) {
+            return Ok(false);
+        }
+        let value = self.remove_value(key).ok_or(CommandError::NoSuchKey)?;
+        let expiry = self.expires.remove(key);
+        self.remove(&new_key);
+        if let Some(at) = expiry {
+            self.expires.insert(new_key.clone(), at);
+        }
+        self.insert_value(new_key.clone(), value);
+        self.notify_new_key(&new_key);
+        self.signal_key_ready(&new_key);
+        Ok(true)
+    }
+
+    /// Copies the value and expiry of `key` to `new_key`.
+    ///
+    /// An existing `new_key` is only overwritten with `replace`. Returns `true` if the value
+    /// was copied.
+    pub fn copy(&self, key: &[u8], new_key: Bytes, replace: bool) -> bool {
+        if !replace && self.contains_key(&new_key) {
+            return false;
+        }
+        self.expire_if_needed(key);
+        let Some(value) = self.keyspace.get(key).map(|value| value.clone()) else {
+            return false;
+        };
+        let expiry = self.expires.get(key);
+        self.remove(&new_key);
+        if let Some(at) = expiry {
+            self.expires.insert(new_key.clone(), at);
+        }
+        self.insert_value(new_key.clone(), value);
+        self.notify_new_key(&new_key);
+        self.signal_key_ready(&new_key);
+        true
+    }
+}
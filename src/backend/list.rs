@@ -0,0 +1,286 @@
+use super::Backend;
+use crate::cmd::CommandError;
+use bytes::Bytes;
+use std::collections::VecDeque;
+use std::ops::RangeInclusive;
+
+type List = VecDeque<Bytes>;
+
+/// An end of a list: the head (left) or the tail (right).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListEnd {
+    Left,
+    Right,
+}
+
+/// Resolves the inclusive index range `start..=stop` of a sequence of `len` elements.
+///
+/// Negative indexes count from the end, `-1` being the last element. Indexes past either
+/// end are clamped. Returns `None` if the range is empty.
+pub(crate) fn index_range(start: i64, stop: i64, len: usize) -> Option<RangeInclusive<usize>> {
+    let len = len as i64;
+    let start = if start < 0 { len + start } else { start }.max(0);
+    let stop = if stop < 0 { len + stop } else { stop }.min(len - 1);
+    if start > stop {
+        return None;
+    }
+    Some(start as usize..=stop as usize)
+}
+
+/// Resolves a possibly negative index of a sequence of `len` elements.
+fn index(index: i64, len: usize) -> Option<usize> {
+    let index = if index < 0 { len as i64 + index } else { index };
+    (0..len as i64).contains(&index).then_some(index as usize)
+}
+
+fn push(list: &mut List, end: ListEnd, value: Bytes) {
+    match end {
+        ListEnd::Left => list.push_front(value),
+        ListEnd::Right => list.push_back(value),
+    }
+}
+
+fn pop(list: &mut List, end: ListEnd) -> Option<Bytes> {
+    match end {
+        ListEnd::Left => list.pop_front(),
+        ListEnd::Right => list.pop_back(),
+    }
+}
+
+impl Backend {
+    /// Pushes values one after the other to an end of the list at `key`, as done by
+    /// `LPUSH` and `RPUSH`: pushing `a b c` to the left leaves `c b a` at the head.
+    ///
+    /// If the key does not exist, a list is created when `create` is `true`, otherwise
+    /// nothing is pushed.
+    ///
+    /// Returns the length of the list after the push, `0` if nothing was pushed.
+    pub fn push(
+        &self,
+        key: &Bytes,
+        end: ListEnd,
+        values: Vec<Bytes>,
+        create: bool,
+    ) -> Result<usize, CommandError> {
+        let len = self.write(key, create, |l: &mut List| {
+            for value in values {
+                push(l, end, value);
+            }
+            l.len()
+        })?;
+        Ok(len.unwrap_or_default())
+    }
+
+    /// Pops up to `count` values from an end of the list at `key`. The list is removed from
+    /// the keyspace once it is empty.
+    ///
+    /// Returns `None` if the key does not exist.
+    pub fn pop(
+        &self,
+        key: &Bytes,
+        end: ListEnd,
+        count: usize,
+    ) -> Result<Option<Vec<Bytes>>, CommandError> {
+        self.write(key, false, |l: &mut List| {
+            (0..count.min(l.len()))
+                .filter_map(|_| pop(l, end))
+                .collect()
+        })
+    }
+
+    /// Returns the length of the list, `0` if the key does not exist.
+    pub fn llen(&self, key: &[u8]) -> Result<usize, CommandError> {
+        let len = self.read(key, |l: &List| l.len())?;
+        Ok(len.unwrap_or_default())
+    }
+
+    /// Returns the values of the list between the indexes `start` and `stop`, both
+    /// inclusive. Negative indexes count from the tail.
+    pub fn lrange(&self, key: &[u8], start: i64, stop: i64) -> Result<Vec<Bytes>, CommandError> {
+        let values = self.read(key, |l: &List| match index_range(start, stop, l.len()) {
+            Some(range) => l.range(range).cloned().collect(),
+            None => Vec::new(),
+        })?;
+        Ok(values.unwrap_or_default())
+    }
+
+    /// Returns the value at `index` in the list, `None` if the index is out of range.
+    pub fn lindex(&self, key: &[u8], index: i64) -> Result<Option<Bytes>, CommandError> {
+        let value = self.read(key, |l: &List| {
+            self::index(index, l.len()).map(|i| l[i].clone())
+        })?;
+        Ok(value.flatten())
+    }
+
+    /// Replaces the value at `index` in the list.
+    ///
+    /// Fails with `CommandError::NoSuchKey` if the key does not exist, and with an error if
+    /// the index is out of range.
+    pub fn lset(&self, key: &Bytes, index: i64, value: Bytes) -> Result<(), CommandError> {
+        let set = self.write(key, false, |l: &mut List| {
+            self::index(index, l.len()).map(|i| l[i] = value)
+        })?;
+        match set {
+            Some(Some(())) => Ok(()),
+            Some(None) => Err(CommandError::InvalidArguments(
+                "index out of range".to_string(),
+            )),
+            None => Err(CommandError::NoSuchKey),
+        }
+    }
+
+    /// Inserts `value` before or after the first occurrence of `pivot` in the list.
+    ///
+    /// Returns the length of the list after the insertion, `-1` if the pivot was not found
+    /// and `0` if the key does not exist.
+    pub fn linsert(
+        &self,
+        key: &Bytes,
+        before: bool,
+        pivot: &[u8],
+        value: Bytes,
+    ) -> Result<i64, CommandError> {
+        let len = self.write(key, false, |l: &mut List| {
+            match l.iter().position(|v| v == pivot) {
+                Some(i) => {
+                    l.insert(if before { i } else { i + 1 }, value);
+                    l.len() as i64
+                }
+                None => -1,
+            }
+        })?;
+        Ok(len.unwrap_or_default())
+    }
+
+    /// Removes occurrences of `value` from the list, as done by `LREM`: the first `count`
+    /// ones from the head if `count` is positive, the last `-count` ones from the tail if it
+    /// is negative, and all of them if it is `0`.
+    ///
+    /// Returns the number of removed values.
+    pub fn lrem(&self, key: &Bytes, count: i64, value: &[u8]) -> Result<usize, CommandError> {
+        let removed = self.write(key, false, |l: &mut List| {
+            let limit = match count {
+                0 => usize::MAX,
+                count => count.unsigned_abs() as usize,
+            };
+            let mut removed = 0;
+            let mut keep = |v: &Bytes| {
+                if removed < limit && v == value {
+                    removed += 1;
+                    false
+                } else {
+                    true
+                }
+            };
+            let kept: List = if count < 0 {
+                let mut kept = l.drain(..).rev().filter(|v| keep(v)).collect::<List>();
+                kept.make_contiguous().reverse();
+                kept
+            } else {
+                l.drain(..).filter(|v| keep(v)).collect()
+            };
+            *l = kept;
+            removed
+        })?;
+        Ok(removed.unwrap_or_default())
+    }
+
+    /// Trims the list to the values between the indexes `start` and `stop`, both inclusive.
+    /// A list trimmed to an empty range is removed.
+    pub fn ltrim(&self, key: &Bytes, start: i64, stop: i64) -> Result<(), CommandError> {
+        self.write(key, false, |l: &mut List| {
+            match index_range(start, stop, l.len()) {
+                Some(range) => {
+                    l.truncate(range.end() + 1);
+                    l.drain(..range.start());
+                }
+                None => l.clear(),
+            }
+        })?;
+        Ok(())
+    }
+
+    /// Returns the indexes of the values of the list equal to `value`, as done by `LPOS`.
+    ///
+    /// The search starts at the `rank`-th match, from the head if `rank` is positive and
+    /// from the tail if it is negative. At most `count` indexes are returned, all of them if
+    /// `count` is `0`, and only the first `maxlen` values are compared, all of them if
+    /// `maxlen` is `0`.
+    pub fn lpos(
+        &self,
+        key: &[u8],
+        value: &[u8],
+        rank: i64,
+        count: usize,
+        maxlen: usize,
+    ) -> Result<Vec<usize>, CommandError> {
+        let positions = self.read(key, |l: &List| {
+            let count = if count == 0 { usize::MAX } else { count };
+            let maxlen = if maxlen == 0 { usize::MAX } else { maxlen };
+            let skip = (rank.unsigned_abs() - 1) as usize;
+            let matches = |(_, v): &(usize, &Bytes)| *v == value;
+            let positions: Box<dyn Iterator<Item = (usize, &Bytes)>> = if rank < 0 {
+                Box::new(l.iter().enumerate().rev().take(maxlen))
+            } else {
+                Box::new(l.iter().enumerate().take(maxlen))
+            };
+            positions
+                .filter(matches)
+                .skip(skip)
+                .take(count)
+                .map(|(i, _)| i)
+                .collect()
+        })?;
+        Ok(positions.unwrap_or_default())
+    }
+
+    /// Atomically pops a value from an end of the list at `source` and pushes it to an end
+    /// of the list at `destination`, creating it if needed.
+    ///
+    /// Nothing is popped if `destination` holds another type. Returns the moved value, or
+    /// `None` if `source` does not exist.
+    pub fn lmove(
+        &self,
+        source: &Bytes,
+        destination: &Bytes,
+        from: ListEnd,
+        to: ListEnd,
+    ) -> Result<Option<Bytes>, CommandError> {
+        if source == destination {
+            let value = self.write(source, false, |l: &mut List| {
+                let value = pop(l, from)?;
+                push(l, to, value.clone());
+                Some(value)
+            })?;
+            return Ok(value.flatten());
+        }
+        if self.read(source, |_: &List| ())?.is_none() {
+            return Ok(None);
+        }
+        self.read(destination, |_: &List| ())?;
+        let value = self.write(source, false, |l: &mut List| pop(l, from))?;
+        let Some(value) = value.flatten() else {
+            return Ok(None);
+        };
+        self.write(destination, true, |l: &mut List| push(l, to, value.clone()))?;
+        Ok(Some(value))
+    }
+
+    /// Pops up to `count` values from an end of the first non-empty list among `keys`.
+    ///
+    /// Returns the key the values were popped from along with the values, or `None` if none
+    /// of the keys exists.
+    pub fn lmpop(
+        &self,
+        keys: &[Bytes],
+        end: ListEnd,
+        count: usize,
+    ) -> Result<Option<(Bytes, Vec<Bytes>)>, CommandError> {
+        for key in keys {
+            if let Some(values) = self.pop(key, end, count)? {
+                return Ok(Some((key.clone(), values)));
+            }
+        }
+        Ok(None)
+    }
+}
@@ -0,0 +1,127 @@
+use super::Backend;
+use std::hash::{BuildHasher, RandomState};
+use std::sync::{PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// Number of stripes of the key lock table.
+const KEY_LOCK_STRIPES: usize = 1024;
+
+/// A striped table of reader-writer locks covering the keyspace.
+///
+/// Every key maps to one stripe. Commands lock the stripes of the keys they access before
+/// running, shared for reads and exclusive for writes, which makes a command touching
+/// several keys atomic with respect to every other command even though the keys may live in
+/// different shards of the keyspace.
+#[derive(Debug)]
+pub(crate) struct KeyLocks {
+    stripes: Box<[RwLock<()>]>,
+    hasher: RandomState,
+}
+
+/// The locks held on a set of keys, released when dropped.
+#[must_use = "the keys are unlocked as soon as the guard is dropped"]
+#[derive(Debug)]
+pub struct KeyLockGuard<'a> {
+    _read: Vec<RwLockReadGuard<'a, ()>>,
+    _write: Vec<RwLockWriteGuard<'a, ()>>,
+}
+
+impl Default for KeyLocks {
+    fn default() -> Self {
+        Self {
+            stripes: (0..KEY_LOCK_STRIPES).map(|_| RwLock::new(())).collect(),
+            hasher: RandomState::new(),
+        }
+    }
+}
+
+impl KeyLocks {
+    /// Returns the sorted and deduplicated stripes of the given keys.
+    ///
+    /// Stripes are always acquired in increasing order, so two commands locking overlapping
+    /// sets of keys can't deadlock.
+    fn stripes<K: AsRef<[u8]>>(&self, keys: &[K]) -> Vec<usize> {
+        let mut stripes = keys
+            .iter()
+            .map(|key| self.hasher.hash_one(key.as_ref()) as usize % self.stripes.len())
+            .collect::<Vec<_>>();
+        stripes.sort_unstable();
+        stripes.dedup();
+        stripes
+    }
+}
+
+impl Backend {
+    /// Locks the given keys until the returned guard is dropped, shared if `write` is
+    /// `false` and exclusive otherwise.
+    ///
+    /// The locks are not reentrant: a thread must not lock keys again while holding a guard,
+    /// which is why they are only taken around the execution of a whole command and never
+    /// by the accessors of the backend themselves.
+    pub fn lock_keys<K: AsRef<[u8]>>(&self, keys: &[K], write: bool) -> KeyLockGuard<'_> {
+        let stripes = self.locks.stripes(keys);
+        self.lock_stripes(stripes, write)
+    }
+
+    /// Locks the whole keyspace until the returned guard is dropped, shared if `write` is
+    /// `false` and exclusive otherwise.
+    pub fn lock_all(&self, write: bool) -> KeyLockGuard<'_> {
+        self.lock_stripes((0..self.locks.stripes.len()).collect(), write)
+    }
+
+    fn lock_stripes(&self, stripes: Vec<usize>, write: bool) -> KeyLockGuard<'_> {
+        let stripes = stripes.into_iter().map(|i| &self.locks.stripes[i]);
+        // a panicking command can't leave the unit value behind the lock inconsistent
+        if write {
+            KeyLockGuard {
+                _read: Vec::new(),
+                _write: stripes
+                    .map(|lock| lock.write().unwrap_or_else(PoisonError::into_inner))
+                    .collect(),
+            }
+        } else {
+            KeyLockGuard {
+                _read: stripes
+                    .map(|lock| lock.read().unwrap_or_else(PoisonError::into_inner))
+                    .collect(),
+                _write: Vec::new(),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    #[test]
+    fn test_stripes_are_sorted_and_unique() {
+        let locks = KeyLocks::default();
+        let stripes = locks.stripes(&["b", "a", "b", "c", "a"]);
+        assert!(stripes.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(stripes, locks.stripes(&["a", "b", "c"]));
+    }
+
+    #[test]
+    fn test_write_lock_excludes_readers() {
+        let backend = Backend::new();
+        let guard = backend.lock_keys(&["a", "b"], true);
+        let (tx, rx) = mpsc::channel();
+        let reader = {
+            let backend = backend.clone();
+            std::thread::spawn(move || {
+                let _guard = backend.lock_keys(&["b"], false);
+                tx.send(()).unwrap();
+            })
+        };
+        assert!(rx.recv_timeout(Duration::from_millis(50)).is_err());
+        drop(guard);
+        rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        reader.join().unwrap();
+
+        // readers share the lock
+        let _first = backend.lock_keys(&["a"], false);
+        let _second = backend.lock_keys(&["a", "b"], false);
+    }
+}
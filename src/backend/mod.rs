@@ -1,15 +1,95 @@
-use crate::RespFrame;
-use dashmap::DashMap;
+mod blocking;
+mod config;
+mod expire;
+mod hash;
+mod keyspace;
+mod list;
+mod lock;
+mod notify;
+mod pubsub;
+mod scan;
+mod set;
+mod skiplist;
+mod stream;
+mod string;
+mod value;
+mod zset;
+
+use crate::cmd::CommandError;
+use bytes::Bytes;
+use dashmap::{mapref::entry::Entry, DashMap};
 use std::ops::Deref;
 use std::sync::Arc;
 
+pub use blocking::KeyWaiter;
+use blocking::Waiters;
+pub(crate) use expire::now_ms;
+use expire::Expires;
+pub use expire::{ExpireCondition, KeyExpiry};
+pub use hash::HashFields;
+pub use list::ListEnd;
+pub use lock::KeyLockGuard;
+use lock::KeyLocks;
+use notify::Notifications;
+pub use notify::{EventClass, KeyspaceEvents};
+use pubsub::PubSub;
+pub use pubsub::{MessageSender, Subscriber};
+use scan::KeyspaceOrder;
+pub use stream::{
+    AutoClaim, ClaimOptions, ConsumerInfo, GroupEntries, GroupInfo, PendingFilter, PendingInfo,
+    PendingSummary, Stream, StreamEntries, StreamFields, StreamId, StreamInfo, StreamTrim,
+    TrimStrategy, XAddId,
+};
+pub(crate) use string::parse_i64;
+pub use value::RedisValue;
+pub(crate) use value::ValueType;
+pub use zset::{
+    Aggregate, LexBound, LexRange, ScoreRange, ScoredMembers, SortedSet, ZAddComparison,
+    ZAddOptions, ZRangeBy, ZSetEnd,
+};
+
 #[derive(Debug, Clone)]
 pub struct Backend(Arc<BackendInner>);
 
 #[derive(Debug)]
 pub struct BackendInner {
-    pub(crate) map: DashMap<String, RespFrame>,
-    pub(crate) hmap: DashMap<String, DashMap<String, RespFrame>>,
+    /// The keyspace: every key maps to exactly one typed value.
+    pub(crate) keyspace: DashMap<Bytes, RedisValue>,
+    /// Expiry index: unix time in milliseconds at which each volatile key expires.
+    pub(crate) expires: Expires,
+    /// The keys in the order `SCAN` visits them in, updated along with the keyspace.
+    pub(crate) scan_order: KeyspaceOrder,
+    /// Locks taken on the keys of a command for the duration of its execution.
+    pub(crate) locks: KeyLocks,
+    /// Clients blocked until keys become ready.
+    pub(crate) waiters: Waiters,
+    /// The channels and patterns clients are subscribed to.
+    pub(crate) pubsub: PubSub,
+    /// The keyspace events published to subscribers, set by `notify-keyspace-events`.
+    pub(crate) notifications: Notifications,
+}
+
+/// Condition under which `SET` is allowed to write a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SetCondition {
+    #[default]
+    Always,
+    /// Only set the key if it does not already exist.
+    Nx,
+    /// Only set the key if it already exists.
+    Xx,
+}
+
+/// What happens to the expiry of a key written by `SET`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SetExpiry {
+    /// Any existing expiry is discarded.
+    #[default]
+    Discard,
+    /// Any existing expiry is retained.
+    Keep,
+    /// The key expires at the given unix time in milliseconds.
+    At(u64),
 }
 
 impl Deref for Backend {
@@ -31,8 +111,13 @@ impl Default for Backend {
 impl Default for BackendInner {
     fn default() -> Self {
         Self {
-            map: DashMap::new(),
-            hmap: DashMap::new(),
+            keyspace: DashMap::new(),
+            expires: Expires::default(),
+            scan_order: KeyspaceOrder::default(),
+            locks: KeyLocks::default(),
+            waiters: Waiters::default(),
+            pubsub: PubSub::default(),
+            notifications: Notifications::default(),
         }
     }
 }
@@ -41,78 +126,217 @@ impl Backend {
         Self::default()
     }
 
-    /// Get a value from the map.
+    /// Runs `f` on the value stored at `key`, borrowed as type `T`.
     ///
-    /// The value is retrieved from the map with the given key.
-    /// If the key is not found, `None` is returned.
-    pub fn get(&self, key: &str) -> Option<RespFrame> {
-        self.map.get(key).map(|v| v.value().clone())
+    /// # Returns
+    ///
+    /// `Ok(None)` if the key does not exist, `Ok(Some(_))` with the result of `f` otherwise.
+    /// If the key holds a value of another type, `CommandError::WrongType` is returned.
+    pub(crate) fn read<T: ValueType, R>(
+        &self,
+        key: &[u8],
+        f: impl FnOnce(&T) -> R,
+    ) -> Result<Option<R>, CommandError> {
+        self.expire_if_needed(key);
+        match self.keyspace.get(key) {
+            Some(value) => T::from_value(value.value())
+                .map(|v| Some(f(v)))
+                .ok_or(CommandError::WrongType),
+            None => {
+                self.notify_keyspace_event(EventClass::KeyMiss, "keymiss", key);
+                Ok(None)
+            }
+        }
     }
 
-    /// Stores a value in the map associated with the given key.
+    /// Runs `f` on the value stored at `key`, mutably borrowed as type `T`.
     ///
-    /// If a value already exists for the given key, it is replaced.
+    /// The key's map entry is held for the duration of `f`, so a read-modify-write done by
+    /// `f` is atomic per key. If the key does not exist and `create` is `true`, `f` runs on
+    /// an empty value which is stored afterwards, unless it is still empty, and the clients
+    /// blocked on the key are woken. A value left empty by `f` is removed from the keyspace.
     ///
-    /// # Arguments
+    /// # Returns
     ///
-    /// * `key` - The key identifying where the value is to be stored in the map.
-    /// * `value` - The value to be stored in the map.
-    pub fn set(&self, key: String, value: RespFrame) {
-        self.map.insert(key, value);
+    /// `Ok(None)` if the key does not exist and `create` is `false`, `Ok(Some(_))` with the
+    /// result of `f` otherwise. If the key holds a value of another type,
+    /// `CommandError::WrongType` is returned.
+    pub(crate) fn write<T: ValueType, R>(
+        &self,
+        key: &Bytes,
+        create: bool,
+        f: impl FnOnce(&mut T) -> R,
+    ) -> Result<Option<R>, CommandError> {
+        self.expire_if_needed(key);
+        match self.keyspace.entry(key.clone()) {
+            Entry::Occupied(mut entry) => {
+                let value = T::from_value_mut(entry.get_mut()).ok_or(CommandError::WrongType)?;
+                let ret = f(value);
+                if value.is_empty() {
+                    self.scan_order.remove(key);
+                    entry.remove();
+                    self.expires.remove(key);
+                }
+                Ok(Some(ret))
+            }
+            Entry::Vacant(entry) => {
+                if !create {
+                    return Ok(None);
+                }
+                let mut value = T::default();
+                let ret = f(&mut value);
+                if !value.is_empty() {
+                    self.scan_order.insert(key);
+                    entry.insert(value.into_value());
+                    self.notify_new_key(key);
+                    self.signal_key_ready(key);
+                }
+                Ok(Some(ret))
+            }
+        }
     }
 
-    /// Get a value from the hash map.
-    ///
-    /// This function retrieves the value associated with the given field within the hash map
-    /// identified by the provided key. If both the key and field are found, the corresponding
-    /// value is returned as a `RespFrame`. If the key or field is not found, `None` is returned.
-    ///
-    /// # Arguments
-    ///
-    /// * `key` - The key identifying the hash map.
-    /// * `field` - The field within the hash map whose value is to be retrieved.
+    /// Get a string value from the keyspace.
     ///
-    /// # Returns
-    ///
-    /// An `Option<RespFrame>` containing the value if it exists, or `None` if either the key or
-    /// field is not present.
-    pub fn hget(&self, key: &str, field: &str) -> Option<RespFrame> {
-        self.hmap
-            .get(key)
-            .and_then(|v| v.get(field).map(|v| v.value().clone()))
+    /// The value is retrieved from the keyspace with the given key.
+    /// If the key is not found, `None` is returned. If the key holds a value that is not a
+    /// string, `CommandError::WrongType` is returned.
+    pub fn get(&self, key: &[u8]) -> Result<Option<Bytes>, CommandError> {
+        self.read(key, |s: &Bytes| s.clone())
     }
 
-    /// Stores a value in the hash map identified by the given key.
+    /// Stores a string value in the keyspace associated with the given key.
     ///
-    /// The value is associated with the given field within the hash map.
-    /// If the key is not found, a new hash map is created and the value is stored. If the field is
-    /// not found, it is created and the value is stored.
+    /// If a value of any type already exists for the given key, it is replaced and its
+    /// expiry is discarded.
     ///
     /// # Arguments
     ///
-    /// * `key` - The key identifying the hash map in which the value is to be stored.
-    /// * `field` - The field within the hash map with which the value is to be associated.
-    /// * `value` - The value to be stored in the hash map.
-    pub fn hset(&self, key: String, field: String, value: RespFrame) {
-        let hmap = self.hmap.entry(key).or_default();
-        hmap.insert(field, value);
+    /// * `key` - The key identifying where the value is to be stored in the keyspace.
+    /// * `value` - The value to be stored in the keyspace.
+    pub fn set(&self, key: Bytes, value: Bytes) {
+        self.expires.remove(&key);
+        if self
+            .insert_value(key.clone(), RedisValue::String(value))
+            .is_none()
+        {
+            self.notify_new_key(&key);
+        }
     }
 
-    /// Retrieves all the key-value pairs in the hash map identified by the given key.
-    ///
-    /// If the key is found, the hash map is cloned and returned. If the key is not found,
-    /// `None` is returned.
-    ///
-    /// # Arguments
+    /// Conditionally stores a string value in the keyspace, as done by `SET` with options.
     ///
-    /// * `key` - The key identifying the hash map from which all key-value pairs are to be
-    ///   retrieved.
+    /// The condition is checked and the value and expiry are written while holding the map
+    /// entry, so concurrent writers to the same key can't interleave. With `get`, the key
+    /// must hold a string, otherwise `CommandError::WrongType` is returned and nothing is
+    /// written.
     ///
     /// # Returns
     ///
-    /// An `Option<DashMap<String, RespFrame>>` containing the hash map if it exists, or
-    /// `None` if the key is not present.
-    pub fn hgetall(&self, key: &str) -> Option<DashMap<String, RespFrame>> {
-        self.hmap.get(key).map(|v| v.clone())
+    /// A tuple of whether the value was written and the string previously stored at the key.
+    pub fn set_with(
+        &self,
+        key: Bytes,
+        value: Bytes,
+        condition: SetCondition,
+        expiry: SetExpiry,
+        get: bool,
+    ) -> Result<(bool, Option<Bytes>), CommandError> {
+        self.expire_if_needed(&key);
+        match self.keyspace.entry(key) {
+            Entry::Occupied(mut entry) => {
+                let old = match entry.get() {
+                    RedisValue::String(old) => Some(old.clone()),
+                    _ if get => return Err(CommandError::WrongType),
+                    _ => None,
+                };
+                if condition == SetCondition::Nx {
+                    return Ok((false, old));
+                }
+                entry.insert(RedisValue::String(value));
+                self.update_expiry(entry.key(), expiry);
+                Ok((true, old))
+            }
+            Entry::Vacant(entry) => {
+                if condition == SetCondition::Xx {
+                    return Ok((false, None));
+                }
+                self.update_expiry(entry.key(), expiry);
+                self.scan_order.insert(entry.key());
+                let entry = entry.insert(RedisValue::String(value));
+                self.notify_new_key(entry.key());
+                Ok((true, None))
+            }
+        }
+    }
+
+    fn update_expiry(&self, key: &[u8], expiry: SetExpiry) {
+        match expiry {
+            SetExpiry::Discard => {
+                self.expires.remove(key);
+            }
+            SetExpiry::Keep => {}
+            SetExpiry::At(at) => {
+                self.expires.insert(Bytes::copy_from_slice(key), at);
+            }
+        }
+    }
+
+    /// Returns `true` if the key exists in the keyspace and has not expired.
+    pub fn contains_key(&self, key: &[u8]) -> bool {
+        self.expire_if_needed(key);
+        self.keyspace.contains_key(key)
+    }
+
+    /// Removes a key, its value and its expiry from the keyspace.
+    ///
+    /// Returns `true` if the key existed.
+    pub(crate) fn remove(&self, key: &[u8]) -> bool {
+        let removed = self.remove_value(key).is_some();
+        self.expires.remove(key);
+        removed
+    }
+
+    /// Stores `value` at `key` and returns the value it replaces, adding a new key to the
+    /// `SCAN` order while holding its entry.
+    pub(crate) fn insert_value(&self, key: Bytes, value: RedisValue) -> Option<RedisValue> {
+        match self.keyspace.entry(key) {
+            Entry::Occupied(mut entry) => Some(entry.insert(value)),
+            Entry::Vacant(entry) => {
+                self.scan_order.insert(entry.key());
+                entry.insert(value);
+                None
+            }
+        }
+    }
+
+    /// Removes `key` from the keyspace and returns its value, removing the key from the
+    /// `SCAN` order while holding its entry.
+    pub(crate) fn remove_value(&self, key: &[u8]) -> Option<RedisValue> {
+        self.keyspace
+            .remove_if(key, |key, _| {
+                self.scan_order.remove(key);
+                true
+            })
+            .map(|(_, value)| value)
+    }
+
+    /// Replaces the value at `key` with `value`, discarding its expiry, as done by the
+    /// commands storing their result such as `SINTERSTORE`. An empty value removes the key.
+    pub(crate) fn store<T: ValueType>(&self, key: Bytes, value: T) {
+        if value.is_empty() {
+            self.remove(&key);
+            return;
+        }
+        self.expires.remove(&key);
+        if self.insert_value(key.clone(), value.into_value()).is_none() {
+            self.notify_new_key(&key);
+        }
+        self.signal_key_ready(&key);
+    }
+
+    /// Publishes the `new` keyspace event of a key just added to the keyspace.
+    pub(crate) fn notify_new_key(&self, key: &[u8]) {
+        self.notify_keyspace_event(EventClass::New, "new", key);
     }
 }
@@ -0,0 +1,279 @@
+use super::Backend;
+use bytes::{BufMut, Bytes, BytesMut};
+use std::fmt;
+use std::sync::atomic::{AtomicU16, Ordering};
+
+/// The prefix of the channels keyspace events are published to, named after the key.
+const KEYSPACE_PREFIX: &[u8] = b"__keyspace@0__:";
+/// The prefix of the channels keyspace events are published to, named after the event.
+const KEYEVENT_PREFIX: &[u8] = b"__keyevent@0__:";
+
+/// The classes of keyspace events, each enabled by a flag of `notify-keyspace-events`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventClass {
+    /// `g`: commands not specific to a type, such as `DEL`, `EXPIRE` or `RENAME`.
+    Generic,
+    /// `$`: string commands.
+    String,
+    /// `l`: list commands.
+    List,
+    /// `s`: set commands.
+    Set,
+    /// `h`: hash commands.
+    Hash,
+    /// `z`: sorted set commands.
+    ZSet,
+    /// `t`: stream commands.
+    Stream,
+    /// `x`: keys removed once their expiry is reached.
+    Expired,
+    /// `e`: keys removed to free memory.
+    Evicted,
+    /// `m`: reads of keys that do not exist.
+    KeyMiss,
+    /// `n`: keys added to the keyspace.
+    New,
+}
+
+/// The keyspace events to publish, as configured by `notify-keyspace-events`.
+///
+/// Besides the classes of events, the `K` and `E` flags select whether events are published
+/// to the `__keyspace@0__:<key>` channels, the `__keyevent@0__:<event>` channels, or both.
+/// Nothing is published unless one of them is set along with a class.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct KeyspaceEvents(u16);
+
+impl KeyspaceEvents {
+    const KEYSPACE: u16 = 1 << 0;
+    const KEYEVENT: u16 = 1 << 1;
+    const GENERIC: u16 = 1 << 2;
+    const STRING: u16 = 1 << 3;
+    const LIST: u16 = 1 << 4;
+    const SET: u16 = 1 << 5;
+    const HASH: u16 = 1 << 6;
+    const ZSET: u16 = 1 << 7;
+    const EXPIRED: u16 = 1 << 8;
+    const EVICTED: u16 = 1 << 9;
+    const STREAM: u16 = 1 << 10;
+    const KEY_MISS: u16 = 1 << 11;
+    const NEW: u16 = 1 << 12;
+    /// `A`: an alias for `g$lshzxet`, which leaves out the `m` and `n` classes.
+    const ALL: u16 = Self::GENERIC
+        | Self::STRING
+        | Self::LIST
+        | Self::SET
+        | Self::HASH
+        | Self::ZSET
+        | Self::EXPIRED
+        | Self::EVICTED
+        | Self::STREAM;
+
+    /// Parses the flags of a `notify-keyspace-events` string.
+    ///
+    /// Returns `None` if it contains a character that is not a flag.
+    pub fn parse(flags: &[u8]) -> Option<Self> {
+        flags
+            .iter()
+            .try_fold(0, |bits, flag| {
+                let bit = match flag {
+                    b'A' => Self::ALL,
+                    b'g' => Self::GENERIC,
+                    b'$' => Self::STRING,
+                    b'l' => Self::LIST,
+                    b's' => Self::SET,
+                    b'h' => Self::HASH,
+                    b'z' => Self::ZSET,
+                    b'x' => Self::EXPIRED,
+                    b'e' => Self::EVICTED,
+                    b't' => Self::STREAM,
+                    b'K' => Self::KEYSPACE,
+                    b'E' => Self::KEYEVENT,
+                    b'm' => Self::KEY_MISS,
+                    b'n' => Self::NEW,
+                    _ => return None,
+                };
+                Some(bits | bit)
+            })
+            .map(Self)
+    }
+
+    /// Returns `true` if events of `class` are published.
+    pub fn contains(self, class: EventClass) -> bool {
+        let bit = match class {
+            EventClass::Generic => Self::GENERIC,
+            EventClass::String => Self::STRING,
+            EventClass::List => Self::LIST,
+            EventClass::Set => Self::SET,
+            EventClass::Hash => Self::HASH,
+            EventClass::ZSet => Self::ZSET,
+            EventClass::Stream => Self::STREAM,
+            EventClass::Expired => Self::EXPIRED,
+            EventClass::Evicted => Self::EVICTED,
+            EventClass::KeyMiss => Self::KEY_MISS,
+            EventClass::New => Self::NEW,
+        };
+        self.0 & bit != 0 && self.0 & (Self::KEYSPACE | Self::KEYEVENT) != 0
+    }
+
+    fn has(self, bits: u16) -> bool {
+        self.0 & bits == bits
+    }
+}
+
+impl fmt::Display for KeyspaceEvents {
+    /// Formats the flags the way Redis reports them, with `A` standing for all the classes
+    /// it covers.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut flags = String::new();
+        if self.has(Self::ALL) {
+            flags.push('A');
+        } else {
+            for (bit, flag) in [
+                (Self::GENERIC, 'g'),
+                (Self::STRING, '$'),
+                (Self::LIST, 'l'),
+                (Self::SET, 's'),
+                (Self::HASH, 'h'),
+                (Self::ZSET, 'z'),
+                (Self::EXPIRED, 'x'),
+                (Self::EVICTED, 'e'),
+                (Self::STREAM, 't'),
+            ] {
+                if self.has(bit) {
+                    flags.push(flag);
+                }
+            }
+        }
+        for (bit, flag) in [
+            (Self::NEW, 'n'),
+            (Self::KEYSPACE, 'K'),
+            (Self::KEYEVENT, 'E'),
+            (Self::KEY_MISS, 'm'),
+        ] {
+            if self.has(bit) {
+                flags.push(flag);
+            }
+        }
+        f.write_str(&flags)
+    }
+}
+
+/// The `notify-keyspace-events` setting, shared by every connection.
+#[derive(Debug, Default)]
+pub(crate) struct Notifications(AtomicU16);
+
+impl Notifications {
+    pub(crate) fn get(&self) -> KeyspaceEvents {
+        KeyspaceEvents(self.0.load(Ordering::Relaxed))
+    }
+
+    pub(crate) fn set(&self, events: KeyspaceEvents) {
+        self.0.store(events.0, Ordering::Relaxed);
+    }
+}
+
+/// Returns `name` prefixed with `prefix`, as a channel name.
+fn channel(prefix: &[u8], name: &[u8]) -> Bytes {
+    let mut channel = BytesMut::with_capacity(prefix.len() + name.len());
+    channel.put_slice(prefix);
+    channel.put_slice(name);
+    channel.freeze()
+}
+
+impl Backend {
+    /// Returns `true` if events of `class` are published, so that callers can skip the work
+    /// of finding out whether an event happened.
+    pub fn notifies(&self, class: EventClass) -> bool {
+        self.notifications.get().contains(class)
+    }
+
+    /// Publishes the keyspace event `event` of `key`, if events of `class` are enabled.
+    ///
+    /// The event is published to `__keyspace@0__:<key>` with `K`, and the key to
+    /// `__keyevent@0__:<event>` with `E`.
+    pub fn notify_keyspace_event(&self, class: EventClass, event: &str, key: &[u8]) {
+        let events = self.notifications.get();
+        if !events.contains(class) {
+            return;
+        }
+        if events.has(KeyspaceEvents::KEYSPACE) {
+            self.publish(&channel(KEYSPACE_PREFIX, key), event.as_bytes());
+        }
+        if events.has(KeyspaceEvents::KEYEVENT) {
+            self.publish(&channel(KEYEVENT_PREFIX, event.as_bytes()), key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BulkString, RespFrame, RespPush, Subscriber};
+    use tokio::sync::mpsc;
+
+    fn message(channel: &str, message: &str) -> RespFrame {
+        RespPush::new([
+            BulkString::new("message").into(),
+            BulkString::new(channel).into(),
+            BulkString::new(message).into(),
+        ])
+        .into()
+    }
+
+    #[test]
+    fn test_parse_flags() {
+        let events = KeyspaceEvents::parse(b"KEA").unwrap();
+        assert_eq!(events.to_string(), "AKE");
+        assert!(events.contains(EventClass::Expired));
+        assert!(!events.contains(EventClass::KeyMiss));
+        assert!(!events.contains(EventClass::New));
+
+        let events = KeyspaceEvents::parse(b"Elg$shzxetmn").unwrap();
+        assert_eq!(events.to_string(), "AnEm");
+        let events = KeyspaceEvents::parse(b"x$K").unwrap();
+        assert_eq!(events.to_string(), "$xK");
+        assert!(events.contains(EventClass::String));
+        assert!(!events.contains(EventClass::Generic));
+
+        // classes without a channel type publish nothing
+        let events = KeyspaceEvents::parse(b"A").unwrap();
+        assert!(!events.contains(EventClass::Generic));
+        assert_eq!(KeyspaceEvents::parse(b"").unwrap().to_string(), "");
+        assert_eq!(KeyspaceEvents::parse(b"Kq"), None);
+    }
+
+    #[test]
+    fn test_notify_keyspace_event() {
+        let backend = Backend::new();
+        let (sender, mut messages) = mpsc::unbounded_channel();
+        let subscriber = Subscriber { id: 1, sender };
+        backend.psubscribe("__key*__:*".into(), &subscriber);
+        backend.subscribe("__keyevent@0__:del".into(), &subscriber);
+
+        // disabled by default
+        backend.notify_keyspace_event(EventClass::Generic, "del", b"key");
+        assert!(messages.try_recv().is_err());
+
+        backend
+            .notifications
+            .set(KeyspaceEvents::parse(b"Kg").unwrap());
+        backend.notify_keyspace_event(EventClass::String, "set", b"key");
+        assert!(messages.try_recv().is_err());
+        backend.notify_keyspace_event(EventClass::Generic, "del", b"key");
+        let RespFrame::Push(push) = messages.try_recv().unwrap() else {
+            panic!("expected a push frame");
+        };
+        assert_eq!(push[2], BulkString::new("__keyspace@0__:key").into());
+        assert_eq!(push[3], BulkString::new("del").into());
+        assert!(messages.try_recv().is_err());
+
+        backend
+            .notifications
+            .set(KeyspaceEvents::parse(b"Eg").unwrap());
+        backend.notify_keyspace_event(EventClass::Generic, "del", b"key");
+        assert_eq!(
+            messages.try_recv().unwrap(),
+            message("__keyevent@0__:del", "key")
+        );
+    }
+}
@@ -0,0 +1,292 @@
+use super::Backend;
+use crate::glob::glob_match;
+use crate::{BulkString, RespFrame, RespPush};
+use bytes::Bytes;
+use dashmap::DashMap;
+use std::collections::HashMap;
+use tokio::sync::mpsc;
+
+/// The queue of the messages delivered to a client, as push frames.
+pub type MessageSender = mpsc::UnboundedSender<RespFrame>;
+
+/// The subscribers of each channel or pattern, by client id.
+type Subscribers = DashMap<Bytes, HashMap<u64, MessageSender>>;
+
+/// The channels and patterns clients are subscribed to.
+#[derive(Debug, Default)]
+pub(crate) struct PubSub {
+    channels: Subscribers,
+    /// The subscribers of each glob-style pattern.
+    patterns: Subscribers,
+    /// The subscribers of each shard channel, a namespace apart from the other channels
+    /// whose messages stay within the shard owning the slot of the channel.
+    shard_channels: Subscribers,
+}
+
+/// A client able to subscribe to channels: its id, and the queue its messages are
+/// delivered to.
+#[derive(Debug, Clone)]
+pub struct Subscriber {
+    pub id: u64,
+    pub sender: MessageSender,
+}
+
+/// Adds `subscriber` to the subscribers of `name`.
+///
+/// Returns `false` if it was already subscribed.
+fn add(map: &Subscribers, name: Bytes, subscriber: &Subscriber) -> bool {
+    map.entry(name)
+        .or_default()
+        .insert(subscriber.id, subscriber.sender.clone())
+        .is_none()
+}
+
+/// Removes the client `id` from the subscribers of `name`.
+///
+/// Returns `false` if it was not subscribed.
+fn remove(map: &Subscribers, name: &[u8], id: u64) -> bool {
+    let removed = map
+        .get_mut(name)
+        .is_some_and(|mut subscribers| subscribers.remove(&id).is_some());
+    map.remove_if(name, |_, subscribers| subscribers.is_empty());
+    removed
+}
+
+/// Sends `push` to each of `subscribers`, and returns the number of them it was sent to.
+fn deliver(subscribers: &HashMap<u64, MessageSender>, push: RespFrame) -> usize {
+    subscribers
+        .values()
+        // the client may have disconnected without unsubscribing yet
+        .filter(|sender| sender.send(push.clone()).is_ok())
+        .count()
+}
+
+/// Returns the channels of `map` matching `pattern` if given, sorted.
+fn channels(map: &Subscribers, pattern: Option<&[u8]>) -> Vec<Bytes> {
+    let mut channels = map
+        .iter()
+        .map(|entry| entry.key().clone())
+        .filter(|channel| pattern.is_none_or(|pattern| glob_match(pattern, channel)))
+        .collect::<Vec<_>>();
+    channels.sort_unstable();
+    channels
+}
+
+impl Backend {
+    /// Subscribes a client to `channel`.
+    ///
+    /// Returns `false` if it was already subscribed.
+    pub fn subscribe(&self, channel: Bytes, subscriber: &Subscriber) -> bool {
+        add(&self.pubsub.channels, channel, subscriber)
+    }
+
+    /// Unsubscribes the client `id` from `channel`.
+    ///
+    /// Returns `false` if it was not subscribed.
+    pub fn unsubscribe(&self, channel: &[u8], id: u64) -> bool {
+        remove(&self.pubsub.channels, channel, id)
+    }
+
+    /// Subscribes a client to the channels matching the glob-style `pattern`.
+    ///
+    /// Returns `false` if it was already subscribed.
+    pub fn psubscribe(&self, pattern: Bytes, subscriber: &Subscriber) -> bool {
+        add(&self.pubsub.patterns, pattern, subscriber)
+    }
+
+    /// Unsubscribes the client `id` from `pattern`.
+    ///
+    /// Returns `false` if it was not subscribed.
+    pub fn punsubscribe(&self, pattern: &[u8], id: u64) -> bool {
+        remove(&self.pubsub.patterns, pattern, id)
+    }
+
+    /// Delivers `message` to the subscribers of `channel`, then to the subscribers of the
+    /// patterns matching it.
+    ///
+    /// Returns the number of clients the message was delivered to, counting a client once
+    /// for each of its matching subscriptions.
+    pub fn publish(&self, channel: &[u8], message: &[u8]) -> usize {
+        let mut receivers = 0;
+        if let Some(subscribers) = self.pubsub.channels.get(channel) {
+            let push = RespPush::new([
+                BulkString::new("message").into(),
+                BulkString::new(channel).into(),
+                BulkString::new(message).into(),
+            ]);
+            receivers += deliver(&subscribers, push.into());
+        }
+        for entry in self.pubsub.patterns.iter() {
+            let (pattern, subscribers) = entry.pair();
+            if !glob_match(pattern, channel) {
+                continue;
+            }
+            let push = RespPush::new([
+                BulkString::new("pmessage").into(),
+                BulkString::new(pattern.clone()).into(),
+                BulkString::new(channel).into(),
+                BulkString::new(message).into(),
+            ]);
+            receivers += deliver(subscribers, push.into());
+        }
+        receivers
+    }
+
+    /// Returns the channels having subscribers, only those matching `pattern` if given.
+    pub fn pubsub_channels(&self, pattern: Option<&[u8]>) -> Vec<Bytes> {
+        channels(&self.pubsub.channels, pattern)
+    }
+
+    /// Returns the number of subscribers of `channel`, not counting pattern subscribers.
+    pub fn pubsub_numsub(&self, channel: &[u8]) -> usize {
+        self.pubsub
+            .channels
+            .get(channel)
+            .map_or(0, |subscribers| subscribers.len())
+    }
+
+    /// Returns the number of patterns having subscribers.
+    pub fn pubsub_numpat(&self) -> usize {
+        self.pubsub.patterns.len()
+    }
+
+    /// Subscribes a client to the shard channel `channel`.
+    ///
+    /// Returns `false` if it was already subscribed.
+    pub fn ssubscribe(&self, channel: Bytes, subscriber: &Subscriber) -> bool {
+        add(&self.pubsub.shard_channels, channel, subscriber)
+    }
+
+    /// Unsubscribes the client `id` from the shard channel `channel`.
+    ///
+    /// Returns `false` if it was not subscribed.
+    pub fn sunsubscribe(&self, channel: &[u8], id: u64) -> bool {
+        remove(&self.pubsub.shard_channels, channel, id)
+    }
+
+    /// Delivers `message` to the subscribers of the shard channel `channel`. Patterns never
+    /// match shard channels.
+    ///
+    /// This server owns every slot, so the message is delivered locally whatever the slot
+    /// of the channel. Returns the number of clients the message was delivered to.
+    pub fn spublish(&self, channel: &[u8], message: &[u8]) -> usize {
+        let Some(subscribers) = self.pubsub.shard_channels.get(channel) else {
+            return 0;
+        };
+        let push = RespPush::new([
+            BulkString::new("smessage").into(),
+            BulkString::new(channel).into(),
+            BulkString::new(message).into(),
+        ]);
+        deliver(&subscribers, push.into())
+    }
+
+    /// Returns the shard channels having subscribers, only those matching `pattern` if
+    /// given.
+    pub fn pubsub_shardchannels(&self, pattern: Option<&[u8]>) -> Vec<Bytes> {
+        channels(&self.pubsub.shard_channels, pattern)
+    }
+
+    /// Returns the number of subscribers of the shard channel `channel`.
+    pub fn pubsub_shardnumsub(&self, channel: &[u8]) -> usize {
+        self.pubsub
+            .shard_channels
+            .get(channel)
+            .map_or(0, |subscribers| subscribers.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subscriber(id: u64) -> (Subscriber, mpsc::UnboundedReceiver<RespFrame>) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        (Subscriber { id, sender }, receiver)
+    }
+
+    fn push(args: &[&str]) -> RespFrame {
+        RespPush::new(
+            args.iter()
+                .map(|arg| BulkString::new(*arg).into())
+                .collect::<Vec<RespFrame>>(),
+        )
+        .into()
+    }
+
+    #[test]
+    fn test_publish() {
+        let backend = Backend::new();
+        let (first, mut first_messages) = subscriber(1);
+        let (second, mut second_messages) = subscriber(2);
+        assert!(backend.subscribe("news".into(), &first));
+        assert!(!backend.subscribe("news".into(), &first));
+        assert!(backend.psubscribe("n*".into(), &first));
+        assert!(backend.psubscribe("n*".into(), &second));
+        assert!(backend.psubscribe("x*".into(), &second));
+
+        assert_eq!(backend.publish(b"news", b"hi"), 3);
+        assert_eq!(
+            first_messages.try_recv().unwrap(),
+            push(&["message", "news", "hi"])
+        );
+        assert_eq!(
+            first_messages.try_recv().unwrap(),
+            push(&["pmessage", "n*", "news", "hi"])
+        );
+        assert_eq!(
+            second_messages.try_recv().unwrap(),
+            push(&["pmessage", "n*", "news", "hi"])
+        );
+        assert!(second_messages.try_recv().is_err());
+
+        assert_eq!(backend.pubsub_channels(None), vec![Bytes::from("news")]);
+        assert!(backend.pubsub_channels(Some(b"x*")).is_empty());
+        assert_eq!(backend.pubsub_numsub(b"news"), 1);
+        assert_eq!(backend.pubsub_numpat(), 2);
+
+        // a disconnected client is not counted
+        drop(second_messages);
+        assert_eq!(backend.publish(b"news", b"hi"), 2);
+
+        assert!(backend.unsubscribe(b"news", 1));
+        assert!(!backend.unsubscribe(b"news", 1));
+        assert!(backend.punsubscribe(b"n*", 2));
+        assert!(backend.pubsub_channels(None).is_empty());
+        assert_eq!(backend.pubsub_numpat(), 2);
+    }
+
+    #[test]
+    fn test_spublish() {
+        let backend = Backend::new();
+        let (first, mut first_messages) = subscriber(1);
+        let (second, mut second_messages) = subscriber(2);
+        assert!(backend.ssubscribe("news".into(), &first));
+        assert!(!backend.ssubscribe("news".into(), &first));
+        assert!(backend.subscribe("news".into(), &second));
+        assert!(backend.psubscribe("*".into(), &second));
+
+        // shard channels and the other channels are apart
+        assert_eq!(backend.spublish(b"news", b"hi"), 1);
+        assert_eq!(
+            first_messages.try_recv().unwrap(),
+            push(&["smessage", "news", "hi"])
+        );
+        assert!(second_messages.try_recv().is_err());
+        assert_eq!(backend.publish(b"news", b"hi"), 2);
+        assert!(first_messages.try_recv().is_err());
+
+        assert_eq!(
+            backend.pubsub_shardchannels(None),
+            vec![Bytes::from("news")]
+        );
+        assert!(backend.pubsub_shardchannels(Some(b"x*")).is_empty());
+        assert_eq!(backend.pubsub_shardnumsub(b"news"), 1);
+        assert_eq!(backend.pubsub_numsub(b"news"), 1);
+
+        assert!(backend.sunsubscribe(b"news", 1));
+        assert!(!backend.sunsubscribe(b"news", 1));
+        assert!(backend.pubsub_shardchannels(None).is_empty());
+        assert_eq!(backend.pubsub_channels(None), vec![Bytes::from("news")]);
+    }
+}
@@ -0,0 +1,265 @@
+use super::{Backend, HashFields};
+use crate::cmd::CommandError;
+use crate::glob::glob_match;
+use bytes::Bytes;
+use std::collections::BTreeSet;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::{PoisonError, RwLock};
+
+/// Number of shards of the `SCAN` order of the keyspace, a power of two.
+const SCAN_ORDER_SHARDS: usize = 64;
+
+/// Returns the position of a key in the iteration order of `SCAN`.
+///
+/// The order is that of a hash with fixed keys, so it is the same for every call and does
+/// not depend on where the key lives in the map.
+fn scan_hash(key: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Keys in the order of their `scan_hash`, which is the order `SCAN` visits them in.
+///
+/// The cursor of an iteration is the hash to resume from, so it stays meaningful no matter
+/// how the keys are mutated between calls: a key present for the whole iteration is
+/// returned exactly once, while keys added or removed in the meantime may or may not be.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct ScanOrder(BTreeSet<(u64, Bytes)>);
+
+impl ScanOrder {
+    pub(crate) fn insert(&mut self, key: &Bytes) {
+        self.0.insert((scan_hash(key), key.clone()));
+    }
+
+    pub(crate) fn remove(&mut self, key: &[u8]) {
+        self.0
+            .remove(&(scan_hash(key), Bytes::copy_from_slice(key)));
+    }
+
+    /// Appends the keys whose hash is not below `cursor` to `batch`, until it holds at
+    /// least `count` keys and all the keys sharing the hash of the last one.
+    ///
+    /// Returns the cursor to resume from, `None` if no key is left.
+    fn batch(&self, cursor: u64, count: usize, batch: &mut Vec<Bytes>) -> Option<u64> {
+        let mut keys = self.0.range((cursor, Bytes::new())..).peekable();
+        while let Some((hash, key)) = keys.next() {
+            batch.push(key.clone());
+            let next = keys.peek().map(|(next, _)| *next);
+            if batch.len() >= count && next != Some(*hash) {
+                return next.map(|_| hash + 1);
+            }
+        }
+        None
+    }
+
+    /// Returns the next batch of about `count` keys of an iteration starting at `cursor`.
+    ///
+    /// Returns the cursor of the next call, `0` once the iteration is complete, and the keys.
+    pub(crate) fn scan(&self, cursor: u64, count: usize) -> (u64, Vec<Bytes>) {
+        let mut batch = Vec::new();
+        let cursor = self.batch(cursor, count.max(1), &mut batch);
+        (cursor.unwrap_or_default(), batch)
+    }
+}
+
+/// The `SCAN` order of the keys of the keyspace, or of a subset of them such as the volatile
+/// keys, split into shards covering equal ranges of hashes so that keys can be added and
+/// removed concurrently.
+#[derive(Debug)]
+pub(crate) struct KeyspaceOrder {
+    shards: Box<[RwLock<ScanOrder>]>,
+}
+
+impl Default for KeyspaceOrder {
+    fn default() -> Self {
+        Self {
+            shards: (0..SCAN_ORDER_SHARDS).map(|_| RwLock::default()).collect(),
+        }
+    }
+}
+
+impl KeyspaceOrder {
+    /// The shard of a key is given by the top bits of its hash.
+    const SHARD_SHIFT: u32 = u64::BITS - SCAN_ORDER_SHARDS.trailing_zeros();
+
+    /// Returns the index of the shard holding the keys with the given hash.
+    fn shard(hash: u64) -> usize {
+        (hash >> Self::SHARD_SHIFT) as usize
+    }
+
+    /// Adds a key, which must be done while holding the keyspace entry of the key, so that
+    /// it can't race with the key being removed.
+    pub(crate) fn insert(&self, key: &Bytes) {
+        self.shards[Self::shard(scan_hash(key))]
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(key);
+    }
+
+    /// Removes a key, which must be done while holding the keyspace entry of the key.
+    pub(crate) fn remove(&self, key: &[u8]) {
+        self.shards[Self::shard(scan_hash(key))]
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(key);
+    }
+
+    /// Returns the next batch of about `count` keys of an iteration starting at `cursor`,
+    /// read from as few shards as needed.
+    ///
+    /// Returns the cursor of the next call, `0` once the iteration is complete, and the keys.
+    pub(crate) fn scan(&self, cursor: u64, count: usize) -> (u64, Vec<Bytes>) {
+        let count = count.max(1);
+        let mut batch = Vec::new();
+        let first = Self::shard(cursor);
+        for (i, shard) in self.shards.iter().enumerate().skip(first) {
+            let shard = shard.read().unwrap_or_else(PoisonError::into_inner);
+            if let Some(next) = shard.batch(cursor, count, &mut batch) {
+                return (next, batch);
+            }
+            if batch.len() >= count && i + 1 < self.shards.len() {
+                // resume at the first hash of the next shard
+                return (((i + 1) as u64) << Self::SHARD_SHIFT, batch);
+            }
+        }
+        (0, batch)
+    }
+}
+
+impl Backend {
+    /// Returns every key matching the glob `pattern`.
+    pub fn keys(&self, pattern: &[u8]) -> Vec<Bytes> {
+        let keys = self
+            .keyspace
+            .iter()
+            .filter(|entry| glob_match(pattern, entry.key()))
+            .map(|entry| entry.key().clone())
+            .collect::<Vec<_>>();
+        // drop the shard locks of the iterator before expiring keys
+        keys.into_iter()
+            .filter(|key| !self.expire_if_needed(key))
+            .collect()
+    }
+
+    /// Returns the next batch of keys of a `SCAN` iteration starting at `cursor`.
+    ///
+    /// About `count` keys are visited per call. The visited keys are then filtered by the
+    /// glob `pattern` and the type name `key_type`, so fewer keys may be returned, possibly
+    /// none while the iteration is not complete yet.
+    ///
+    /// Returns the cursor of the next call, `0` once the iteration is complete, and the keys.
+    pub fn scan(
+        &self,
+        cursor: u64,
+        count: usize,
+        pattern: Option<&[u8]>,
+        key_type: Option<&str>,
+    ) -> (u64, Vec<Bytes>) {
+        let (cursor, batch) = self.scan_order.scan(cursor, count);
+        let keys = batch
+            .into_iter()
+            .filter(|key| {
+                pattern.is_none_or(|pattern| glob_match(pattern, key))
+                    && !self.expire_if_needed(key)
+                    && key_type.is_none_or(|key_type| {
+                        self.key_type(key)
+                            .is_some_and(|type_name| key_type.eq_ignore_ascii_case(type_name))
+                    })
+            })
+            .collect();
+        (cursor, keys)
+    }
+
+    /// Returns the next batch of field-value pairs of an `HSCAN` iteration over the hash at
+    /// `key`, with the same guarantees as `scan`.
+    ///
+    /// The pairs are filtered by matching the field against the glob `pattern`.
+    pub fn hscan(
+        &self,
+        key: &[u8],
+        cursor: u64,
+        count: usize,
+        pattern: Option<&[u8]>,
+    ) -> Result<(u64, Vec<(Bytes, Bytes)>), CommandError> {
+        let batch = self.read(key, |h: &HashFields| h.scan(cursor, count))?;
+        let (cursor, batch) = batch.unwrap_or_default();
+        let pairs = batch
+            .into_iter()
+            .filter(|(field, _)| pattern.is_none_or(|pattern| glob_match(pattern, field)))
+            .collect();
+        Ok((cursor, pairs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SetExpiry;
+
+    /// Returns every key of the `SCAN` order of the keyspace, sorted.
+    fn ordered_keys(backend: &Backend) -> Vec<Bytes> {
+        let (cursor, mut keys) = backend.scan_order.scan(0, usize::MAX);
+        assert_eq!(cursor, 0);
+        keys.sort();
+        keys
+    }
+
+    #[test]
+    fn test_scan_order_follows_keyspace() {
+        let backend = Backend::new();
+        for key in ["a", "b", "c", "d", "e"] {
+            backend.set(key.into(), "1".into());
+        }
+        backend.hset("h".into(), "f".into(), "v".into()).unwrap();
+        backend.del(b"a");
+        backend.unlink(b"b");
+        backend.rename(b"c", "r".into(), false).unwrap();
+        backend.copy(b"d", "cp".into(), false);
+        backend.getdel(&"e".into()).unwrap();
+        backend.hdel(&"h".into(), &["f".into()]).unwrap();
+        backend.incr_by(&"n".into(), 1).unwrap();
+        backend
+            .set_with(
+                "x".into(),
+                "1".into(),
+                Default::default(),
+                SetExpiry::At(1),
+                false,
+            )
+            .unwrap();
+        assert!(!backend.contains_key(b"x"));
+
+        let mut keys = backend.keys(b"*");
+        keys.sort();
+        assert_eq!(keys, ["cp", "d", "n", "r"].map(Bytes::from));
+        assert_eq!(ordered_keys(&backend), keys);
+    }
+
+    #[test]
+    fn test_scan_crosses_shards() {
+        let backend = Backend::new();
+        for i in 0..1000 {
+            backend.set(format!("k{}", i).into(), "1".into());
+        }
+        let mut cursor = 0;
+        let mut keys = Vec::new();
+        loop {
+            let (next, batch) = backend.scan_order.scan(cursor, 10);
+            assert!(next == 0 || batch.len() >= 10);
+            // the keys come in the order of their hash
+            assert!(batch
+                .windows(2)
+                .all(|w| scan_hash(&w[0]) <= scan_hash(&w[1])));
+            keys.extend(batch);
+            if next == 0 {
+                break;
+            }
+            assert!(next > cursor);
+            cursor = next;
+        }
+        keys.sort();
+        assert_eq!(keys, ordered_keys(&backend));
+        assert_eq!(keys.len(), 1000);
+    }
+}
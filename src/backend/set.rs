@@ -0,0 +1,236 @@
+use super::Backend;
+use crate::cmd::CommandError;
+use bytes::Bytes;
+use rand::seq::IteratorRandom;
+use rand::Rng;
+use std::collections::HashSet;
+
+type Set = HashSet<Bytes>;
+
+impl Backend {
+    /// Adds members to the set at `key`, creating the set if needed.
+    ///
+    /// Returns the number of members that were added, not counting those already present.
+    pub fn sadd(&self, key: &Bytes, members: Vec<Bytes>) -> Result<usize, CommandError> {
+        let added = self.write(key, true, |s: &mut Set| {
+            members
+                .into_iter()
+                .filter(|member| s.insert(member.clone()))
+                .count()
+        })?;
+        Ok(added.unwrap_or_default())
+    }
+
+    /// Removes members from a set. The set is removed from the keyspace once it is empty.
+    ///
+    /// Returns the number of members that were removed.
+    pub fn srem(&self, key: &Bytes, members: &[Bytes]) -> Result<usize, CommandError> {
+        let removed = self.write(key, false, |s: &mut Set| {
+            members.iter().filter(|member| s.remove(*member)).count()
+        })?;
+        Ok(removed.unwrap_or_default())
+    }
+
+    /// Returns the members of the set, none if the key does not exist.
+    pub fn smembers(&self, key: &[u8]) -> Result<Vec<Bytes>, CommandError> {
+        let members = self.read(key, |s: &Set| s.iter().cloned().collect())?;
+        Ok(members.unwrap_or_default())
+    }
+
+    /// Returns `true` if `member` belongs to the set.
+    pub fn sismember(&self, key: &[u8], member: &[u8]) -> Result<bool, CommandError> {
+        let exists = self.read(key, |s: &Set| s.contains(member))?;
+        Ok(exists.unwrap_or_default())
+    }
+
+    /// Returns whether each of the given members belongs to the set.
+    pub fn smismember(&self, key: &[u8], members: &[Bytes]) -> Result<Vec<bool>, CommandError> {
+        let exists = self.read(key, |s: &Set| {
+            members.iter().map(|member| s.contains(member)).collect()
+        })?;
+        Ok(exists.unwrap_or_else(|| vec![false; members.len()]))
+    }
+
+    /// Returns the number of members of the set, `0` if the key does not exist.
+    pub fn scard(&self, key: &[u8]) -> Result<usize, CommandError> {
+        let len = self.read(key, |s: &Set| s.len())?;
+        Ok(len.unwrap_or_default())
+    }
+
+    /// Removes up to `count` random members from the set and returns them.
+    pub fn spop(&self, key: &Bytes, count: usize) -> Result<Vec<Bytes>, CommandError> {
+        let popped = self.write(key, false, |s: &mut Set| {
+            let popped = s
+                .iter()
+                .choose_multiple(&mut rand::rng(), count)
+                .into_iter()
+                .cloned()
+                .collect::<Vec<_>>();
+            for member in &popped {
+                s.remove(member);
+            }
+            popped
+        })?;
+        Ok(popped.unwrap_or_default())
+    }
+
+    /// Returns random members of the set, as done by `SRANDMEMBER`.
+    ///
+    /// With a positive `count`, up to `count` distinct members are returned. With a
+    /// negative `count`, exactly `-count` members are returned, possibly repeated.
+    pub fn srandmember(&self, key: &[u8], count: i64) -> Result<Vec<Bytes>, CommandError> {
+        let members = self.read(key, |s: &Set| {
+            let mut rng = rand::rng();
+            if count >= 0 {
+                s.iter()
+                    .choose_multiple(&mut rng, count as usize)
+                    .into_iter()
+                    .cloned()
+                    .collect()
+            } else {
+                let members = s.iter().collect::<Vec<_>>();
+                (0..count.unsigned_abs())
+                    .map(|_| members[rng.random_range(0..members.len())].clone())
+                    .collect()
+            }
+        })?;
+        Ok(members.unwrap_or_default())
+    }
+
+    /// Returns the members common to all the sets at `keys`, a missing key being an empty
+    /// set.
+    ///
+    /// The smallest set is copied and then narrowed down by each of the others, so the cost
+    /// is bound by the size of the smallest set.
+    pub fn sinter(&self, keys: &[Bytes]) -> Result<Vec<Bytes>, CommandError> {
+        Ok(self.inter(keys)?.into_iter().collect())
+    }
+
+    /// Returns the members of any of the sets at `keys`.
+    pub fn sunion(&self, keys: &[Bytes]) -> Result<Vec<Bytes>, CommandError> {
+        Ok(self.union(keys)?.into_iter().collect())
+    }
+
+    /// Returns the members of the first set at `keys` that are in none of the others.
+    pub fn sdiff(&self, keys: &[Bytes]) -> Result<Vec<Bytes>, CommandError> {
+        Ok(self.diff(keys)?.into_iter().collect())
+    }
+
+    /// Stores the intersection of the sets at `keys` at `destination`, replacing any value.
+    ///
+    /// Returns the number of members of the stored set. An empty result removes the
+    /// destination.
+    pub fn sinterstore(&self, destination: Bytes, keys: &[Bytes]) -> Result<usize, CommandError> {
+        let set = self.inter(keys)?;
+        let len = set.len();
+        self.store(destination, set);
+        Ok(len)
+    }
+
+    /// Stores the union of the sets at `keys` at `destination`, as done by `sinterstore`.
+    pub fn sunionstore(&self, destination: Bytes, keys: &[Bytes]) -> Result<usize, CommandError> {
+        let set = self.union(keys)?;
+        let len = set.len();
+        self.store(destination, set);
+        Ok(len)
+    }
+
+    /// Stores the difference of the sets at `keys` at `destination`, as done by
+    /// `sinterstore`.
+    pub fn sdiffstore(&self, destination: Bytes, keys: &[Bytes]) -> Result<usize, CommandError> {
+        let set = self.diff(keys)?;
+        let len = set.len();
+        self.store(destination, set);
+        Ok(len)
+    }
+
+    /// Returns the number of members of the intersection of the sets at `keys`, counting
+    /// no further than `limit` if it is not `0`.
+    pub fn sintercard(&self, keys: &[Bytes], limit: usize) -> Result<usize, CommandError> {
+        let len = self.inter(keys)?.len();
+        Ok(if limit == 0 { len } else { len.min(limit) })
+    }
+
+    /// Moves `member` from the set at `source` to the set at `destination`.
+    ///
+    /// Returns `false` if `member` is not in the source set. Both keys must hold sets, or
+    /// not exist.
+    pub fn smove(
+        &self,
+        source: &Bytes,
+        destination: &Bytes,
+        member: Bytes,
+    ) -> Result<bool, CommandError> {
+        self.read(destination, |_: &Set| ())?;
+        if source == destination {
+            return self.sismember(source, &member);
+        }
+        if self.srem(source, std::slice::from_ref(&member))? == 0 {
+            return Ok(false);
+        }
+        self.sadd(destination, vec![member])?;
+        Ok(true)
+    }
+
+    fn inter(&self, keys: &[Bytes]) -> Result<Set, CommandError> {
+        let Some((smallest, others)) = self.smallest_first(keys)? else {
+            return Ok(Set::new());
+        };
+        let mut members = self
+            .read(&smallest, |s: &Set| s.clone())?
+            .unwrap_or_default();
+        for key in others {
+            if members.is_empty() {
+                break;
+            }
+            self.read(&key, |s: &Set| members.retain(|member| s.contains(member)))?;
+        }
+        Ok(members)
+    }
+
+    fn union(&self, keys: &[Bytes]) -> Result<Set, CommandError> {
+        let mut members = Set::new();
+        for key in keys {
+            self.read(key, |s: &Set| members.extend(s.iter().cloned()))?;
+        }
+        Ok(members)
+    }
+
+    fn diff(&self, keys: &[Bytes]) -> Result<Set, CommandError> {
+        let Some((first, others)) = keys.split_first() else {
+            return Ok(Set::new());
+        };
+        let mut members = self.read(first, |s: &Set| s.clone())?.unwrap_or_default();
+        for key in others {
+            self.read(key, |s: &Set| {
+                if s.len() < members.len() {
+                    for member in s {
+                        members.remove(member);
+                    }
+                } else {
+                    members.retain(|member| !s.contains(member));
+                }
+            })?;
+        }
+        Ok(members)
+    }
+
+    /// Orders `keys` by the size of their set, checking they all hold sets.
+    ///
+    /// Returns `None` if one of the keys does not exist, since the intersection is then
+    /// empty, or the smallest set followed by the others otherwise.
+    fn smallest_first(&self, keys: &[Bytes]) -> Result<Option<(Bytes, Vec<Bytes>)>, CommandError> {
+        let mut sizes = Vec::with_capacity(keys.len());
+        for key in keys {
+            sizes.push((self.scard(key)?, key.clone()));
+        }
+        sizes.sort_by_key(|(len, _)| *len);
+        let mut keys = sizes.into_iter();
+        match keys.next() {
+            Some((len, smallest)) if len > 0 => {
+                Ok(Some((smallest, keys.map(|(_, key)| key).collect())))
+            }
+            _ => Ok(None),
+        }
+    }
+}
@@ -0,0 +1,376 @@
+use bytes::Bytes;
+use rand::Rng;
+
+/// Maximum number of levels of a node, enough for 2^64 elements with `P = 1/4`.
+const MAX_LEVEL: usize = 32;
+/// Probability for a node of level `n` to also be of level `n + 1`.
+const P: f64 = 0.25;
+/// The header node, which holds no element and starts every level.
+const HEAD: usize = 0;
+
+/// A skiplist of members ordered by score, then by member, as used by sorted sets.
+///
+/// Every link records its span, the number of elements it skips over, so the rank of an
+/// element and the element at a rank are both found in O(log n). Nodes live in an arena
+/// and refer to each other by index; the slots of removed nodes are reused.
+#[derive(Debug, Clone)]
+pub(crate) struct SkipList {
+    nodes: Vec<Node>,
+    free: Vec<usize>,
+    len: usize,
+    level: usize,
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+    member: Bytes,
+    score: f64,
+    prev: Option<usize>,
+    levels: Vec<Link>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Link {
+    next: Option<usize>,
+    span: usize,
+}
+
+impl Node {
+    /// Returns `true` if the node sorts before `(score, member)`.
+    fn is_before(&self, score: f64, member: &[u8]) -> bool {
+        self.score < score || (self.score == score && self.member.as_ref() < member)
+    }
+}
+
+impl Default for SkipList {
+    fn default() -> Self {
+        let head = Node {
+            member: Bytes::new(),
+            score: 0.0,
+            prev: None,
+            levels: vec![Link::default(); MAX_LEVEL],
+        };
+        Self {
+            nodes: vec![head],
+            free: Vec::new(),
+            len: 0,
+            level: 1,
+        }
+    }
+}
+
+impl SkipList {
+    /// Inserts a member, which must not already be in the list.
+    pub(crate) fn insert(&mut self, score: f64, member: Bytes) {
+        let mut update = [HEAD; MAX_LEVEL];
+        let mut rank = [0; MAX_LEVEL];
+        let mut x = HEAD;
+        for i in (0..self.level).rev() {
+            rank[i] = if i + 1 == self.level { 0 } else { rank[i + 1] };
+            while let Some(next) = self.nodes[x].levels[i].next {
+                if !self.nodes[next].is_before(score, &member) {
+                    break;
+                }
+                rank[i] += self.nodes[x].levels[i].span;
+                x = next;
+            }
+            update[i] = x;
+        }
+
+        let level = random_level();
+        if level > self.level {
+            for i in self.level..level {
+                rank[i] = 0;
+                update[i] = HEAD;
+                self.nodes[HEAD].levels[i] = Link {
+                    next: None,
+                    span: self.len,
+                };
+            }
+            self.level = level;
+        }
+
+        let node = self.alloc(Node {
+            member,
+            score,
+            prev: (update[0] != HEAD).then_some(update[0]),
+            levels: vec![Link::default(); level],
+        });
+        for i in 0..level {
+            let link = self.nodes[update[i]].levels[i];
+            self.nodes[node].levels[i] = Link {
+                next: link.next,
+                span: link.span - (rank[0] - rank[i]),
+            };
+            self.nodes[update[i]].levels[i] = Link {
+                next: Some(node),
+                span: rank[0] - rank[i] + 1,
+            };
+        }
+        for (i, &x) in update.iter().enumerate().take(self.level).skip(level) {
+            self.nodes[x].levels[i].span += 1;
+        }
+        if let Some(next) = self.nodes[node].levels[0].next {
+            self.nodes[next].prev = Some(node);
+        }
+        self.len += 1;
+    }
+
+    /// Removes a member with the given score.
+    ///
+    /// Returns `false` if the member is not in the list with that score.
+    pub(crate) fn remove(&mut self, score: f64, member: &[u8]) -> bool {
+        let mut update = [HEAD; MAX_LEVEL];
+        let mut x = HEAD;
+        for i in (0..self.level).rev() {
+            while let Some(next) = self.nodes[x].levels[i].next {
+                if !self.nodes[next].is_before(score, member) {
+                    break;
+                }
+                x = next;
+            }
+            update[i] = x;
+        }
+        match self.nodes[x].levels[0].next {
+            Some(node)
+                if self.nodes[node].score == score
+                    && self.nodes[node].member.as_ref() == member =>
+            {
+                self.unlink(node, &update);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the 0-based rank of a member with the given score.
+    pub(crate) fn rank(&self, score: f64, member: &[u8]) -> Option<usize> {
+        let mut rank = 0;
+        let mut x = HEAD;
+        for i in (0..self.level).rev() {
+            while let Some(next) = self.nodes[x].levels[i].next {
+                let node = &self.nodes[next];
+                if !(node.is_before(score, member)
+                    || (node.score == score && node.member.as_ref() == member))
+                {
+                    break;
+                }
+                rank += self.nodes[x].levels[i].span;
+                x = next;
+            }
+            if x != HEAD && self.nodes[x].member.as_ref() == member {
+                return Some(rank - 1);
+            }
+        }
+        None
+    }
+
+    /// Returns the node at a 0-based rank.
+    pub(crate) fn at_rank(&self, rank: usize) -> Option<usize> {
+        let target = rank + 1;
+        let mut traversed = 0;
+        let mut x = HEAD;
+        for i in (0..self.level).rev() {
+            while let Some(next) = self.nodes[x].levels[i].next {
+                if traversed + self.nodes[x].levels[i].span > target {
+                    break;
+                }
+                traversed += self.nodes[x].levels[i].span;
+                x = next;
+            }
+            if traversed == target {
+                return Some(x);
+            }
+        }
+        None
+    }
+
+    /// Returns the rank and the node of the first element for which `before` is `false`.
+    ///
+    /// `before` must hold for a prefix of the list only, e.g. the elements below the lower
+    /// bound of a range.
+    pub(crate) fn first_after(
+        &self,
+        before: impl Fn(f64, &Bytes) -> bool,
+    ) -> Option<(usize, usize)> {
+        let mut rank = 0;
+        let mut x = HEAD;
+        for i in (0..self.level).rev() {
+            while let Some(next) = self.nodes[x].levels[i].next {
+                if !before(self.nodes[next].score, &self.nodes[next].member) {
+                    break;
+                }
+                rank += self.nodes[x].levels[i].span;
+                x = next;
+            }
+        }
+        self.nodes[x].levels[0].next.map(|node| (rank, node))
+    }
+
+    /// Returns the rank and the node of the last element for which `within` is `true`.
+    ///
+    /// `within` must hold for a prefix of the list only, e.g. the elements up to the upper
+    /// bound of a range.
+    pub(crate) fn last_within(
+        &self,
+        within: impl Fn(f64, &Bytes) -> bool,
+    ) -> Option<(usize, usize)> {
+        let mut rank = 0;
+        let mut x = HEAD;
+        for i in (0..self.level).rev() {
+            while let Some(next) = self.nodes[x].levels[i].next {
+                if !within(self.nodes[next].score, &self.nodes[next].member) {
+                    break;
+                }
+                rank += self.nodes[x].levels[i].span;
+                x = next;
+            }
+        }
+        (x != HEAD).then(|| (rank - 1, x))
+    }
+
+    /// Returns the score and the member of a node.
+    pub(crate) fn get(&self, node: usize) -> (f64, &Bytes) {
+        (self.nodes[node].score, &self.nodes[node].member)
+    }
+
+    /// Returns the node following `node`, or preceding it if `rev` is `true`.
+    pub(crate) fn step(&self, node: usize, rev: bool) -> Option<usize> {
+        if rev {
+            self.nodes[node].prev
+        } else {
+            self.nodes[node].levels[0].next
+        }
+    }
+
+    /// Iterates over the elements from `node` onwards, towards the tail or towards the head
+    /// if `rev` is `true`.
+    pub(crate) fn iter_from(
+        &self,
+        node: Option<usize>,
+        rev: bool,
+    ) -> impl Iterator<Item = (f64, &Bytes)> + '_ {
+        std::iter::successors(node, move |&node| self.step(node, rev)).map(|node| self.get(node))
+    }
+
+    /// Iterates over all the elements in order.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (f64, &Bytes)> + '_ {
+        self.iter_from(self.nodes[HEAD].levels[0].next, false)
+    }
+
+    fn alloc(&mut self, node: Node) -> usize {
+        match self.free.pop() {
+            Some(slot) => {
+                self.nodes[slot] = node;
+                slot
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    /// Unlinks `node`, given the rightmost node before it on every level.
+    fn unlink(&mut self, node: usize, update: &[usize; MAX_LEVEL]) {
+        for (i, &x) in update.iter().enumerate().take(self.level) {
+            if self.nodes[x].levels[i].next == Some(node) {
+                let link = self.nodes[node].levels[i];
+                self.nodes[x].levels[i] = Link {
+                    next: link.next,
+                    span: self.nodes[x].levels[i].span + link.span - 1,
+                };
+            } else {
+                self.nodes[x].levels[i].span -= 1;
+            }
+        }
+        if let Some(next) = self.nodes[node].levels[0].next {
+            self.nodes[next].prev = self.nodes[node].prev;
+        }
+        while self.level > 1 && self.nodes[HEAD].levels[self.level - 1].next.is_none() {
+            self.level -= 1;
+        }
+        // release the member and the links, the slot is reused by the next insertion
+        self.nodes[node] = Node {
+            member: Bytes::new(),
+            score: 0.0,
+            prev: None,
+            levels: Vec::new(),
+        };
+        self.free.push(node);
+        self.len -= 1;
+    }
+}
+
+fn random_level() -> usize {
+    let mut rng = rand::rng();
+    let mut level = 1;
+    while level < MAX_LEVEL && rng.random_bool(P) {
+        level += 1;
+    }
+    level
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn members(list: &SkipList) -> Vec<(f64, Bytes)> {
+        list.iter()
+            .map(|(score, member)| (score, member.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_skiplist_order_and_rank() {
+        let mut list = SkipList::default();
+        let mut expected = Vec::new();
+        for i in 0..1000 {
+            // scores repeat, so members with equal scores are ordered by name
+            let score = (i * 7 % 100) as f64;
+            let member = Bytes::from(format!("m{:04}", i));
+            list.insert(score, member.clone());
+            expected.push((score, member));
+        }
+        expected.sort_by(|a, b| a.0.total_cmp(&b.0).then_with(|| a.1.cmp(&b.1)));
+        assert_eq!(members(&list), expected);
+        assert_eq!(list.len, 1000);
+        for (rank, (score, member)) in expected.iter().enumerate() {
+            assert_eq!(list.rank(*score, member), Some(rank));
+            let node = list.at_rank(rank).unwrap();
+            assert_eq!(list.get(node), (*score, member));
+        }
+        assert_eq!(list.at_rank(1000), None);
+        assert_eq!(list.rank(1.0, b"missing"), None);
+
+        // remove every other element, then check the spans are still right
+        for (score, member) in expected.iter().step_by(2) {
+            assert!(list.remove(*score, member));
+        }
+        assert!(!list.remove(expected[0].0, &expected[0].1));
+        let expected = expected.into_iter().skip(1).step_by(2).collect::<Vec<_>>();
+        assert_eq!(members(&list), expected);
+        for (rank, (score, member)) in expected.iter().enumerate() {
+            assert_eq!(list.rank(*score, member), Some(rank));
+        }
+        let reversed = list
+            .iter_from(list.at_rank(list.len - 1), true)
+            .map(|(score, member)| (score, member.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(reversed, expected.iter().rev().cloned().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_skiplist_bounds() {
+        let mut list = SkipList::default();
+        for (score, member) in [(1.0, "a"), (2.0, "b"), (2.0, "c"), (3.0, "d")] {
+            list.insert(score, Bytes::from(member));
+        }
+        let (rank, node) = list.first_after(|score, _| score < 2.0).unwrap();
+        assert_eq!((rank, list.get(node).1.as_ref()), (1, b"b".as_ref()));
+        let (rank, node) = list.last_within(|score, _| score <= 2.0).unwrap();
+        assert_eq!((rank, list.get(node).1.as_ref()), (2, b"c".as_ref()));
+        assert!(list.first_after(|score, _| score < 4.0).is_none());
+        assert!(list.last_within(|score, _| score < 1.0).is_none());
+    }
+}
@@ -0,0 +1,1480 @@
+use super::{now_ms, Backend};
+use crate::cmd::CommandError;
+use bytes::Bytes;
+use std::collections::{btree_map, BTreeMap, BTreeSet};
+use std::fmt;
+use std::ops::Bound;
+
+/// The number of entries Redis packs into a node of a stream. Approximate trimming only
+/// removes whole nodes, so it removes entries by multiples of this.
+const STREAM_NODE_MAX_ENTRIES: usize = 100;
+
+/// The ID of a stream entry: a unix time in milliseconds and a sequence number
+/// distinguishing the entries added within the same millisecond, written `ms-seq`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct StreamId {
+    pub ms: u64,
+    pub seq: u64,
+}
+
+/// The fields and values of a stream entry, in the order they were given to `XADD`.
+pub type StreamFields = Vec<(Bytes, Bytes)>;
+
+/// Stream entries along with their ID, in ID order.
+pub type StreamEntries = Vec<(StreamId, StreamFields)>;
+
+/// How the ID of an entry added by `XADD` is chosen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XAddId {
+    /// `*`: the current time, or the last ID of the stream incremented if it is ahead.
+    Auto,
+    /// `ms-*`: the given time, with the next sequence number within that millisecond.
+    AutoSeq(u64),
+    Explicit(StreamId),
+}
+
+/// Which entries trimming evicts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrimStrategy {
+    /// Evict the oldest entries until at most this many are left.
+    MaxLen(usize),
+    /// Evict the entries whose ID is lower than this one.
+    MinId(StreamId),
+}
+
+/// The trimming options of `XADD` and `XTRIM`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StreamTrim {
+    pub strategy: TrimStrategy,
+    /// `~`: only evict whole nodes of entries, which may leave a few more entries than
+    /// asked for.
+    pub approximate: bool,
+    /// The maximum number of entries evicted by approximate trimming, `0` for no limit.
+    pub limit: Option<usize>,
+}
+
+/// The entries read from a stream by a consumer of a group. The entries deleted from the
+/// stream while pending have no fields.
+pub type GroupEntries = Vec<(StreamId, Option<StreamFields>)>;
+
+/// A consumer group of a stream: a cursor into the stream shared by its consumers, along
+/// with the entries delivered to them and not acknowledged yet.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConsumerGroup {
+    /// The ID of the last entry delivered to the group.
+    last_id: StreamId,
+    /// The number of entries the group has read, if it can be told.
+    entries_read: Option<u64>,
+    /// The pending entries list: the entries delivered and not acknowledged yet.
+    pending: BTreeMap<StreamId, PendingEntry>,
+    consumers: BTreeMap<Bytes, Consumer>,
+}
+
+/// An entry delivered to a consumer of a group and not acknowledged yet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PendingEntry {
+    consumer: Bytes,
+    /// The unix time in milliseconds of the last delivery.
+    delivery_time: u64,
+    delivery_count: u64,
+}
+
+/// A consumer of a group, created the first time it is named.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Consumer {
+    /// The unix time in milliseconds of the last attempted interaction, e.g. a read.
+    seen_time: u64,
+    /// The unix time in milliseconds of the last interaction which delivered or claimed
+    /// entries.
+    active_time: Option<u64>,
+    /// The IDs of the entries pending for this consumer.
+    pending: BTreeSet<StreamId>,
+}
+
+/// A pending entry, as listed by `XPENDING`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingInfo {
+    pub id: StreamId,
+    pub consumer: Bytes,
+    /// The milliseconds elapsed since the last delivery.
+    pub idle: u64,
+    pub delivery_count: u64,
+}
+
+/// The summary of the pending entries of a group, as returned by `XPENDING`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PendingSummary {
+    pub count: usize,
+    /// The lowest and the highest pending IDs.
+    pub bounds: Option<(StreamId, StreamId)>,
+    /// The number of pending entries of each consumer having some.
+    pub consumers: Vec<(Bytes, usize)>,
+}
+
+/// Which entries `XPENDING` lists.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingFilter {
+    pub start: StreamId,
+    pub end: StreamId,
+    pub count: usize,
+    pub consumer: Option<Bytes>,
+    /// The minimum idle time in milliseconds.
+    pub min_idle: u64,
+}
+
+/// The options of `XCLAIM`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ClaimOptions {
+    /// `IDLE`: the idle time in milliseconds to record, instead of none.
+    pub idle: Option<u64>,
+    /// `TIME`: the unix time in milliseconds to record as the delivery time.
+    pub time: Option<u64>,
+    /// `RETRYCOUNT`: the delivery count to record, instead of incrementing it.
+    pub retry_count: Option<u64>,
+    /// `FORCE`: make the existing entries which are not pending yet pending.
+    pub force: bool,
+    /// `JUSTID`: leave the delivery count as is.
+    pub just_id: bool,
+    /// `LASTID`: move the last delivered ID of the group forward to this ID.
+    pub last_id: Option<StreamId>,
+}
+
+/// The result of `XAUTOCLAIM`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AutoClaim {
+    /// The ID to resume scanning the pending entries from, `0-0` once they were all
+    /// scanned.
+    pub next: StreamId,
+    pub claimed: StreamEntries,
+    /// The IDs of the pending entries found deleted from the stream, which are no longer
+    /// pending.
+    pub deleted: Vec<StreamId>,
+}
+
+/// The state of a stream, as reported by `XINFO STREAM`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamInfo {
+    pub length: usize,
+    pub last_generated_id: StreamId,
+    pub max_deleted_id: StreamId,
+    pub entries_added: u64,
+    pub recorded_first_id: StreamId,
+    pub groups: usize,
+    pub first_entry: Option<(StreamId, StreamFields)>,
+    pub last_entry: Option<(StreamId, StreamFields)>,
+}
+
+/// The state of a consumer group, as reported by `XINFO GROUPS`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupInfo {
+    pub name: Bytes,
+    pub consumers: usize,
+    pub pending: usize,
+    pub last_delivered_id: StreamId,
+    pub entries_read: Option<u64>,
+    /// The number of entries the group has yet to read, if it can be told.
+    pub lag: Option<u64>,
+}
+
+/// The state of a consumer, as reported by `XINFO CONSUMERS`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConsumerInfo {
+    pub name: Bytes,
+    pub pending: usize,
+    /// The milliseconds elapsed since the last attempted interaction.
+    pub idle: u64,
+    /// The milliseconds elapsed since the last successful interaction, if any.
+    pub inactive: Option<u64>,
+}
+
+/// A stream: an append-only log of entries, ordered by ID.
+///
+/// The entries are kept in a B-tree, whose nodes hold blocks of consecutive entries, so
+/// appending, looking up an ID and iterating over a range are all cheap.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Stream {
+    entries: BTreeMap<StreamId, StreamFields>,
+    /// The ID of the last entry ever added, which may have been deleted since.
+    last_id: StreamId,
+    /// The highest ID among the deleted entries.
+    max_deleted_id: StreamId,
+    /// The number of entries ever added, including the deleted ones.
+    entries_added: u64,
+    groups: BTreeMap<Bytes, ConsumerGroup>,
+}
+
+impl StreamId {
+    pub const MIN: StreamId = StreamId { ms: 0, seq: 0 };
+    pub const MAX: StreamId = StreamId {
+        ms: u64::MAX,
+        seq: u64::MAX,
+    };
+
+    /// Returns the ID following this one, or `None` if this is the greatest ID.
+    pub fn next(self) -> Option<StreamId> {
+        match self.seq.checked_add(1) {
+            Some(seq) => Some(StreamId { seq, ..self }),
+            None => Some(StreamId {
+                ms: self.ms.checked_add(1)?,
+                seq: 0,
+            }),
+        }
+    }
+
+    /// Returns the ID preceding this one, or `None` if this is `0-0`.
+    pub fn prev(self) -> Option<StreamId> {
+        match self.seq.checked_sub(1) {
+            Some(seq) => Some(StreamId { seq, ..self }),
+            None => Some(StreamId {
+                ms: self.ms.checked_sub(1)?,
+                seq: u64::MAX,
+            }),
+        }
+    }
+}
+
+impl fmt::Display for StreamId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.ms, self.seq)
+    }
+}
+
+impl Stream {
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn last_id(&self) -> StreamId {
+        self.last_id
+    }
+
+    pub fn max_deleted_id(&self) -> StreamId {
+        self.max_deleted_id
+    }
+
+    pub fn entries_added(&self) -> u64 {
+        self.entries_added
+    }
+
+    /// Returns the entry with the lowest ID.
+    pub fn first_entry(&self) -> Option<(StreamId, &StreamFields)> {
+        self.entries
+            .first_key_value()
+            .map(|(id, fields)| (*id, fields))
+    }
+
+    /// Returns the entry with the highest ID.
+    pub fn last_entry(&self) -> Option<(StreamId, &StreamFields)> {
+        self.entries
+            .last_key_value()
+            .map(|(id, fields)| (*id, fields))
+    }
+
+    /// Appends an entry with an ID chosen as specified, which must be greater than the last
+    /// ID of the stream.
+    pub fn add(&mut self, id: XAddId, fields: StreamFields) -> Result<StreamId, CommandError> {
+        let id = self.next_id(id)?;
+        self.entries.insert(id, fields);
+        self.last_id = id;
+        self.entries_added += 1;
+        Ok(id)
+    }
+
+    fn next_id(&self, id: XAddId) -> Result<StreamId, CommandError> {
+        let last = self.last_id;
+        let id = match id {
+            XAddId::Auto => {
+                let ms = now_ms();
+                if ms > last.ms {
+                    Some(StreamId { ms, seq: 0 })
+                } else {
+                    Some(last.next().ok_or_else(|| {
+                        CommandError::InvalidArguments(
+                            "The stream has exhausted the last possible ID, unable to add more \
+                             items"
+                                .to_string(),
+                        )
+                    })?)
+                }
+            }
+            XAddId::AutoSeq(ms) if ms > last.ms => Some(StreamId { ms, seq: 0 }),
+            XAddId::AutoSeq(ms) if ms == last.ms => {
+                last.seq.checked_add(1).map(|seq| StreamId { ms, seq })
+            }
+            XAddId::AutoSeq(_) => None,
+            XAddId::Explicit(id) => Some(id),
+        };
+        match id {
+            Some(id) if id > last => Ok(id),
+            _ => Err(CommandError::InvalidArguments(
+                "The ID specified in XADD is equal or smaller than the target stream top item"
+                    .to_string(),
+            )),
+        }
+    }
+
+    /// Evicts the oldest entries as specified.
+    ///
+    /// Returns the number of entries evicted.
+    pub fn trim(&mut self, trim: &StreamTrim) -> usize {
+        let mut count = match trim.strategy {
+            TrimStrategy::MaxLen(max_len) => self.len().saturating_sub(max_len),
+            TrimStrategy::MinId(min_id) => self.entries.range(..min_id).count(),
+        };
+        if trim.approximate {
+            let limit = match trim.limit {
+                Some(0) => usize::MAX,
+                Some(limit) => limit,
+                None => STREAM_NODE_MAX_ENTRIES * 100,
+            };
+            count = count.min(limit);
+            count -= count % STREAM_NODE_MAX_ENTRIES;
+        }
+        for _ in 0..count {
+            self.entries.pop_first();
+        }
+        count
+    }
+
+    /// Returns up to `count` entries with an ID between `start` and `end`, in reverse
+    /// order if `rev` is `true`.
+    pub fn range(
+        &self,
+        start: Bound<StreamId>,
+        end: Bound<StreamId>,
+        rev: bool,
+        count: Option<usize>,
+    ) -> StreamEntries {
+        let valid = match (start, end) {
+            (Bound::Included(s), Bound::Included(e)) => s <= e,
+            (Bound::Included(s) | Bound::Excluded(s), Bound::Excluded(e))
+            | (Bound::Excluded(s), Bound::Included(e)) => s < e,
+            _ => true,
+        };
+        if !valid {
+            return Vec::new();
+        }
+        let range = self.entries.range((start, end));
+        let entries: Box<dyn Iterator<Item = _>> = if rev {
+            Box::new(range.rev())
+        } else {
+            Box::new(range)
+        };
+        entries
+            .take(count.unwrap_or(usize::MAX))
+            .map(|(id, fields)| (*id, fields.clone()))
+            .collect()
+    }
+
+    /// Deletes the entries with the given IDs.
+    ///
+    /// Returns the number of entries deleted.
+    pub fn remove(&mut self, ids: &[StreamId]) -> usize {
+        let mut removed = 0;
+        for id in ids {
+            if self.entries.remove(id).is_some() {
+                self.max_deleted_id = self.max_deleted_id.max(*id);
+                removed += 1;
+            }
+        }
+        removed
+    }
+
+    /// Returns `true` if entries with an ID from `start` onwards may have been deleted.
+    fn has_tombstones_from(&self, start: StreamId) -> bool {
+        match self.first_entry() {
+            Some((first, _)) => {
+                self.max_deleted_id != StreamId::MIN
+                    && first <= self.max_deleted_id
+                    && start <= self.max_deleted_id
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the number of entries added up to `id` included, which is the number of
+    /// entries read by a group having read up to `id`, or `None` if it can't be told
+    /// because of deleted entries.
+    fn entries_added_until(&self, id: StreamId) -> Option<u64> {
+        if self.entries_added == 0 || (self.is_empty() && id <= self.last_id) {
+            return Some(self.entries_added);
+        }
+        if id >= self.last_id {
+            return (id == self.last_id).then_some(self.entries_added);
+        }
+        let (first, _) = self.first_entry()?;
+        if self.max_deleted_id == StreamId::MIN || self.max_deleted_id < first {
+            let before_first = self.entries_added - self.len() as u64;
+            if id < first {
+                return Some(before_first);
+            } else if id == first {
+                return Some(before_first + 1);
+            }
+        }
+        None
+    }
+
+    /// Runs `f` on the group named `name`, which is handed the rest of the stream.
+    ///
+    /// Returns `None` if there is no such group.
+    fn with_group<R>(
+        &mut self,
+        name: &[u8],
+        f: impl FnOnce(&mut ConsumerGroup, &Stream) -> R,
+    ) -> Option<R> {
+        let (name, mut group) = self.groups.remove_entry(name)?;
+        let ret = f(&mut group, self);
+        self.groups.insert(name, group);
+        Some(ret)
+    }
+}
+
+impl ConsumerGroup {
+    /// Returns the consumer named `name`, created if needed, and records an interaction
+    /// with it at `now`.
+    fn consumer(&mut self, name: &Bytes, now: u64) -> &mut Consumer {
+        let consumer = self.consumers.entry(name.clone()).or_default();
+        consumer.seen_time = now;
+        consumer
+    }
+
+    /// Makes an entry pending for `consumer`, taking it from the consumer it was pending
+    /// for, if any.
+    fn assign(&mut self, id: StreamId, consumer: &Bytes, delivery_time: u64, delivery_count: u64) {
+        let entry = PendingEntry {
+            consumer: consumer.clone(),
+            delivery_time,
+            delivery_count,
+        };
+        if let Some(previous) = self.pending.insert(id, entry) {
+            if let Some(owner) = self.consumers.get_mut(&previous.consumer) {
+                owner.pending.remove(&id);
+            }
+        }
+        self.consumers
+            .entry(consumer.clone())
+            .or_default()
+            .pending
+            .insert(id);
+    }
+
+    /// Acknowledges an entry, which is no longer pending.
+    ///
+    /// Returns `false` if the entry was not pending.
+    fn ack(&mut self, id: StreamId) -> bool {
+        match self.pending.remove(&id) {
+            Some(entry) => {
+                if let Some(consumer) = self.consumers.get_mut(&entry.consumer) {
+                    consumer.pending.remove(&id);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Delivers up to `count` entries never delivered to the group to `consumer`, for
+    /// which they become pending unless `noack`.
+    fn deliver(
+        &mut self,
+        stream: &Stream,
+        consumer: &Bytes,
+        count: Option<usize>,
+        noack: bool,
+        now: u64,
+    ) -> GroupEntries {
+        let entries = stream.range(
+            Bound::Excluded(self.last_id),
+            Bound::Unbounded,
+            false,
+            count,
+        );
+        for (id, _) in &entries {
+            self.entries_read = match self.entries_read {
+                Some(read) if !stream.has_tombstones_from(*id) => Some(read + 1),
+                _ => stream.entries_added_until(*id),
+            };
+            self.last_id = *id;
+            if !noack {
+                self.assign(*id, consumer, now, 1);
+            }
+        }
+        if !entries.is_empty() {
+            self.consumer(consumer, now).active_time = Some(now);
+        }
+        entries
+            .into_iter()
+            .map(|(id, fields)| (id, Some(fields)))
+            .collect()
+    }
+
+    /// Delivers again up to `count` entries pending for `consumer` with an ID greater than
+    /// `after`.
+    fn redeliver(
+        &mut self,
+        stream: &Stream,
+        consumer: &Bytes,
+        after: StreamId,
+        count: Option<usize>,
+        now: u64,
+    ) -> GroupEntries {
+        let ids = match self.consumers.get(consumer) {
+            Some(consumer) => consumer
+                .pending
+                .range((Bound::Excluded(after), Bound::Unbounded))
+                .take(count.unwrap_or(usize::MAX))
+                .copied()
+                .collect(),
+            None => Vec::new(),
+        };
+        ids.into_iter()
+            .map(|id| {
+                if let Some(entry) = self.pending.get_mut(&id) {
+                    entry.delivery_time = now;
+                    entry.delivery_count += 1;
+                }
+                (id, stream.entries.get(&id).cloned())
+            })
+            .collect()
+    }
+
+    /// Returns the number of entries the group has yet to read, if it can be told.
+    fn lag(&self, stream: &Stream) -> Option<u64> {
+        if stream.entries_added == 0 {
+            return Some(0);
+        }
+        let read = match self.entries_read {
+            Some(read) if !stream.has_tombstones_from(self.last_id) => Some(read),
+            _ => stream.entries_added_until(self.last_id),
+        };
+        read.map(|read| stream.entries_added.saturating_sub(read))
+    }
+}
+
+impl Backend {
+    /// Appends an entry to the stream at `key`, then trims the stream if asked to.
+    ///
+    /// If the key does not exist, a stream is created when `create` is `true`, otherwise
+    /// nothing is added. The clients blocked reading the stream are woken.
+    ///
+    /// Returns the ID of the new entry, or `None` if nothing was added.
+    pub fn xadd(
+        &self,
+        key: &Bytes,
+        id: XAddId,
+        fields: StreamFields,
+        trim: Option<StreamTrim>,
+        create: bool,
+    ) -> Result<Option<StreamId>, CommandError> {
+        let id = self
+            .write(key, create, |s: &mut Stream| -> Result<_, CommandError> {
+                let id = s.add(id, fields)?;
+                if let Some(trim) = trim {
+                    s.trim(&trim);
+                }
+                Ok(id)
+            })?
+            .transpose()?;
+        if id.is_some() {
+            self.signal_key_updated(key);
+        }
+        Ok(id)
+    }
+
+    /// Returns the number of entries of the stream at `key`, `0` if the key does not exist.
+    pub fn xlen(&self, key: &[u8]) -> Result<usize, CommandError> {
+        Ok(self.read(key, |s: &Stream| s.len())?.unwrap_or_default())
+    }
+
+    /// Returns the entries of the stream at `key` with an ID between `start` and `end`
+    /// inclusive, as done by `XRANGE` and by `XREVRANGE` if `rev` is `true`.
+    pub fn xrange(
+        &self,
+        key: &[u8],
+        start: StreamId,
+        end: StreamId,
+        rev: bool,
+        count: Option<usize>,
+    ) -> Result<StreamEntries, CommandError> {
+        let entries = self.read(key, |s: &Stream| {
+            s.range(Bound::Included(start), Bound::Included(end), rev, count)
+        })?;
+        Ok(entries.unwrap_or_default())
+    }
+
+    /// Trims the stream at `key`.
+    ///
+    /// Returns the number of entries evicted.
+    pub fn xtrim(&self, key: &Bytes, trim: &StreamTrim) -> Result<usize, CommandError> {
+        let evicted = self.write(key, false, |s: &mut Stream| s.trim(trim))?;
+        Ok(evicted.unwrap_or_default())
+    }
+
+    /// Deletes entries from the stream at `key`.
+    ///
+    /// Returns the number of entries deleted.
+    pub fn xdel(&self, key: &Bytes, ids: &[StreamId]) -> Result<usize, CommandError> {
+        let removed = self.write(key, false, |s: &mut Stream| s.remove(ids))?;
+        Ok(removed.unwrap_or_default())
+    }
+
+    /// Returns the last ID of the stream at `key`, `0-0` if the key does not exist.
+    pub fn xlast_id(&self, key: &[u8]) -> Result<StreamId, CommandError> {
+        Ok(self.read(key, |s: &Stream| s.last_id)?.unwrap_or_default())
+    }
+
+    /// Returns up to `count` entries of each stream with an ID greater than the one given
+    /// along with its key, as done by `XREAD`.
+    ///
+    /// Only the streams having such entries are part of the result.
+    pub fn xread(
+        &self,
+        streams: &[(Bytes, StreamId)],
+        count: Option<usize>,
+    ) -> Result<Vec<(Bytes, StreamEntries)>, CommandError> {
+        let mut result = Vec::new();
+        for (key, after) in streams {
+            let entries = self.read(key, |s: &Stream| {
+                s.range(Bound::Excluded(*after), Bound::Unbounded, false, count)
+            })?;
+            match entries {
+                Some(entries) if !entries.is_empty() => result.push((key.clone(), entries)),
+                _ => {}
+            }
+        }
+        Ok(result)
+    }
+
+    /// Runs `f` on the group `group` of the stream at `key`, which is handed the rest of
+    /// the stream.
+    ///
+    /// Returns `None` if the key or the group does not exist.
+    fn write_group<R>(
+        &self,
+        key: &Bytes,
+        group: &[u8],
+        f: impl FnOnce(&mut ConsumerGroup, &Stream) -> R,
+    ) -> Result<Option<R>, CommandError> {
+        Ok(self
+            .write(key, false, |s: &mut Stream| s.with_group(group, f))?
+            .flatten())
+    }
+
+    /// Runs `f` on the group `group` of the stream at `key`, along with the stream.
+    ///
+    /// Returns `None` if the key or the group does not exist.
+    fn read_group<R>(
+        &self,
+        key: &[u8],
+        group: &[u8],
+        f: impl FnOnce(&ConsumerGroup, &Stream) -> R,
+    ) -> Result<Option<R>, CommandError> {
+        Ok(self
+            .read(key, |s: &Stream| s.groups.get(group).map(|g| f(g, s)))?
+            .flatten())
+    }
+
+    /// Returns `true` if the stream at `key` has a group named `group`.
+    pub fn xgroup_exists(&self, key: &[u8], group: &[u8]) -> Result<bool, CommandError> {
+        Ok(self.read_group(key, group, |_, _| ())?.is_some())
+    }
+
+    /// Creates a group whose last delivered ID is `id`, or the last ID of the stream if
+    /// `None`. If the key does not exist, an empty stream is created when `mkstream` is
+    /// `true`.
+    ///
+    /// Returns `None` if the key does not exist, `Some(false)` if the group already exists.
+    pub fn xgroup_create(
+        &self,
+        key: &Bytes,
+        group: Bytes,
+        id: Option<StreamId>,
+        entries_read: Option<u64>,
+        mkstream: bool,
+    ) -> Result<Option<bool>, CommandError> {
+        self.write(key, mkstream, |s: &mut Stream| {
+            let last_id = id.unwrap_or(s.last_id);
+            match s.groups.entry(group) {
+                btree_map::Entry::Vacant(entry) => {
+                    entry.insert(ConsumerGroup {
+                        last_id,
+                        entries_read,
+                        ..Default::default()
+                    });
+                    true
+                }
+                btree_map::Entry::Occupied(_) => false,
+            }
+        })
+    }
+
+    /// Sets the last delivered ID of a group to `id`, or to the last ID of the stream if
+    /// `None`.
+    ///
+    /// Returns `false` if the key or the group does not exist.
+    pub fn xgroup_setid(
+        &self,
+        key: &Bytes,
+        group: &[u8],
+        id: Option<StreamId>,
+        entries_read: Option<u64>,
+    ) -> Result<bool, CommandError> {
+        let set = self.write_group(key, group, |g, s| {
+            g.last_id = id.unwrap_or(s.last_id);
+            g.entries_read = entries_read;
+        })?;
+        Ok(set.is_some())
+    }
+
+    /// Destroys a group, along with its consumers and its pending entries. The clients
+    /// blocked reading from the group are woken.
+    ///
+    /// Returns `false` if the group does not exist.
+    pub fn xgroup_destroy(&self, key: &Bytes, group: &[u8]) -> Result<bool, CommandError> {
+        let destroyed = self
+            .write(key, false, |s: &mut Stream| {
+                s.groups.remove(group).is_some()
+            })?
+            .unwrap_or_default();
+        if destroyed {
+            self.signal_key_updated(key);
+        }
+        Ok(destroyed)
+    }
+
+    /// Creates a consumer in a group.
+    ///
+    /// Returns `None` if the key or the group does not exist, `Some(false)` if the consumer
+    /// already exists.
+    pub fn xgroup_createconsumer(
+        &self,
+        key: &Bytes,
+        group: &[u8],
+        consumer: &Bytes,
+    ) -> Result<Option<bool>, CommandError> {
+        self.write_group(key, group, |g, _| {
+            if g.consumers.contains_key(consumer) {
+                return false;
+            }
+            g.consumer(consumer, now_ms());
+            true
+        })
+    }
+
+    /// Deletes a consumer from a group, along with its pending entries.
+    ///
+    /// Returns the number of entries that were pending for the consumer, or `None` if the
+    /// key or the group does not exist.
+    pub fn xgroup_delconsumer(
+        &self,
+        key: &Bytes,
+        group: &[u8],
+        consumer: &[u8],
+    ) -> Result<Option<usize>, CommandError> {
+        self.write_group(key, group, |g, _| match g.consumers.remove(consumer) {
+            Some(consumer) => {
+                for id in &consumer.pending {
+                    g.pending.remove(id);
+                }
+                consumer.pending.len()
+            }
+            None => 0,
+        })
+    }
+
+    /// Reads entries from each stream on behalf of a consumer of a group, as done by
+    /// `XREADGROUP`.
+    ///
+    /// With `None` as ID, up to `count` entries never delivered to the group are read,
+    /// which become pending for the consumer unless `noack`. Only the streams having such
+    /// entries are part of the result. With an ID, the entries pending for the consumer
+    /// with a greater ID are read again. Streams without the group are skipped.
+    pub fn xreadgroup(
+        &self,
+        group: &[u8],
+        consumer: &Bytes,
+        streams: &[(Bytes, Option<StreamId>)],
+        count: Option<usize>,
+        noack: bool,
+    ) -> Result<Vec<(Bytes, GroupEntries)>, CommandError> {
+        let now = now_ms();
+        let mut result = Vec::new();
+        for (key, id) in streams {
+            let entries = self.write_group(key, group, |g, s| {
+                g.consumer(consumer, now);
+                match id {
+                    None => g.deliver(s, consumer, count, noack, now),
+                    Some(after) => g.redeliver(s, consumer, *after, count, now),
+                }
+            })?;
+            match (id, entries) {
+                (None, Some(entries)) if entries.is_empty() => {}
+                (_, Some(entries)) => result.push((key.clone(), entries)),
+                (_, None) => {}
+            }
+        }
+        Ok(result)
+    }
+
+    /// Acknowledges entries pending in a group.
+    ///
+    /// Returns the number of entries that were pending.
+    pub fn xack(&self, key: &Bytes, group: &[u8], ids: &[StreamId]) -> Result<usize, CommandError> {
+        let acked = self.write_group(key, group, |g, _| {
+            ids.iter().filter(|&&id| g.ack(id)).count()
+        })?;
+        Ok(acked.unwrap_or_default())
+    }
+
+    /// Summarizes the entries pending in a group.
+    ///
+    /// Returns `None` if the key or the group does not exist.
+    pub fn xpending_summary(
+        &self,
+        key: &[u8],
+        group: &[u8],
+    ) -> Result<Option<PendingSummary>, CommandError> {
+        self.read_group(key, group, |g, _| PendingSummary {
+            count: g.pending.len(),
+            bounds: g
+                .pending
+                .first_key_value()
+                .zip(g.pending.last_key_value())
+                .map(|((first, _), (last, _))| (*first, *last)),
+            consumers: g
+                .consumers
+                .iter()
+                .filter(|(_, consumer)| !consumer.pending.is_empty())
+                .map(|(name, consumer)| (name.clone(), consumer.pending.len()))
+                .collect(),
+        })
+    }
+
+    /// Lists the entries pending in a group which match `filter`.
+    ///
+    /// Returns `None` if the key or the group does not exist.
+    pub fn xpending(
+        &self,
+        key: &[u8],
+        group: &[u8],
+        filter: &PendingFilter,
+    ) -> Result<Option<Vec<PendingInfo>>, CommandError> {
+        let now = now_ms();
+        self.read_group(key, group, |g, _| {
+            if filter.start > filter.end {
+                return Vec::new();
+            }
+            g.pending
+                .range(filter.start..=filter.end)
+                .map(|(id, entry)| PendingInfo {
+                    id: *id,
+                    consumer: entry.consumer.clone(),
+                    idle: now.saturating_sub(entry.delivery_time),
+                    delivery_count: entry.delivery_count,
+                })
+                .filter(|info| {
+                    info.idle >= filter.min_idle
+                        && filter
+                            .consumer
+                            .as_ref()
+                            .is_none_or(|consumer| *consumer == info.consumer)
+                })
+                .take(filter.count)
+                .collect()
+        })
+    }
+
+    /// Transfers the ownership of pending entries idle for at least `min_idle`
+    /// milliseconds to `consumer`, as done by `XCLAIM`.
+    ///
+    /// The entries deleted from the stream are no longer pending and are not claimed.
+    ///
+    /// Returns the claimed entries, or `None` if the key or the group does not exist.
+    pub fn xclaim(
+        &self,
+        key: &Bytes,
+        group: &[u8],
+        consumer: &Bytes,
+        min_idle: u64,
+        ids: &[StreamId],
+        options: &ClaimOptions,
+    ) -> Result<Option<StreamEntries>, CommandError> {
+        let now = now_ms();
+        // a bogus delivery time is taken as now, clients may compute it with their clock
+        let delivery_time = match (options.idle, options.time) {
+            (Some(idle), _) => now.checked_sub(idle),
+            (None, Some(time)) => Some(time).filter(|&time| time <= now),
+            (None, None) => None,
+        }
+        .unwrap_or(now);
+        self.write_group(key, group, |g, s| {
+            g.consumer(consumer, now);
+            if let Some(last_id) = options.last_id {
+                g.last_id = g.last_id.max(last_id);
+            }
+            let mut claimed = Vec::new();
+            for &id in ids {
+                let delivery_count = match g.pending.get(&id) {
+                    Some(entry) if now.saturating_sub(entry.delivery_time) < min_idle => continue,
+                    Some(entry) => entry.delivery_count,
+                    None if options.force && s.entries.contains_key(&id) => 0,
+                    None => continue,
+                };
+                let Some(fields) = s.entries.get(&id) else {
+                    g.ack(id);
+                    continue;
+                };
+                let delivery_count = match options.retry_count {
+                    Some(retry_count) => retry_count,
+                    None if options.just_id => delivery_count,
+                    None => delivery_count + 1,
+                };
+                g.assign(id, consumer, delivery_time, delivery_count);
+                claimed.push((id, fields.clone()));
+            }
+            if !claimed.is_empty() {
+                g.consumer(consumer, now).active_time = Some(now);
+            }
+            claimed
+        })
+    }
+
+    /// Claims up to `count` pending entries idle for at least `min_idle` milliseconds for
+    /// `consumer`, scanning the pending entries from `start`, as done by `XAUTOCLAIM`.
+    ///
+    /// At most ten times `count` pending entries are scanned. The entries deleted from the
+    /// stream are no longer pending, and count towards `count`.
+    ///
+    /// Returns `None` if the key or the group does not exist.
+    #[allow(clippy::too_many_arguments)]
+    pub fn xautoclaim(
+        &self,
+        key: &Bytes,
+        group: &[u8],
+        consumer: &Bytes,
+        min_idle: u64,
+        start: StreamId,
+        count: usize,
+        just_id: bool,
+    ) -> Result<Option<AutoClaim>, CommandError> {
+        let now = now_ms();
+        self.write_group(key, group, |g, s| {
+            g.consumer(consumer, now);
+            let attempts = count.saturating_mul(10);
+            let ids = g
+                .pending
+                .range(start..)
+                .map(|(id, _)| *id)
+                .take(attempts.saturating_add(1))
+                .collect::<Vec<_>>();
+            let mut result = AutoClaim {
+                next: StreamId::MIN,
+                claimed: Vec::new(),
+                deleted: Vec::new(),
+            };
+            let mut scanned = 0;
+            while scanned < ids.len().min(attempts)
+                && result.claimed.len() + result.deleted.len() < count
+            {
+                let id = ids[scanned];
+                scanned += 1;
+                let Some(fields) = s.entries.get(&id) else {
+                    g.ack(id);
+                    result.deleted.push(id);
+                    continue;
+                };
+                let entry = &g.pending[&id];
+                if now.saturating_sub(entry.delivery_time) < min_idle {
+                    continue;
+                }
+                let delivery_count = entry.delivery_count + u64::from(!just_id);
+                g.assign(id, consumer, now, delivery_count);
+                result.claimed.push((id, fields.clone()));
+            }
+            if let Some(&next) = ids.get(scanned) {
+                result.next = next;
+            }
+            if !result.claimed.is_empty() {
+                g.consumer(consumer, now).active_time = Some(now);
+            }
+            result
+        })
+    }
+
+    /// Returns the state of the stream at `key`, or `None` if the key does not exist.
+    pub fn xinfo_stream(&self, key: &[u8]) -> Result<Option<StreamInfo>, CommandError> {
+        let entry = |entry: Option<(StreamId, &StreamFields)>| {
+            entry.map(|(id, fields)| (id, fields.clone()))
+        };
+        self.read(key, |s: &Stream| StreamInfo {
+            length: s.len(),
+            last_generated_id: s.last_id,
+            max_deleted_id: s.max_deleted_id,
+            entries_added: s.entries_added,
+            recorded_first_id: s.first_entry().map(|(id, _)| id).unwrap_or_default(),
+            groups: s.groups.len(),
+            first_entry: entry(s.first_entry()),
+            last_entry: entry(s.last_entry()),
+        })
+    }
+
+    /// Returns the state of each group of the stream at `key`, or `None` if the key does
+    /// not exist.
+    pub fn xinfo_groups(&self, key: &[u8]) -> Result<Option<Vec<GroupInfo>>, CommandError> {
+        self.read(key, |s: &Stream| {
+            s.groups
+                .iter()
+                .map(|(name, g)| GroupInfo {
+                    name: name.clone(),
+                    consumers: g.consumers.len(),
+                    pending: g.pending.len(),
+                    last_delivered_id: g.last_id,
+                    entries_read: g.entries_read,
+                    lag: g.lag(s),
+                })
+                .collect()
+        })
+    }
+
+    /// Returns the state of each consumer of a group, or `None` if the key or the group
+    /// does not exist.
+    pub fn xinfo_consumers(
+        &self,
+        key: &[u8],
+        group: &[u8],
+    ) -> Result<Option<Vec<ConsumerInfo>>, CommandError> {
+        let now = now_ms();
+        self.read_group(key, group, |g, _| {
+            g.consumers
+                .iter()
+                .map(|(name, consumer)| ConsumerInfo {
+                    name: name.clone(),
+                    pending: consumer.pending.len(),
+                    idle: now.saturating_sub(consumer.seen_time),
+                    inactive: consumer
+                        .active_time
+                        .map(|active_time| now.saturating_sub(active_time)),
+                })
+                .collect()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn id(ms: u64, seq: u64) -> StreamId {
+        StreamId { ms, seq }
+    }
+
+    fn fields(field: &'static str, value: &'static str) -> StreamFields {
+        vec![(Bytes::from_static(field.as_bytes()), Bytes::from(value))]
+    }
+
+    fn ids(entries: StreamEntries) -> Vec<StreamId> {
+        entries.into_iter().map(|(id, _)| id).collect()
+    }
+
+    #[test]
+    fn test_stream_ids() {
+        assert_eq!(id(1, u64::MAX).next(), Some(id(2, 0)));
+        assert_eq!(StreamId::MAX.next(), None);
+        assert_eq!(id(2, 0).prev(), Some(id(1, u64::MAX)));
+        assert_eq!(StreamId::MIN.prev(), None);
+        assert_eq!(id(5, 3).to_string(), "5-3");
+    }
+
+    #[test]
+    fn test_stream_add() {
+        let mut stream = Stream::default();
+        assert_eq!(
+            stream.add(XAddId::AutoSeq(0), fields("a", "1")).unwrap(),
+            id(0, 1)
+        );
+        assert_eq!(
+            stream.add(XAddId::AutoSeq(5), fields("a", "2")).unwrap(),
+            id(5, 0)
+        );
+        assert_eq!(
+            stream.add(XAddId::AutoSeq(5), fields("a", "3")).unwrap(),
+            id(5, 1)
+        );
+        let err = stream
+            .add(XAddId::Explicit(id(5, 1)), fields("a", "4"))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "ERR The ID specified in XADD is equal or smaller than the target stream top item"
+        );
+        assert!(stream.add(XAddId::AutoSeq(4), fields("a", "4")).is_err());
+
+        let auto = stream.add(XAddId::Auto, fields("a", "4")).unwrap();
+        assert!(auto > id(5, 1));
+        // an ID ahead of the clock is incremented
+        stream
+            .add(XAddId::Explicit(id(u64::MAX, 7)), fields("a", "5"))
+            .unwrap();
+        assert_eq!(
+            stream.add(XAddId::Auto, fields("a", "6")).unwrap(),
+            id(u64::MAX, 8)
+        );
+        assert_eq!(stream.len(), 6);
+        assert_eq!(stream.entries_added(), 6);
+    }
+
+    #[test]
+    fn test_stream_trim() {
+        let mut stream = Stream::default();
+        for ms in 1..=250 {
+            stream.add(XAddId::AutoSeq(ms), fields("n", "x")).unwrap();
+        }
+        let approximate = |strategy| StreamTrim {
+            strategy,
+            approximate: true,
+            limit: None,
+        };
+        // only whole nodes of 100 entries are evicted
+        assert_eq!(stream.trim(&approximate(TrimStrategy::MaxLen(60))), 100);
+        assert_eq!(stream.len(), 150);
+        assert_eq!(stream.trim(&approximate(TrimStrategy::MaxLen(60))), 0);
+        assert_eq!(
+            stream.trim(&StreamTrim {
+                limit: Some(50),
+                ..approximate(TrimStrategy::MinId(id(251, 0)))
+            }),
+            0
+        );
+        assert_eq!(
+            stream.trim(&StreamTrim {
+                strategy: TrimStrategy::MinId(id(150, 0)),
+                approximate: false,
+                limit: None,
+            }),
+            49
+        );
+        assert_eq!(stream.first_entry().unwrap().0, id(150, 0));
+        assert_eq!(
+            stream.trim(&StreamTrim {
+                strategy: TrimStrategy::MaxLen(0),
+                approximate: false,
+                limit: None,
+            }),
+            101
+        );
+        // an empty stream remembers its last ID
+        assert!(stream.is_empty());
+        assert_eq!(stream.last_id(), id(250, 0));
+    }
+
+    #[test]
+    fn test_stream_range_and_remove() {
+        let mut stream = Stream::default();
+        for ms in 1..=5 {
+            stream.add(XAddId::AutoSeq(ms), fields("n", "x")).unwrap();
+        }
+        assert_eq!(
+            ids(stream.range(
+                Bound::Included(id(2, 0)),
+                Bound::Included(id(4, 0)),
+                false,
+                None
+            )),
+            vec![id(2, 0), id(3, 0), id(4, 0)]
+        );
+        assert_eq!(
+            ids(stream.range(Bound::Unbounded, Bound::Unbounded, true, Some(2))),
+            vec![id(5, 0), id(4, 0)]
+        );
+        assert!(stream
+            .range(
+                Bound::Included(id(4, 0)),
+                Bound::Included(id(2, 0)),
+                false,
+                None
+            )
+            .is_empty());
+        assert!(stream
+            .range(
+                Bound::Excluded(id(5, 0)),
+                Bound::Excluded(id(5, 0)),
+                false,
+                None
+            )
+            .is_empty());
+
+        assert_eq!(stream.remove(&[id(4, 0), id(2, 0), id(9, 0)]), 2);
+        assert_eq!(stream.max_deleted_id(), id(4, 0));
+        assert_eq!(stream.len(), 3);
+    }
+
+    #[test]
+    fn test_xread() {
+        let backend = Backend::new();
+        let key = Bytes::from("s");
+        backend
+            .xadd(&key, XAddId::AutoSeq(1), fields("a", "1"), None, false)
+            .unwrap();
+        assert!(!backend.contains_key(b"s"));
+        for ms in 1..=3 {
+            backend
+                .xadd(&key, XAddId::AutoSeq(ms), fields("a", "1"), None, true)
+                .unwrap();
+        }
+        let read = backend
+            .xread(
+                &[(key.clone(), id(1, 0)), (Bytes::from("missing"), id(0, 0))],
+                Some(1),
+            )
+            .unwrap();
+        assert_eq!(read.len(), 1);
+        assert_eq!(ids(read[0].1.clone()), vec![id(2, 0)]);
+        assert!(backend.xread(&[(key, id(3, 0))], None).unwrap().is_empty());
+    }
+
+    fn group_ids(entries: GroupEntries) -> Vec<StreamId> {
+        entries.into_iter().map(|(id, _)| id).collect()
+    }
+
+    #[test]
+    fn test_consumer_groups() {
+        let backend = Backend::new();
+        let key = Bytes::from("s");
+        let (alice, bob) = (Bytes::from("alice"), Bytes::from("bob"));
+        assert_eq!(
+            backend
+                .xgroup_create(&key, "g".into(), None, None, false)
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            backend
+                .xgroup_create(&key, "g".into(), None, None, true)
+                .unwrap(),
+            Some(true)
+        );
+        assert_eq!(
+            backend
+                .xgroup_create(&key, "g".into(), None, None, true)
+                .unwrap(),
+            Some(false)
+        );
+        for ms in 1..=3 {
+            backend
+                .xadd(&key, XAddId::AutoSeq(ms), fields("a", "1"), None, true)
+                .unwrap();
+        }
+        let streams = [(key.clone(), None)];
+        let read = backend
+            .xreadgroup(b"g", &alice, &streams, Some(2), false)
+            .unwrap();
+        assert_eq!(group_ids(read[0].1.clone()), vec![id(1, 0), id(2, 0)]);
+        let read = backend
+            .xreadgroup(b"g", &bob, &streams, None, false)
+            .unwrap();
+        assert_eq!(group_ids(read[0].1.clone()), vec![id(3, 0)]);
+        // nothing new to deliver
+        assert!(backend
+            .xreadgroup(b"g", &bob, &streams, None, false)
+            .unwrap()
+            .is_empty());
+
+        let summary = backend.xpending_summary(b"s", b"g").unwrap().unwrap();
+        assert_eq!(summary.count, 3);
+        assert_eq!(summary.bounds, Some((id(1, 0), id(3, 0))));
+        assert_eq!(
+            summary.consumers,
+            vec![(alice.clone(), 2), (bob.clone(), 1)]
+        );
+
+        assert_eq!(backend.xack(&key, b"g", &[id(1, 0), id(9, 0)]).unwrap(), 1);
+        // the history of a consumer holds the entries still pending for it
+        let read = backend
+            .xreadgroup(
+                b"g",
+                &alice,
+                &[(key.clone(), Some(StreamId::MIN))],
+                None,
+                false,
+            )
+            .unwrap();
+        assert_eq!(read[0].1, vec![(id(2, 0), Some(fields("a", "1")))]);
+        backend.xdel(&key, &[id(2, 0)]).unwrap();
+        let read = backend
+            .xreadgroup(
+                b"g",
+                &alice,
+                &[(key.clone(), Some(StreamId::MIN))],
+                None,
+                false,
+            )
+            .unwrap();
+        assert_eq!(read[0].1, vec![(id(2, 0), None)]);
+
+        let filter = PendingFilter {
+            start: StreamId::MIN,
+            end: StreamId::MAX,
+            count: 10,
+            consumer: Some(alice.clone()),
+            min_idle: 0,
+        };
+        let pending = backend.xpending(b"s", b"g", &filter).unwrap().unwrap();
+        assert_eq!(pending.len(), 1);
+        // delivered once, then read twice from the history
+        assert_eq!(pending[0].delivery_count, 3);
+
+        assert_eq!(
+            backend.xgroup_delconsumer(&key, b"g", &alice).unwrap(),
+            Some(1)
+        );
+        assert_eq!(
+            backend.xgroup_delconsumer(&key, b"x", &alice).unwrap(),
+            None
+        );
+        assert!(backend.xgroup_destroy(&key, b"g").unwrap());
+        assert!(!backend.xgroup_exists(b"s", b"g").unwrap());
+    }
+
+    #[test]
+    fn test_claims() {
+        let backend = Backend::new();
+        let key = Bytes::from("s");
+        let (alice, bob) = (Bytes::from("alice"), Bytes::from("bob"));
+        backend
+            .xgroup_create(&key, "g".into(), Some(StreamId::MIN), None, true)
+            .unwrap();
+        for ms in 1..=4 {
+            backend
+                .xadd(&key, XAddId::AutoSeq(ms), fields("a", "1"), None, true)
+                .unwrap();
+        }
+        backend
+            .xreadgroup(b"g", &alice, &[(key.clone(), None)], Some(3), false)
+            .unwrap();
+
+        // the entries were just delivered
+        let claimed = backend
+            .xclaim(
+                &key,
+                b"g",
+                &bob,
+                60_000,
+                &[id(1, 0)],
+                &ClaimOptions::default(),
+            )
+            .unwrap()
+            .unwrap();
+        assert!(claimed.is_empty());
+        let options = ClaimOptions {
+            retry_count: Some(7),
+            ..Default::default()
+        };
+        let claimed = backend
+            .xclaim(&key, b"g", &bob, 0, &[id(1, 0), id(4, 0)], &options)
+            .unwrap()
+            .unwrap();
+        assert_eq!(ids(claimed), vec![id(1, 0)]);
+        let options = ClaimOptions {
+            idle: Some(60_000),
+            force: true,
+            last_id: Some(id(4, 0)),
+            ..Default::default()
+        };
+        let claimed = backend
+            .xclaim(&key, b"g", &bob, 0, &[id(4, 0), id(9, 0)], &options)
+            .unwrap()
+            .unwrap();
+        assert_eq!(ids(claimed), vec![id(4, 0)]);
+        let pending = backend
+            .xpending(
+                b"s",
+                b"g",
+                &PendingFilter {
+                    start: StreamId::MIN,
+                    end: StreamId::MAX,
+                    count: 10,
+                    consumer: None,
+                    min_idle: 0,
+                },
+            )
+            .unwrap()
+            .unwrap();
+        let pending = pending
+            .iter()
+            .map(|info| (info.id, info.consumer.clone(), info.delivery_count))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            pending,
+            vec![
+                (id(1, 0), bob.clone(), 7),
+                (id(2, 0), alice.clone(), 1),
+                (id(3, 0), alice.clone(), 1),
+                (id(4, 0), bob.clone(), 1),
+            ]
+        );
+        let groups = backend.xinfo_groups(b"s").unwrap().unwrap();
+        assert_eq!(groups[0].last_delivered_id, id(4, 0));
+
+        backend.xdel(&key, &[id(2, 0)]).unwrap();
+        let claim = backend
+            .xautoclaim(&key, b"g", &bob, 0, StreamId::MIN, 1, false)
+            .unwrap()
+            .unwrap();
+        // the scan stops once `count` entries are claimed
+        assert_eq!(ids(claim.claimed), vec![id(1, 0)]);
+        assert!(claim.deleted.is_empty());
+        assert_eq!(claim.next, id(2, 0));
+        let claim = backend
+            .xautoclaim(&key, b"g", &bob, 0, claim.next, 10, true)
+            .unwrap()
+            .unwrap();
+        assert_eq!(ids(claim.claimed), vec![id(3, 0), id(4, 0)]);
+        assert_eq!(claim.deleted, vec![id(2, 0)]);
+        assert_eq!(claim.next, StreamId::MIN);
+
+        let consumers = backend.xinfo_consumers(b"s", b"g").unwrap().unwrap();
+        let consumers = consumers
+            .iter()
+            .map(|info| (info.name.clone(), info.pending))
+            .collect::<Vec<_>>();
+        assert_eq!(consumers, vec![(alice, 0), (bob, 3)]);
+    }
+
+    #[test]
+    fn test_group_lag() {
+        let backend = Backend::new();
+        let key = Bytes::from("s");
+        for ms in 1..=3 {
+            backend
+                .xadd(&key, XAddId::AutoSeq(ms), fields("a", "1"), None, true)
+                .unwrap();
+        }
+        backend
+            .xgroup_create(&key, "g".into(), Some(StreamId::MIN), None, false)
+            .unwrap();
+        let lag = |backend: &Backend| {
+            let group = &backend.xinfo_groups(b"s").unwrap().unwrap()[0];
+            (group.entries_read, group.lag)
+        };
+        assert_eq!(lag(&backend), (None, Some(3)));
+        backend
+            .xreadgroup(b"g", &"c".into(), &[(key.clone(), None)], Some(1), true)
+            .unwrap();
+        assert_eq!(lag(&backend), (Some(1), Some(2)));
+        // a deleted entry past the last delivered one makes the lag unknown
+        backend.xdel(&key, &[id(3, 0)]).unwrap();
+        assert_eq!(lag(&backend), (Some(1), None));
+
+        let info = backend.xinfo_stream(b"s").unwrap().unwrap();
+        assert_eq!(info.length, 2);
+        assert_eq!(info.entries_added, 3);
+        assert_eq!(info.max_deleted_id, id(3, 0));
+        assert_eq!(info.groups, 1);
+        assert_eq!(info.last_entry.map(|(id, _)| id), Some(id(2, 0)));
+        assert!(backend.xinfo_stream(b"missing").unwrap().is_none());
+    }
+}
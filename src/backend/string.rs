@@ -0,0 +1,290 @@
+use super::{now_ms, Backend, RedisValue, SetExpiry};
+use crate::cmd::CommandError;
+use bytes::{Bytes, BytesMut};
+use dashmap::mapref::entry::Entry;
+
+/// Largest string a write is allowed to produce, the default `proto-max-bulk-len` of Redis.
+const MAX_STRING_LEN: usize = 512 * 1024 * 1024;
+
+/// Takes a string out of `value` as a mutable buffer.
+///
+/// The buffer is reused without copying when the string is not shared, so repeated appends
+/// to the same key are amortized like in Redis.
+fn take_mut(value: &mut Bytes) -> BytesMut {
+    std::mem::take(value)
+        .try_into_mut()
+        .unwrap_or_else(|shared| BytesMut::from(shared.as_ref()))
+}
+
+/// Significant digits kept by `format_float`.
+///
+/// Redis computes in `long double` and prints 17 decimals, which hides the rounding error of
+/// sums such as `0.1 + 0.2`. An `f64` only holds about 16 significant digits, so its 17th
+/// digit is mostly that error and is rounded away.
+const FLOAT_DIGITS: i32 = 16;
+/// Decimals printed at most, as with the `%.17Lf` format of Redis.
+const FLOAT_DECIMALS: i32 = 17;
+
+/// Formats a float the way `INCRBYFLOAT` replies with and stores it: rounded to 16
+/// significant digits and at most 17 decimals, in plain decimal notation without an
+/// exponent and without trailing zeros, so integral values have no fractional part at all.
+pub(crate) fn format_float(value: f64) -> String {
+    let exponent = format!("{value:e}")
+        .split_once('e')
+        .and_then(|(_, exponent)| exponent.parse::<i32>().ok())
+        .unwrap_or_default();
+    let decimals = (FLOAT_DIGITS - 1 - exponent).clamp(0, FLOAT_DECIMALS) as usize;
+    let mut formatted = format!("{value:.decimals$}");
+    if formatted.contains('.') {
+        let len = formatted.trim_end_matches('0').trim_end_matches('.').len();
+        formatted.truncate(len);
+    }
+    if formatted == "-0" {
+        formatted.remove(0);
+    }
+    formatted
+}
+
+/// Parses an integer the way Redis's `string2ll` does: an optional `-` followed by digits
+/// without a leading zero, so that `+5` or `007` are not integers.
+pub(crate) fn parse_i64(bytes: &[u8]) -> Option<i64> {
+    let digits = bytes.strip_prefix(b"-").unwrap_or(bytes);
+    match digits {
+        b"0" if digits.len() == bytes.len() => return Some(0),
+        [b'1'..=b'9', rest @ ..] if rest.iter().all(u8::is_ascii_digit) => {}
+        _ => return None,
+    }
+    std::str::from_utf8(bytes).ok()?.parse().ok()
+}
+
+fn check_len(len: usize) -> Result<(), CommandError> {
+    if len > MAX_STRING_LEN {
+        return Err(CommandError::InvalidArguments(
+            "string exceeds maximum allowed size (proto-max-bulk-len)".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+impl Backend {
+    /// Appends `value` to the string at `key`, creating it if needed.
+    ///
+    /// Returns the length of the string after the append.
+    pub fn append(&self, key: &Bytes, value: &[u8]) -> Result<usize, CommandError> {
+        let len = self.write(key, true, |s: &mut Bytes| {
+            check_len(s.len() + value.len())?;
+            let mut buf = take_mut(s);
+            buf.extend_from_slice(value);
+            *s = buf.freeze();
+            Ok(s.len())
+        })?;
+        len.expect("the string is created when missing")
+    }
+
+    /// Returns the length of the string at `key`, `0` if the key does not exist.
+    pub fn strlen(&self, key: &[u8]) -> Result<usize, CommandError> {
+        let len = self.read(key, |s: &Bytes| s.len())?;
+        Ok(len.unwrap_or_default())
+    }
+
+    /// Returns the substring of the string at `key` between the offsets `start` and `end`,
+    /// both inclusive.
+    ///
+    /// Negative offsets count from the end of the string, `-1` being the last byte. Offsets
+    /// past either end are clamped, and an empty range yields an empty string.
+    pub fn getrange(&self, key: &[u8], start: i64, end: i64) -> Result<Bytes, CommandError> {
+        let range = self.read(key, |s: &Bytes| {
+            let len = s.len() as i64;
+            if (start < 0 && end < 0 && start > end) || len == 0 {
+                return Bytes::new();
+            }
+            let start = if start < 0 { len + start } else { start }.max(0);
+            let end = if end < 0 { len + end } else { end }.clamp(0, len - 1);
+            if start > end {
+                return Bytes::new();
+            }
+            s.slice(start as usize..=end as usize)
+        })?;
+        Ok(range.unwrap_or_default())
+    }
+
+    /// Overwrites the string at `key` with `value`, starting at `offset`.
+    ///
+    /// A string shorter than `offset` is padded with zero bytes first, and a missing key is
+    /// treated as an empty string. Writing an empty value only reports the current length
+    /// and does not create the key.
+    ///
+    /// Returns the length of the string after the write.
+    pub fn setrange(
+        &self,
+        key: &Bytes,
+        offset: usize,
+        value: &[u8],
+    ) -> Result<usize, CommandError> {
+        if value.is_empty() {
+            return self.strlen(key);
+        }
+        check_len(offset.saturating_add(value.len()))?;
+        let len = self.write(key, true, |s: &mut Bytes| {
+            let mut buf = take_mut(s);
+            let end = offset + value.len();
+            if buf.len() < end {
+                buf.resize(end, 0);
+            }
+            buf[offset..end].copy_from_slice(value);
+            *s = buf.freeze();
+            s.len()
+        })?;
+        Ok(len.unwrap_or_default())
+    }
+
+    /// Removes the string at `key` and returns it.
+    ///
+    /// The key is left untouched if it holds another type, in which case
+    /// `CommandError::WrongType` is returned.
+    pub fn getdel(&self, key: &Bytes) -> Result<Option<Bytes>, CommandError> {
+        self.expire_if_needed(key);
+        match self.keyspace.entry(key.clone()) {
+            Entry::Occupied(entry) => match entry.get() {
+                RedisValue::String(_) => {
+                    self.scan_order.remove(key);
+                    let (key, value) = entry.remove_entry();
+                    self.expires.remove(&key);
+                    match value {
+                        RedisValue::String(value) => Ok(Some(value)),
+                        _ => unreachable!("the value was checked to be a string"),
+                    }
+                }
+                _ => Err(CommandError::WrongType),
+            },
+            Entry::Vacant(_) => Ok(None),
+        }
+    }
+
+    /// Returns the string at `key` and changes its expiry, as `GETEX` does.
+    ///
+    /// `SetExpiry::Discard` makes the key persistent, `SetExpiry::Keep` leaves the expiry
+    /// alone and `SetExpiry::At` sets it; a time in the past deletes the key.
+    pub fn getex(&self, key: &Bytes, expiry: SetExpiry) -> Result<Option<Bytes>, CommandError> {
+        self.expire_if_needed(key);
+        match self.keyspace.entry(key.clone()) {
+            Entry::Occupied(entry) => {
+                let RedisValue::String(value) = entry.get() else {
+                    return Err(CommandError::WrongType);
+                };
+                let value = value.clone();
+                match expiry {
+                    SetExpiry::At(at) if at <= now_ms() => {
+                        self.scan_order.remove(key);
+                        entry.remove();
+                        self.expires.remove(key);
+                    }
+                    expiry => self.update_expiry(key, expiry),
+                }
+                Ok(Some(value))
+            }
+            Entry::Vacant(_) => Ok(None),
+        }
+    }
+
+    /// Replaces the string at `key` with the one computed by `f` from the current string,
+    /// or from `None` if the key does not exist.
+    ///
+    /// `f` runs while holding the key's map entry, so the read-modify-write is atomic and
+    /// concurrent updates are never lost. The expiry of the key is retained. Nothing is
+    /// written if `f` fails.
+    fn update_string<R>(
+        &self,
+        key: &Bytes,
+        f: impl FnOnce(Option<&Bytes>) -> Result<(Bytes, R), CommandError>,
+    ) -> Result<R, CommandError> {
+        self.expire_if_needed(key);
+        match self.keyspace.entry(key.clone()) {
+            Entry::Occupied(mut entry) => {
+                let RedisValue::String(current) = entry.get_mut() else {
+                    return Err(CommandError::WrongType);
+                };
+                let (value, ret) = f(Some(current))?;
+                *current = value;
+                Ok(ret)
+            }
+            Entry::Vacant(entry) => {
+                let (value, ret) = f(None)?;
+                self.scan_order.insert(key);
+                entry.insert(RedisValue::String(value));
+                self.notify_new_key(key);
+                Ok(ret)
+            }
+        }
+    }
+
+    /// Increments the integer stored at `key` by `delta`, a missing key counting as `0`.
+    ///
+    /// Returns the new value, or an error if the value is not an integer or the increment
+    /// would overflow.
+    pub fn incr_by(&self, key: &Bytes, delta: i64) -> Result<i64, CommandError> {
+        self.update_string(key, |current| {
+            let current = match current {
+                Some(current) => parse_i64(current).ok_or(CommandError::NotAnInteger)?,
+                None => 0,
+            };
+            let value = current.checked_add(delta).ok_or(CommandError::Overflow)?;
+            Ok((Bytes::from(value.to_string()), value))
+        })
+    }
+
+    /// Increments the float stored at `key` by `delta`, a missing key counting as `0`.
+    ///
+    /// Returns the new value as stored, or an error if the value is not a float or the
+    /// result is not finite.
+    pub fn incr_by_float(&self, key: &Bytes, delta: f64) -> Result<Bytes, CommandError> {
+        self.update_string(key, |current| {
+            let current = match current {
+                Some(current) => std::str::from_utf8(current)
+                    .ok()
+                    .and_then(|current| current.parse::<f64>().ok())
+                    .filter(|current| current.is_finite())
+                    .ok_or(CommandError::NotAFloat)?,
+                None => 0.0,
+            };
+            let value = current + delta;
+            if !value.is_finite() {
+                return Err(CommandError::InvalidArguments(
+                    "increment would produce NaN or Infinity".to_string(),
+                ));
+            }
+            let value = Bytes::from(format_float(value));
+            Ok((value.clone(), value))
+        })
+    }
+
+    /// Returns the strings stored at the given keys, `None` for keys that do not exist or
+    /// hold another type.
+    pub fn mget(&self, keys: &[Bytes]) -> Vec<Option<Bytes>> {
+        keys.iter()
+            .map(|key| self.get(key).ok().flatten())
+            .collect()
+    }
+
+    /// Stores several strings, replacing any existing value and expiry.
+    ///
+    /// The keys are written one after the other; callers lock them with `lock_keys` first
+    /// to make the whole write atomic, as the `MSET` command does.
+    pub fn mset(&self, pairs: Vec<(Bytes, Bytes)>) {
+        for (key, value) in pairs {
+            self.set(key, value);
+        }
+    }
+
+    /// Stores several strings only if none of the keys exists.
+    ///
+    /// Like `mset`, the keys must be locked to make the check and the writes atomic.
+    /// Returns `true` if the strings were stored.
+    pub fn msetnx(&self, pairs: Vec<(Bytes, Bytes)>) -> bool {
+        if pairs.iter().any(|(key, _)| self.contains_key(key)) {
+            return false;
+        }
+        self.mset(pairs);
+        true
+    }
+}
@@ -0,0 +1,201 @@
+use super::{HashFields, SortedSet, Stream};
+use bytes::Bytes;
+use std::collections::{HashSet, VecDeque};
+
+/// A value stored in the keyspace.
+///
+/// Every key holds exactly one value of one type, so commands of one type can't observe or
+/// clobber a key of another type by accident.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RedisValue {
+    String(Bytes),
+    Hash(HashFields),
+    List(VecDeque<Bytes>),
+    Set(HashSet<Bytes>),
+    ZSet(SortedSet),
+    Stream(Stream),
+}
+
+impl RedisValue {
+    /// Returns the name of the value's type, as reported by `TYPE`.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            RedisValue::String(_) => "string",
+            RedisValue::Hash(_) => "hash",
+            RedisValue::List(_) => "list",
+            RedisValue::Set(_) => "set",
+            RedisValue::ZSet(_) => "zset",
+            RedisValue::Stream(_) => "stream",
+        }
+    }
+
+    /// Returns roughly how many allocations make up the value, which is how much work
+    /// freeing it takes.
+    pub(crate) fn effort(&self) -> usize {
+        match self {
+            RedisValue::String(_) => 1,
+            RedisValue::Hash(hash) => hash.len(),
+            RedisValue::List(list) => list.len(),
+            RedisValue::Set(set) => set.len(),
+            RedisValue::ZSet(zset) => zset.len(),
+            RedisValue::Stream(stream) => stream.len(),
+        }
+    }
+}
+
+/// A concrete type a `RedisValue` can hold.
+///
+/// Used by the typed accessors of `Backend` to borrow the value of a key as a given type,
+/// failing with `WRONGTYPE` when the key holds another type.
+pub(crate) trait ValueType: Default + Sized {
+    fn from_value(value: &RedisValue) -> Option<&Self>;
+
+    fn from_value_mut(value: &mut RedisValue) -> Option<&mut Self>;
+
+    fn into_value(self) -> RedisValue;
+
+    /// Returns `true` if the key holding this value should be removed from the keyspace.
+    ///
+    /// Aggregate types are never stored empty; an empty string is a valid value.
+    fn is_empty(&self) -> bool {
+        false
+    }
+}
+
+impl ValueType for Bytes {
+    fn from_value(value: &RedisValue) -> Option<&Self> {
+        match value {
+            RedisValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    fn from_value_mut(value: &mut RedisValue) -> Option<&mut Self> {
+        match value {
+            RedisValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    fn into_value(self) -> RedisValue {
+        RedisValue::String(self)
+    }
+}
+
+impl ValueType for HashFields {
+    fn from_value(value: &RedisValue) -> Option<&Self> {
+        match value {
+            RedisValue::Hash(h) => Some(h),
+            _ => None,
+        }
+    }
+
+    fn from_value_mut(value: &mut RedisValue) -> Option<&mut Self> {
+        match value {
+            RedisValue::Hash(h) => Some(h),
+            _ => None,
+        }
+    }
+
+    fn into_value(self) -> RedisValue {
+        RedisValue::Hash(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        HashFields::is_empty(self)
+    }
+}
+
+impl ValueType for VecDeque<Bytes> {
+    fn from_value(value: &RedisValue) -> Option<&Self> {
+        match value {
+            RedisValue::List(l) => Some(l),
+            _ => None,
+        }
+    }
+
+    fn from_value_mut(value: &mut RedisValue) -> Option<&mut Self> {
+        match value {
+            RedisValue::List(l) => Some(l),
+            _ => None,
+        }
+    }
+
+    fn into_value(self) -> RedisValue {
+        RedisValue::List(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        VecDeque::is_empty(self)
+    }
+}
+
+impl ValueType for HashSet<Bytes> {
+    fn from_value(value: &RedisValue) -> Option<&Self> {
+        match value {
+            RedisValue::Set(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    fn from_value_mut(value: &mut RedisValue) -> Option<&mut Self> {
+        match value {
+            RedisValue::Set(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    fn into_value(self) -> RedisValue {
+        RedisValue::Set(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        HashSet::is_empty(self)
+    }
+}
+
+impl ValueType for SortedSet {
+    fn from_value(value: &RedisValue) -> Option<&Self> {
+        match value {
+            RedisValue::ZSet(z) => Some(z),
+            _ => None,
+        }
+    }
+
+    fn from_value_mut(value: &mut RedisValue) -> Option<&mut Self> {
+        match value {
+            RedisValue::ZSet(z) => Some(z),
+            _ => None,
+        }
+    }
+
+    fn into_value(self) -> RedisValue {
+        RedisValue::ZSet(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        SortedSet::is_empty(self)
+    }
+}
+
+/// Unlike the other aggregate types, a stream is kept when it becomes empty, so that its
+/// last ID and its consumer groups are retained.
+impl ValueType for Stream {
+    fn from_value(value: &RedisValue) -> Option<&Self> {
+        match value {
+            RedisValue::Stream(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    fn from_value_mut(value: &mut RedisValue) -> Option<&mut Self> {
+        match value {
+            RedisValue::Stream(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    fn into_value(self) -> RedisValue {
+        RedisValue::Stream(self)
+    }
+}
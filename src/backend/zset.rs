@@ -0,0 +1,911 @@
+use super::list::index_range;
+use super::skiplist::SkipList;
+use super::{Backend, SetCondition};
+use crate::cmd::CommandError;
+use bytes::Bytes;
+use rand::seq::IteratorRandom;
+use rand::Rng;
+use std::collections::{HashMap, HashSet};
+
+/// Members paired with their score, in the order of a sorted set.
+pub type ScoredMembers = Vec<(Bytes, f64)>;
+
+/// The score of each member of a sorted set.
+type Scores = HashMap<Bytes, f64>;
+
+/// A sorted set: members with a score, ordered by score and then by member.
+///
+/// A hash index maps each member to its score, and a skiplist keeps the members in order,
+/// so looking up a score is O(1) while ranks and ranges are O(log n).
+#[derive(Debug, Clone, Default)]
+pub struct SortedSet {
+    scores: Scores,
+    list: SkipList,
+}
+
+/// An interval of scores, whose bounds may be excluded, written `(score` by clients.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScoreRange {
+    pub min: f64,
+    pub min_exclusive: bool,
+    pub max: f64,
+    pub max_exclusive: bool,
+}
+
+/// A bound of an interval of members, compared byte by byte.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LexBound {
+    /// `-`, lower than every member.
+    Min,
+    /// `+`, greater than every member.
+    Max,
+    /// `[member`
+    Inclusive(Bytes),
+    /// `(member`
+    Exclusive(Bytes),
+}
+
+/// An interval of members, only meaningful when all the members have the same score.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LexRange {
+    pub min: LexBound,
+    pub max: LexBound,
+}
+
+/// What `ZRANGE` and friends select.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ZRangeBy {
+    /// An inclusive range of ranks, negative ranks counting from the highest score.
+    Rank(i64, i64),
+    Score(ScoreRange),
+    Lex(LexRange),
+}
+
+/// Which end of a sorted set members are popped from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ZSetEnd {
+    /// The lowest scores, popped by `ZPOPMIN`.
+    Min,
+    /// The highest scores, popped by `ZPOPMAX`.
+    Max,
+}
+
+/// How the scores of a member found in several sorted sets are combined by `ZUNION` and
+/// `ZINTER`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Aggregate {
+    #[default]
+    Sum,
+    Min,
+    Max,
+}
+
+/// Only update a score if the new one compares to the current one as given, as done by
+/// `ZADD` with `GT` or `LT`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ZAddComparison {
+    Gt,
+    Lt,
+}
+
+/// The options of `ZADD` applying to each member.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ZAddOptions {
+    /// Only add new members (`NX`) or only update existing ones (`XX`).
+    pub condition: SetCondition,
+    pub comparison: Option<ZAddComparison>,
+    /// Count the updated members along with the added ones (`CH`).
+    pub changed: bool,
+}
+
+impl ScoreRange {
+    /// Returns `true` if `score` is not below the lower bound.
+    pub fn above_min(&self, score: f64) -> bool {
+        if self.min_exclusive {
+            score > self.min
+        } else {
+            score >= self.min
+        }
+    }
+
+    /// Returns `true` if `score` is not above the upper bound.
+    pub fn below_max(&self, score: f64) -> bool {
+        if self.max_exclusive {
+            score < self.max
+        } else {
+            score <= self.max
+        }
+    }
+}
+
+impl LexRange {
+    /// Returns `true` if `member` is not below the lower bound.
+    pub fn above_min(&self, member: &[u8]) -> bool {
+        match &self.min {
+            LexBound::Min => true,
+            LexBound::Max => false,
+            LexBound::Inclusive(min) => member >= min.as_ref(),
+            LexBound::Exclusive(min) => member > min.as_ref(),
+        }
+    }
+
+    /// Returns `true` if `member` is not above the upper bound.
+    pub fn below_max(&self, member: &[u8]) -> bool {
+        match &self.max {
+            LexBound::Min => false,
+            LexBound::Max => true,
+            LexBound::Inclusive(max) => member <= max.as_ref(),
+            LexBound::Exclusive(max) => member < max.as_ref(),
+        }
+    }
+}
+
+impl Aggregate {
+    fn apply(self, a: f64, b: f64) -> f64 {
+        match self {
+            // the sum of opposite infinities is taken as 0
+            Aggregate::Sum => zero_if_nan(a + b),
+            Aggregate::Min => a.min(b),
+            Aggregate::Max => a.max(b),
+        }
+    }
+}
+
+fn zero_if_nan(score: f64) -> f64 {
+    if score.is_nan() {
+        0.0
+    } else {
+        score
+    }
+}
+
+impl ZAddOptions {
+    /// Returns `true` if the score of an existing member may change from `old` to `new`.
+    fn allows_update(&self, old: f64, new: f64) -> bool {
+        self.condition != SetCondition::Nx
+            && match self.comparison {
+                Some(ZAddComparison::Gt) => new > old,
+                Some(ZAddComparison::Lt) => new < old,
+                None => true,
+            }
+    }
+}
+
+impl PartialEq for SortedSet {
+    fn eq(&self, other: &Self) -> bool {
+        self.scores == other.scores
+    }
+}
+
+impl SortedSet {
+    pub fn len(&self) -> usize {
+        self.scores.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scores.is_empty()
+    }
+
+    pub fn score(&self, member: &[u8]) -> Option<f64> {
+        self.scores.get(member).copied()
+    }
+
+    /// Adds a member or updates its score.
+    ///
+    /// Returns the previous score of the member, if it was already in the set.
+    pub fn insert(&mut self, member: Bytes, score: f64) -> Option<f64> {
+        let old = self.scores.insert(member.clone(), score);
+        match old {
+            Some(old) if old == score => {}
+            Some(old) => {
+                self.list.remove(old, &member);
+                self.list.insert(score, member);
+            }
+            None => self.list.insert(score, member),
+        }
+        old
+    }
+
+    /// Removes a member and returns its score.
+    pub fn remove(&mut self, member: &[u8]) -> Option<f64> {
+        let score = self.scores.remove(member)?;
+        self.list.remove(score, member);
+        Some(score)
+    }
+
+    /// Returns the 0-based rank of a member along with its score. With `rev`, ranks are
+    /// counted from the highest score.
+    pub fn rank(&self, member: &[u8], rev: bool) -> Option<(usize, f64)> {
+        let score = self.score(member)?;
+        let rank = self.list.rank(score, member)?;
+        Some((if rev { self.len() - 1 - rank } else { rank }, score))
+    }
+
+    /// Returns the number of members whose score is within `range`.
+    pub fn count(&self, range: &ScoreRange) -> usize {
+        let first = self.list.first_after(|score, _| !range.above_min(score));
+        let last = self.list.last_within(|score, _| range.below_max(score));
+        match (first, last) {
+            (Some((first, _)), Some((last, _))) if first <= last => last - first + 1,
+            _ => 0,
+        }
+    }
+
+    /// Returns the members selected by `range` with their scores, in order or from the
+    /// highest score if `rev` is `true`, skipping `offset` of them and returning at most
+    /// `count`.
+    ///
+    /// With `rev`, score and lex ranges are still given from their lower to upper bound.
+    pub fn range(&self, range: &ZRangeBy, rev: bool, offset: usize, count: usize) -> ScoredMembers {
+        let list = &self.list;
+        let members: Box<dyn Iterator<Item = (f64, &Bytes)>> = match range {
+            ZRangeBy::Rank(start, stop) => {
+                let Some(ranks) = index_range(*start, *stop, self.len()) else {
+                    return Vec::new();
+                };
+                let first = if rev {
+                    self.len() - 1 - ranks.start()
+                } else {
+                    *ranks.start()
+                };
+                Box::new(
+                    list.iter_from(list.at_rank(first), rev)
+                        .take(ranks.end() - ranks.start() + 1),
+                )
+            }
+            ZRangeBy::Score(range) => {
+                let first = if rev {
+                    list.last_within(|score, _| range.below_max(score))
+                } else {
+                    list.first_after(|score, _| !range.above_min(score))
+                };
+                Box::new(list.iter_from(first.map(|(_, node)| node), rev).take_while(
+                    move |(score, _)| {
+                        if rev {
+                            range.above_min(*score)
+                        } else {
+                            range.below_max(*score)
+                        }
+                    },
+                ))
+            }
+            ZRangeBy::Lex(range) => {
+                let first = if rev {
+                    list.last_within(|_, member| range.below_max(member))
+                } else {
+                    list.first_after(|_, member| !range.above_min(member))
+                };
+                Box::new(list.iter_from(first.map(|(_, node)| node), rev).take_while(
+                    move |(_, member)| {
+                        if rev {
+                            range.above_min(member)
+                        } else {
+                            range.below_max(member)
+                        }
+                    },
+                ))
+            }
+        };
+        members
+            .skip(offset)
+            .take(count)
+            .map(|(score, member)| (member.clone(), score))
+            .collect()
+    }
+
+    /// Removes the members selected by `range` and returns how many were removed.
+    pub fn remove_range(&mut self, range: &ZRangeBy) -> usize {
+        let members = self.range(range, false, 0, usize::MAX);
+        for (member, _) in &members {
+            self.remove(member);
+        }
+        members.len()
+    }
+
+    /// Removes up to `count` members from an end and returns them with their scores, from
+    /// that end.
+    pub fn pop(&mut self, end: ZSetEnd, count: usize) -> ScoredMembers {
+        if count == 0 {
+            return Vec::new();
+        }
+        let range = ZRangeBy::Rank(0, (count - 1).min(i64::MAX as usize) as i64);
+        let members = self.range(&range, end == ZSetEnd::Max, 0, count);
+        for (member, _) in &members {
+            self.remove(member);
+        }
+        members
+    }
+
+    /// Iterates over the members and their scores, from the lowest score.
+    pub fn iter(&self) -> impl Iterator<Item = (&Bytes, f64)> + '_ {
+        self.list.iter().map(|(score, member)| (member, score))
+    }
+}
+
+impl Backend {
+    /// Adds members with their score to the sorted set at `key`, or updates their score,
+    /// as allowed by `options`.
+    ///
+    /// Returns the number of members added, plus the number of members whose score changed
+    /// if `options.changed` is set.
+    pub fn zadd(
+        &self,
+        key: &Bytes,
+        members: Vec<(f64, Bytes)>,
+        options: ZAddOptions,
+    ) -> Result<usize, CommandError> {
+        let (added, updated) = self.zadd_counts(key, members, options)?;
+        Ok(if options.changed {
+            added + updated
+        } else {
+            added
+        })
+    }
+
+    /// Adds or updates members like `zadd`, and returns the number of members added and the
+    /// number of members whose score changed.
+    pub fn zadd_counts(
+        &self,
+        key: &Bytes,
+        members: Vec<(f64, Bytes)>,
+        options: ZAddOptions,
+    ) -> Result<(usize, usize), CommandError> {
+        let counts = self.write(key, true, |z: &mut SortedSet| {
+            let mut added = 0;
+            let mut updated = 0;
+            for (score, member) in members {
+                match z.score(&member) {
+                    Some(old) if old != score && options.allows_update(old, score) => {
+                        z.insert(member, score);
+                        updated += 1;
+                    }
+                    None if options.condition != SetCondition::Xx => {
+                        z.insert(member, score);
+                        added += 1;
+                    }
+                    _ => {}
+                }
+            }
+            (added, updated)
+        })?;
+        Ok(counts.unwrap_or_default())
+    }
+
+    /// Increments the score of a member of the sorted set at `key`, adding the member with
+    /// `increment` as its score if needed, as allowed by `options`.
+    ///
+    /// Returns the new score, or `None` if `options` prevented the update.
+    pub fn zincrby(
+        &self,
+        key: &Bytes,
+        member: Bytes,
+        increment: f64,
+        options: ZAddOptions,
+    ) -> Result<Option<f64>, CommandError> {
+        let score = self.write(key, true, |z: &mut SortedSet| {
+            let score = match z.score(&member) {
+                Some(old) => {
+                    let score = old + increment;
+                    if score.is_nan() {
+                        return Err(CommandError::InvalidArguments(
+                            "resulting score is not a number (NaN)".to_string(),
+                        ));
+                    }
+                    if !options.allows_update(old, score) {
+                        return Ok(None);
+                    }
+                    score
+                }
+                None if options.condition == SetCondition::Xx => return Ok(None),
+                None => increment,
+            };
+            z.insert(member, score);
+            Ok(Some(score))
+        })?;
+        score.transpose().map(Option::flatten)
+    }
+
+    /// Removes members from a sorted set. The sorted set is removed from the keyspace once
+    /// it is empty.
+    ///
+    /// Returns the number of members that were removed.
+    pub fn zrem(&self, key: &Bytes, members: &[Bytes]) -> Result<usize, CommandError> {
+        let removed = self.write(key, false, |z: &mut SortedSet| {
+            members
+                .iter()
+                .filter(|member| z.remove(member).is_some())
+                .count()
+        })?;
+        Ok(removed.unwrap_or_default())
+    }
+
+    /// Returns the score of a member of the sorted set.
+    pub fn zscore(&self, key: &[u8], member: &[u8]) -> Result<Option<f64>, CommandError> {
+        let score = self.read(key, |z: &SortedSet| z.score(member))?;
+        Ok(score.flatten())
+    }
+
+    /// Returns the score of each of the given members.
+    pub fn zmscore(&self, key: &[u8], members: &[Bytes]) -> Result<Vec<Option<f64>>, CommandError> {
+        let scores = self.read(key, |z: &SortedSet| {
+            members.iter().map(|member| z.score(member)).collect()
+        })?;
+        Ok(scores.unwrap_or_else(|| vec![None; members.len()]))
+    }
+
+    /// Returns the number of members of the sorted set, `0` if the key does not exist.
+    pub fn zcard(&self, key: &[u8]) -> Result<usize, CommandError> {
+        let len = self.read(key, |z: &SortedSet| z.len())?;
+        Ok(len.unwrap_or_default())
+    }
+
+    /// Returns the number of members whose score is within `range`.
+    pub fn zcount(&self, key: &[u8], range: &ScoreRange) -> Result<usize, CommandError> {
+        let count = self.read(key, |z: &SortedSet| z.count(range))?;
+        Ok(count.unwrap_or_default())
+    }
+
+    /// Returns the rank of a member along with its score, counted from the highest score
+    /// if `rev` is `true`.
+    pub fn zrank(
+        &self,
+        key: &[u8],
+        member: &[u8],
+        rev: bool,
+    ) -> Result<Option<(usize, f64)>, CommandError> {
+        let rank = self.read(key, |z: &SortedSet| z.rank(member, rev))?;
+        Ok(rank.flatten())
+    }
+
+    /// Returns the members selected by `range` with their scores, as done by `ZRANGE`.
+    ///
+    /// See `SortedSet::range`.
+    pub fn zrange(
+        &self,
+        key: &[u8],
+        range: &ZRangeBy,
+        rev: bool,
+        offset: usize,
+        count: usize,
+    ) -> Result<ScoredMembers, CommandError> {
+        let members = self.read(key, |z: &SortedSet| z.range(range, rev, offset, count))?;
+        Ok(members.unwrap_or_default())
+    }
+}
+
+impl Backend {
+    /// Removes the members of the sorted set at `key` selected by `range`.
+    ///
+    /// Returns the number of members that were removed.
+    pub fn zremrange(&self, key: &Bytes, range: &ZRangeBy) -> Result<usize, CommandError> {
+        let removed = self.write(key, false, |z: &mut SortedSet| z.remove_range(range))?;
+        Ok(removed.unwrap_or_default())
+    }
+
+    /// Pops up to `count` members from an end of the sorted set at `key`.
+    ///
+    /// Returns `None` if the key does not exist.
+    pub fn zpop(
+        &self,
+        key: &Bytes,
+        end: ZSetEnd,
+        count: usize,
+    ) -> Result<Option<ScoredMembers>, CommandError> {
+        self.write(key, false, |z: &mut SortedSet| z.pop(end, count))
+    }
+
+    /// Pops up to `count` members from an end of the first non-empty sorted set among
+    /// `keys`.
+    ///
+    /// Returns the key the members were popped from along with the members, or `None` if
+    /// none of the keys exists.
+    pub fn zmpop(
+        &self,
+        keys: &[Bytes],
+        end: ZSetEnd,
+        count: usize,
+    ) -> Result<Option<(Bytes, ScoredMembers)>, CommandError> {
+        for key in keys {
+            if let Some(members) = self.zpop(key, end, count)? {
+                return Ok(Some((key.clone(), members)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns random members of the sorted set with their scores, as done by
+    /// `ZRANDMEMBER`.
+    ///
+    /// With a positive `count`, up to `count` distinct members are returned. With a
+    /// negative `count`, exactly `-count` members are returned, possibly repeated.
+    pub fn zrandmember(&self, key: &[u8], count: i64) -> Result<ScoredMembers, CommandError> {
+        let members = self.read(key, |z: &SortedSet| {
+            let mut rng = rand::rng();
+            if count >= 0 {
+                z.iter()
+                    .choose_multiple(&mut rng, count as usize)
+                    .into_iter()
+                    .map(|(member, score)| (member.clone(), score))
+                    .collect()
+            } else {
+                (0..count.unsigned_abs())
+                    .flat_map(|_| {
+                        let rank = rng.random_range(0..z.len()) as i64;
+                        z.range(&ZRangeBy::Rank(rank, rank), false, 0, 1)
+                    })
+                    .collect()
+            }
+        })?;
+        Ok(members.unwrap_or_default())
+    }
+
+    /// Stores the members of the sorted set at `source` selected as done by `zrange` at
+    /// `destination`, replacing any value.
+    ///
+    /// Returns the number of members of the stored sorted set. An empty result removes
+    /// the destination.
+    pub fn zrangestore(
+        &self,
+        destination: Bytes,
+        source: &[u8],
+        range: &ZRangeBy,
+        rev: bool,
+        offset: usize,
+        count: usize,
+    ) -> Result<usize, CommandError> {
+        let members = self.zrange(source, range, rev, offset, count)?;
+        Ok(self.zstore(destination, members.into_iter()))
+    }
+
+    /// Returns the union of the sorted sets at `keys`, each score being multiplied by the
+    /// weight of its set and combined with `aggregate`.
+    ///
+    /// Sets are accepted as inputs, with a score of 1 for each member. `weights` is either
+    /// empty or has one weight per key.
+    pub fn zunion(
+        &self,
+        keys: &[Bytes],
+        weights: &[f64],
+        aggregate: Aggregate,
+    ) -> Result<ScoredMembers, CommandError> {
+        let mut union = HashMap::new();
+        for (members, weight) in self.weighted_inputs(keys, weights)? {
+            for (member, score) in members {
+                let score = zero_if_nan(score * weight);
+                union
+                    .entry(member)
+                    .and_modify(|total| *total = aggregate.apply(*total, score))
+                    .or_insert(score);
+            }
+        }
+        Ok(sorted(union))
+    }
+
+    /// Returns the intersection of the sorted sets at `keys`, with scores computed as done
+    /// by `zunion`.
+    ///
+    /// The smallest input is iterated and the others are looked up, so the cost is bound by
+    /// the size of the smallest input.
+    pub fn zinter(
+        &self,
+        keys: &[Bytes],
+        weights: &[f64],
+        aggregate: Aggregate,
+    ) -> Result<ScoredMembers, CommandError> {
+        let inputs = self.weighted_inputs(keys, weights)?;
+        let Some((smallest, _)) = inputs.iter().enumerate().min_by_key(|(_, (m, _))| m.len())
+        else {
+            return Ok(Vec::new());
+        };
+        let mut inter = HashMap::new();
+        'members: for member in inputs[smallest].0.keys() {
+            let mut total = None;
+            for (members, weight) in &inputs {
+                let Some(score) = members.get(member) else {
+                    continue 'members;
+                };
+                let score = zero_if_nan(score * weight);
+                total = Some(match total {
+                    Some(total) => aggregate.apply(total, score),
+                    None => score,
+                });
+            }
+            inter.insert(member.clone(), total.unwrap_or_default());
+        }
+        Ok(sorted(inter))
+    }
+
+    /// Returns the members of the first sorted set at `keys` that are in none of the others,
+    /// with their score.
+    pub fn zdiff(&self, keys: &[Bytes]) -> Result<ScoredMembers, CommandError> {
+        let mut inputs = self.weighted_inputs(keys, &[])?.into_iter();
+        let Some((mut diff, _)) = inputs.next() else {
+            return Ok(Vec::new());
+        };
+        for (members, _) in inputs {
+            if diff.is_empty() {
+                break;
+            }
+            diff.retain(|member, _| !members.contains_key(member));
+        }
+        Ok(sorted(diff))
+    }
+
+    /// Stores members with their scores as a sorted set at `destination`, replacing any
+    /// value, and returns its size.
+    pub fn zstore(&self, destination: Bytes, members: impl Iterator<Item = (Bytes, f64)>) -> usize {
+        let mut z = SortedSet::default();
+        for (member, score) in members {
+            z.insert(member, score);
+        }
+        let len = z.len();
+        self.store(destination, z);
+        len
+    }
+
+    /// Reads the members and scores of each key, paired with the weight of the key.
+    fn weighted_inputs(
+        &self,
+        keys: &[Bytes],
+        weights: &[f64],
+    ) -> Result<Vec<(Scores, f64)>, CommandError> {
+        keys.iter()
+            .enumerate()
+            .map(|(i, key)| {
+                let members = match self.read(key, |z: &SortedSet| z.scores.clone()) {
+                    Err(CommandError::WrongType) => self.read(key, |s: &HashSet<Bytes>| {
+                        s.iter().map(|member| (member.clone(), 1.0)).collect()
+                    })?,
+                    members => members?,
+                };
+                Ok((
+                    members.unwrap_or_default(),
+                    weights.get(i).copied().unwrap_or(1.0),
+                ))
+            })
+            .collect()
+    }
+}
+
+/// Orders members by score, then by member.
+fn sorted(members: Scores) -> ScoredMembers {
+    let mut members = members.into_iter().collect::<Vec<_>>();
+    members.sort_by(|a, b| a.1.total_cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+    members
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn zset(members: &[(f64, &'static str)]) -> SortedSet {
+        let mut z = SortedSet::default();
+        for (score, member) in members {
+            z.insert(Bytes::from_static(member.as_bytes()), *score);
+        }
+        z
+    }
+
+    fn names(members: ScoredMembers) -> Vec<Bytes> {
+        members.into_iter().map(|(member, _)| member).collect()
+    }
+
+    #[test]
+    fn test_sorted_set_update_keeps_order() {
+        let mut z = zset(&[(1.0, "a"), (2.0, "b"), (3.0, "c")]);
+        assert_eq!(z.insert("a".into(), 4.0), Some(1.0));
+        assert_eq!(z.rank(b"a", false), Some((2, 4.0)));
+        assert_eq!(z.rank(b"a", true), Some((0, 4.0)));
+        assert_eq!(z.remove(b"b"), Some(2.0));
+        assert_eq!(z.remove(b"b"), None);
+        assert_eq!(
+            z.iter()
+                .map(|(member, _)| member.clone())
+                .collect::<Vec<_>>(),
+            vec![Bytes::from("c"), Bytes::from("a")]
+        );
+        assert_eq!(z, zset(&[(3.0, "c"), (4.0, "a")]));
+    }
+
+    #[test]
+    fn test_sorted_set_ranges() {
+        let z = zset(&[
+            (1.0, "a"),
+            (2.0, "b"),
+            (2.0, "c"),
+            (3.0, "d"),
+            (f64::INFINITY, "e"),
+        ]);
+        let all = usize::MAX;
+        assert_eq!(
+            names(z.range(&ZRangeBy::Rank(1, -2), false, 0, all)),
+            vec![Bytes::from("b"), Bytes::from("c"), Bytes::from("d")]
+        );
+        assert_eq!(
+            names(z.range(&ZRangeBy::Rank(0, 1), true, 0, all)),
+            vec![Bytes::from("e"), Bytes::from("d")]
+        );
+
+        let range = ScoreRange {
+            min: 1.0,
+            min_exclusive: true,
+            max: f64::INFINITY,
+            max_exclusive: false,
+        };
+        assert_eq!(z.count(&range), 4);
+        assert_eq!(
+            names(z.range(&ZRangeBy::Score(range), true, 1, 2)),
+            vec![Bytes::from("d"), Bytes::from("c")]
+        );
+        let empty = ScoreRange {
+            min: 2.0,
+            min_exclusive: true,
+            max: 2.0,
+            max_exclusive: false,
+        };
+        assert_eq!(z.count(&empty), 0);
+        assert!(z.range(&ZRangeBy::Score(empty), false, 0, all).is_empty());
+
+        let z = zset(&[(0.0, "a"), (0.0, "b"), (0.0, "c"), (0.0, "d")]);
+        let range = LexRange {
+            min: LexBound::Exclusive("a".into()),
+            max: LexBound::Inclusive("c".into()),
+        };
+        assert_eq!(
+            names(z.range(&ZRangeBy::Lex(range.clone()), false, 0, all)),
+            vec![Bytes::from("b"), Bytes::from("c")]
+        );
+        assert_eq!(
+            names(z.range(&ZRangeBy::Lex(range), true, 0, all)),
+            vec![Bytes::from("c"), Bytes::from("b")]
+        );
+        let range = LexRange {
+            min: LexBound::Min,
+            max: LexBound::Max,
+        };
+        assert_eq!(z.range(&ZRangeBy::Lex(range), false, 0, all).len(), 4);
+    }
+
+    #[test]
+    fn test_zadd_options() -> Result<(), CommandError> {
+        let backend = Backend::new();
+        let key = Bytes::from("z");
+        let members = |members: &[(f64, &'static str)]| {
+            members
+                .iter()
+                .map(|(score, member)| (*score, Bytes::from_static(member.as_bytes())))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            backend.zadd(&key, members(&[(1.0, "a"), (2.0, "b")]), Default::default())?,
+            2
+        );
+
+        let xx = ZAddOptions {
+            condition: SetCondition::Xx,
+            changed: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            backend.zadd(&key, members(&[(5.0, "a"), (1.0, "c")]), xx)?,
+            1
+        );
+        assert_eq!(backend.zscore(b"z", b"c")?, None);
+
+        let gt = ZAddOptions {
+            comparison: Some(ZAddComparison::Gt),
+            changed: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            backend.zadd(&key, members(&[(1.0, "a"), (3.0, "b"), (0.0, "c")]), gt)?,
+            2
+        );
+        assert_eq!(
+            backend.zmscore(b"z", &["a".into(), "b".into(), "x".into()])?,
+            vec![Some(5.0), Some(3.0), None]
+        );
+
+        let nx = ZAddOptions {
+            condition: SetCondition::Nx,
+            ..Default::default()
+        };
+        assert_eq!(backend.zincrby(&key, "a".into(), 1.0, nx)?, None);
+        assert_eq!(
+            backend.zincrby(&key, "a".into(), 1.5, Default::default())?,
+            Some(6.5)
+        );
+        backend.zincrby(&key, "inf".into(), f64::INFINITY, Default::default())?;
+        assert!(backend
+            .zincrby(&key, "inf".into(), f64::NEG_INFINITY, Default::default())
+            .is_err());
+
+        // XX never creates the key
+        let missing = Bytes::from("missing");
+        assert_eq!(backend.zadd(&missing, members(&[(1.0, "a")]), xx)?, 0);
+        assert!(!backend.contains_key(b"missing"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_zpop_and_remove_range() -> Result<(), CommandError> {
+        let backend = Backend::new();
+        let key = Bytes::from("z");
+        backend.store(
+            key.clone(),
+            zset(&[(1.0, "a"), (2.0, "b"), (3.0, "c"), (4.0, "d")]),
+        );
+        assert_eq!(
+            backend.zpop(&key, ZSetEnd::Max, 2)?,
+            Some(vec![(Bytes::from("d"), 4.0), (Bytes::from("c"), 3.0)])
+        );
+        let range = ZRangeBy::Score(ScoreRange {
+            min: f64::NEG_INFINITY,
+            min_exclusive: false,
+            max: 2.0,
+            max_exclusive: true,
+        });
+        assert_eq!(backend.zremrange(&key, &range)?, 1);
+        assert_eq!(
+            backend.zpop(&key, ZSetEnd::Min, 5)?,
+            Some(vec![(Bytes::from("b"), 2.0)])
+        );
+        assert!(!backend.contains_key(b"z"));
+        assert_eq!(backend.zpop(&key, ZSetEnd::Min, 1)?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_aggregation() -> Result<(), CommandError> {
+        let backend = Backend::new();
+        backend.store("z1".into(), zset(&[(1.0, "a"), (2.0, "b"), (3.0, "c")]));
+        backend.store("z2".into(), zset(&[(10.0, "b"), (20.0, "c"), (30.0, "d")]));
+        backend.sadd(&"s".into(), vec!["c".into(), "d".into()])?;
+        let keys = [Bytes::from("z1"), Bytes::from("z2"), Bytes::from("s")];
+
+        assert_eq!(
+            backend.zinter(&keys, &[], Aggregate::Sum)?,
+            vec![(Bytes::from("c"), 24.0)]
+        );
+        assert_eq!(
+            backend.zunion(&keys[..2], &[2.0, 0.5], Aggregate::Max)?,
+            vec![
+                (Bytes::from("a"), 2.0),
+                (Bytes::from("b"), 5.0),
+                (Bytes::from("c"), 10.0),
+                (Bytes::from("d"), 15.0)
+            ]
+        );
+        assert_eq!(
+            backend.zunion(&keys[..2], &[], Aggregate::Min)?,
+            vec![
+                (Bytes::from("a"), 1.0),
+                (Bytes::from("b"), 2.0),
+                (Bytes::from("c"), 3.0),
+                (Bytes::from("d"), 30.0)
+            ]
+        );
+        assert_eq!(
+            backend.zdiff(&[Bytes::from("z1"), Bytes::from("s")])?,
+            vec![(Bytes::from("a"), 1.0), (Bytes::from("b"), 2.0)]
+        );
+        assert!(backend
+            .zinter(
+                &[Bytes::from("z1"), Bytes::from("missing")],
+                &[],
+                Aggregate::Sum
+            )?
+            .is_empty());
+
+        backend.set("string".into(), "value".into());
+        assert!(matches!(
+            backend.zunion(
+                &[Bytes::from("z1"), Bytes::from("string")],
+                &[],
+                Aggregate::Sum
+            ),
+            Err(CommandError::WrongType)
+        ));
+        Ok(())
+    }
+}
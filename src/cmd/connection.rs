@@ -0,0 +1,276 @@
+use super::hmap::parse_key_and_args;
+use super::list::bulk_string_args;
+use super::pubsub::Subscriptions;
+use super::transaction::Transaction;
+use super::{CommandError, CommandExecutor, SessionCommand};
+use crate::{Backend, BulkString, RespArray, RespFrame, RespMap, SimpleString};
+use bytes::Bytes;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// The Redis version reported to clients, the one whose commands the server follows.
+const SERVER_VERSION: &str = "7.4.0";
+
+/// The id given to the next client connection.
+static NEXT_CLIENT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// The protocol spoken on a connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Protocol {
+    /// RESP2, which every connection starts with.
+    #[default]
+    Resp2,
+    /// RESP3, chosen with `HELLO 3`.
+    Resp3,
+}
+
+/// The state of a client connection, which commands such as `HELLO` act on.
+#[derive(Debug)]
+pub struct Session {
+    id: u64,
+    protocol: Protocol,
+    name: Option<Bytes>,
+    pub(super) subscriptions: Subscriptions,
+    /// The commands queued since `MULTI`, until `EXEC` or `DISCARD`.
+    pub(super) transaction: Option<Transaction>,
+}
+
+/// `HELLO [protover [AUTH username password] [SETNAME clientname]]`
+#[derive(Debug)]
+pub struct Hello {
+    protocol: Option<Protocol>,
+    name: Option<Bytes>,
+}
+
+impl Session {
+    /// Creates the state of a new connection, with a unique id.
+    pub fn new() -> Self {
+        let id = NEXT_CLIENT_ID.fetch_add(1, Ordering::Relaxed);
+        Self {
+            id,
+            protocol: Protocol::default(),
+            name: None,
+            subscriptions: Subscriptions::new(id),
+            transaction: None,
+        }
+    }
+
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn protocol(&self) -> Protocol {
+        self.protocol
+    }
+
+    pub fn name(&self) -> Option<&Bytes> {
+        self.name.as_ref()
+    }
+
+    /// Converts a reply into a frame the client understands with the protocol of the
+    /// connection.
+    pub fn encode_reply(&self, frame: RespFrame) -> RespFrame {
+        match self.protocol {
+            Protocol::Resp2 => frame.into_resp2(),
+            Protocol::Resp3 => frame,
+        }
+    }
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// `PING [message]`
+#[derive(Debug)]
+pub struct Ping {
+    message: Option<Bytes>,
+}
+
+impl SessionCommand for Hello {
+    /// Switches the connection to the requested protocol and returns the properties of the
+    /// server and the connection.
+    fn execute_session(self, _backend: &Backend, session: &mut Session) -> RespFrame {
+        if let Some(protocol) = self.protocol {
+            session.protocol = protocol;
+        }
+        if let Some(name) = self.name {
+            session.name = Some(name);
+        }
+        let proto = match session.protocol {
+            Protocol::Resp2 => 2,
+            Protocol::Resp3 => 3,
+        };
+        let mut map = RespMap::new();
+        map.insert("server".into(), BulkString::new("redis").into());
+        map.insert("version".into(), BulkString::new(SERVER_VERSION).into());
+        map.insert("proto".into(), RespFrame::Integer(proto));
+        map.insert("id".into(), RespFrame::Integer(session.id as i64));
+        map.insert("mode".into(), BulkString::new("standalone").into());
+        map.insert("role".into(), BulkString::new("master").into());
+        map.insert("modules".into(), RespArray::new([]).into());
+        map.into()
+    }
+}
+
+impl CommandExecutor for Hello {
+    /// Runs `HELLO` on a session of its own, since there is no connection to act on.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        self.execute_session(backend, &mut Session::new())
+    }
+}
+
+impl SessionCommand for Ping {
+    /// Replies with `PONG`, or with the message if given.
+    ///
+    /// A RESP2 connection subscribed to channels gets an array of `pong` and the message,
+    /// empty if not given, so that the reply can't be mistaken for a published message.
+    fn execute_session(self, _backend: &Backend, session: &mut Session) -> RespFrame {
+        if session.protocol == Protocol::Resp2 && session.is_subscribed() {
+            return RespArray::new([
+                BulkString::new("pong").into(),
+                BulkString::new(self.message.unwrap_or_default()).into(),
+            ])
+            .into();
+        }
+        match self.message {
+            Some(message) => BulkString::new(message).into(),
+            None => SimpleString::new("PONG").into(),
+        }
+    }
+}
+
+impl CommandExecutor for Ping {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        self.execute_session(backend, &mut Session::new())
+    }
+}
+
+impl TryFrom<RespArray> for Ping {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = bulk_string_args(value)?;
+        if args.len() > 1 {
+            return Err(CommandError::WrongArity("ping".to_string()));
+        }
+        Ok(Ping {
+            message: args.pop(),
+        })
+    }
+}
+
+impl TryFrom<RespArray> for Hello {
+    type Error = CommandError;
+    /// Converts a RESP array into a `Hello` command.
+    ///
+    /// The protocol version may be followed by `AUTH username password` and
+    /// `SETNAME clientname`. Since the server has no users, only the `default` user can
+    /// authenticate, with any password.
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        if value.len() == 1 {
+            return Ok(Hello {
+                protocol: None,
+                name: None,
+            });
+        }
+        let (version, args) = parse_key_and_args(value)?;
+        let protocol = match std::str::from_utf8(&version)
+            .ok()
+            .and_then(|version| version.parse::<i64>().ok())
+        {
+            Some(2) => Protocol::Resp2,
+            Some(3) => Protocol::Resp3,
+            Some(_) => return Err(CommandError::NoProto),
+            None => {
+                return Err(CommandError::InvalidArguments(
+                    "Protocol version is not an integer or out of range".to_string(),
+                ))
+            }
+        };
+        let mut args = args.into_iter();
+        let mut name = None;
+        while let Some(option) = args.next() {
+            if option.eq_ignore_ascii_case(b"auth") {
+                let (Some(user), Some(_)) = (args.next(), args.next()) else {
+                    return Err(CommandError::SyntaxError);
+                };
+                if user.as_ref() != b"default" {
+                    return Err(CommandError::WrongPass);
+                }
+            } else if option.eq_ignore_ascii_case(b"setname") {
+                let client_name = args.next().ok_or(CommandError::SyntaxError)?;
+                if client_name.iter().any(|&c| c <= b' ' || c > b'~') {
+                    return Err(CommandError::InvalidArguments(
+                        "Client names cannot contain spaces, newlines or special characters."
+                            .to_string(),
+                    ));
+                }
+                name = Some(client_name);
+            } else {
+                return Err(CommandError::SyntaxError);
+            }
+        }
+        Ok(Hello {
+            protocol: Some(protocol),
+            name,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hello(args: &[&str]) -> Result<Hello, CommandError> {
+        let frames = ["hello"]
+            .iter()
+            .chain(args)
+            .map(|arg| BulkString::new(*arg).into())
+            .collect::<Vec<RespFrame>>();
+        Hello::try_from(RespArray::new(frames))
+    }
+
+    #[test]
+    fn test_hello_switches_protocol() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let mut session = Session::new();
+        assert_eq!(session.protocol(), Protocol::Resp2);
+
+        let reply = hello(&["3", "SETNAME", "worker"])?.execute_session(&backend, &mut session);
+        assert_eq!(session.protocol(), Protocol::Resp3);
+        assert_eq!(session.name(), Some(&Bytes::from("worker")));
+        let RespFrame::Map(map) = reply else {
+            panic!("HELLO must reply with a map");
+        };
+        assert_eq!(map.get(b"proto".as_slice()), Some(&RespFrame::Integer(3)));
+        assert_eq!(
+            map.get(b"id".as_slice()),
+            Some(&RespFrame::Integer(session.id() as i64))
+        );
+
+        // without arguments, the protocol is kept
+        hello(&[])?.execute_session(&backend, &mut session);
+        assert_eq!(session.protocol(), Protocol::Resp3);
+        hello(&["2", "AUTH", "default", "secret"])?.execute_session(&backend, &mut session);
+        assert_eq!(session.protocol(), Protocol::Resp2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_hello_errors() {
+        assert!(matches!(hello(&["4"]), Err(CommandError::NoProto)));
+        assert!(matches!(
+            hello(&["3", "AUTH", "admin", "secret"]),
+            Err(CommandError::WrongPass)
+        ));
+        assert!(matches!(
+            hello(&["3", "AUTH", "default"]),
+            Err(CommandError::SyntaxError)
+        ));
+        assert_eq!(
+            hello(&["three"]).unwrap_err().to_string(),
+            "ERR Protocol version is not an integer or out of range"
+        );
+    }
+}
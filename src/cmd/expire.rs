@@ -0,0 +1,480 @@
+use super::{extract_args, parse_integer, parse_key_arg, CommandError, CommandExecutor};
+use crate::{
+    now_ms, Backend, EventClass, ExpireCondition, KeyExpiry, RespArray, RespFrame, SimpleError,
+};
+use bytes::Bytes;
+
+#[derive(Debug)]
+pub struct Expire {
+    key: Bytes,
+    seconds: i64,
+    condition: ExpireCondition,
+}
+
+#[derive(Debug)]
+pub struct PExpire {
+    key: Bytes,
+    milliseconds: i64,
+    condition: ExpireCondition,
+}
+
+#[derive(Debug)]
+pub struct ExpireAt {
+    key: Bytes,
+    timestamp: i64,
+    condition: ExpireCondition,
+}
+
+#[derive(Debug)]
+pub struct PExpireAt {
+    key: Bytes,
+    timestamp_ms: i64,
+    condition: ExpireCondition,
+}
+
+#[derive(Debug)]
+pub struct Ttl {
+    key: Bytes,
+}
+
+#[derive(Debug)]
+pub struct PTtl {
+    key: Bytes,
+}
+
+#[derive(Debug)]
+pub struct ExpireTime {
+    key: Bytes,
+}
+
+#[derive(Debug)]
+pub struct PExpireTime {
+    key: Bytes,
+}
+
+#[derive(Debug)]
+pub struct Persist {
+    key: Bytes,
+}
+
+impl CommandExecutor for Expire {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let at = self
+            .seconds
+            .checked_mul(1000)
+            .and_then(|ms| ms.checked_add(now_ms() as i64));
+        expire_generic(backend, &self.key, at, self.condition, "expire")
+    }
+}
+
+impl CommandExecutor for PExpire {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let at = self.milliseconds.checked_add(now_ms() as i64);
+        expire_generic(backend, &self.key, at, self.condition, "pexpire")
+    }
+}
+
+impl CommandExecutor for ExpireAt {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let at = self.timestamp.checked_mul(1000);
+        expire_generic(backend, &self.key, at, self.condition, "expireat")
+    }
+}
+
+impl CommandExecutor for PExpireAt {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let at = Some(self.timestamp_ms);
+        expire_generic(backend, &self.key, at, self.condition, "pexpireat")
+    }
+}
+
+impl CommandExecutor for Ttl {
+    /// Returns the remaining time to live of a key in seconds, `-1` if the key has no
+    /// expiry and `-2` if the key does not exist.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.expiry(&self.key) {
+            KeyExpiry::ExpiresAt(at) => RespFrame::Integer((remaining_ms(at) + 500) / 1000),
+            expiry => expiry_code(expiry),
+        }
+    }
+}
+
+impl CommandExecutor for PTtl {
+    /// Returns the remaining time to live of a key in milliseconds, `-1` if the key has no
+    /// expiry and `-2` if the key does not exist.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.expiry(&self.key) {
+            KeyExpiry::ExpiresAt(at) => RespFrame::Integer(remaining_ms(at)),
+            expiry => expiry_code(expiry),
+        }
+    }
+}
+
+impl CommandExecutor for ExpireTime {
+    /// Returns the absolute unix time in seconds at which the key expires, `-1` if the key
+    /// has no expiry and `-2` if the key does not exist.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.expiry(&self.key) {
+            KeyExpiry::ExpiresAt(at) => RespFrame::Integer((at / 1000) as i64),
+            expiry => expiry_code(expiry),
+        }
+    }
+}
+
+impl CommandExecutor for PExpireTime {
+    /// Returns the absolute unix time in milliseconds at which the key expires, `-1` if the
+    /// key has no expiry and `-2` if the key does not exist.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.expiry(&self.key) {
+            KeyExpiry::ExpiresAt(at) => RespFrame::Integer(at as i64),
+            expiry => expiry_code(expiry),
+        }
+    }
+}
+
+impl CommandExecutor for Persist {
+    /// Removes the expiry of a key. Returns `1` if the expiry was removed, `0` if the key
+    /// does not exist or has no expiry.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let persisted = backend.persist(&self.key);
+        if persisted {
+            backend.notify_keyspace_event(EventClass::Generic, "persist", &self.key);
+        }
+        RespFrame::Integer(persisted as i64)
+    }
+}
+
+/// Applies an absolute expiry in milliseconds to a key.
+///
+/// `at` is `None` when computing the absolute time overflowed, which Redis reports as an
+/// invalid expire time. Returns `1` if the expiry was set and `0` otherwise.
+fn expire_generic(
+    backend: &Backend,
+    key: &[u8],
+    at: Option<i64>,
+    condition: ExpireCondition,
+    name: &str,
+) -> RespFrame {
+    match at {
+        Some(at) => {
+            // negative timestamps are in the past, the key is deleted right away
+            let at = u64::try_from(at).unwrap_or_default();
+            let set = backend.expire_at(key, at, condition);
+            if set {
+                let event = if at <= now_ms() { "del" } else { "expire" };
+                backend.notify_keyspace_event(EventClass::Generic, event, key);
+            }
+            RespFrame::Integer(set as i64)
+        }
+        None => SimpleError::new(format!("ERR invalid expire time in '{}' command", name)).into(),
+    }
+}
+
+fn remaining_ms(at: u64) -> i64 {
+    at.saturating_sub(now_ms()) as i64
+}
+
+fn expiry_code(expiry: KeyExpiry) -> RespFrame {
+    match expiry {
+        KeyExpiry::NotFound => RespFrame::Integer(-2),
+        _ => RespFrame::Integer(-1),
+    }
+}
+
+/// Parses the arguments shared by `EXPIRE`, `PEXPIRE`, `EXPIREAT` and `PEXPIREAT`:
+/// a key, an integer time and an optional `NX`, `XX`, `GT` or `LT` condition.
+fn parse_expire_args(value: RespArray) -> Result<(Bytes, i64, ExpireCondition), CommandError> {
+    let mut args = extract_args(value, 1)?.into_iter();
+    let key = match args.next() {
+        Some(RespFrame::BulkString(key)) => Bytes::from(key.0),
+        _ => return Err(CommandError::InvalidArguments("Invalid key".to_string())),
+    };
+    let time = parse_integer(args.next().ok_or(CommandError::SyntaxError)?)?;
+    let condition = match args.next() {
+        None => ExpireCondition::Always,
+        Some(option) => parse_expire_condition(option)?,
+    };
+    if let Some(option) = args.next() {
+        // Redis names the options that can't be combined
+        return Err(match (condition, parse_expire_condition(option)?) {
+            (ExpireCondition::Nx, _) | (_, ExpireCondition::Nx) => CommandError::InvalidArguments(
+                "NX and XX, GT or LT options at the same time are not compatible".to_string(),
+            ),
+            (ExpireCondition::Gt, ExpireCondition::Lt)
+            | (ExpireCondition::Lt, ExpireCondition::Gt) => CommandError::InvalidArguments(
+                "GT and LT options at the same time are not compatible".to_string(),
+            ),
+            _ => CommandError::SyntaxError,
+        });
+    }
+    Ok((key, time, condition))
+}
+
+fn parse_expire_condition(option: RespFrame) -> Result<ExpireCondition, CommandError> {
+    let RespFrame::BulkString(option) = option else {
+        return Err(CommandError::SyntaxError);
+    };
+    match option.to_ascii_lowercase().as_slice() {
+        b"nx" => Ok(ExpireCondition::Nx),
+        b"xx" => Ok(ExpireCondition::Xx),
+        b"gt" => Ok(ExpireCondition::Gt),
+        b"lt" => Ok(ExpireCondition::Lt),
+        _ => Err(CommandError::InvalidArguments(format!(
+            "Unsupported option {}",
+            String::from_utf8_lossy(&option)
+        ))),
+    }
+}
+
+impl TryFrom<RespArray> for Expire {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, seconds, condition) = parse_expire_args(value)?;
+        Ok(Expire {
+            key,
+            seconds,
+            condition,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for PExpire {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, milliseconds, condition) = parse_expire_args(value)?;
+        Ok(PExpire {
+            key,
+            milliseconds,
+            condition,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ExpireAt {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, timestamp, condition) = parse_expire_args(value)?;
+        Ok(ExpireAt {
+            key,
+            timestamp,
+            condition,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for PExpireAt {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, timestamp_ms, condition) = parse_expire_args(value)?;
+        Ok(PExpireAt {
+            key,
+            timestamp_ms,
+            condition,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for Ttl {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let key = parse_key_arg(value)?;
+        Ok(Ttl { key })
+    }
+}
+
+impl TryFrom<RespArray> for PTtl {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let key = parse_key_arg(value)?;
+        Ok(PTtl { key })
+    }
+}
+
+impl TryFrom<RespArray> for ExpireTime {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let key = parse_key_arg(value)?;
+        Ok(ExpireTime { key })
+    }
+}
+
+impl TryFrom<RespArray> for PExpireTime {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let key = parse_key_arg(value)?;
+        Ok(PExpireTime { key })
+    }
+}
+
+impl TryFrom<RespArray> for Persist {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let key = parse_key_arg(value)?;
+        Ok(Persist { key })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cmd::Get, RespDecode, RespNull};
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    fn set(backend: &Backend, key: &str) {
+        backend.set(Bytes::copy_from_slice(key.as_bytes()), "world".into());
+    }
+
+    #[test]
+    fn test_expire_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$6\r\nexpire\r\n$5\r\nhello\r\n$2\r\n10\r\n$2\r\nNX\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Expire = frame.try_into()?;
+        assert_eq!(result.key, "hello");
+        assert_eq!(result.seconds, 10);
+        assert_eq!(result.condition, ExpireCondition::Nx);
+
+        buf.extend_from_slice(b"*3\r\n$6\r\nexpire\r\n$5\r\nhello\r\n$3\r\nabc\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Result<Expire, _> = frame.try_into();
+        assert!(result.is_err());
+
+        buf.extend_from_slice(
+            b"*5\r\n$6\r\nexpire\r\n$5\r\nhello\r\n$2\r\n10\r\n$2\r\nNX\r\n$2\r\nXX\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let err = Expire::try_from(frame).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "ERR NX and XX, GT or LT options at the same time are not compatible"
+        );
+
+        buf.extend_from_slice(
+            b"*5\r\n$6\r\nexpire\r\n$5\r\nhello\r\n$2\r\n10\r\n$2\r\nGT\r\n$2\r\nLT\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let err = Expire::try_from(frame).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "ERR GT and LT options at the same time are not compatible"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_ttl_codes() {
+        let backend = Backend::new();
+        let ttl = |key: &str| {
+            Ttl {
+                key: Bytes::copy_from_slice(key.as_bytes()),
+            }
+            .execute(&backend)
+        };
+        assert_eq!(ttl("hello"), RespFrame::Integer(-2));
+
+        set(&backend, "hello");
+        assert_eq!(ttl("hello"), RespFrame::Integer(-1));
+
+        let ret = Expire {
+            key: "hello".into(),
+            seconds: 100,
+            condition: ExpireCondition::Always,
+        }
+        .execute(&backend);
+        assert_eq!(ret, RespFrame::Integer(1));
+        assert_eq!(ttl("hello"), RespFrame::Integer(100));
+
+        let ret = Persist {
+            key: "hello".into(),
+        }
+        .execute(&backend);
+        assert_eq!(ret, RespFrame::Integer(1));
+        assert_eq!(ttl("hello"), RespFrame::Integer(-1));
+    }
+
+    #[test]
+    fn test_expire_conditions() {
+        let backend = Backend::new();
+        set(&backend, "hello");
+        let pexpire = |ms: i64, condition: ExpireCondition| {
+            PExpire {
+                key: "hello".into(),
+                milliseconds: ms,
+                condition,
+            }
+            .execute(&backend)
+        };
+        assert_eq!(pexpire(10_000, ExpireCondition::Xx), RespFrame::Integer(0));
+        assert_eq!(pexpire(10_000, ExpireCondition::Gt), RespFrame::Integer(0));
+        assert_eq!(pexpire(10_000, ExpireCondition::Nx), RespFrame::Integer(1));
+        assert_eq!(pexpire(20_000, ExpireCondition::Nx), RespFrame::Integer(0));
+        assert_eq!(pexpire(20_000, ExpireCondition::Lt), RespFrame::Integer(0));
+        assert_eq!(pexpire(20_000, ExpireCondition::Gt), RespFrame::Integer(1));
+    }
+
+    #[test]
+    fn test_expire_in_the_past_deletes_key() {
+        let backend = Backend::new();
+        set(&backend, "hello");
+        let ret = ExpireAt {
+            key: "hello".into(),
+            timestamp: 1,
+            condition: ExpireCondition::Always,
+        }
+        .execute(&backend);
+        assert_eq!(ret, RespFrame::Integer(1));
+        let ret = Get {
+            key: "hello".into(),
+        }
+        .execute(&backend);
+        assert_eq!(ret, RespFrame::Null(RespNull));
+    }
+
+    #[test]
+    fn test_lazy_and_active_expiry() {
+        let backend = Backend::new();
+        set(&backend, "hello");
+        set(&backend, "world");
+        // bypass `expire_at`, which would delete keys expiring in the past right away
+        backend.expires.insert("hello".into(), now_ms() - 1);
+        backend.expires.insert("world".into(), now_ms() - 1);
+
+        assert_eq!(backend.get(b"hello").unwrap(), None);
+        assert!(!backend.keyspace.contains_key(b"hello".as_slice()));
+
+        assert_eq!(backend.active_expire_round(), 1);
+        assert!(!backend.keyspace.contains_key(b"world".as_slice()));
+        assert_eq!(backend.expires.get(b"world"), None);
+    }
+
+    #[test]
+    fn test_active_expiry_walks_all_volatile_keys() {
+        let backend = Backend::new();
+        let now = now_ms();
+        for i in 0..1000 {
+            let key = format!("key:{i}");
+            set(&backend, &key);
+            // every tenth key is already expired, the others expire later
+            let at = if i % 10 == 0 { now - 1 } else { now + 100_000 };
+            backend.expires.insert(key.into(), at);
+        }
+
+        // each round samples a few keys and resumes where the previous one stopped, so enough
+        // rounds walk all the volatile keys
+        let removed = (0..200)
+            .map(|_| backend.active_expire_round())
+            .sum::<usize>();
+        assert_eq!(removed, 100);
+        assert_eq!(backend.keyspace.len(), 900);
+    }
+
+    #[test]
+    fn test_set_discards_expiry() {
+        let backend = Backend::new();
+        set(&backend, "hello");
+        backend.expire_at(b"hello", now_ms() + 10_000, ExpireCondition::Always);
+        set(&backend, "hello");
+        assert_eq!(backend.expiry(b"hello"), KeyExpiry::Persistent);
+    }
+}
@@ -1,36 +1,359 @@
-use super::{extract_args, validate_command, CommandExecutor, HGet, HGetAll, HSet, RESP_OK};
-use crate::{cmd::CommandError, RespArray, RespFrame, RespMap};
+use super::keyspace::{parse_cursor, scan_reply, ScanOptions, DEFAULT_SCAN_COUNT};
+use super::{
+    extract_args, notify_removed, optional_bulk_string, parse_float, parse_integer, parse_key_arg,
+    CommandExecutor, HGet, HGetAll, HSet, RESP_OK,
+};
+use crate::{
+    cmd::CommandError, Backend, BulkString, EventClass, RespArray, RespFrame, RespMap, RespNull,
+};
+use bytes::Bytes;
+
+#[derive(Debug)]
+pub struct HDel {
+    key: Bytes,
+    fields: Vec<Bytes>,
+}
+
+#[derive(Debug)]
+pub struct HExists {
+    key: Bytes,
+    field: Bytes,
+}
+
+#[derive(Debug)]
+pub struct HLen {
+    key: Bytes,
+}
+
+#[derive(Debug)]
+pub struct HKeys {
+    key: Bytes,
+}
+
+#[derive(Debug)]
+pub struct HVals {
+    key: Bytes,
+}
+
+#[derive(Debug)]
+pub struct HMGet {
+    key: Bytes,
+    fields: Vec<Bytes>,
+}
+
+#[derive(Debug)]
+pub struct HMSet {
+    key: Bytes,
+    pairs: Vec<(Bytes, Bytes)>,
+}
+
+#[derive(Debug)]
+pub struct HSetNx {
+    key: Bytes,
+    field: Bytes,
+    value: Bytes,
+}
+
+#[derive(Debug)]
+pub struct HStrLen {
+    key: Bytes,
+    field: Bytes,
+}
+
+#[derive(Debug)]
+pub struct HIncrBy {
+    key: Bytes,
+    field: Bytes,
+    increment: i64,
+}
+
+#[derive(Debug)]
+pub struct HIncrByFloat {
+    key: Bytes,
+    field: Bytes,
+    increment: f64,
+}
+
+#[derive(Debug)]
+pub struct HRandField {
+    key: Bytes,
+    count: Option<i64>,
+    with_values: bool,
+}
+
+#[derive(Debug)]
+pub struct HScan {
+    key: Bytes,
+    cursor: u64,
+    options: ScanOptions,
+}
 
 impl CommandExecutor for HGet {
     fn execute(self, backend: &crate::Backend) -> RespFrame {
         match backend.hget(&self.key, &self.field) {
-            Some(value) => value,
-            None => RespFrame::Null(crate::RespNull),
+            Ok(value) => optional_bulk_string(value),
+            Err(e) => e.into(),
         }
     }
 }
 
 impl CommandExecutor for HGetAll {
     fn execute(self, backend: &crate::Backend) -> RespFrame {
-        let hmap = backend.hmap.get(&self.key);
+        let hmap = backend.hgetall(&self.key);
         match hmap {
-            Some(hmap) => {
+            Ok(Some(hmap)) => {
                 let mut map = RespMap::new();
-                for v in hmap.iter() {
-                    let key = v.key().to_owned();
-                    map.insert(key, v.value().clone());
+                for (field, value) in hmap {
+                    map.insert(field, BulkString::new(value).into());
                 }
                 map.into()
             }
-            None => RespArray::new([]).into(),
+            Ok(None) => RespArray::new([]).into(),
+            Err(e) => e.into(),
         }
     }
 }
 
 impl CommandExecutor for HSet {
+    /// Returns the number of fields that were created.
     fn execute(self, backend: &crate::Backend) -> RespFrame {
-        backend.hset(self.key, self.field, self.value);
-        RESP_OK.clone()
+        match backend.hset_many(self.key.clone(), self.pairs) {
+            Ok(created) => {
+                backend.notify_keyspace_event(EventClass::Hash, "hset", &self.key);
+                RespFrame::Integer(created as i64)
+            }
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for HDel {
+    /// Returns the number of fields that were removed.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.hdel(&self.key, &self.fields) {
+            Ok(removed) => {
+                if removed > 0 {
+                    notify_removed(backend, EventClass::Hash, "hdel", &self.key);
+                }
+                RespFrame::Integer(removed as i64)
+            }
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for HExists {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.hexists(&self.key, &self.field) {
+            Ok(exists) => RespFrame::Integer(exists as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for HLen {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.hlen(&self.key) {
+            Ok(len) => RespFrame::Integer(len as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for HKeys {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.hkeys(&self.key) {
+            Ok(fields) => bulk_string_array(fields),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for HVals {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.hvals(&self.key) {
+            Ok(values) => bulk_string_array(values),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for HMGet {
+    /// Returns the value of each field, or a null reply for the fields that do not exist.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.hmget(&self.key, &self.fields) {
+            Ok(values) => RespArray::new(
+                values
+                    .into_iter()
+                    .map(optional_bulk_string)
+                    .collect::<Vec<_>>(),
+            )
+            .into(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for HMSet {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.hset_many(self.key.clone(), self.pairs) {
+            Ok(_) => {
+                backend.notify_keyspace_event(EventClass::Hash, "hset", &self.key);
+                RESP_OK.clone()
+            }
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for HSetNx {
+    /// Returns `1` if the field was created, `0` if it already existed.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.hsetnx(self.key.clone(), self.field, self.value) {
+            Ok(created) => {
+                if created {
+                    backend.notify_keyspace_event(EventClass::Hash, "hset", &self.key);
+                }
+                RespFrame::Integer(created as i64)
+            }
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for HStrLen {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.hstrlen(&self.key, &self.field) {
+            Ok(len) => RespFrame::Integer(len as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for HIncrBy {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.hincrby(self.key.clone(), self.field, self.increment) {
+            Ok(value) => {
+                backend.notify_keyspace_event(EventClass::Hash, "hincrby", &self.key);
+                RespFrame::Integer(value)
+            }
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for HIncrByFloat {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.hincrbyfloat(self.key.clone(), self.field, self.increment) {
+            Ok(value) => {
+                backend.notify_keyspace_event(EventClass::Hash, "hincrbyfloat", &self.key);
+                BulkString::new(value).into()
+            }
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for HRandField {
+    /// Without a count, returns a single random field or a null reply if the key does not
+    /// exist. With a count, returns an array of fields, interleaved with their values with
+    /// `WITHVALUES`.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let pairs = match backend.hrandfield(&self.key, self.count.unwrap_or(1)) {
+            Ok(pairs) => pairs,
+            Err(e) => return e.into(),
+        };
+        if self.count.is_none() {
+            return match pairs.into_iter().next() {
+                Some((field, _)) => BulkString::new(field).into(),
+                None => RespFrame::Null(RespNull),
+            };
+        }
+        let mut reply = Vec::with_capacity(pairs.len() * (1 + self.with_values as usize));
+        for (field, value) in pairs {
+            reply.push(BulkString::new(field).into());
+            if self.with_values {
+                reply.push(BulkString::new(value).into());
+            }
+        }
+        RespArray::new(reply).into()
+    }
+}
+
+impl CommandExecutor for HScan {
+    /// Returns the cursor of the next call and the fields of this batch, each followed by
+    /// its value.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let result = backend.hscan(
+            &self.key,
+            self.cursor,
+            self.options.count.unwrap_or(DEFAULT_SCAN_COUNT),
+            self.options.pattern.as_deref(),
+        );
+        match result {
+            Ok((cursor, pairs)) => {
+                let elements = pairs
+                    .into_iter()
+                    .flat_map(|(field, value)| {
+                        [BulkString::new(field).into(), BulkString::new(value).into()]
+                    })
+                    .collect();
+                scan_reply(cursor, elements)
+            }
+            Err(e) => e.into(),
+        }
+    }
+}
+
+pub(super) fn bulk_string_array(values: Vec<Bytes>) -> RespFrame {
+    RespArray::new(
+        values
+            .into_iter()
+            .map(|value| BulkString::new(value).into())
+            .collect::<Vec<_>>(),
+    )
+    .into()
+}
+
+/// Parses a command whose arguments are a key followed by BulkStrings.
+pub(super) fn parse_key_and_args(value: RespArray) -> Result<(Bytes, Vec<Bytes>), CommandError> {
+    let mut args = extract_args(value, 1)?.into_iter();
+    let key = match args.next() {
+        Some(RespFrame::BulkString(key)) => Bytes::from(key.0),
+        _ => return Err(CommandError::InvalidArguments("Invalid key".to_string())),
+    };
+    let args = args
+        .map(|arg| match arg {
+            RespFrame::BulkString(arg) => Ok(Bytes::from(arg.0)),
+            _ => Err(CommandError::InvalidArguments("Invalid field".to_string())),
+        })
+        .collect::<Result<_, _>>()?;
+    Ok((key, args))
+}
+
+/// Parses a key followed by field-value pairs, as taken by `HSET` and `HMSET`.
+fn parse_field_value_pairs(
+    value: RespArray,
+    name: &str,
+) -> Result<(Bytes, Vec<(Bytes, Bytes)>), CommandError> {
+    let (key, args) = parse_key_and_args(value)?;
+    if args.is_empty() || args.len() % 2 != 0 {
+        return Err(CommandError::WrongArity(name.to_string()));
+    }
+    let mut args = args.into_iter();
+    let mut pairs = Vec::with_capacity(args.len() / 2);
+    while let (Some(field), Some(value)) = (args.next(), args.next()) {
+        pairs.push((field, value));
+    }
+    Ok((key, pairs))
+}
+
+/// Parses a key followed by a single field, as taken by `HEXISTS` and `HSTRLEN`.
+fn parse_key_field(value: RespArray) -> Result<(Bytes, Bytes), CommandError> {
+    let (key, args) = parse_key_and_args(value)?;
+    match <[Bytes; 1]>::try_from(args) {
+        Ok([field]) => Ok((key, field)),
+        Err(_) => Err(CommandError::SyntaxError),
     }
 }
 
@@ -44,12 +367,11 @@ impl TryFrom<RespArray> for HGet {
     /// If the conversion is successful, an `HGet` struct with the key and field fields set is returned.
     /// If the conversion fails, an `Err` containing the `CommandError` is returned.
     fn try_from(value: RespArray) -> Result<Self, Self::Error> {
-        validate_command(&value, &["hget"], 2)?;
         let mut args = extract_args(value, 1)?.into_iter();
         match (args.next(), args.next()) {
             (Some(RespFrame::BulkString(key)), Some(RespFrame::BulkString(field))) => Ok(HGet {
-                key: String::from_utf8(key.0)?,
-                field: String::from_utf8(field.0)?,
+                key: Bytes::from(key.0),
+                field: Bytes::from(field.0),
             }),
             _ => Err(CommandError::InvalidArguments(
                 "Invalid key or field".to_string(),
@@ -61,11 +383,10 @@ impl TryFrom<RespArray> for HGet {
 impl TryFrom<RespArray> for HGetAll {
     type Error = CommandError;
     fn try_from(value: RespArray) -> Result<Self, Self::Error> {
-        validate_command(&value, &["hgetall"], 1)?;
         let mut args = extract_args(value, 1)?.into_iter();
         match args.next() {
             Some(RespFrame::BulkString(key)) => Ok(HGetAll {
-                key: String::from_utf8(key.0)?,
+                key: Bytes::from(key.0),
             }),
             _ => Err(CommandError::InvalidArguments("Invalid key".to_string())),
         }
@@ -76,35 +397,175 @@ impl TryFrom<RespArray> for HSet {
     type Error = CommandError;
     /// Converts a RESP array into an `HSet` command.
     ///
-    /// The RESP array must have exactly 4 elements: the command name "hset", the key,
-    /// the field, and the value. The key and field must be BulkString frames, and the
-    /// value can be any `RespFrame`.
+    /// The RESP array holds the command name "hset", the key and one or more field-value
+    /// pairs. All of them must be BulkString frames.
     ///
-    /// If the conversion is successful, an `HSet` struct with the key, field, and value
-    /// fields set is returned. If the conversion fails, an `Err` containing the
-    /// `CommandError` is returned.
+    /// If the conversion is successful, an `HSet` struct with the key and the pairs set is
+    /// returned. If the conversion fails, an `Err` containing the `CommandError` is returned.
     fn try_from(value: RespArray) -> Result<Self, Self::Error> {
-        validate_command(&value, &["hset"], 3)?;
-        let mut args = extract_args(value, 1)?.into_iter();
-        match (args.next(), args.next(), args.next()) {
-            (Some(RespFrame::BulkString(key)), Some(RespFrame::BulkString(field)), Some(value)) => {
-                Ok(HSet {
-                    key: String::from_utf8(key.0)?,
-                    field: String::from_utf8(field.0)?,
-                    value,
-                })
-            }
-            _ => Err(CommandError::InvalidArguments(
-                "Invalid key, field or value".to_string(),
-            )),
+        let (key, pairs) = parse_field_value_pairs(value, "hset")?;
+        Ok(HSet { key, pairs })
+    }
+}
+
+impl TryFrom<RespArray> for HDel {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, fields) = parse_key_and_args(value)?;
+        Ok(HDel { key, fields })
+    }
+}
+
+impl TryFrom<RespArray> for HExists {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, field) = parse_key_field(value)?;
+        Ok(HExists { key, field })
+    }
+}
+
+impl TryFrom<RespArray> for HLen {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let key = parse_key_arg(value)?;
+        Ok(HLen { key })
+    }
+}
+
+impl TryFrom<RespArray> for HKeys {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let key = parse_key_arg(value)?;
+        Ok(HKeys { key })
+    }
+}
+
+impl TryFrom<RespArray> for HVals {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let key = parse_key_arg(value)?;
+        Ok(HVals { key })
+    }
+}
+
+impl TryFrom<RespArray> for HMGet {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, fields) = parse_key_and_args(value)?;
+        Ok(HMGet { key, fields })
+    }
+}
+
+impl TryFrom<RespArray> for HMSet {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, pairs) = parse_field_value_pairs(value, "hmset")?;
+        Ok(HMSet { key, pairs })
+    }
+}
+
+impl TryFrom<RespArray> for HSetNx {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, args) = parse_key_and_args(value)?;
+        match <[Bytes; 2]>::try_from(args) {
+            Ok([field, value]) => Ok(HSetNx { key, field, value }),
+            Err(_) => Err(CommandError::SyntaxError),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for HStrLen {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, field) = parse_key_field(value)?;
+        Ok(HStrLen { key, field })
+    }
+}
+
+impl TryFrom<RespArray> for HIncrBy {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, args) = parse_key_and_args(value)?;
+        match <[Bytes; 2]>::try_from(args) {
+            Ok([field, increment]) => Ok(HIncrBy {
+                key,
+                field,
+                increment: parse_integer(RespFrame::BulkString(BulkString::new(increment)))?,
+            }),
+            Err(_) => Err(CommandError::SyntaxError),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for HIncrByFloat {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, args) = parse_key_and_args(value)?;
+        match <[Bytes; 2]>::try_from(args) {
+            Ok([field, increment]) => Ok(HIncrByFloat {
+                key,
+                field,
+                increment: parse_float(RespFrame::BulkString(BulkString::new(increment)))?,
+            }),
+            Err(_) => Err(CommandError::SyntaxError),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for HRandField {
+    type Error = CommandError;
+    /// Converts a RESP array into an `HRandField` command.
+    ///
+    /// The key may be followed by a count, itself optionally followed by `WITHVALUES`.
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, args) = parse_key_and_args(value)?;
+        let mut args = args.into_iter();
+        let count = args
+            .next()
+            .map(|count| parse_integer(RespFrame::BulkString(BulkString::new(count))))
+            .transpose()?;
+        let with_values = match args.next() {
+            None => false,
+            Some(option) if option.eq_ignore_ascii_case(b"withvalues") => true,
+            Some(_) => return Err(CommandError::SyntaxError),
+        };
+        if args.next().is_some() {
+            return Err(CommandError::SyntaxError);
         }
+        Ok(HRandField {
+            key,
+            count,
+            with_values,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for HScan {
+    type Error = CommandError;
+    /// Converts a RESP array into an `HScan` command.
+    ///
+    /// The key and cursor may be followed by `MATCH pattern` and `COUNT count`.
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = match args.next() {
+            Some(RespFrame::BulkString(key)) => Bytes::from(key.0),
+            _ => return Err(CommandError::InvalidArguments("Invalid key".to_string())),
+        };
+        let cursor = parse_cursor(args.next())?;
+        let options = ScanOptions::parse(args, false)?;
+        Ok(HScan {
+            key,
+            cursor,
+            options,
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::RespDecode;
+    use crate::{Backend, RespDecode};
     use anyhow::Result;
     use bytes::BytesMut;
 
@@ -135,8 +596,7 @@ mod tests {
         let frame = RespArray::decode(&mut buf)?;
         let result: HSet = frame.try_into()?;
         assert_eq!(result.key, "map");
-        assert_eq!(result.field, "hello");
-        assert_eq!(result.value, RespFrame::BulkString(b"world".into()));
+        assert_eq!(result.pairs, vec![("hello".into(), "world".into())]);
         Ok(())
     }
 
@@ -144,37 +604,315 @@ mod tests {
     fn test_hset_hget_hgetall_commands() -> Result<()> {
         let backend = crate::Backend::new();
         let cmd = HSet {
-            key: "map".to_string(),
-            field: "hello".to_string(),
-            value: RespFrame::BulkString(b"world".into()),
+            key: "map".into(),
+            pairs: vec![("hello".into(), "world".into())],
         };
         let result = cmd.execute(&backend);
-        assert_eq!(result, RESP_OK.clone());
+        assert_eq!(result, RespFrame::Integer(1));
 
         let cmd = HSet {
-            key: "map".to_string(),
-            field: "hello1".to_string(),
-            value: RespFrame::BulkString(b"world1".into()),
+            key: "map".into(),
+            pairs: vec![("hello1".into(), "world1".into())],
         };
         cmd.execute(&backend);
         let cmd = HGet {
-            key: "map".to_string(),
-            field: "hello".to_string(),
+            key: "map".into(),
+            field: "hello".into(),
         };
         let result = cmd.execute(&backend);
         assert_eq!(result, RespFrame::BulkString(b"world".into()));
 
-        let cmd = HGetAll {
-            key: "map".to_string(),
-        };
+        let cmd = HGetAll { key: "map".into() };
         let result = cmd.execute(&backend);
         let mut expected = RespMap::new();
-        expected.insert("hello".to_string(), RespFrame::BulkString(b"world".into()));
-        expected.insert(
-            "hello1".to_string(),
-            RespFrame::BulkString(b"world1".into()),
-        );
+        expected.insert("hello".into(), RespFrame::BulkString(b"world".into()));
+        expected.insert("hello1".into(), RespFrame::BulkString(b"world1".into()));
         assert_eq!(result, expected.into());
         Ok(())
     }
+
+    #[test]
+    fn test_hash_commands_on_string_key() -> Result<()> {
+        let backend = crate::Backend::new();
+        backend.set("key".into(), "value".into());
+        let cmd = HSet {
+            key: "key".into(),
+            pairs: vec![("hello".into(), "world".into())],
+        };
+        assert_eq!(cmd.execute(&backend), CommandError::WrongType.into());
+        let cmd = HGet {
+            key: "key".into(),
+            field: "hello".into(),
+        };
+        assert_eq!(cmd.execute(&backend), CommandError::WrongType.into());
+        let cmd = HGetAll { key: "key".into() };
+        assert_eq!(cmd.execute(&backend), CommandError::WrongType.into());
+        assert_eq!(backend.get(b"key")?, Some("value".into()));
+        Ok(())
+    }
+
+    fn decode(buf: &[u8]) -> Result<RespArray> {
+        let mut buf = BytesMut::from(buf);
+        Ok(RespArray::decode(&mut buf)?)
+    }
+
+    #[test]
+    fn test_hset_multiple_pairs() -> Result<()> {
+        let frame = decode(
+            b"*6\r\n$4\r\nhset\r\n$3\r\nmap\r\n$1\r\na\r\n$1\r\n1\r\n$1\r\nb\r\n$1\r\n2\r\n",
+        )?;
+        let cmd: HSet = frame.try_into()?;
+        let backend = Backend::new();
+        backend.hset("map".into(), "a".into(), "0".into())?;
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        assert_eq!(backend.hget(b"map", b"a")?, Some("1".into()));
+
+        let frame = decode(b"*5\r\n$4\r\nhset\r\n$3\r\nmap\r\n$1\r\na\r\n$1\r\n1\r\n$1\r\nb\r\n")?;
+        let err = HSet::try_from(frame).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "ERR wrong number of arguments for 'hset' command"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_hdel_removes_empty_hash() -> Result<()> {
+        let backend = Backend::new();
+        backend.hset_many(
+            "map".into(),
+            vec![("a".into(), "1".into()), ("b".into(), "2".into())],
+        )?;
+        let cmd = HDel {
+            key: "map".into(),
+            fields: vec!["a".into(), "c".into()],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        assert_eq!(backend.hlen(b"map")?, 1);
+        let cmd = HDel {
+            key: "map".into(),
+            fields: vec!["b".into()],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        assert!(!backend.contains_key(b"map"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_hash_read_commands() -> Result<()> {
+        let backend = Backend::new();
+        backend.hset_many(
+            "map".into(),
+            vec![("a".into(), "1".into()), ("b".into(), "22".into())],
+        )?;
+
+        let exists = |field: &'static str| {
+            HExists {
+                key: "map".into(),
+                field: field.into(),
+            }
+            .execute(&backend)
+        };
+        assert_eq!(exists("a"), RespFrame::Integer(1));
+        assert_eq!(exists("c"), RespFrame::Integer(0));
+        assert_eq!(
+            HLen { key: "map".into() }.execute(&backend),
+            RespFrame::Integer(2)
+        );
+        assert_eq!(
+            HLen { key: "none".into() }.execute(&backend),
+            RespFrame::Integer(0)
+        );
+        let strlen = HStrLen {
+            key: "map".into(),
+            field: "b".into(),
+        };
+        assert_eq!(strlen.execute(&backend), RespFrame::Integer(2));
+
+        let mut fields = backend.hkeys(b"map")?;
+        fields.sort();
+        assert_eq!(fields, vec![Bytes::from("a"), Bytes::from("b")]);
+        let mut values = backend.hvals(b"map")?;
+        values.sort();
+        assert_eq!(values, vec![Bytes::from("1"), Bytes::from("22")]);
+
+        let cmd = HMGet {
+            key: "map".into(),
+            fields: vec!["a".into(), "c".into()],
+        };
+        let expected = RespArray::new([BulkString::new("1").into(), RespFrame::Null(RespNull)]);
+        assert_eq!(cmd.execute(&backend), expected.into());
+        Ok(())
+    }
+
+    #[test]
+    fn test_hmset_and_hsetnx() -> Result<()> {
+        let backend = Backend::new();
+        let cmd = HMSet {
+            key: "map".into(),
+            pairs: vec![("a".into(), "1".into()), ("b".into(), "2".into())],
+        };
+        assert_eq!(cmd.execute(&backend), RESP_OK.clone());
+        let setnx = |field: &'static str| {
+            HSetNx {
+                key: "map".into(),
+                field: field.into(),
+                value: "new".into(),
+            }
+            .execute(&backend)
+        };
+        assert_eq!(setnx("a"), RespFrame::Integer(0));
+        assert_eq!(setnx("c"), RespFrame::Integer(1));
+        assert_eq!(backend.hget(b"map", b"a")?, Some("1".into()));
+        assert_eq!(backend.hget(b"map", b"c")?, Some("new".into()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_hincrby_and_hincrbyfloat() -> Result<()> {
+        let backend = Backend::new();
+        let incr = |increment: i64| {
+            HIncrBy {
+                key: "map".into(),
+                field: "n".into(),
+                increment,
+            }
+            .execute(&backend)
+        };
+        assert_eq!(incr(5), RespFrame::Integer(5));
+        assert_eq!(incr(-7), RespFrame::Integer(-2));
+        assert_eq!(incr(i64::MIN), CommandError::Overflow.into());
+
+        let incr_float = |increment: f64| {
+            HIncrByFloat {
+                key: "map".into(),
+                field: "f".into(),
+                increment,
+            }
+            .execute(&backend)
+        };
+        assert_eq!(incr_float(10.5), BulkString::new("10.5").into());
+        assert_eq!(incr_float(0.1), BulkString::new("10.6").into());
+        assert_eq!(incr_float(-5.6), BulkString::new("5").into());
+        backend.hset("map".into(), "f".into(), "0.1".into())?;
+        assert_eq!(incr_float(0.2), BulkString::new("0.3").into());
+
+        backend.hset("map".into(), "s".into(), "abc".into())?;
+        let cmd = HIncrBy {
+            key: "map".into(),
+            field: "s".into(),
+            increment: 1,
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            CommandError::InvalidArguments("hash value is not an integer".to_string()).into()
+        );
+        backend.hset("map".into(), "s".into(), "+5".into())?;
+        let cmd = HIncrBy {
+            key: "map".into(),
+            field: "s".into(),
+            increment: 1,
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            CommandError::InvalidArguments("hash value is not an integer".to_string()).into()
+        );
+        let frame = decode(b"*4\r\n$12\r\nhincrbyfloat\r\n$3\r\nmap\r\n$1\r\nf\r\n$3\r\ninf\r\n")?;
+        assert!(HIncrByFloat::try_from(frame).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_hrandfield() -> Result<()> {
+        let backend = Backend::new();
+        backend.hset_many(
+            "map".into(),
+            vec![("a".into(), "1".into()), ("b".into(), "2".into())],
+        )?;
+        let rand = |count: Option<i64>, with_values: bool| {
+            HRandField {
+                key: "map".into(),
+                count,
+                with_values,
+            }
+            .execute(&backend)
+        };
+        assert!(matches!(rand(None, false), RespFrame::BulkString(_)));
+        let len = |frame: RespFrame| match frame {
+            RespFrame::Array(array) => array.len(),
+            _ => panic!("expected an array"),
+        };
+        assert_eq!(len(rand(Some(5), false)), 2);
+        assert_eq!(len(rand(Some(5), true)), 4);
+        assert_eq!(len(rand(Some(-5), false)), 5);
+        assert_eq!(len(rand(Some(-5), true)), 10);
+        assert_eq!(len(rand(Some(0), false)), 0);
+
+        let cmd = HRandField {
+            key: "none".into(),
+            count: None,
+            with_values: false,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Null(RespNull));
+        Ok(())
+    }
+
+    #[test]
+    fn test_hscan() -> Result<()> {
+        let backend = Backend::new();
+        let pairs = (0..30)
+            .map(|i| (format!("f{}", i).into(), i.to_string().into()))
+            .collect();
+        backend.hset_many("map".into(), pairs)?;
+        let mut cursor = 0;
+        let mut fields = Vec::new();
+        loop {
+            let (next, pairs) = backend.hscan(b"map", cursor, 4, Some(b"f1*"))?;
+            fields.extend(pairs.into_iter().map(|(field, _)| field));
+            if next == 0 {
+                break;
+            }
+            cursor = next;
+        }
+        fields.sort();
+        assert_eq!(fields.len(), 11);
+        assert_eq!(backend.hscan(b"none", 0, 10, None)?, (0, vec![]));
+
+        let frame =
+            decode(b"*5\r\n$5\r\nhscan\r\n$3\r\nmap\r\n$1\r\n0\r\n$4\r\ntype\r\n$4\r\nhash\r\n")?;
+        assert!(HScan::try_from(frame).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_hscan_survives_mutation() -> Result<()> {
+        let backend = Backend::new();
+        let pairs = (0..200)
+            .map(|i| (format!("kept{}", i).into(), "v".into()))
+            .collect();
+        backend.hset_many("map".into(), pairs)?;
+        let mut cursor = 0;
+        let mut fields = Vec::new();
+        for call in 0.. {
+            let (next, pairs) = backend.hscan(b"map", cursor, 5, None)?;
+            fields.extend(pairs.into_iter().map(|(field, _)| field));
+            // fields are added and removed between the calls
+            backend.hset("map".into(), format!("new{}", call).into(), "v".into())?;
+            backend.hdel(&"map".into(), &[format!("new{}", call / 2).into()])?;
+            if next == 0 {
+                break;
+            }
+            cursor = next;
+        }
+        let mut kept = fields
+            .into_iter()
+            .filter(|field| field.starts_with(b"kept"))
+            .collect::<Vec<_>>();
+        kept.sort();
+        let len = kept.len();
+        kept.dedup();
+        assert_eq!(kept.len(), len, "no field is returned twice");
+        assert_eq!(kept.len(), 200);
+        Ok(())
+    }
 }
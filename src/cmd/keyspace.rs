@@ -0,0 +1,691 @@
+use super::{
+    extract_args, parse_integer, parse_key_arg, parse_keys, CommandError, CommandExecutor, RESP_OK,
+};
+use crate::{Backend, BulkString, EventClass, RespArray, RespFrame, SimpleString};
+use bytes::Bytes;
+
+#[derive(Debug)]
+pub struct Del {
+    keys: Vec<Bytes>,
+}
+
+#[derive(Debug)]
+pub struct Exists {
+    keys: Vec<Bytes>,
+}
+
+#[derive(Debug)]
+pub struct Type {
+    key: Bytes,
+}
+
+#[derive(Debug)]
+pub struct Rename {
+    key: Bytes,
+    new_key: Bytes,
+}
+
+#[derive(Debug)]
+pub struct RenameNx {
+    key: Bytes,
+    new_key: Bytes,
+}
+
+/// The `COPY` command, named so it does not shadow the `Copy` trait.
+#[derive(Debug)]
+pub struct CopyCmd {
+    key: Bytes,
+    new_key: Bytes,
+    replace: bool,
+}
+
+#[derive(Debug)]
+pub struct Touch {
+    keys: Vec<Bytes>,
+}
+
+#[derive(Debug)]
+pub struct Unlink {
+    keys: Vec<Bytes>,
+}
+
+#[derive(Debug)]
+pub struct Keys {
+    pattern: Bytes,
+}
+
+#[derive(Debug)]
+pub struct Scan {
+    cursor: u64,
+    options: ScanOptions,
+}
+
+/// The options shared by `SCAN` and the per type scan commands.
+#[derive(Debug, Default)]
+pub(super) struct ScanOptions {
+    /// `MATCH pattern`
+    pub(super) pattern: Option<Bytes>,
+    /// `COUNT count`
+    pub(super) count: Option<usize>,
+    /// `TYPE type`, only accepted by `SCAN`.
+    pub(super) key_type: Option<String>,
+}
+
+/// Number of elements visited per scan call when `COUNT` is not given.
+pub(super) const DEFAULT_SCAN_COUNT: usize = 10;
+
+impl CommandExecutor for Del {
+    /// Returns the number of keys that were removed.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let removed = self
+            .keys
+            .iter()
+            .filter(|key| backend.del(key))
+            .inspect(|key| backend.notify_keyspace_event(EventClass::Generic, "del", key))
+            .count();
+        RespFrame::Integer(removed as i64)
+    }
+}
+
+impl CommandExecutor for Exists {
+    /// Returns the number of keys that exist. A key given several times is counted as
+    /// many times.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let found = self
+            .keys
+            .iter()
+            .filter(|key| backend.contains_key(key))
+            .count();
+        RespFrame::Integer(found as i64)
+    }
+}
+
+impl CommandExecutor for Type {
+    /// Returns the type of the value stored at the key, or `none` if the key does not exist.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        SimpleString::new(backend.key_type(&self.key).unwrap_or("none")).into()
+    }
+}
+
+impl CommandExecutor for Rename {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.rename(&self.key, self.new_key.clone(), false) {
+            Ok(_) => {
+                notify_rename(backend, &self.key, &self.new_key);
+                RESP_OK.clone()
+            }
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for RenameNx {
+    /// Returns `1` if the key was renamed, `0` if the new key already exists.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.rename(&self.key, self.new_key.clone(), true) {
+            Ok(renamed) => {
+                if renamed {
+                    notify_rename(backend, &self.key, &self.new_key);
+                }
+                RespFrame::Integer(renamed as i64)
+            }
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for CopyCmd {
+    /// Returns `1` if the value was copied, `0` otherwise.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        if self.key == self.new_key {
+            return CommandError::InvalidArguments(
+                "source and destination objects are the same".to_string(),
+            )
+            .into();
+        }
+        let copied = backend.copy(&self.key, self.new_key.clone(), self.replace);
+        if copied {
+            backend.notify_keyspace_event(EventClass::Generic, "copy_to", &self.new_key);
+        }
+        RespFrame::Integer(copied as i64)
+    }
+}
+
+/// Publishes the events of a key renamed to `new_key`.
+fn notify_rename(backend: &Backend, key: &[u8], new_key: &[u8]) {
+    backend.notify_keyspace_event(EventClass::Generic, "rename_from", key);
+    backend.notify_keyspace_event(EventClass::Generic, "rename_to", new_key);
+}
+
+impl CommandExecutor for Touch {
+    /// Returns the number of keys that exist.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let found = self
+            .keys
+            .iter()
+            .filter(|key| backend.contains_key(key))
+            .count();
+        RespFrame::Integer(found as i64)
+    }
+}
+
+impl CommandExecutor for Unlink {
+    /// Returns the number of keys that were removed.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let removed = self
+            .keys
+            .iter()
+            .filter(|key| backend.unlink(key))
+            .inspect(|key| backend.notify_keyspace_event(EventClass::Generic, "del", key))
+            .count();
+        RespFrame::Integer(removed as i64)
+    }
+}
+
+impl CommandExecutor for Keys {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let keys = backend
+            .keys(&self.pattern)
+            .into_iter()
+            .map(|key| BulkString::new(key).into())
+            .collect::<Vec<_>>();
+        RespArray::new(keys).into()
+    }
+}
+
+impl CommandExecutor for Scan {
+    /// Returns the cursor of the next call and the keys of this batch.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let (cursor, keys) = backend.scan(
+            self.cursor,
+            self.options.count.unwrap_or(DEFAULT_SCAN_COUNT),
+            self.options.pattern.as_deref(),
+            self.options.key_type.as_deref(),
+        );
+        let keys = keys
+            .into_iter()
+            .map(|key| BulkString::new(key).into())
+            .collect::<Vec<_>>();
+        scan_reply(cursor, keys)
+    }
+}
+
+/// Builds the reply of a scan command: the cursor as a BulkString, then the elements.
+pub(super) fn scan_reply(cursor: u64, elements: Vec<RespFrame>) -> RespFrame {
+    RespArray::new([
+        BulkString::new(cursor.to_string()).into(),
+        RespArray::new(elements).into(),
+    ])
+    .into()
+}
+
+/// Parses the cursor argument of a scan command.
+pub(super) fn parse_cursor(frame: Option<RespFrame>) -> Result<u64, CommandError> {
+    match frame {
+        Some(RespFrame::BulkString(cursor)) => std::str::from_utf8(&cursor)
+            .ok()
+            .and_then(|cursor| cursor.parse().ok())
+            .ok_or_else(|| CommandError::InvalidArguments("invalid cursor".to_string())),
+        _ => Err(CommandError::InvalidArguments("invalid cursor".to_string())),
+    }
+}
+
+impl ScanOptions {
+    /// Parses the `MATCH`, `COUNT` and, if `allow_type` is set, `TYPE` options of a scan
+    /// command.
+    pub(super) fn parse(
+        mut args: impl Iterator<Item = RespFrame>,
+        allow_type: bool,
+    ) -> Result<Self, CommandError> {
+        let mut options = ScanOptions::default();
+        while let Some(arg) = args.next() {
+            let (RespFrame::BulkString(option), Some(RespFrame::BulkString(value))) =
+                (arg, args.next())
+            else {
+                return Err(CommandError::SyntaxError);
+            };
+            match option.to_ascii_lowercase().as_slice() {
+                b"match" => options.pattern = Some(Bytes::from(value.0)),
+                b"count" => {
+                    let count = parse_integer(RespFrame::BulkString(value))?;
+                    if count < 1 {
+                        return Err(CommandError::SyntaxError);
+                    }
+                    options.count = Some(count as usize);
+                }
+                b"type" if allow_type => {
+                    options.key_type = Some(String::from_utf8_lossy(&value).into_owned())
+                }
+                _ => return Err(CommandError::SyntaxError),
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// Parses the two key arguments of `RENAME`, `RENAMENX` and `COPY`, returning the
+/// remaining arguments.
+fn parse_key_pair(
+    value: RespArray,
+) -> Result<(Bytes, Bytes, std::vec::IntoIter<RespFrame>), CommandError> {
+    let mut args = extract_args(value, 1)?.into_iter();
+    match (args.next(), args.next()) {
+        (Some(RespFrame::BulkString(key)), Some(RespFrame::BulkString(new_key))) => {
+            Ok((Bytes::from(key.0), Bytes::from(new_key.0), args))
+        }
+        _ => Err(CommandError::InvalidArguments("Invalid key".to_string())),
+    }
+}
+
+impl TryFrom<RespArray> for Del {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(Del {
+            keys: parse_keys(value)?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for Exists {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(Exists {
+            keys: parse_keys(value)?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for Type {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let key = parse_key_arg(value)?;
+        Ok(Type { key })
+    }
+}
+
+impl TryFrom<RespArray> for Rename {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, new_key, _) = parse_key_pair(value)?;
+        Ok(Rename { key, new_key })
+    }
+}
+
+impl TryFrom<RespArray> for RenameNx {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, new_key, _) = parse_key_pair(value)?;
+        Ok(RenameNx { key, new_key })
+    }
+}
+
+impl TryFrom<RespArray> for CopyCmd {
+    type Error = CommandError;
+    /// Converts a RESP array into a `CopyCmd` command.
+    ///
+    /// The source and destination keys may be followed by `REPLACE` and by `DB 0`; only
+    /// the default database exists, so any other database index is rejected.
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, new_key, mut args) = parse_key_pair(value)?;
+        let mut replace = false;
+        while let Some(arg) = args.next() {
+            let RespFrame::BulkString(option) = arg else {
+                return Err(CommandError::SyntaxError);
+            };
+            match option.to_ascii_lowercase().as_slice() {
+                b"replace" => replace = true,
+                b"db" => {
+                    let db = parse_integer(args.next().ok_or(CommandError::SyntaxError)?)?;
+                    if db != 0 {
+                        return Err(CommandError::InvalidArguments(
+                            "DB index is out of range".to_string(),
+                        ));
+                    }
+                }
+                _ => return Err(CommandError::SyntaxError),
+            }
+        }
+        Ok(CopyCmd {
+            key,
+            new_key,
+            replace,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for Touch {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(Touch {
+            keys: parse_keys(value)?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for Unlink {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(Unlink {
+            keys: parse_keys(value)?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for Keys {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        match args.next() {
+            Some(RespFrame::BulkString(pattern)) => Ok(Keys {
+                pattern: Bytes::from(pattern.0),
+            }),
+            _ => Err(CommandError::InvalidArguments(
+                "Invalid pattern".to_string(),
+            )),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Scan {
+    type Error = CommandError;
+    /// Converts a RESP array into a `Scan` command.
+    ///
+    /// The cursor may be followed by `MATCH pattern`, `COUNT count` and `TYPE type`, in any
+    /// order.
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        let cursor = parse_cursor(args.next())?;
+        let options = ScanOptions::parse(args, true)?;
+        Ok(Scan { cursor, options })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{KeyExpiry, RespDecode};
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    fn keys(keys: &[&str]) -> Vec<Bytes> {
+        keys.iter()
+            .map(|key| Bytes::copy_from_slice(key.as_bytes()))
+            .collect()
+    }
+
+    #[test]
+    fn test_del_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$3\r\nDEL\r\n$1\r\na\r\n$1\r\nb\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Del = frame.try_into()?;
+        assert_eq!(result.keys, keys(&["a", "b"]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_copy_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*6\r\n$4\r\ncopy\r\n$1\r\na\r\n$1\r\nb\r\n$2\r\ndb\r\n$1\r\n0\r\n$7\r\nREPLACE\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let result: CopyCmd = frame.try_into()?;
+        assert_eq!(result.key, "a");
+        assert_eq!(result.new_key, "b");
+        assert!(result.replace);
+
+        buf.extend_from_slice(b"*5\r\n$4\r\ncopy\r\n$1\r\na\r\n$1\r\nb\r\n$2\r\ndb\r\n$1\r\n1\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(CopyCmd::try_from(frame).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_del_exists_touch_commands() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("a".into(), "1".into());
+        backend.hset("b".into(), "field".into(), "value".into())?;
+
+        let cmd = Exists {
+            keys: keys(&["a", "a", "b", "c"]),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(3));
+        let cmd = Touch {
+            keys: keys(&["a", "c"]),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+
+        let cmd = Del {
+            keys: keys(&["a", "b", "c", "a"]),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(2));
+        assert!(backend.keyspace.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_type_command() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("a".into(), "1".into());
+        backend.hset("b".into(), "field".into(), "value".into())?;
+        let key_type = |key: &'static str| Type { key: key.into() }.execute(&backend);
+        assert_eq!(key_type("a"), SimpleString::new("string").into());
+        assert_eq!(key_type("b"), SimpleString::new("hash").into());
+        assert_eq!(key_type("c"), SimpleString::new("none").into());
+        Ok(())
+    }
+
+    #[test]
+    fn test_rename_commands() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("a".into(), "1".into());
+        backend.set("b".into(), "2".into());
+        backend.expire_at(b"a", u64::MAX, Default::default());
+
+        let cmd = RenameNx {
+            key: "a".into(),
+            new_key: "b".into(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+
+        let cmd = Rename {
+            key: "a".into(),
+            new_key: "b".into(),
+        };
+        assert_eq!(cmd.execute(&backend), RESP_OK.clone());
+        assert_eq!(backend.get(b"a")?, None);
+        assert_eq!(backend.get(b"b")?, Some("1".into()));
+        assert_eq!(backend.expiry(b"b"), KeyExpiry::ExpiresAt(u64::MAX));
+
+        let cmd = RenameNx {
+            key: "b".into(),
+            new_key: "c".into(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+
+        let cmd = Rename {
+            key: "a".into(),
+            new_key: "b".into(),
+        };
+        assert_eq!(cmd.execute(&backend), CommandError::NoSuchKey.into());
+        Ok(())
+    }
+
+    #[test]
+    fn test_copy_command() -> Result<()> {
+        let backend = Backend::new();
+        backend.hset("a".into(), "field".into(), "value".into())?;
+        backend.set("b".into(), "2".into());
+        let copy = |new_key: &str, replace: bool| {
+            CopyCmd {
+                key: "a".into(),
+                new_key: Bytes::copy_from_slice(new_key.as_bytes()),
+                replace,
+            }
+            .execute(&backend)
+        };
+        assert_eq!(copy("b", false), RespFrame::Integer(0));
+        assert_eq!(copy("b", true), RespFrame::Integer(1));
+        assert_eq!(copy("c", false), RespFrame::Integer(1));
+        assert!(matches!(copy("a", true), RespFrame::Error(_)));
+
+        // the copy is independent of the source
+        backend.hset("a".into(), "other".into(), "value".into())?;
+        assert_eq!(backend.hgetall(b"b")?.map(|h| h.len()), Some(1));
+        assert_eq!(backend.hgetall(b"c")?.map(|h| h.len()), Some(1));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_unlink_large_hash() -> Result<()> {
+        let backend = Backend::new();
+        for i in 0..1000 {
+            backend.hset("big".into(), i.to_string().into(), "value".into())?;
+        }
+        backend.set("small".into(), "1".into());
+        let cmd = Unlink {
+            keys: keys(&["big", "small", "missing"]),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(2));
+        assert!(backend.keyspace.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*8\r\n$4\r\nscan\r\n$2\r\n42\r\n$5\r\nMATCH\r\n$3\r\nk:*\r\n$5\r\ncount\r\n$3\r\n100\r\n$4\r\nTYPE\r\n$4\r\nhash\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Scan = frame.try_into()?;
+        assert_eq!(result.cursor, 42);
+        assert_eq!(result.options.pattern, Some("k:*".into()));
+        assert_eq!(result.options.count, Some(100));
+        assert_eq!(result.options.key_type.as_deref(), Some("hash"));
+
+        buf.extend_from_slice(b"*2\r\n$4\r\nscan\r\n$2\r\n-1\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(Scan::try_from(frame).is_err());
+        buf.extend_from_slice(b"*4\r\n$4\r\nscan\r\n$1\r\n0\r\n$5\r\ncount\r\n$1\r\n0\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(Scan::try_from(frame).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_keys_command() -> Result<()> {
+        let backend = Backend::new();
+        for key in ["hello", "hallo", "hxllo", "world"] {
+            backend.set(key.into(), "1".into());
+        }
+        let cmd = Keys {
+            pattern: "h[ae]llo".into(),
+        };
+        let RespFrame::Array(keys) = cmd.execute(&backend) else {
+            panic!("expected an array");
+        };
+        assert_eq!(keys.len(), 2);
+        assert!(keys.contains(&b"hallo".into()));
+        assert!(keys.contains(&b"hello".into()));
+        Ok(())
+    }
+
+    /// Runs a full `SCAN` iteration, calling `between` after every call.
+    fn scan_all(
+        backend: &Backend,
+        pattern: Option<&[u8]>,
+        key_type: Option<&str>,
+        mut between: impl FnMut(usize),
+    ) -> Vec<Bytes> {
+        let mut cursor = 0;
+        let mut found = Vec::new();
+        for call in 0.. {
+            let (next, keys) = backend.scan(cursor, 7, pattern, key_type);
+            found.extend(keys);
+            between(call);
+            if next == 0 {
+                break;
+            }
+            cursor = next;
+        }
+        found
+    }
+
+    #[test]
+    fn test_scan_filters() -> Result<()> {
+        let backend = Backend::new();
+        for i in 0..50 {
+            backend.set(format!("s:{}", i).into(), "1".into());
+            backend.hset(format!("h:{}", i).into(), "f".into(), "v".into())?;
+        }
+        let mut found = scan_all(&backend, None, None, |_| {});
+        found.sort();
+        found.dedup();
+        assert_eq!(found.len(), 100);
+
+        let found = scan_all(&backend, Some(b"s:1*"), None, |_| {});
+        assert_eq!(found.len(), 11);
+        let found = scan_all(&backend, None, Some("HASH"), |_| {});
+        assert_eq!(found.len(), 50);
+        assert!(found.iter().all(|key| key.starts_with(b"h:")));
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan_survives_concurrent_mutation() {
+        let backend = Backend::new();
+        for i in 0..500 {
+            backend.set(format!("stable:{}", i).into(), "1".into());
+        }
+        // grow the map and churn it between calls, forcing the shards to resize
+        let found = scan_all(&backend, None, None, |call| {
+            for i in 0..5 {
+                backend.set(format!("new:{}:{}", call, i).into(), "1".into());
+            }
+            backend.del(format!("new:{}:0", call.saturating_sub(1)).as_bytes());
+        });
+        let mut stable = found
+            .into_iter()
+            .filter(|key| key.starts_with(b"stable:"))
+            .collect::<Vec<_>>();
+        stable.sort();
+        let len = stable.len();
+        stable.dedup();
+        assert_eq!(stable.len(), len, "no stable key is returned twice");
+        assert_eq!(stable.len(), 500, "every stable key is returned");
+    }
+
+    #[test]
+    fn test_scan_returns_keys_present_throughout() {
+        let backend = Backend::new();
+        for i in 0..300 {
+            backend.set(format!("kept:{}", i).into(), "1".into());
+            backend.set(format!("gone:{}", i).into(), "1".into());
+        }
+        // keys are removed, renamed and added between the calls
+        let found = scan_all(&backend, None, None, |call| {
+            for i in call * 10..call * 10 + 10 {
+                backend.del(format!("gone:{}", i).as_bytes());
+                backend.set(format!("new:{}", i).into(), "1".into());
+                let _ = backend.rename(
+                    format!("new:{}", i).as_bytes(),
+                    format!("renamed:{}", i).into(),
+                    false,
+                );
+            }
+        });
+        let mut kept = found
+            .iter()
+            .filter(|key| key.starts_with(b"kept:"))
+            .collect::<Vec<_>>();
+        kept.sort();
+        kept.dedup();
+        assert_eq!(kept.len(), 300);
+        // a key removed before the iteration started is not returned
+        backend.set("late".into(), "1".into());
+        backend.del(b"late");
+        assert!(!scan_all(&backend, None, None, |_| {}).contains(&Bytes::from("late")));
+    }
+}
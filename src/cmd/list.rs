@@ -0,0 +1,1368 @@
+use super::hmap::{bulk_string_array, parse_key_and_args};
+use super::{
+    extract_args, notify_removed, optional_bulk_string, parse_integer, parse_key_arg,
+    BlockingCommand, CommandError, CommandExecutor, RESP_OK,
+};
+use crate::{
+    Backend, BulkString, EventClass, ListEnd, RespArray, RespFrame, RespNull, RespNullArray,
+};
+use bytes::Bytes;
+use std::time::Duration;
+
+#[derive(Debug)]
+pub struct LPush {
+    key: Bytes,
+    values: Vec<Bytes>,
+}
+
+#[derive(Debug)]
+pub struct RPush {
+    key: Bytes,
+    values: Vec<Bytes>,
+}
+
+#[derive(Debug)]
+pub struct LPushX {
+    key: Bytes,
+    values: Vec<Bytes>,
+}
+
+#[derive(Debug)]
+pub struct RPushX {
+    key: Bytes,
+    values: Vec<Bytes>,
+}
+
+#[derive(Debug)]
+pub struct LPop {
+    key: Bytes,
+    count: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct RPop {
+    key: Bytes,
+    count: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct LLen {
+    key: Bytes,
+}
+
+#[derive(Debug)]
+pub struct LRange {
+    key: Bytes,
+    start: i64,
+    stop: i64,
+}
+
+#[derive(Debug)]
+pub struct LIndex {
+    key: Bytes,
+    index: i64,
+}
+
+#[derive(Debug)]
+pub struct LSet {
+    key: Bytes,
+    index: i64,
+    value: Bytes,
+}
+
+#[derive(Debug)]
+pub struct LInsert {
+    key: Bytes,
+    before: bool,
+    pivot: Bytes,
+    value: Bytes,
+}
+
+#[derive(Debug)]
+pub struct LRem {
+    key: Bytes,
+    count: i64,
+    value: Bytes,
+}
+
+#[derive(Debug)]
+pub struct LTrim {
+    key: Bytes,
+    start: i64,
+    stop: i64,
+}
+
+#[derive(Debug)]
+pub struct LPos {
+    key: Bytes,
+    value: Bytes,
+    rank: i64,
+    count: Option<usize>,
+    maxlen: usize,
+}
+
+#[derive(Debug)]
+pub struct LMove {
+    source: Bytes,
+    destination: Bytes,
+    from: ListEnd,
+    to: ListEnd,
+}
+
+#[derive(Debug)]
+pub struct LMPop {
+    keys: Vec<Bytes>,
+    end: ListEnd,
+    count: usize,
+}
+
+#[derive(Debug)]
+pub struct BLPop {
+    keys: Vec<Bytes>,
+    timeout: Option<Duration>,
+}
+
+#[derive(Debug)]
+pub struct BRPop {
+    keys: Vec<Bytes>,
+    timeout: Option<Duration>,
+}
+
+#[derive(Debug)]
+pub struct BLMove {
+    source: [Bytes; 1],
+    destination: Bytes,
+    from: ListEnd,
+    to: ListEnd,
+    timeout: Option<Duration>,
+}
+
+#[derive(Debug)]
+pub struct BLMPop {
+    keys: Vec<Bytes>,
+    end: ListEnd,
+    count: usize,
+    timeout: Option<Duration>,
+}
+
+impl CommandExecutor for LPush {
+    /// Returns the length of the list after the push.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        push(backend, self.key, ListEnd::Left, self.values, true)
+    }
+}
+
+impl CommandExecutor for RPush {
+    /// Returns the length of the list after the push.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        push(backend, self.key, ListEnd::Right, self.values, true)
+    }
+}
+
+impl CommandExecutor for LPushX {
+    /// Returns the length of the list after the push, `0` if the key does not exist.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        push(backend, self.key, ListEnd::Left, self.values, false)
+    }
+}
+
+impl CommandExecutor for RPushX {
+    /// Returns the length of the list after the push, `0` if the key does not exist.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        push(backend, self.key, ListEnd::Right, self.values, false)
+    }
+}
+
+impl CommandExecutor for LPop {
+    /// Without a count, returns the popped value. With a count, returns an array of the
+    /// popped values.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        pop(backend, self.key, ListEnd::Left, self.count)
+    }
+}
+
+impl CommandExecutor for RPop {
+    /// Without a count, returns the popped value. With a count, returns an array of the
+    /// popped values.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        pop(backend, self.key, ListEnd::Right, self.count)
+    }
+}
+
+impl CommandExecutor for LLen {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.llen(&self.key) {
+            Ok(len) => RespFrame::Integer(len as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for LRange {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.lrange(&self.key, self.start, self.stop) {
+            Ok(values) => bulk_string_array(values),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for LIndex {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.lindex(&self.key, self.index) {
+            Ok(value) => optional_bulk_string(value),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for LSet {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.lset(&self.key, self.index, self.value) {
+            Ok(()) => {
+                backend.notify_keyspace_event(EventClass::List, "lset", &self.key);
+                RESP_OK.clone()
+            }
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for LInsert {
+    /// Returns the length of the list after the insertion, `-1` if the pivot was not found
+    /// and `0` if the key does not exist.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.linsert(&self.key, self.before, &self.pivot, self.value) {
+            Ok(len) => {
+                if len > 0 {
+                    backend.notify_keyspace_event(EventClass::List, "linsert", &self.key);
+                }
+                RespFrame::Integer(len)
+            }
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for LRem {
+    /// Returns the number of removed values.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.lrem(&self.key, self.count, &self.value) {
+            Ok(removed) => {
+                if removed > 0 {
+                    notify_removed(backend, EventClass::List, "lrem", &self.key);
+                }
+                RespFrame::Integer(removed as i64)
+            }
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for LTrim {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        // trimming a key that does not exist is not an event
+        let existed = backend.notifies(EventClass::List) && backend.contains_key(&self.key);
+        match backend.ltrim(&self.key, self.start, self.stop) {
+            Ok(()) => {
+                if existed {
+                    notify_removed(backend, EventClass::List, "ltrim", &self.key);
+                }
+                RESP_OK.clone()
+            }
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for LPos {
+    /// Without `COUNT`, returns the index of the match or a null reply. With `COUNT`,
+    /// returns an array of indexes.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let positions = backend.lpos(
+            &self.key,
+            &self.value,
+            self.rank,
+            self.count.unwrap_or(1),
+            self.maxlen,
+        );
+        match (positions, self.count) {
+            (Ok(positions), Some(_)) => RespArray::new(
+                positions
+                    .into_iter()
+                    .map(|i| RespFrame::Integer(i as i64))
+                    .collect::<Vec<_>>(),
+            )
+            .into(),
+            (Ok(positions), None) => match positions.first() {
+                Some(&i) => RespFrame::Integer(i as i64),
+                None => RespFrame::Null(RespNull),
+            },
+            (Err(e), _) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for LMove {
+    /// Returns the moved value, or a null reply if the source list does not exist.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.lmove(&self.source, &self.destination, self.from, self.to) {
+            Ok(value) => {
+                if value.is_some() {
+                    notify_move(backend, &self.source, &self.destination, self.from, self.to);
+                }
+                optional_bulk_string(value)
+            }
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for LMPop {
+    /// Returns the key the values were popped from and an array of the values, or a null
+    /// reply if none of the keys exists.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.lmpop(&self.keys, self.end, self.count) {
+            Ok(popped) => {
+                if let Some((key, _)) = &popped {
+                    notify_removed(backend, EventClass::List, pop_event(self.end), key);
+                }
+                key_values_reply(popped)
+            }
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl BlockingCommand for BLPop {
+    fn wait_keys(&self) -> &[Bytes] {
+        &self.keys
+    }
+
+    fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Returns the key the value was popped from and the value.
+    fn try_execute(&self, backend: &Backend) -> Option<RespFrame> {
+        blocking_pop(backend, &self.keys, ListEnd::Left)
+    }
+}
+
+impl BlockingCommand for BRPop {
+    fn wait_keys(&self) -> &[Bytes] {
+        &self.keys
+    }
+
+    fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Returns the key the value was popped from and the value.
+    fn try_execute(&self, backend: &Backend) -> Option<RespFrame> {
+        blocking_pop(backend, &self.keys, ListEnd::Right)
+    }
+}
+
+impl BlockingCommand for BLMove {
+    fn wait_keys(&self) -> &[Bytes] {
+        &self.source
+    }
+
+    fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Returns the moved value.
+    fn try_execute(&self, backend: &Backend) -> Option<RespFrame> {
+        let [source] = &self.source;
+        match backend.lmove(source, &self.destination, self.from, self.to) {
+            Ok(Some(value)) => {
+                notify_move(backend, source, &self.destination, self.from, self.to);
+                Some(BulkString::new(value).into())
+            }
+            Ok(None) => None,
+            Err(e) => Some(e.into()),
+        }
+    }
+
+    fn timeout_reply(&self) -> RespFrame {
+        RespFrame::Null(RespNull)
+    }
+}
+
+impl BlockingCommand for BLMPop {
+    fn wait_keys(&self) -> &[Bytes] {
+        &self.keys
+    }
+
+    fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Returns the key the values were popped from and an array of the values.
+    fn try_execute(&self, backend: &Backend) -> Option<RespFrame> {
+        match backend.lmpop(&self.keys, self.end, self.count) {
+            Ok(None) => None,
+            Ok(popped) => {
+                if let Some((key, _)) = &popped {
+                    notify_removed(backend, EventClass::List, pop_event(self.end), key);
+                }
+                Some(key_values_reply(popped))
+            }
+            Err(e) => Some(e.into()),
+        }
+    }
+}
+
+impl CommandExecutor for BLPop {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        self.try_execute(backend)
+            .unwrap_or_else(|| self.timeout_reply())
+    }
+}
+
+impl CommandExecutor for BRPop {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        self.try_execute(backend)
+            .unwrap_or_else(|| self.timeout_reply())
+    }
+}
+
+impl CommandExecutor for BLMove {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        self.try_execute(backend)
+            .unwrap_or_else(|| self.timeout_reply())
+    }
+}
+
+impl CommandExecutor for BLMPop {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        self.try_execute(backend)
+            .unwrap_or_else(|| self.timeout_reply())
+    }
+}
+
+/// Pops a single value from the first non-empty list among `keys`, as done by `BLPOP` and
+/// `BRPOP`. Returns `None` if the command has to wait.
+fn blocking_pop(backend: &Backend, keys: &[Bytes], end: ListEnd) -> Option<RespFrame> {
+    match backend.lmpop(keys, end, 1) {
+        Ok(Some((key, values))) => {
+            let value = values.into_iter().next()?;
+            notify_removed(backend, EventClass::List, pop_event(end), &key);
+            Some(
+                RespArray::new([BulkString::new(key).into(), BulkString::new(value).into()]).into(),
+            )
+        }
+        Ok(None) => None,
+        Err(e) => Some(e.into()),
+    }
+}
+
+fn push(
+    backend: &Backend,
+    key: Bytes,
+    end: ListEnd,
+    values: Vec<Bytes>,
+    create: bool,
+) -> RespFrame {
+    match backend.push(&key, end, values, create) {
+        Ok(len) => {
+            // `LPUSHX` and `RPUSHX` leave a key that does not exist alone
+            if len > 0 {
+                backend.notify_keyspace_event(EventClass::List, push_event(end), &key);
+            }
+            RespFrame::Integer(len as i64)
+        }
+        Err(e) => e.into(),
+    }
+}
+
+fn pop(backend: &Backend, key: Bytes, end: ListEnd, count: Option<usize>) -> RespFrame {
+    let popped = backend.pop(&key, end, count.unwrap_or(1));
+    if let Ok(Some(_)) = popped {
+        notify_removed(backend, EventClass::List, pop_event(end), &key);
+    }
+    match (popped, count) {
+        (Ok(Some(values)), Some(_)) => bulk_string_array(values),
+        (Ok(Some(values)), None) => optional_bulk_string(values.into_iter().next()),
+        (Ok(None), Some(_)) => RespFrame::NullArray(RespNullArray),
+        (Ok(None), None) => RespFrame::Null(RespNull),
+        (Err(e), _) => e.into(),
+    }
+}
+
+/// Publishes the events of a value moved from the list at `source` to the one at
+/// `destination`: the push first, then the pop and the removal of an emptied source.
+fn notify_move(backend: &Backend, source: &[u8], destination: &[u8], from: ListEnd, to: ListEnd) {
+    backend.notify_keyspace_event(EventClass::List, push_event(to), destination);
+    notify_removed(backend, EventClass::List, pop_event(from), source);
+}
+
+fn push_event(end: ListEnd) -> &'static str {
+    match end {
+        ListEnd::Left => "lpush",
+        ListEnd::Right => "rpush",
+    }
+}
+
+fn pop_event(end: ListEnd) -> &'static str {
+    match end {
+        ListEnd::Left => "lpop",
+        ListEnd::Right => "rpop",
+    }
+}
+
+/// Builds the reply of the commands popping from one of several keys: the key followed by
+/// an array of the popped values, or a null reply.
+pub(super) fn key_values_reply(popped: Option<(Bytes, Vec<Bytes>)>) -> RespFrame {
+    match popped {
+        Some((key, values)) => {
+            RespArray::new([BulkString::new(key).into(), bulk_string_array(values)]).into()
+        }
+        None => RespFrame::NullArray(RespNullArray),
+    }
+}
+
+fn integer(arg: Bytes) -> Result<i64, CommandError> {
+    parse_integer(RespFrame::BulkString(BulkString::new(arg)))
+}
+
+/// Parses a count that must be positive, as taken by `LPOP` and `RPOP`.
+fn positive_count(arg: Bytes) -> Result<usize, CommandError> {
+    usize::try_from(integer(arg)?).map_err(|_| {
+        CommandError::InvalidArguments("value is out of range, must be positive".to_string())
+    })
+}
+
+/// Parses the timeout of a blocking command, in seconds with an optional fraction. `0`
+/// means waiting forever and is returned as `None`.
+pub(super) fn parse_timeout(arg: &[u8]) -> Result<Option<Duration>, CommandError> {
+    let timeout = std::str::from_utf8(arg)
+        .ok()
+        .and_then(|timeout| timeout.parse::<f64>().ok())
+        .filter(|timeout| timeout.is_finite())
+        .ok_or_else(|| {
+            CommandError::InvalidArguments("timeout is not a float or out of range".to_string())
+        })?;
+    if timeout < 0.0 {
+        return Err(CommandError::InvalidArguments(
+            "timeout is negative".to_string(),
+        ));
+    }
+    if timeout == 0.0 {
+        return Ok(None);
+    }
+    Duration::try_from_secs_f64(timeout)
+        .map(Some)
+        .map_err(|_| CommandError::InvalidArguments("timeout is out of range".to_string()))
+}
+
+/// Parses `LEFT` or `RIGHT`.
+pub(super) fn parse_list_end(arg: &[u8]) -> Result<ListEnd, CommandError> {
+    if arg.eq_ignore_ascii_case(b"left") {
+        Ok(ListEnd::Left)
+    } else if arg.eq_ignore_ascii_case(b"right") {
+        Ok(ListEnd::Right)
+    } else {
+        Err(CommandError::SyntaxError)
+    }
+}
+
+/// Parses a key followed by one or more values, as taken by the push commands.
+fn parse_key_values(value: RespArray, name: &str) -> Result<(Bytes, Vec<Bytes>), CommandError> {
+    let (key, values) = parse_key_and_args(value)?;
+    if values.is_empty() {
+        return Err(CommandError::WrongArity(name.to_string()));
+    }
+    Ok((key, values))
+}
+
+/// Parses a key followed by an optional count, as taken by `LPOP` and `RPOP`.
+fn parse_key_count(value: RespArray) -> Result<(Bytes, Option<usize>), CommandError> {
+    let (key, args) = parse_key_and_args(value)?;
+    let mut args = args.into_iter();
+    let count = args.next().map(positive_count).transpose()?;
+    if args.next().is_some() {
+        return Err(CommandError::SyntaxError);
+    }
+    Ok((key, count))
+}
+
+/// Parses a key followed by two integers, as taken by `LRANGE` and `LTRIM`.
+fn parse_key_range(value: RespArray) -> Result<(Bytes, i64, i64), CommandError> {
+    let (key, args) = parse_key_and_args(value)?;
+    match <[Bytes; 2]>::try_from(args) {
+        Ok([start, stop]) => Ok((key, integer(start)?, integer(stop)?)),
+        Err(_) => Err(CommandError::SyntaxError),
+    }
+}
+
+/// Parses `numkeys key [key ...]` followed by the remaining arguments, as taken by the
+/// commands popping from one of several keys.
+pub(super) fn parse_numkeys(
+    args: &mut impl Iterator<Item = Bytes>,
+) -> Result<Vec<Bytes>, CommandError> {
+    let numkeys = args.next().map(integer).transpose()?.unwrap_or_default();
+    if numkeys <= 0 {
+        return Err(CommandError::InvalidArguments(
+            "numkeys should be greater than 0".to_string(),
+        ));
+    }
+    let keys = args.take(numkeys as usize).collect::<Vec<_>>();
+    if keys.len() < numkeys as usize {
+        return Err(CommandError::InvalidArguments(
+            "Number of keys can't be greater than number of args".to_string(),
+        ));
+    }
+    Ok(keys)
+}
+
+/// Parses the optional `COUNT count` closing the commands popping from one of several
+/// keys. The count must be positive.
+pub(super) fn parse_mpop_count(
+    mut args: impl Iterator<Item = Bytes>,
+) -> Result<usize, CommandError> {
+    let count = match args.next() {
+        None => return Ok(1),
+        Some(option) if option.eq_ignore_ascii_case(b"count") => {
+            let count = args.next().ok_or(CommandError::SyntaxError)?;
+            integer(count)?
+        }
+        Some(_) => return Err(CommandError::SyntaxError),
+    };
+    if args.next().is_some() {
+        return Err(CommandError::SyntaxError);
+    }
+    if count <= 0 {
+        return Err(CommandError::InvalidArguments(
+            "count should be greater than 0".to_string(),
+        ));
+    }
+    Ok(count as usize)
+}
+
+impl TryFrom<RespArray> for LPush {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, values) = parse_key_values(value, "lpush")?;
+        Ok(LPush { key, values })
+    }
+}
+
+impl TryFrom<RespArray> for RPush {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, values) = parse_key_values(value, "rpush")?;
+        Ok(RPush { key, values })
+    }
+}
+
+impl TryFrom<RespArray> for LPushX {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, values) = parse_key_values(value, "lpushx")?;
+        Ok(LPushX { key, values })
+    }
+}
+
+impl TryFrom<RespArray> for RPushX {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, values) = parse_key_values(value, "rpushx")?;
+        Ok(RPushX { key, values })
+    }
+}
+
+impl TryFrom<RespArray> for LPop {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, count) = parse_key_count(value)?;
+        Ok(LPop { key, count })
+    }
+}
+
+impl TryFrom<RespArray> for RPop {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, count) = parse_key_count(value)?;
+        Ok(RPop { key, count })
+    }
+}
+
+impl TryFrom<RespArray> for LLen {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let key = parse_key_arg(value)?;
+        Ok(LLen { key })
+    }
+}
+
+impl TryFrom<RespArray> for LRange {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, start, stop) = parse_key_range(value)?;
+        Ok(LRange { key, start, stop })
+    }
+}
+
+impl TryFrom<RespArray> for LIndex {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, args) = parse_key_and_args(value)?;
+        match <[Bytes; 1]>::try_from(args) {
+            Ok([index]) => Ok(LIndex {
+                key,
+                index: integer(index)?,
+            }),
+            Err(_) => Err(CommandError::SyntaxError),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for LSet {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, args) = parse_key_and_args(value)?;
+        match <[Bytes; 2]>::try_from(args) {
+            Ok([index, value]) => Ok(LSet {
+                key,
+                index: integer(index)?,
+                value,
+            }),
+            Err(_) => Err(CommandError::SyntaxError),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for LInsert {
+    type Error = CommandError;
+    /// Converts a RESP array into an `LInsert` command.
+    ///
+    /// The key is followed by `BEFORE` or `AFTER`, the pivot and the value to insert.
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, args) = parse_key_and_args(value)?;
+        let Ok([position, pivot, value]) = <[Bytes; 3]>::try_from(args) else {
+            return Err(CommandError::SyntaxError);
+        };
+        let before = if position.eq_ignore_ascii_case(b"before") {
+            true
+        } else if position.eq_ignore_ascii_case(b"after") {
+            false
+        } else {
+            return Err(CommandError::SyntaxError);
+        };
+        Ok(LInsert {
+            key,
+            before,
+            pivot,
+            value,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for LRem {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, args) = parse_key_and_args(value)?;
+        match <[Bytes; 2]>::try_from(args) {
+            Ok([count, value]) => Ok(LRem {
+                key,
+                count: integer(count)?,
+                value,
+            }),
+            Err(_) => Err(CommandError::SyntaxError),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for LTrim {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, start, stop) = parse_key_range(value)?;
+        Ok(LTrim { key, start, stop })
+    }
+}
+
+impl TryFrom<RespArray> for LPos {
+    type Error = CommandError;
+    /// Converts a RESP array into an `LPos` command.
+    ///
+    /// The key and value may be followed by `RANK rank`, `COUNT count` and `MAXLEN len` in
+    /// any order.
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, args) = parse_key_and_args(value)?;
+        let mut args = args.into_iter();
+        let value = args.next().ok_or(CommandError::SyntaxError)?;
+        let (mut rank, mut count, mut maxlen) = (1, None, 0);
+        while let Some(option) = args.next() {
+            let arg = args.next().ok_or(CommandError::SyntaxError)?;
+            match option.to_ascii_lowercase().as_slice() {
+                b"rank" => {
+                    rank = integer(arg)?;
+                    if rank == 0 {
+                        return Err(CommandError::InvalidArguments(
+                            "RANK can't be zero: use 1 to start from the first match, 2 from the second ... or use negative to start from the last match".to_string(),
+                        ));
+                    }
+                    if rank == i64::MIN {
+                        return Err(CommandError::InvalidArguments(format!(
+                            "value is out of range, value must between {} and {}",
+                            -i64::MAX,
+                            i64::MAX
+                        )));
+                    }
+                }
+                b"count" => {
+                    count = Some(usize::try_from(integer(arg)?).map_err(|_| {
+                        CommandError::InvalidArguments("COUNT can't be negative".to_string())
+                    })?)
+                }
+                b"maxlen" => {
+                    maxlen = usize::try_from(integer(arg)?).map_err(|_| {
+                        CommandError::InvalidArguments("MAXLEN can't be negative".to_string())
+                    })?
+                }
+                _ => return Err(CommandError::SyntaxError),
+            }
+        }
+        Ok(LPos {
+            key,
+            value,
+            rank,
+            count,
+            maxlen,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for LMove {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (source, args) = parse_key_and_args(value)?;
+        match <[Bytes; 3]>::try_from(args) {
+            Ok([destination, from, to]) => Ok(LMove {
+                source,
+                destination,
+                from: parse_list_end(&from)?,
+                to: parse_list_end(&to)?,
+            }),
+            Err(_) => Err(CommandError::SyntaxError),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for LMPop {
+    type Error = CommandError;
+    /// Converts a RESP array into an `LMPop` command.
+    ///
+    /// The arguments are `numkeys`, the keys, `LEFT` or `RIGHT` and optionally
+    /// `COUNT count`.
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = bulk_string_args(value)?.into_iter();
+        let keys = parse_numkeys(&mut args)?;
+        let end = parse_list_end(&args.next().ok_or(CommandError::SyntaxError)?)?;
+        let count = parse_mpop_count(args)?;
+        Ok(LMPop { keys, end, count })
+    }
+}
+
+/// Converts the arguments of a command into BulkStrings.
+pub(super) fn bulk_string_args(value: RespArray) -> Result<Vec<Bytes>, CommandError> {
+    extract_args(value, 1)?
+        .into_iter()
+        .map(|arg| match arg {
+            RespFrame::BulkString(arg) => Ok(Bytes::from(arg.0)),
+            _ => Err(CommandError::InvalidArguments(
+                "Invalid argument".to_string(),
+            )),
+        })
+        .collect()
+}
+
+/// Parses one or more keys followed by a timeout, as taken by `BLPOP` and `BRPOP`.
+pub(super) fn parse_keys_timeout(
+    value: RespArray,
+) -> Result<(Vec<Bytes>, Option<Duration>), CommandError> {
+    let mut keys = bulk_string_args(value)?;
+    let timeout = keys.pop().ok_or(CommandError::SyntaxError)?;
+    Ok((keys, parse_timeout(&timeout)?))
+}
+
+impl TryFrom<RespArray> for BLPop {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (keys, timeout) = parse_keys_timeout(value)?;
+        Ok(BLPop { keys, timeout })
+    }
+}
+
+impl TryFrom<RespArray> for BRPop {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (keys, timeout) = parse_keys_timeout(value)?;
+        Ok(BRPop { keys, timeout })
+    }
+}
+
+impl TryFrom<RespArray> for BLMove {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (source, args) = parse_key_and_args(value)?;
+        match <[Bytes; 4]>::try_from(args) {
+            Ok([destination, from, to, timeout]) => Ok(BLMove {
+                source: [source],
+                destination,
+                from: parse_list_end(&from)?,
+                to: parse_list_end(&to)?,
+                timeout: parse_timeout(&timeout)?,
+            }),
+            Err(_) => Err(CommandError::SyntaxError),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for BLMPop {
+    type Error = CommandError;
+    /// Converts a RESP array into a `BLMPop` command.
+    ///
+    /// The arguments are those of `LMPOP`, preceded by the timeout.
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = bulk_string_args(value)?.into_iter();
+        let timeout = parse_timeout(&args.next().ok_or(CommandError::SyntaxError)?)?;
+        let keys = parse_numkeys(&mut args)?;
+        let end = parse_list_end(&args.next().ok_or(CommandError::SyntaxError)?)?;
+        let count = parse_mpop_count(args)?;
+        Ok(BLMPop {
+            keys,
+            end,
+            count,
+            timeout,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cmd::{CommandRequest, Session},
+        RespDecode,
+    };
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    fn values(values: &[&'static str]) -> Vec<Bytes> {
+        values
+            .iter()
+            .map(|v| Bytes::from_static(v.as_bytes()))
+            .collect()
+    }
+
+    fn array(values: &[&'static str]) -> RespFrame {
+        bulk_string_array(self::values(values))
+    }
+
+    fn rpush(backend: &Backend, key: &'static str, items: &[&'static str]) {
+        RPush {
+            key: key.into(),
+            values: values(items),
+        }
+        .execute(backend);
+    }
+
+    fn lrange(backend: &Backend, key: &'static str) -> RespFrame {
+        LRange {
+            key: key.into(),
+            start: 0,
+            stop: -1,
+        }
+        .execute(backend)
+    }
+
+    #[test]
+    fn test_lmpop_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*6\r\n$5\r\nlmpop\r\n$1\r\n2\r\n$1\r\na\r\n$1\r\nb\r\n$5\r\nRIGHT\r\n$5\r\ncount\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(matches!(
+            LMPop::try_from(frame),
+            Err(CommandError::SyntaxError)
+        ));
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*7\r\n$5\r\nlmpop\r\n$1\r\n2\r\n$1\r\na\r\n$1\r\nb\r\n$5\r\nRIGHT\r\n$5\r\ncount\r\n$1\r\n3\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let result = LMPop::try_from(frame)?;
+        assert_eq!(result.keys, values(&["a", "b"]));
+        assert_eq!(result.end, ListEnd::Right);
+        assert_eq!(result.count, 3);
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$5\r\nlmpop\r\n$1\r\n3\r\n$1\r\na\r\n$4\r\nleft\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let err = LMPop::try_from(frame).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "ERR Number of keys can't be greater than number of args"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_push_pop() {
+        let backend = Backend::new();
+        let ret = LPush {
+            key: "list".into(),
+            values: values(&["a", "b", "c"]),
+        }
+        .execute(&backend);
+        assert_eq!(ret, RespFrame::Integer(3));
+        rpush(&backend, "list", &["d"]);
+        assert_eq!(lrange(&backend, "list"), array(&["c", "b", "a", "d"]));
+
+        let ret = LPushX {
+            key: "missing".into(),
+            values: values(&["a"]),
+        }
+        .execute(&backend);
+        assert_eq!(ret, RespFrame::Integer(0));
+        assert!(!backend.contains_key(b"missing"));
+        let ret = RPushX {
+            key: "list".into(),
+            values: values(&["e"]),
+        }
+        .execute(&backend);
+        assert_eq!(ret, RespFrame::Integer(5));
+
+        let pop = |count| {
+            LPop {
+                key: "list".into(),
+                count,
+            }
+            .execute(&backend)
+        };
+        assert_eq!(pop(None), RespFrame::BulkString(b"c".into()));
+        assert_eq!(pop(Some(2)), array(&["b", "a"]));
+        assert_eq!(pop(Some(0)), array(&[]));
+        let ret = RPop {
+            key: "list".into(),
+            count: Some(10),
+        }
+        .execute(&backend);
+        assert_eq!(ret, array(&["e", "d"]));
+        // the emptied list is removed
+        assert!(!backend.contains_key(b"list"));
+        assert_eq!(pop(None), RespFrame::Null(RespNull));
+        assert_eq!(pop(Some(1)), RespFrame::NullArray(RespNullArray));
+
+        backend.set("string".into(), "value".into());
+        let ret = LPush {
+            key: "string".into(),
+            values: values(&["a"]),
+        }
+        .execute(&backend);
+        assert_eq!(ret, CommandError::WrongType.into());
+    }
+
+    #[test]
+    fn test_lrange_lindex_negative_indexes() {
+        let backend = Backend::new();
+        rpush(&backend, "list", &["a", "b", "c", "d"]);
+        let range = |start, stop| {
+            LRange {
+                key: "list".into(),
+                start,
+                stop,
+            }
+            .execute(&backend)
+        };
+        assert_eq!(range(1, 2), array(&["b", "c"]));
+        assert_eq!(range(-3, -2), array(&["b", "c"]));
+        assert_eq!(range(-100, 100), array(&["a", "b", "c", "d"]));
+        assert_eq!(range(2, 1), array(&[]));
+        assert_eq!(range(5, 10), array(&[]));
+        assert_eq!(range(-1, -2), array(&[]));
+
+        let index = |index| {
+            LIndex {
+                key: "list".into(),
+                index,
+            }
+            .execute(&backend)
+        };
+        assert_eq!(index(0), RespFrame::BulkString(b"a".into()));
+        assert_eq!(index(-1), RespFrame::BulkString(b"d".into()));
+        assert_eq!(index(-5), RespFrame::Null(RespNull));
+        assert_eq!(index(4), RespFrame::Null(RespNull));
+    }
+
+    #[test]
+    fn test_lset_linsert() {
+        let backend = Backend::new();
+        rpush(&backend, "list", &["a", "b", "c"]);
+        let set = |index| {
+            LSet {
+                key: "list".into(),
+                index,
+                value: "x".into(),
+            }
+            .execute(&backend)
+        };
+        assert_eq!(set(-1), RESP_OK.clone());
+        assert_eq!(
+            set(3),
+            CommandError::InvalidArguments("index out of range".to_string()).into()
+        );
+        let ret = LSet {
+            key: "missing".into(),
+            index: 0,
+            value: "x".into(),
+        }
+        .execute(&backend);
+        assert_eq!(ret, CommandError::NoSuchKey.into());
+
+        let insert = |key: &'static str, before, pivot: &'static str| {
+            LInsert {
+                key: key.into(),
+                before,
+                pivot: pivot.into(),
+                value: "y".into(),
+            }
+            .execute(&backend)
+        };
+        assert_eq!(insert("list", true, "b"), RespFrame::Integer(4));
+        assert_eq!(insert("list", false, "x"), RespFrame::Integer(5));
+        assert_eq!(insert("list", true, "z"), RespFrame::Integer(-1));
+        assert_eq!(insert("missing", true, "a"), RespFrame::Integer(0));
+        assert_eq!(lrange(&backend, "list"), array(&["a", "y", "b", "x", "y"]));
+    }
+
+    #[test]
+    fn test_lrem_ltrim() {
+        let backend = Backend::new();
+        let rem = |count| {
+            LRem {
+                key: "list".into(),
+                count,
+                value: "a".into(),
+            }
+            .execute(&backend)
+        };
+        rpush(&backend, "list", &["a", "b", "a", "c", "a"]);
+        assert_eq!(rem(-2), RespFrame::Integer(2));
+        assert_eq!(lrange(&backend, "list"), array(&["a", "b", "c"]));
+        rpush(&backend, "list", &["a", "a"]);
+        assert_eq!(rem(1), RespFrame::Integer(1));
+        assert_eq!(lrange(&backend, "list"), array(&["b", "c", "a", "a"]));
+        assert_eq!(rem(0), RespFrame::Integer(2));
+        assert_eq!(lrange(&backend, "list"), array(&["b", "c"]));
+
+        let trim = |start, stop| {
+            LTrim {
+                key: "list".into(),
+                start,
+                stop,
+            }
+            .execute(&backend)
+        };
+        rpush(&backend, "list", &["d", "e"]);
+        assert_eq!(trim(1, -2), RESP_OK.clone());
+        assert_eq!(lrange(&backend, "list"), array(&["c", "d"]));
+        assert_eq!(trim(5, 10), RESP_OK.clone());
+        assert!(!backend.contains_key(b"list"));
+    }
+
+    #[test]
+    fn test_lpos() {
+        let backend = Backend::new();
+        rpush(&backend, "list", &["a", "b", "c", "1", "2", "3", "c", "c"]);
+        let pos = |rank, count, maxlen| {
+            LPos {
+                key: "list".into(),
+                value: "c".into(),
+                rank,
+                count,
+                maxlen,
+            }
+            .execute(&backend)
+        };
+        let integers = |values: &[i64]| {
+            RespFrame::from(RespArray::new(
+                values
+                    .iter()
+                    .map(|&i| RespFrame::Integer(i))
+                    .collect::<Vec<_>>(),
+            ))
+        };
+        assert_eq!(pos(1, None, 0), RespFrame::Integer(2));
+        assert_eq!(pos(-1, None, 0), RespFrame::Integer(7));
+        assert_eq!(pos(2, None, 0), RespFrame::Integer(6));
+        assert_eq!(pos(1, Some(0), 0), integers(&[2, 6, 7]));
+        assert_eq!(pos(-2, Some(2), 0), integers(&[6, 2]));
+        assert_eq!(pos(1, Some(0), 3), integers(&[2]));
+        assert_eq!(pos(4, None, 0), RespFrame::Null(RespNull));
+    }
+
+    #[test]
+    fn test_lmove() {
+        let backend = Backend::new();
+        rpush(&backend, "src", &["a", "b", "c"]);
+        let lmove = |source: &'static str, destination: &'static str, from, to| {
+            LMove {
+                source: source.into(),
+                destination: destination.into(),
+                from,
+                to,
+            }
+            .execute(&backend)
+        };
+        let ret = lmove("src", "dst", ListEnd::Right, ListEnd::Left);
+        assert_eq!(ret, RespFrame::BulkString(b"c".into()));
+        let ret = lmove("src", "dst", ListEnd::Left, ListEnd::Right);
+        assert_eq!(ret, RespFrame::BulkString(b"a".into()));
+        assert_eq!(lrange(&backend, "src"), array(&["b"]));
+        assert_eq!(lrange(&backend, "dst"), array(&["c", "a"]));
+
+        // rotating a list keeps the key
+        let ret = lmove("dst", "dst", ListEnd::Left, ListEnd::Right);
+        assert_eq!(ret, RespFrame::BulkString(b"c".into()));
+        assert_eq!(lrange(&backend, "dst"), array(&["a", "c"]));
+
+        backend.set("string".into(), "value".into());
+        let ret = lmove("src", "string", ListEnd::Left, ListEnd::Left);
+        assert_eq!(ret, CommandError::WrongType.into());
+        assert_eq!(lrange(&backend, "src"), array(&["b"]));
+        let ret = lmove("missing", "dst", ListEnd::Left, ListEnd::Left);
+        assert_eq!(ret, RespFrame::Null(RespNull));
+    }
+
+    #[test]
+    fn test_lmpop() {
+        let backend = Backend::new();
+        rpush(&backend, "b", &["1", "2", "3"]);
+        let lmpop = |keys: &[&'static str], end, count| {
+            LMPop {
+                keys: values(keys),
+                end,
+                count,
+            }
+            .execute(&backend)
+        };
+        let ret = lmpop(&["a", "b"], ListEnd::Right, 2);
+        assert_eq!(
+            ret,
+            RespArray::new([BulkString::new("b").into(), array(&["3", "2"])]).into()
+        );
+        assert_eq!(
+            lmpop(&["a", "c"], ListEnd::Left, 1),
+            RespFrame::NullArray(RespNullArray)
+        );
+        backend.set("a".into(), "value".into());
+        assert_eq!(
+            lmpop(&["a", "b"], ListEnd::Left, 1),
+            CommandError::WrongType.into()
+        );
+    }
+
+    fn request(args: &[&str]) -> CommandRequest {
+        let frames = args
+            .iter()
+            .map(|arg| RespFrame::BulkString(arg.as_bytes().into()))
+            .collect::<Vec<_>>();
+        CommandRequest::try_from(RespFrame::Array(RespArray::new(frames))).unwrap()
+    }
+
+    #[test]
+    fn test_parse_timeout() {
+        assert_eq!(parse_timeout(b"0").unwrap(), None);
+        assert_eq!(
+            parse_timeout(b"0.25").unwrap(),
+            Some(Duration::from_millis(250))
+        );
+        assert_eq!(
+            parse_timeout(b"-1").unwrap_err().to_string(),
+            "ERR timeout is negative"
+        );
+        assert_eq!(
+            parse_timeout(b"abc").unwrap_err().to_string(),
+            "ERR timeout is not a float or out of range"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_blocking_pop_timeout() {
+        let backend = Backend::new();
+        let ret = request(&["blpop", "list", "0.05"])
+            .run(&backend, &mut Session::new())
+            .await;
+        assert_eq!(ret, RespFrame::NullArray(RespNullArray));
+        let ret = request(&["blmove", "list", "dst", "left", "left", "0.01"])
+            .run(&backend, &mut Session::new())
+            .await;
+        assert_eq!(ret, RespFrame::Null(RespNull));
+
+        // without waiting, a blocking command replies as if it timed out
+        let ret = BRPop {
+            keys: values(&["list"]),
+            timeout: None,
+        }
+        .execute(&backend);
+        assert_eq!(ret, RespFrame::NullArray(RespNullArray));
+    }
+
+    #[tokio::test]
+    async fn test_blocking_pop_is_woken_by_push() {
+        let backend = Backend::new();
+        rpush(&backend, "other", &["x"]);
+        let ret = request(&["brpop", "list", "other", "0"])
+            .run(&backend, &mut Session::new())
+            .await;
+        assert_eq!(ret, array(&["other", "x"]));
+
+        let blocked = tokio::spawn({
+            let backend = backend.clone();
+            async move {
+                request(&["blmpop", "0", "1", "list", "left", "count", "5"])
+                    .run(&backend, &mut Session::new())
+                    .await
+            }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        request(&["rpush", "list", "a", "b"])
+            .run(&backend, &mut Session::new())
+            .await;
+        assert_eq!(
+            blocked.await.unwrap(),
+            RespArray::new([BulkString::new("list").into(), array(&["a", "b"])]).into()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_blocked_clients_are_served_in_order() {
+        let backend = Backend::new();
+        let mut blocked = Vec::new();
+        for _ in 0..3 {
+            blocked.push(tokio::spawn({
+                let backend = backend.clone();
+                async move {
+                    request(&["blpop", "list", "5"])
+                        .run(&backend, &mut Session::new())
+                        .await
+                }
+            }));
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        request(&["rpush", "list", "1", "2", "3"])
+            .run(&backend, &mut Session::new())
+            .await;
+        for (blocked, value) in blocked.into_iter().zip(["1", "2", "3"]) {
+            assert_eq!(blocked.await.unwrap(), array(&["list", value]));
+        }
+        assert!(!backend.contains_key(b"list"));
+    }
+}
@@ -1,9 +1,121 @@
 use crate::{
     cmd::{CommandError, Get},
-    RespArray, RespFrame, RespNull,
+    now_ms, Backend, BulkString, EventClass, KeyExpiry, RespArray, RespFrame, RespNull,
+    SetCondition, SetExpiry,
 };
 
-use super::{extract_args, validate_command, CommandExecutor, Set, RESP_OK};
+use bytes::Bytes;
+
+use super::{
+    extract_args, notify_set, optional_bulk_string, parse_float, parse_integer, parse_key_arg,
+    parse_keys, CommandExecutor, Set, SetExpireOption, RESP_OK,
+};
+
+#[derive(Debug)]
+pub struct Append {
+    key: Bytes,
+    value: Bytes,
+}
+
+#[derive(Debug)]
+pub struct StrLen {
+    key: Bytes,
+}
+
+#[derive(Debug)]
+pub struct GetRange {
+    key: Bytes,
+    start: i64,
+    end: i64,
+}
+
+#[derive(Debug)]
+pub struct SetRange {
+    key: Bytes,
+    offset: usize,
+    value: Bytes,
+}
+
+#[derive(Debug)]
+pub struct GetSet {
+    key: Bytes,
+    value: Bytes,
+}
+
+#[derive(Debug)]
+pub struct GetDel {
+    key: Bytes,
+}
+
+#[derive(Debug)]
+pub struct GetEx {
+    key: Bytes,
+    expiry: Option<SetExpireOption>,
+    persist: bool,
+}
+
+#[derive(Debug)]
+pub struct SetNx {
+    key: Bytes,
+    value: Bytes,
+}
+
+#[derive(Debug)]
+pub struct SetEx {
+    key: Bytes,
+    seconds: i64,
+    value: Bytes,
+}
+
+#[derive(Debug)]
+pub struct PSetEx {
+    key: Bytes,
+    milliseconds: i64,
+    value: Bytes,
+}
+
+#[derive(Debug)]
+pub struct Incr {
+    key: Bytes,
+}
+
+#[derive(Debug)]
+pub struct Decr {
+    key: Bytes,
+}
+
+#[derive(Debug)]
+pub struct IncrBy {
+    key: Bytes,
+    increment: i64,
+}
+
+#[derive(Debug)]
+pub struct DecrBy {
+    key: Bytes,
+    decrement: i64,
+}
+
+#[derive(Debug)]
+pub struct IncrByFloat {
+    key: Bytes,
+    increment: f64,
+}
+
+#[derive(Debug)]
+pub struct MGet {
+    keys: Vec<Bytes>,
+}
+
+#[derive(Debug)]
+pub struct MSet {
+    pairs: Vec<(Bytes, Bytes)>,
+}
+
+#[derive(Debug)]
+pub struct MSetNx {
+    pairs: Vec<(Bytes, Bytes)>,
+}
 
 impl CommandExecutor for Get {
     /// Executes the `Get` command on the provided backend.
@@ -23,8 +135,8 @@ impl CommandExecutor for Get {
     /// a null response if the key is not present.
     fn execute(self, backend: &crate::Backend) -> RespFrame {
         match backend.get(&self.key) {
-            Some(value) => value,
-            None => RespFrame::Null(RespNull),
+            Ok(value) => optional_bulk_string(value),
+            Err(e) => e.into(),
         }
     }
 }
@@ -34,13 +146,362 @@ impl CommandExecutor for Set {
     ///
     /// This function attempts to store the value associated with the key
     /// stored in the `Set` command in the backend. A successful store
-    /// results in a `RespFrame` containing an `Ok` response.
+    /// results in a `RespFrame` containing an `Ok` response, a store skipped
+    /// because of `NX` or `XX` results in a null response.
+    ///
+    /// With the `GET` option the value previously stored at the key is
+    /// returned instead, or a null response if there was none.
     fn execute(self, backend: &crate::Backend) -> RespFrame {
-        backend.set(self.key, self.value);
+        let expiry = match SetExpireOption::resolve(self.expiry, "set") {
+            Ok(expiry) => expiry,
+            Err(e) => return e.into(),
+        };
+        let key = self.key.clone();
+        let (written, old) =
+            match backend.set_with(self.key, self.value, self.condition, expiry, self.get) {
+                Ok(ret) => ret,
+                Err(e) => return e.into(),
+            };
+        if written {
+            notify_set(backend, &key, expiry);
+        }
+        if self.get {
+            optional_bulk_string(old)
+        } else if written {
+            RESP_OK.clone()
+        } else {
+            RespFrame::Null(RespNull)
+        }
+    }
+}
+
+impl CommandExecutor for Append {
+    /// Returns the length of the string after the append.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.append(&self.key, &self.value) {
+            Ok(len) => {
+                backend.notify_keyspace_event(EventClass::String, "append", &self.key);
+                RespFrame::Integer(len as i64)
+            }
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for StrLen {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.strlen(&self.key) {
+            Ok(len) => RespFrame::Integer(len as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for GetRange {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.getrange(&self.key, self.start, self.end) {
+            Ok(range) => BulkString::new(range).into(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for SetRange {
+    /// Returns the length of the string after the write.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.setrange(&self.key, self.offset, &self.value) {
+            Ok(len) => {
+                // an empty value leaves the string as it is
+                if !self.value.is_empty() {
+                    backend.notify_keyspace_event(EventClass::String, "setrange", &self.key);
+                }
+                RespFrame::Integer(len as i64)
+            }
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for GetSet {
+    /// Returns the string previously stored at the key, or a null reply.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let key = self.key.clone();
+        let result = backend.set_with(
+            self.key,
+            self.value,
+            SetCondition::Always,
+            SetExpiry::Discard,
+            true,
+        );
+        match result {
+            Ok((_, old)) => {
+                notify_set(backend, &key, SetExpiry::Discard);
+                optional_bulk_string(old)
+            }
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for GetDel {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.getdel(&self.key) {
+            Ok(value) => {
+                if value.is_some() {
+                    backend.notify_keyspace_event(EventClass::Generic, "del", &self.key);
+                }
+                optional_bulk_string(value)
+            }
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for GetEx {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let expiry = match (self.persist, self.expiry) {
+            (true, _) => SetExpiry::Discard,
+            (false, None) => SetExpiry::Keep,
+            (false, expiry) => match SetExpireOption::resolve(expiry, "getex") {
+                Ok(expiry) => expiry,
+                Err(e) => return e.into(),
+            },
+        };
+        // only a key losing its expiry is notified as persisted
+        let persisted =
+            self.persist && matches!(backend.expiry(&self.key), KeyExpiry::ExpiresAt(_));
+        match backend.getex(&self.key, expiry) {
+            Ok(Some(value)) => {
+                let event = match expiry {
+                    SetExpiry::At(at) if at <= now_ms() => Some("del"),
+                    SetExpiry::At(_) => Some("expire"),
+                    _ if persisted => Some("persist"),
+                    _ => None,
+                };
+                if let Some(event) = event {
+                    backend.notify_keyspace_event(EventClass::Generic, event, &self.key);
+                }
+                BulkString::new(value).into()
+            }
+            Ok(None) => RespFrame::Null(RespNull),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for SetNx {
+    /// Returns `1` if the key was set, `0` if it already existed.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let key = self.key.clone();
+        let result = backend.set_with(
+            self.key,
+            self.value,
+            SetCondition::Nx,
+            SetExpiry::Discard,
+            false,
+        );
+        match result {
+            Ok((written, _)) => {
+                if written {
+                    notify_set(backend, &key, SetExpiry::Discard);
+                }
+                RespFrame::Integer(written as i64)
+            }
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for SetEx {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        set_expiring(
+            backend,
+            self.key,
+            self.value,
+            SetExpireOption::Ex(self.seconds),
+            "setex",
+        )
+    }
+}
+
+impl CommandExecutor for PSetEx {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let option = SetExpireOption::Px(self.milliseconds);
+        set_expiring(backend, self.key, self.value, option, "psetex")
+    }
+}
+
+impl CommandExecutor for Incr {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        incr_by(backend, &self.key, Some(1))
+    }
+}
+
+impl CommandExecutor for Decr {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        incr_by(backend, &self.key, Some(-1))
+    }
+}
+
+impl CommandExecutor for IncrBy {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        incr_by(backend, &self.key, Some(self.increment))
+    }
+}
+
+impl CommandExecutor for DecrBy {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        incr_by(backend, &self.key, self.decrement.checked_neg())
+    }
+}
+
+impl CommandExecutor for IncrByFloat {
+    /// Returns the new value as a BulkString, formatted as it is stored.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.incr_by_float(&self.key, self.increment) {
+            Ok(value) => {
+                backend.notify_keyspace_event(EventClass::String, "incrbyfloat", &self.key);
+                BulkString::new(value).into()
+            }
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for MGet {
+    /// Returns the string of each key, or a null reply for keys that do not exist or hold
+    /// another type.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let values = backend
+            .mget(&self.keys)
+            .into_iter()
+            .map(optional_bulk_string)
+            .collect::<Vec<_>>();
+        RespArray::new(values).into()
+    }
+}
+
+impl CommandExecutor for MSet {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let keys = self
+            .pairs
+            .iter()
+            .map(|(key, _)| key.clone())
+            .collect::<Vec<_>>();
+        backend.mset(self.pairs);
+        for key in keys {
+            notify_set(backend, &key, SetExpiry::Discard);
+        }
         RESP_OK.clone()
     }
 }
 
+impl CommandExecutor for MSetNx {
+    /// Returns `1` if every key was set, `0` if none was because one of them existed.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let keys = self
+            .pairs
+            .iter()
+            .map(|(key, _)| key.clone())
+            .collect::<Vec<_>>();
+        let written = backend.msetnx(self.pairs);
+        if written {
+            for key in keys {
+                notify_set(backend, &key, SetExpiry::Discard);
+            }
+        }
+        RespFrame::Integer(written as i64)
+    }
+}
+
+/// Increments the integer at `key`, as done by `INCR`, `DECR`, `INCRBY` and `DECRBY`.
+///
+/// `delta` is `None` when negating the decrement overflowed.
+fn incr_by(backend: &Backend, key: &Bytes, delta: Option<i64>) -> RespFrame {
+    let Some(delta) = delta else {
+        return CommandError::InvalidArguments("decrement would overflow".to_string()).into();
+    };
+    match backend.incr_by(key, delta) {
+        Ok(value) => {
+            backend.notify_keyspace_event(EventClass::String, "incrby", key);
+            RespFrame::Integer(value)
+        }
+        Err(e) => e.into(),
+    }
+}
+
+/// Sets a string with an expiry, as done by `SETEX` and `PSETEX`.
+fn set_expiring(
+    backend: &Backend,
+    key: Bytes,
+    value: Bytes,
+    option: SetExpireOption,
+    name: &str,
+) -> RespFrame {
+    let expiry = match SetExpireOption::resolve(Some(option), name) {
+        Ok(expiry) => expiry,
+        Err(e) => return e.into(),
+    };
+    match backend.set_with(key.clone(), value, SetCondition::Always, expiry, false) {
+        Ok(_) => {
+            notify_set(backend, &key, expiry);
+            RESP_OK.clone()
+        }
+        Err(e) => e.into(),
+    }
+}
+
+impl SetExpireOption {
+    /// Resolves the expiry option of the command `name` into the expiry to apply to the
+    /// key. No option discards any existing expiry.
+    fn resolve(option: Option<Self>, name: &str) -> Result<SetExpiry, CommandError> {
+        match option {
+            None => Ok(SetExpiry::Discard),
+            Some(SetExpireOption::KeepTtl) => Ok(SetExpiry::Keep),
+            Some(option) => option.expire_at().map(SetExpiry::At).ok_or_else(|| {
+                CommandError::InvalidArguments(format!("invalid expire time in '{}' command", name))
+            }),
+        }
+    }
+
+    /// Parses the time following one of the `EX`, `PX`, `EXAT` or `PXAT` options of the
+    /// command `name`.
+    fn parse(
+        option: &[u8],
+        time: Option<RespFrame>,
+        name: &str,
+    ) -> Result<SetExpireOption, CommandError> {
+        let time = parse_integer(time.ok_or(CommandError::SyntaxError)?)?;
+        if time <= 0 {
+            return Err(CommandError::InvalidArguments(format!(
+                "invalid expire time in '{}' command",
+                name
+            )));
+        }
+        match option {
+            b"ex" => Ok(SetExpireOption::Ex(time)),
+            b"px" => Ok(SetExpireOption::Px(time)),
+            b"exat" => Ok(SetExpireOption::ExAt(time)),
+            b"pxat" => Ok(SetExpireOption::PxAt(time)),
+            _ => Err(CommandError::SyntaxError),
+        }
+    }
+
+    /// Returns the absolute unix time in milliseconds the option resolves to, or `None`
+    /// if it overflows.
+    fn expire_at(self) -> Option<u64> {
+        let now = now_ms() as i64;
+        let at = match self {
+            SetExpireOption::Ex(seconds) => seconds.checked_mul(1000)?.checked_add(now)?,
+            SetExpireOption::Px(milliseconds) => milliseconds.checked_add(now)?,
+            SetExpireOption::ExAt(timestamp) => timestamp.checked_mul(1000)?,
+            SetExpireOption::PxAt(timestamp_ms) => timestamp_ms,
+            SetExpireOption::KeepTtl => return None,
+        };
+        u64::try_from(at).ok()
+    }
+}
+
 impl TryFrom<RespArray> for Get {
     type Error = CommandError;
 
@@ -52,11 +513,10 @@ impl TryFrom<RespArray> for Get {
     /// If the conversion is successful, a `Get` struct with the key field set is returned.
     /// If the conversion fails, an `Err` containing the `CommandError` is returned.
     fn try_from(frame: RespArray) -> Result<Self, Self::Error> {
-        validate_command(&frame, &["get"], 1)?;
         let mut args = extract_args(frame, 1)?.into_iter();
         match args.next() {
             Some(RespFrame::BulkString(key)) => Ok(Get {
-                key: String::from_utf8(key.0)?,
+                key: Bytes::from(key.0),
             }),
             _ => Err(CommandError::InvalidArguments("Invalid key".to_string())),
         }
@@ -67,32 +527,336 @@ impl TryFrom<RespArray> for Set {
     type Error = CommandError;
     /// Converts a RESP array into a `Set` command.
     ///
-    /// The RESP array must have exactly 3 elements: the command name "set", the key,
-    /// and the value. The key must be a BulkString frame, and the value can be any
-    /// `RespFrame`.
+    /// The RESP array must have at least 3 elements: the command name "set", the key,
+    /// and the value, optionally followed by `NX` or `XX`, `GET`, and one of `EX`, `PX`,
+    /// `EXAT`, `PXAT` or `KEEPTTL`. The key must be a BulkString frame, and the value can
+    /// be any `RespFrame`.
     ///
-    /// If the conversion is successful, a `Set` struct with the key and value fields
+    /// If the conversion is successful, a `Set` struct with the key, value and options
     /// set is returned. If the conversion fails, an `Err` containing the
     /// `CommandError` is returned.
     fn try_from(value: RespArray) -> Result<Self, Self::Error> {
-        validate_command(&value, &["set"], 2)?;
         let mut args = extract_args(value, 1)?.into_iter();
-        match (args.next(), args.next()) {
-            (Some(RespFrame::BulkString(key)), Some(value)) => Ok(Set {
-                key: String::from_utf8(key.0)?,
-                value,
-            }),
-            _ => Err(CommandError::InvalidArguments(
+        let (key, value) = match (args.next(), args.next()) {
+            (Some(RespFrame::BulkString(key)), Some(RespFrame::BulkString(value))) => {
+                (Bytes::from(key.0), Bytes::from(value.0))
+            }
+            _ => {
+                return Err(CommandError::InvalidArguments(
+                    "Invalid key or value".to_string(),
+                ))
+            }
+        };
+        let mut condition = SetCondition::Always;
+        let mut expiry = None;
+        let mut get = false;
+        while let Some(arg) = args.next() {
+            let RespFrame::BulkString(option) = arg else {
+                return Err(CommandError::SyntaxError);
+            };
+            match option.to_ascii_lowercase().as_slice() {
+                b"nx" if condition != SetCondition::Xx => condition = SetCondition::Nx,
+                b"xx" if condition != SetCondition::Nx => condition = SetCondition::Xx,
+                b"get" => get = true,
+                b"keepttl" if expiry.is_none_or(|e| e == SetExpireOption::KeepTtl) => {
+                    expiry = Some(SetExpireOption::KeepTtl)
+                }
+                name @ (b"ex" | b"px" | b"exat" | b"pxat") if expiry.is_none() => {
+                    expiry = Some(SetExpireOption::parse(name, args.next(), "set")?);
+                }
+                _ => return Err(CommandError::SyntaxError),
+            }
+        }
+        Ok(Set {
+            key,
+            value,
+            condition,
+            expiry,
+            get,
+        })
+    }
+}
+
+/// Parses a command whose arguments are a key and a value.
+fn parse_key_value(value: RespArray) -> Result<(Bytes, Bytes), CommandError> {
+    let mut args = extract_args(value, 1)?.into_iter();
+    match (args.next(), args.next()) {
+        (Some(RespFrame::BulkString(key)), Some(RespFrame::BulkString(value))) => {
+            Ok((Bytes::from(key.0), Bytes::from(value.0)))
+        }
+        _ => Err(CommandError::InvalidArguments(
+            "Invalid key or value".to_string(),
+        )),
+    }
+}
+
+/// Parses a command whose arguments are a key, an integer and a value, as taken by
+/// `SETRANGE`, `SETEX` and `PSETEX`.
+fn parse_key_integer_value(value: RespArray) -> Result<(Bytes, i64, Bytes), CommandError> {
+    let mut args = extract_args(value, 1)?.into_iter();
+    let (key, integer, value) = match (args.next(), args.next(), args.next()) {
+        (Some(RespFrame::BulkString(key)), Some(integer), Some(RespFrame::BulkString(value))) => {
+            (key, integer, value)
+        }
+        _ => {
+            return Err(CommandError::InvalidArguments(
                 "Invalid key or value".to_string(),
-            )),
+            ))
+        }
+    };
+    Ok((
+        Bytes::from(key.0),
+        parse_integer(integer)?,
+        Bytes::from(value.0),
+    ))
+}
+
+impl TryFrom<RespArray> for Append {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, value) = parse_key_value(value)?;
+        Ok(Append { key, value })
+    }
+}
+
+impl TryFrom<RespArray> for StrLen {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let key = parse_key_arg(value)?;
+        Ok(StrLen { key })
+    }
+}
+
+impl TryFrom<RespArray> for GetRange {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next(), args.next()) {
+            (Some(RespFrame::BulkString(key)), Some(start), Some(end)) => Ok(GetRange {
+                key: Bytes::from(key.0),
+                start: parse_integer(start)?,
+                end: parse_integer(end)?,
+            }),
+            _ => Err(CommandError::InvalidArguments("Invalid key".to_string())),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for SetRange {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, offset, value) = parse_key_integer_value(value)?;
+        let offset = usize::try_from(offset)
+            .map_err(|_| CommandError::InvalidArguments("offset is out of range".to_string()))?;
+        Ok(SetRange { key, offset, value })
+    }
+}
+
+impl TryFrom<RespArray> for GetSet {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, value) = parse_key_value(value)?;
+        Ok(GetSet { key, value })
+    }
+}
+
+impl TryFrom<RespArray> for GetDel {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let key = parse_key_arg(value)?;
+        Ok(GetDel { key })
+    }
+}
+
+impl TryFrom<RespArray> for GetEx {
+    type Error = CommandError;
+    /// Converts a RESP array into a `GetEx` command.
+    ///
+    /// The key may be followed by one of `EX`, `PX`, `EXAT`, `PXAT` or `PERSIST`.
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = match args.next() {
+            Some(RespFrame::BulkString(key)) => Bytes::from(key.0),
+            _ => return Err(CommandError::InvalidArguments("Invalid key".to_string())),
+        };
+        let mut expiry = None;
+        let mut persist = false;
+        match args.next() {
+            None => {}
+            Some(RespFrame::BulkString(option)) => match option.to_ascii_lowercase().as_slice() {
+                b"persist" => persist = true,
+                name @ (b"ex" | b"px" | b"exat" | b"pxat") => {
+                    expiry = Some(SetExpireOption::parse(name, args.next(), "getex")?);
+                }
+                _ => return Err(CommandError::SyntaxError),
+            },
+            Some(_) => return Err(CommandError::SyntaxError),
+        }
+        if args.next().is_some() {
+            return Err(CommandError::SyntaxError);
+        }
+        Ok(GetEx {
+            key,
+            expiry,
+            persist,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for SetNx {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, value) = parse_key_value(value)?;
+        Ok(SetNx { key, value })
+    }
+}
+
+impl TryFrom<RespArray> for SetEx {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, seconds, value) = parse_key_integer_value(value)?;
+        if seconds <= 0 {
+            return Err(CommandError::InvalidArguments(
+                "invalid expire time in 'setex' command".to_string(),
+            ));
+        }
+        Ok(SetEx {
+            key,
+            seconds,
+            value,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for PSetEx {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, milliseconds, value) = parse_key_integer_value(value)?;
+        if milliseconds <= 0 {
+            return Err(CommandError::InvalidArguments(
+                "invalid expire time in 'psetex' command".to_string(),
+            ));
+        }
+        Ok(PSetEx {
+            key,
+            milliseconds,
+            value,
+        })
+    }
+}
+
+/// Parses a command whose arguments are a key and a number.
+fn parse_key_number(value: RespArray) -> Result<(Bytes, RespFrame), CommandError> {
+    let mut args = extract_args(value, 1)?.into_iter();
+    match (args.next(), args.next()) {
+        (Some(RespFrame::BulkString(key)), Some(number)) => Ok((Bytes::from(key.0), number)),
+        _ => Err(CommandError::InvalidArguments("Invalid key".to_string())),
+    }
+}
+
+impl TryFrom<RespArray> for Incr {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let key = parse_key_arg(value)?;
+        Ok(Incr { key })
+    }
+}
+
+impl TryFrom<RespArray> for Decr {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let key = parse_key_arg(value)?;
+        Ok(Decr { key })
+    }
+}
+
+impl TryFrom<RespArray> for IncrBy {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, increment) = parse_key_number(value)?;
+        Ok(IncrBy {
+            key,
+            increment: parse_integer(increment)?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for DecrBy {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, decrement) = parse_key_number(value)?;
+        Ok(DecrBy {
+            key,
+            decrement: parse_integer(decrement)?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for IncrByFloat {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, increment) = parse_key_number(value)?;
+        Ok(IncrByFloat {
+            key,
+            increment: parse_float(increment)?,
+        })
+    }
+}
+
+/// Parses the key-value pairs of `MSET` and `MSETNX`.
+fn parse_key_value_pairs(
+    value: RespArray,
+    name: &str,
+) -> Result<Vec<(Bytes, Bytes)>, CommandError> {
+    let args = extract_args(value, 1)?;
+    if args.is_empty() || args.len() % 2 != 0 {
+        return Err(CommandError::WrongArity(name.to_string()));
+    }
+    let mut args = args.into_iter();
+    let mut pairs = Vec::with_capacity(args.len() / 2);
+    while let (Some(key), Some(value)) = (args.next(), args.next()) {
+        match (key, value) {
+            (RespFrame::BulkString(key), RespFrame::BulkString(value)) => {
+                pairs.push((Bytes::from(key.0), Bytes::from(value.0)))
+            }
+            _ => {
+                return Err(CommandError::InvalidArguments(
+                    "Invalid key or value".to_string(),
+                ))
+            }
         }
     }
+    Ok(pairs)
+}
+
+impl TryFrom<RespArray> for MGet {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let keys = parse_keys(value)?;
+        Ok(MGet { keys })
+    }
+}
+
+impl TryFrom<RespArray> for MSet {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let pairs = parse_key_value_pairs(value, "mset")?;
+        Ok(MSet { pairs })
+    }
+}
+
+impl TryFrom<RespArray> for MSetNx {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let pairs = parse_key_value_pairs(value, "msetnx")?;
+        Ok(MSetNx { pairs })
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{Backend, RespDecode};
+    use crate::{cmd::CommandRequest, Backend, KeyExpiry, RespDecode};
     use anyhow::Result;
     use bytes::BytesMut;
 
@@ -124,7 +888,7 @@ mod tests {
         let result: Set = frame.try_into()?;
 
         assert_eq!(result.key, "hello");
-        assert_eq!(result.value, RespFrame::BulkString(b"world".into()));
+        assert_eq!(result.value, "world");
 
         Ok(())
     }
@@ -133,18 +897,530 @@ mod tests {
     fn test_set_get_command() -> Result<()> {
         let backend = Backend::new();
         let cmd = Set {
-            key: "hello".to_string(),
-            value: RespFrame::BulkString(b"world".into()),
+            key: "hello".into(),
+            value: "world".into(),
+            condition: SetCondition::Always,
+            expiry: None,
+            get: false,
         };
         let result = cmd.execute(&backend);
         assert_eq!(result, RESP_OK.clone());
 
         let cmd = Get {
-            key: "hello".to_string(),
+            key: "hello".into(),
         };
         let result = cmd.execute(&backend);
         assert_eq!(result, RespFrame::BulkString(b"world".into()));
 
         Ok(())
     }
+
+    #[test]
+    fn test_binary_key() -> Result<()> {
+        let backend = Backend::new();
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$3\r\nset\r\n$4\r\n\xff\x00\r\n\r\n$5\r\nworld\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: Set = frame.try_into()?;
+        assert_eq!(cmd.key, b"\xff\x00\r\n".as_slice());
+        cmd.execute(&backend);
+
+        buf.extend_from_slice(b"*2\r\n$3\r\nget\r\n$4\r\n\xff\x00\r\n\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: Get = frame.try_into()?;
+        let result = cmd.execute(&backend);
+        assert_eq!(result, RespFrame::BulkString(b"world".into()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_options_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*6\r\n$3\r\nSET\r\n$4\r\nlock\r\n$1\r\n1\r\n$2\r\nNX\r\n$2\r\nPX\r\n$5\r\n30000\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Set = frame.try_into()?;
+        assert_eq!(result.key, "lock");
+        assert_eq!(result.condition, SetCondition::Nx);
+        assert_eq!(result.expiry, Some(SetExpireOption::Px(30000)));
+        assert!(!result.get);
+
+        buf.extend_from_slice(b"*5\r\n$3\r\nset\r\n$1\r\nk\r\n$1\r\nv\r\n$2\r\nnx\r\n$2\r\nxx\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(Set::try_from(frame).is_err());
+
+        buf.extend_from_slice(
+            b"*6\r\n$3\r\nset\r\n$1\r\nk\r\n$1\r\nv\r\n$7\r\nkeepttl\r\n$2\r\nex\r\n$2\r\n10\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(Set::try_from(frame).is_err());
+
+        buf.extend_from_slice(b"*5\r\n$3\r\nset\r\n$1\r\nk\r\n$1\r\nv\r\n$2\r\nex\r\n$1\r\n0\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(Set::try_from(frame).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_conditions_and_get() -> Result<()> {
+        let backend = Backend::new();
+        let set = |value: &[u8], condition: SetCondition, get: bool| {
+            Set {
+                key: "lock".into(),
+                value: Bytes::copy_from_slice(value),
+                condition,
+                expiry: None,
+                get,
+            }
+            .execute(&backend)
+        };
+        assert_eq!(
+            set(b"1", SetCondition::Xx, false),
+            RespFrame::Null(RespNull)
+        );
+        assert_eq!(set(b"1", SetCondition::Nx, false), RESP_OK.clone());
+        assert_eq!(
+            set(b"2", SetCondition::Nx, false),
+            RespFrame::Null(RespNull)
+        );
+        assert_eq!(
+            set(b"2", SetCondition::Nx, true),
+            RespFrame::BulkString(b"1".into())
+        );
+        assert_eq!(
+            set(b"3", SetCondition::Xx, true),
+            RespFrame::BulkString(b"1".into())
+        );
+        assert_eq!(backend.get(b"lock")?, Some("3".into()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_expiry_options() -> Result<()> {
+        let backend = Backend::new();
+        let set = |expiry: Option<SetExpireOption>| {
+            Set {
+                key: "hello".into(),
+                value: "world".into(),
+                condition: SetCondition::Always,
+                expiry,
+                get: false,
+            }
+            .execute(&backend)
+        };
+        set(Some(SetExpireOption::Ex(100)));
+        assert!(matches!(backend.expiry(b"hello"), KeyExpiry::ExpiresAt(_)));
+        set(Some(SetExpireOption::KeepTtl));
+        assert!(matches!(backend.expiry(b"hello"), KeyExpiry::ExpiresAt(_)));
+        set(None);
+        assert_eq!(backend.expiry(b"hello"), KeyExpiry::Persistent);
+        set(Some(SetExpireOption::PxAt(1)));
+        assert_eq!(backend.get(b"hello")?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_string_and_hash_share_the_keyspace() -> Result<()> {
+        let backend = Backend::new();
+        backend.hset("key".into(), "field".into(), "value".into())?;
+
+        let result = Get { key: "key".into() }.execute(&backend);
+        assert_eq!(result, CommandError::WrongType.into());
+
+        let cmd = Set {
+            key: "key".into(),
+            value: "world".into(),
+            condition: SetCondition::Always,
+            expiry: None,
+            get: true,
+        };
+        assert_eq!(cmd.execute(&backend), CommandError::WrongType.into());
+        assert!(backend.hget(b"key", b"field")?.is_some());
+
+        // a plain SET replaces a value of any type
+        let cmd = Set {
+            key: "key".into(),
+            value: "world".into(),
+            condition: SetCondition::Always,
+            expiry: None,
+            get: false,
+        };
+        assert_eq!(cmd.execute(&backend), RESP_OK.clone());
+        assert_eq!(backend.get(b"key")?, Some("world".into()));
+        assert!(matches!(
+            backend.hget(b"key", b"field"),
+            Err(CommandError::WrongType)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_append_and_strlen() -> Result<()> {
+        let backend = Backend::new();
+        let append = |value: &'static str| {
+            Append {
+                key: "key".into(),
+                value: value.into(),
+            }
+            .execute(&backend)
+        };
+        assert_eq!(append("hello"), RespFrame::Integer(5));
+        assert_eq!(append(" world"), RespFrame::Integer(11));
+        assert_eq!(backend.get(b"key")?, Some("hello world".into()));
+        let cmd = StrLen { key: "key".into() };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(11));
+        let cmd = StrLen { key: "none".into() };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_getrange() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("key".into(), "This is a string".into());
+        let range = |start: i64, end: i64| backend.getrange(b"key", start, end).unwrap();
+        assert_eq!(range(0, 3), "This");
+        assert_eq!(range(-3, -1), "ing");
+        assert_eq!(range(0, -1), "This is a string");
+        assert_eq!(range(10, 100), "string");
+        assert_eq!(range(-100, 3), "This");
+        assert_eq!(range(5, 3), "");
+        assert_eq!(range(-1, -5), "");
+        assert_eq!(backend.getrange(b"none", 0, -1)?, "");
+        Ok(())
+    }
+
+    #[test]
+    fn test_setrange() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("key".into(), "Hello World".into());
+        assert_eq!(backend.setrange(&"key".into(), 6, b"Redis")?, 11);
+        assert_eq!(backend.get(b"key")?, Some("Hello Redis".into()));
+
+        // a missing key is zero padded up to the offset
+        assert_eq!(backend.setrange(&"pad".into(), 3, b"ab")?, 5);
+        assert_eq!(backend.get(b"pad")?, Some(Bytes::from_static(b"\0\0\0ab")));
+
+        // an empty value does not create the key
+        assert_eq!(backend.setrange(&"empty".into(), 10, b"")?, 0);
+        assert!(!backend.contains_key(b"empty"));
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$8\r\nsetrange\r\n$3\r\nkey\r\n$2\r\n-1\r\n$1\r\na\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(SetRange::try_from(frame).is_err());
+        assert!(backend
+            .setrange(&"key".into(), 512 * 1024 * 1024, b"a")
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_getset_getdel() -> Result<()> {
+        let backend = Backend::new();
+        let cmd = GetSet {
+            key: "key".into(),
+            value: "1".into(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Null(RespNull));
+        let cmd = GetSet {
+            key: "key".into(),
+            value: "2".into(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::BulkString(b"1".into()));
+
+        let cmd = GetDel { key: "key".into() };
+        assert_eq!(cmd.execute(&backend), RespFrame::BulkString(b"2".into()));
+        assert!(!backend.contains_key(b"key"));
+
+        backend.hset("hash".into(), "field".into(), "value".into())?;
+        let cmd = GetDel { key: "hash".into() };
+        assert_eq!(cmd.execute(&backend), CommandError::WrongType.into());
+        assert!(backend.contains_key(b"hash"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_getex() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("key".into(), "value".into());
+        let getex = |expiry: Option<SetExpireOption>, persist: bool| {
+            GetEx {
+                key: "key".into(),
+                expiry,
+                persist,
+            }
+            .execute(&backend)
+        };
+        assert_eq!(
+            getex(Some(SetExpireOption::Ex(100)), false),
+            RespFrame::BulkString(b"value".into())
+        );
+        assert!(matches!(backend.expiry(b"key"), KeyExpiry::ExpiresAt(_)));
+        getex(None, false);
+        assert!(matches!(backend.expiry(b"key"), KeyExpiry::ExpiresAt(_)));
+        getex(None, true);
+        assert_eq!(backend.expiry(b"key"), KeyExpiry::Persistent);
+        assert_eq!(
+            getex(Some(SetExpireOption::PxAt(1)), false),
+            RespFrame::BulkString(b"value".into())
+        );
+        assert!(!backend.contains_key(b"key"));
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*5\r\n$5\r\ngetex\r\n$3\r\nkey\r\n$2\r\nEX\r\n$1\r\n1\r\n$7\r\nPERSIST\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(GetEx::try_from(frame).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_setnx_setex_psetex() -> Result<()> {
+        let backend = Backend::new();
+        let setnx = |value: &'static str| {
+            SetNx {
+                key: "key".into(),
+                value: value.into(),
+            }
+            .execute(&backend)
+        };
+        assert_eq!(setnx("1"), RespFrame::Integer(1));
+        assert_eq!(setnx("2"), RespFrame::Integer(0));
+        assert_eq!(backend.get(b"key")?, Some("1".into()));
+
+        let cmd = SetEx {
+            key: "key".into(),
+            seconds: 100,
+            value: "3".into(),
+        };
+        assert_eq!(cmd.execute(&backend), RESP_OK.clone());
+        let KeyExpiry::ExpiresAt(at) = backend.expiry(b"key") else {
+            panic!("expected an expiry");
+        };
+        assert!(at > now_ms() + 99_000);
+
+        let cmd = PSetEx {
+            key: "key".into(),
+            milliseconds: 1500,
+            value: "4".into(),
+        };
+        assert_eq!(cmd.execute(&backend), RESP_OK.clone());
+        let KeyExpiry::ExpiresAt(at) = backend.expiry(b"key") else {
+            panic!("expected an expiry");
+        };
+        assert!(at <= now_ms() + 1500);
+        assert_eq!(backend.get(b"key")?, Some("4".into()));
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$5\r\nsetex\r\n$3\r\nkey\r\n$1\r\n0\r\n$1\r\na\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert_eq!(
+            SetEx::try_from(frame).unwrap_err().to_string(),
+            "ERR invalid expire time in 'setex' command"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_incr_decr() -> Result<()> {
+        let backend = Backend::new();
+        assert_eq!(
+            Incr { key: "n".into() }.execute(&backend),
+            RespFrame::Integer(1)
+        );
+        let cmd = IncrBy {
+            key: "n".into(),
+            increment: 10,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(11));
+        let cmd = DecrBy {
+            key: "n".into(),
+            decrement: 20,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(-9));
+        assert_eq!(
+            Decr { key: "n".into() }.execute(&backend),
+            RespFrame::Integer(-10)
+        );
+        assert_eq!(backend.get(b"n")?, Some("-10".into()));
+
+        // the expiry of the key is retained
+        backend.expire_at(b"n", u64::MAX, Default::default());
+        Incr { key: "n".into() }.execute(&backend);
+        assert_eq!(backend.expiry(b"n"), KeyExpiry::ExpiresAt(u64::MAX));
+        Ok(())
+    }
+
+    #[test]
+    fn test_incr_errors() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("s".into(), "abc".into());
+        backend.set("empty".into(), "".into());
+        backend.set("max".into(), i64::MAX.to_string().into());
+        let incr = |key: &'static str| Incr { key: key.into() }.execute(&backend);
+        assert_eq!(incr("s"), CommandError::NotAnInteger.into());
+        assert_eq!(incr("empty"), CommandError::NotAnInteger.into());
+        assert_eq!(incr("max"), CommandError::Overflow.into());
+        // only integers written the way Redis writes them are accepted
+        for value in ["+5", "05", "-0", " 5"] {
+            backend.set("n".into(), value.into());
+            assert_eq!(incr("n"), CommandError::NotAnInteger.into());
+        }
+        assert_eq!(backend.get(b"max")?, Some(i64::MAX.to_string().into()));
+        let cmd = DecrBy {
+            key: "n".into(),
+            decrement: i64::MIN,
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            CommandError::InvalidArguments("decrement would overflow".to_string()).into()
+        );
+        backend.hset("hash".into(), "field".into(), "1".into())?;
+        assert_eq!(incr("hash"), CommandError::WrongType.into());
+        Ok(())
+    }
+
+    #[test]
+    fn test_incrbyfloat() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("f".into(), "10.50".into());
+        let incr = |increment: f64| {
+            IncrByFloat {
+                key: "f".into(),
+                increment,
+            }
+            .execute(&backend)
+        };
+        assert_eq!(incr(0.1), RespFrame::BulkString(b"10.6".into()));
+        assert_eq!(incr(-5.0), RespFrame::BulkString(b"5.6".into()));
+        backend.set("f".into(), "5.0e3".into());
+        assert_eq!(incr(2.0e2), RespFrame::BulkString(b"5200".into()));
+        // the rounding error of the sum is not shown
+        backend.set("f".into(), "0.1".into());
+        assert_eq!(incr(0.2), RespFrame::BulkString(b"0.3".into()));
+        assert_eq!(incr(-0.3), RespFrame::BulkString(b"0".into()));
+        assert_eq!(incr(1e-20), RespFrame::BulkString(b"0".into()));
+        assert_eq!(
+            incr(1e20),
+            RespFrame::BulkString(b"100000000000000000000".into())
+        );
+        backend.set("f".into(), f64::MAX.to_string().into());
+        assert_eq!(
+            incr(f64::MAX),
+            CommandError::InvalidArguments("increment would produce NaN or Infinity".to_string())
+                .into()
+        );
+        backend.set("f".into(), "abc".into());
+        assert_eq!(incr(1.0), CommandError::NotAFloat.into());
+        Ok(())
+    }
+
+    #[test]
+    fn test_concurrent_incr() {
+        let backend = Backend::new();
+        let threads = (0..8)
+            .map(|_| {
+                let backend = backend.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..1000 {
+                        Incr { key: "n".into() }.execute(&backend);
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        assert_eq!(backend.get(b"n").unwrap(), Some("8000".into()));
+    }
+
+    #[test]
+    fn test_mset_mget_msetnx() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$4\r\nmset\r\n$1\r\na\r\n$1\r\n1\r\n$1\r\nb\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let err = MSet::try_from(frame).unwrap_err();
+        assert!(matches!(err, CommandError::WrongArity(name) if name == "mset"));
+
+        let backend = Backend::new();
+        backend.hset("hash".into(), "field".into(), "1".into())?;
+        backend.set_with(
+            "a".into(),
+            "old".into(),
+            SetCondition::Always,
+            SetExpiry::At(u64::MAX),
+            false,
+        )?;
+        let ret = MSet {
+            pairs: vec![("a".into(), "1".into()), ("b".into(), "2".into())],
+        }
+        .execute(&backend);
+        assert_eq!(ret, RESP_OK.clone());
+        assert_eq!(backend.expiry(b"a"), KeyExpiry::Persistent);
+        let ret = MGet {
+            keys: vec!["a".into(), "missing".into(), "hash".into(), "b".into()],
+        }
+        .execute(&backend);
+        assert_eq!(
+            ret,
+            RespArray::new(vec![
+                RespFrame::BulkString(b"1".into()),
+                RespFrame::Null(RespNull),
+                RespFrame::Null(RespNull),
+                RespFrame::BulkString(b"2".into()),
+            ])
+            .into()
+        );
+
+        // a single existing key makes MSETNX set nothing
+        let ret = MSetNx {
+            pairs: vec![("c".into(), "3".into()), ("b".into(), "3".into())],
+        }
+        .execute(&backend);
+        assert_eq!(ret, RespFrame::Integer(0));
+        assert_eq!(backend.get(b"c")?, None);
+        assert_eq!(backend.get(b"b")?, Some("2".into()));
+        let ret = MSetNx {
+            pairs: vec![("c".into(), "3".into()), ("d".into(), "4".into())],
+        }
+        .execute(&backend);
+        assert_eq!(ret, RespFrame::Integer(1));
+        assert_eq!(backend.get(b"d")?, Some("4".into()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_concurrent_mset_is_atomic() {
+        let request = |args: &[&str]| {
+            let frames = args
+                .iter()
+                .map(|arg| RespFrame::BulkString(arg.as_bytes().into()))
+                .collect::<Vec<_>>();
+            CommandRequest::try_from(RespFrame::Array(RespArray::new(frames))).unwrap()
+        };
+        let backend = Backend::new();
+        let writers = (0..4)
+            .map(|i| {
+                let backend = backend.clone();
+                std::thread::spawn(move || {
+                    for j in 0..500 {
+                        let value = format!("{i}-{j}");
+                        request(&["mset", "a", &value, "b", &value]).execute(&backend);
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        for _ in 0..2000 {
+            let RespFrame::Array(values) = request(&["mget", "a", "b"]).execute(&backend) else {
+                panic!("MGET must reply with an array");
+            };
+            assert_eq!(values[0], values[1]);
+        }
+        for writer in writers {
+            writer.join().unwrap();
+        }
+    }
 }
@@ -1,30 +1,164 @@
+mod connection;
+mod expire;
 mod hmap;
+mod keyspace;
+mod list;
 mod map;
+mod pubsub;
+mod server;
+mod set;
+mod stream;
+mod table;
+mod transaction;
+mod zset;
 
-use crate::{Backend, RespArray, RespError, RespFrame, SimpleString};
+pub use connection::{Hello, Ping, Protocol, Session};
+pub use expire::{
+    Expire, ExpireAt, ExpireTime, PExpire, PExpireAt, PExpireTime, PTtl, Persist, Ttl,
+};
+pub use hmap::{
+    HDel, HExists, HIncrBy, HIncrByFloat, HKeys, HLen, HMGet, HMSet, HRandField, HScan, HSetNx,
+    HStrLen, HVals,
+};
+pub use keyspace::{CopyCmd, Del, Exists, Keys, Rename, RenameNx, Scan, Touch, Type, Unlink};
+pub use list::{
+    BLMPop, BLMove, BLPop, BRPop, LIndex, LInsert, LLen, LMPop, LMove, LPop, LPos, LPush, LPushX,
+    LRange, LRem, LSet, LTrim, RPop, RPush, RPushX,
+};
+pub use map::{
+    Append, Decr, DecrBy, GetDel, GetEx, GetRange, GetSet, Incr, IncrBy, IncrByFloat, MGet, MSet,
+    MSetNx, PSetEx, SetEx, SetNx, SetRange, StrLen,
+};
+pub use pubsub::{
+    PSubscribe, PUnsubscribe, PubSubCmd, Publish, SPublish, SSubscribe, SUnsubscribe, Subscribe,
+    Unsubscribe,
+};
+pub use server::{CommandCmd, ConfigCmd};
+pub use set::{
+    SAdd, SCard, SDiff, SDiffStore, SInter, SInterCard, SInterStore, SIsMember, SMIsMember,
+    SMembers, SMove, SPop, SRandMember, SRem, SUnion, SUnionStore,
+};
+pub use stream::{
+    XAck, XAdd, XAutoClaim, XClaim, XDel, XGroup, XInfo, XLen, XPending, XRange, XRead, XReadGroup,
+    XRevRange, XTrim,
+};
+pub use table::{lookup_command, CommandFlag, CommandSpec, COMMAND_TABLE};
+pub use transaction::{Discard, Exec, Multi};
+pub use zset::{
+    BZMPop, BZPopMax, BZPopMin, ZAdd, ZCard, ZCount, ZDiff, ZDiffStore, ZIncrBy, ZInter,
+    ZInterStore, ZMPop, ZMScore, ZPopMax, ZPopMin, ZRandMember, ZRange, ZRangeStore, ZRank, ZRem,
+    ZRemRangeByLex, ZRemRangeByRank, ZRemRangeByScore, ZRevRank, ZScore, ZUnion, ZUnionStore,
+};
+
+use crate::{
+    parse_i64, Backend, BulkString, EventClass, KeyLockGuard, RespArray, RespError, RespFrame,
+    RespNull, RespNullArray, SetCondition, SetExpiry, SimpleError, SimpleString,
+};
+use bytes::Bytes;
 use enum_dispatch::enum_dispatch;
 use lazy_static::lazy_static;
+use std::ops::ControlFlow;
+use std::time::Duration;
 use thiserror::Error;
+use tokio::time::{timeout_at, Instant};
 
 lazy_static! {
     static ref RESP_OK: RespFrame = SimpleString::new("OK").into();
 }
 
+/// Errors raised while parsing or executing a command.
+///
+/// The `Display` output of each variant is a Redis style error message, starting with an
+/// error prefix such as `ERR` or `WRONGTYPE`, so it can be sent to the client as is.
 #[derive(Error, Debug)]
 pub enum CommandError {
-    #[error("Invalid command: {0}")]
+    #[error("ERR {0}")]
     InvalidCommand(String),
-    #[error("Invalid arguments: {0}")]
+    #[error("ERR {0}")]
     InvalidArguments(String),
-    #[error("{0}")]
+    #[error("ERR unknown command '{name}', with args beginning with: {args}")]
+    UnknownCommand { name: String, args: String },
+    #[error("ERR wrong number of arguments for '{0}' command")]
+    WrongArity(String),
+    #[error("ERR syntax error")]
+    SyntaxError,
+    #[error("ERR value is not an integer or out of range")]
+    NotAnInteger,
+    #[error("ERR no such key")]
+    NoSuchKey,
+    #[error("ERR value is not a valid float")]
+    NotAFloat,
+    #[error("ERR increment or decrement would overflow")]
+    Overflow,
+    #[error("WRONGTYPE Operation against a key holding the wrong kind of value")]
+    WrongType,
+    #[error("NOGROUP {0}")]
+    NoGroup(String),
+    #[error("BUSYGROUP Consumer Group name already exists")]
+    BusyGroup,
+    #[error("CROSSSLOT Keys in request don't hash to the same slot")]
+    CrossSlot,
+    #[error("EXECABORT Transaction discarded because of previous errors.")]
+    ExecAbort,
+    #[error("NOPROTO unsupported protocol version")]
+    NoProto,
+    #[error("WRONGPASS invalid username-password pair or user is disabled.")]
+    WrongPass,
+    #[error("ERR Protocol error: {0}")]
     RespError(#[from] RespError),
-    #[error("Utf8 error: {0}")]
+    #[error("ERR invalid utf8 string: {0}")]
     Utf8Error(#[from] std::string::FromUtf8Error),
 }
 
+impl From<CommandError> for RespFrame {
+    /// Converts a `CommandError` into an error reply for the client.
+    fn from(err: CommandError) -> Self {
+        SimpleError::new(err.to_string()).into()
+    }
+}
+
 #[enum_dispatch]
 pub trait CommandExecutor {
     fn execute(self, backend: &Backend) -> RespFrame;
+
+    /// Returns `true` if the reply is an array of pairs, e.g. members and their scores,
+    /// which RESP2 clients expect flattened into a single array.
+    fn has_pair_reply(&self) -> bool {
+        false
+    }
+}
+
+/// A command acting on the connection that sent it, e.g. `HELLO`.
+///
+/// Its `CommandExecutor` implementation runs it on a fresh session, for the places where
+/// there is no connection to act on.
+pub trait SessionCommand {
+    fn execute_session(self, backend: &Backend, session: &mut Session) -> RespFrame;
+}
+
+/// A command that waits until its keys can serve it, e.g. `BLPOP`.
+///
+/// The `CommandExecutor` implementation of a blocking command runs it once and replies as
+/// if the timeout expired when it would have to wait, which is how it behaves where a
+/// client can't be blocked.
+pub trait BlockingCommand {
+    /// Returns the keys whose readiness may unblock the command.
+    fn wait_keys(&self) -> &[Bytes];
+
+    /// Returns how long to wait, `None` to wait forever.
+    fn timeout(&self) -> Option<Duration>;
+
+    /// Called once before the first attempt to run the command, e.g. to resolve arguments
+    /// relative to the state of the keys at the time the client blocked.
+    fn prepare(&mut self, _backend: &Backend) {}
+
+    /// Tries to run the command, returning `None` if it has to wait.
+    fn try_execute(&self, backend: &Backend) -> Option<RespFrame>;
+
+    /// Returns the reply sent when the timeout expires.
+    fn timeout_reply(&self) -> RespFrame {
+        RespFrame::NullArray(RespNullArray)
+    }
 }
 
 #[derive(Debug)]
@@ -32,45 +166,200 @@ pub trait CommandExecutor {
 pub enum Command {
     Get(Get),
     Set(Set),
+    Append(Append),
+    StrLen(StrLen),
+    GetRange(GetRange),
+    SetRange(SetRange),
+    GetSet(GetSet),
+    GetDel(GetDel),
+    GetEx(GetEx),
+    SetNx(SetNx),
+    SetEx(SetEx),
+    PSetEx(PSetEx),
+    Incr(Incr),
+    Decr(Decr),
+    IncrBy(IncrBy),
+    DecrBy(DecrBy),
+    IncrByFloat(IncrByFloat),
+    MGet(MGet),
+    MSet(MSet),
+    MSetNx(MSetNx),
     HGet(HGet),
     HSet(HSet),
     HGetAll(HGetAll),
-    // unrecognized commands
-    Unrecognized(Unrecognized),
+    HDel(HDel),
+    HExists(HExists),
+    HLen(HLen),
+    HKeys(HKeys),
+    HVals(HVals),
+    HMGet(HMGet),
+    HMSet(HMSet),
+    HSetNx(HSetNx),
+    HStrLen(HStrLen),
+    HIncrBy(HIncrBy),
+    HIncrByFloat(HIncrByFloat),
+    HRandField(HRandField),
+    HScan(HScan),
+    LPush(LPush),
+    RPush(RPush),
+    LPushX(LPushX),
+    RPushX(RPushX),
+    LPop(LPop),
+    RPop(RPop),
+    LLen(LLen),
+    LRange(LRange),
+    LIndex(LIndex),
+    LSet(LSet),
+    LInsert(LInsert),
+    LRem(LRem),
+    LTrim(LTrim),
+    LPos(LPos),
+    LMove(LMove),
+    LMPop(LMPop),
+    BLPop(BLPop),
+    BRPop(BRPop),
+    BLMove(BLMove),
+    BLMPop(BLMPop),
+    SAdd(SAdd),
+    SRem(SRem),
+    SMembers(SMembers),
+    SIsMember(SIsMember),
+    SMIsMember(SMIsMember),
+    SCard(SCard),
+    SPop(SPop),
+    SRandMember(SRandMember),
+    SInter(SInter),
+    SUnion(SUnion),
+    SDiff(SDiff),
+    SInterStore(SInterStore),
+    SUnionStore(SUnionStore),
+    SDiffStore(SDiffStore),
+    SInterCard(SInterCard),
+    SMove(SMove),
+    ZAdd(ZAdd),
+    ZRem(ZRem),
+    ZScore(ZScore),
+    ZMScore(ZMScore),
+    ZIncrBy(ZIncrBy),
+    ZCard(ZCard),
+    ZCount(ZCount),
+    ZRank(ZRank),
+    ZRevRank(ZRevRank),
+    ZRange(ZRange),
+    ZUnionStore(ZUnionStore),
+    ZInterStore(ZInterStore),
+    ZDiffStore(ZDiffStore),
+    ZUnion(ZUnion),
+    ZInter(ZInter),
+    ZDiff(ZDiff),
+    ZRemRangeByScore(ZRemRangeByScore),
+    ZRemRangeByRank(ZRemRangeByRank),
+    ZRemRangeByLex(ZRemRangeByLex),
+    ZPopMin(ZPopMin),
+    ZPopMax(ZPopMax),
+    ZRandMember(ZRandMember),
+    ZRangeStore(ZRangeStore),
+    ZMPop(ZMPop),
+    BZPopMin(BZPopMin),
+    BZPopMax(BZPopMax),
+    BZMPop(BZMPop),
+    XAdd(XAdd),
+    XRange(XRange),
+    XRevRange(XRevRange),
+    XLen(XLen),
+    XTrim(XTrim),
+    XDel(XDel),
+    XRead(XRead),
+    XGroup(XGroup),
+    XReadGroup(XReadGroup),
+    XAck(XAck),
+    XPending(XPending),
+    XClaim(XClaim),
+    XAutoClaim(XAutoClaim),
+    XInfo(XInfo),
+    Expire(Expire),
+    PExpire(PExpire),
+    ExpireAt(ExpireAt),
+    PExpireAt(PExpireAt),
+    Ttl(Ttl),
+    PTtl(PTtl),
+    ExpireTime(ExpireTime),
+    PExpireTime(PExpireTime),
+    Persist(Persist),
+    Del(Del),
+    Exists(Exists),
+    Type(Type),
+    Rename(Rename),
+    RenameNx(RenameNx),
+    Copy(CopyCmd),
+    Touch(Touch),
+    Unlink(Unlink),
+    Keys(Keys),
+    Scan(Scan),
+    Command(CommandCmd),
+    Config(ConfigCmd),
+    Hello(Hello),
+    Ping(Ping),
+    Subscribe(Subscribe),
+    Unsubscribe(Unsubscribe),
+    PSubscribe(PSubscribe),
+    PUnsubscribe(PUnsubscribe),
+    Publish(Publish),
+    PubSubCmd(PubSubCmd),
+    SSubscribe(SSubscribe),
+    SUnsubscribe(SUnsubscribe),
+    SPublish(SPublish),
+    Multi(Multi),
+    Exec(Exec),
+    Discard(Discard),
 }
 
 #[derive(Debug)]
 pub struct Get {
-    key: String,
+    key: Bytes,
 }
 
 #[derive(Debug)]
 pub struct Set {
-    key: String,
-    value: RespFrame,
+    key: Bytes,
+    value: Bytes,
+    condition: SetCondition,
+    expiry: Option<SetExpireOption>,
+    get: bool,
+}
+
+/// The expiry options accepted by `SET`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SetExpireOption {
+    /// `EX seconds`
+    Ex(i64),
+    /// `PX milliseconds`
+    Px(i64),
+    /// `EXAT unix-time-seconds`
+    ExAt(i64),
+    /// `PXAT unix-time-milliseconds`
+    PxAt(i64),
+    /// `KEEPTTL`
+    KeepTtl,
 }
 
 #[derive(Debug)]
 pub struct HGet {
-    key: String,
-    field: String,
+    key: Bytes,
+    field: Bytes,
 }
 
 #[derive(Debug)]
 pub struct HSet {
-    key: String,
-    field: String,
-    value: RespFrame,
+    key: Bytes,
+    pairs: Vec<(Bytes, Bytes)>,
 }
 
 #[derive(Debug)]
 pub struct HGetAll {
-    key: String,
+    key: Bytes,
 }
 
-#[derive(Debug)]
-pub struct Unrecognized;
-
 impl TryFrom<RespFrame> for Command {
     type Error = CommandError;
 
@@ -95,15 +384,17 @@ impl TryFrom<RespFrame> for Command {
 impl TryFrom<RespArray> for Command {
     type Error = CommandError;
 
+    /// Converts a RESP array into a Command.
+    ///
+    /// The command name is looked up case-insensitively in the command table, and the
+    /// number of arguments is checked against the arity of the table entry before the
+    /// arguments are parsed.
     fn try_from(frame: RespArray) -> Result<Self, Self::Error> {
         match frame.first() {
-            Some(RespFrame::BulkString(ref cmd)) => match cmd.as_ref() {
-                b"get" => Ok(Get::try_from(frame)?.into()),
-                b"set" => Ok(Set::try_from(frame)?.into()),
-                b"hget" => Ok(HGet::try_from(frame)?.into()),
-                b"hset" => Ok(HSet::try_from(frame)?.into()),
-                b"hgetall" => Ok(HGetAll::try_from(frame)?.into()),
-                _ => Ok(Unrecognized.into()),
+            Some(RespFrame::BulkString(ref cmd)) => match lookup_command(cmd) {
+                Some(spec) if spec.check_arity(frame.len()) => (spec.parse)(frame),
+                Some(spec) => Err(CommandError::WrongArity(spec.name.to_string())),
+                None => Err(unknown_command(&frame)),
             },
             _ => Err(CommandError::InvalidCommand(
                 "Command must have a BulkString as the first argument".to_string(),
@@ -112,50 +403,270 @@ impl TryFrom<RespArray> for Command {
     }
 }
 
-impl CommandExecutor for Unrecognized {
-    fn execute(self, _backend: &Backend) -> RespFrame {
-        RESP_OK.clone()
+impl Command {
+    /// Returns `true` if a RESP2 client subscribed to channels may run the command.
+    fn allowed_when_subscribed(&self) -> bool {
+        matches!(
+            self,
+            Command::Ping(_)
+                | Command::Subscribe(_)
+                | Command::Unsubscribe(_)
+                | Command::PSubscribe(_)
+                | Command::PUnsubscribe(_)
+                | Command::SSubscribe(_)
+                | Command::SUnsubscribe(_)
+        )
+    }
+
+    /// Returns `true` if the command controls the transaction of the connection, rather than
+    /// being queued in it.
+    fn controls_transaction(&self) -> bool {
+        matches!(
+            self,
+            Command::Multi(_) | Command::Exec(_) | Command::Discard(_)
+        )
+    }
+
+    /// Runs the command on `session` if it acts on the connection, breaking with its reply,
+    /// or gives it back otherwise.
+    fn execute_session(
+        self,
+        backend: &Backend,
+        session: &mut Session,
+    ) -> ControlFlow<RespFrame, Self> {
+        match self {
+            Command::Hello(cmd) => ControlFlow::Break(cmd.execute_session(backend, session)),
+            Command::Ping(cmd) => ControlFlow::Break(cmd.execute_session(backend, session)),
+            Command::Subscribe(cmd) => ControlFlow::Break(cmd.execute_session(backend, session)),
+            Command::Unsubscribe(cmd) => ControlFlow::Break(cmd.execute_session(backend, session)),
+            Command::PSubscribe(cmd) => ControlFlow::Break(cmd.execute_session(backend, session)),
+            Command::PUnsubscribe(cmd) => ControlFlow::Break(cmd.execute_session(backend, session)),
+            Command::SSubscribe(cmd) => ControlFlow::Break(cmd.execute_session(backend, session)),
+            Command::SUnsubscribe(cmd) => ControlFlow::Break(cmd.execute_session(backend, session)),
+            Command::Multi(cmd) => ControlFlow::Break(cmd.execute_session(backend, session)),
+            Command::Exec(cmd) => ControlFlow::Break(cmd.execute_session(backend, session)),
+            Command::Discard(cmd) => ControlFlow::Break(cmd.execute_session(backend, session)),
+            command => ControlFlow::Continue(command),
+        }
+    }
+
+    /// Returns the command as a `BlockingCommand` if it may block the client.
+    fn as_blocking(&mut self) -> Option<&mut (dyn BlockingCommand + Send)> {
+        match self {
+            Command::BLPop(cmd) => Some(cmd),
+            Command::BRPop(cmd) => Some(cmd),
+            Command::BLMove(cmd) => Some(cmd),
+            Command::BLMPop(cmd) => Some(cmd),
+            Command::BZPopMin(cmd) => Some(cmd),
+            Command::BZPopMax(cmd) => Some(cmd),
+            Command::BZMPop(cmd) => Some(cmd),
+            Command::XRead(cmd) if cmd.blocks() => Some(cmd),
+            Command::XReadGroup(cmd) if cmd.blocks() => Some(cmd),
+            _ => None,
+        }
     }
 }
 
-/// Validate a RESP array as a command.
+/// A parsed command along with the keys it accesses.
 ///
-/// The RESP array must have length `names.len() + n_args`, and the first `names.len()` elements
-/// must be BulkString frames that match the given command names case-insensitively.
-///
-/// If the validation fails, a `CommandError` is returned explaining the error.
-fn validate_command(
-    value: &RespArray,
-    names: &[&'static str],
-    n_args: usize,
-) -> Result<(), CommandError> {
-    if value.len() != n_args + names.len() {
-        return Err(CommandError::InvalidArguments(format!(
-            "{} command must have exactly {} argument",
-            names.join(" "),
-            n_args
-        )));
-    }
-
-    for (i, name) in names.iter().enumerate() {
-        match value[i] {
-            RespFrame::BulkString(ref cmd) => {
-                if cmd.as_ref().to_ascii_lowercase() != name.as_bytes() {
-                    return Err(CommandError::InvalidCommand(format!(
-                        "Invalid command: expected {}, got {}",
-                        name,
-                        String::from_utf8_lossy(cmd.as_ref())
-                    )));
+/// The keys are located through the command table before the arguments are parsed. While
+/// the command runs they are locked, exclusively if the command may write, so commands
+/// touching several keys are atomic with respect to every other command. Commands scanning
+/// the keyspace, e.g. `KEYS`, lock all of it instead.
+#[derive(Debug)]
+pub struct CommandRequest {
+    /// The name of the command, as written in the command table.
+    name: &'static str,
+    command: Command,
+    scope: LockScope,
+}
+
+/// What a command locks while it runs.
+#[derive(Debug, Default)]
+struct LockScope {
+    keys: Vec<Bytes>,
+    /// Set if the command may write, which makes the locks exclusive.
+    write: bool,
+    /// Set for commands accessing keys they don't name, e.g. `KEYS`, which lock the whole
+    /// keyspace.
+    keyspace: bool,
+}
+
+impl LockScope {
+    /// Takes the locks until the returned guard is dropped.
+    fn lock<'a>(&self, backend: &'a Backend) -> KeyLockGuard<'a> {
+        match self.keyspace {
+            true => backend.lock_all(self.write),
+            false => backend.lock_keys(&self.keys, self.write),
+        }
+    }
+}
+
+impl TryFrom<RespFrame> for CommandRequest {
+    type Error = CommandError;
+
+    fn try_from(frame: RespFrame) -> Result<Self, Self::Error> {
+        let RespFrame::Array(array) = frame else {
+            return Err(CommandError::InvalidCommand(
+                "Command must be an array".to_string(),
+            ));
+        };
+        let (name, scope) = match array.first() {
+            Some(RespFrame::BulkString(name)) => match lookup_command(name) {
+                Some(spec) if spec.check_arity(array.len()) => {
+                    let keys = spec
+                        .keys(&array)
+                        .into_iter()
+                        .filter_map(|key| match key {
+                            RespFrame::BulkString(key) => Some(Bytes::copy_from_slice(key)),
+                            _ => None,
+                        })
+                        .collect::<Vec<_>>();
+                    let write = spec.has_flag(CommandFlag::Write);
+                    let scope = LockScope {
+                        keyspace: keys.is_empty()
+                            && (write || spec.has_flag(CommandFlag::ReadOnly)),
+                        keys,
+                        write,
+                    };
+                    (spec.name, scope)
                 }
+                _ => ("", LockScope::default()),
+            },
+            _ => ("", LockScope::default()),
+        };
+        let command = Command::try_from(array)?;
+        Ok(CommandRequest {
+            name,
+            command,
+            scope,
+        })
+    }
+}
+
+impl CommandRequest {
+    /// Executes the command while holding the locks of its keys.
+    pub fn execute(self, backend: &Backend) -> RespFrame {
+        let _guard = self.scope.lock(backend);
+        self.command.execute(backend)
+    }
+
+    /// Executes a command queued in a transaction on behalf of the connection, while `EXEC`
+    /// holds the locks of the whole transaction.
+    fn execute_queued(self, backend: &Backend, session: &mut Session) -> RespFrame {
+        let command = match self.command.execute_session(backend, session) {
+            ControlFlow::Break(reply) => return reply,
+            ControlFlow::Continue(command) => command,
+        };
+        let flatten = session.protocol() == Protocol::Resp2 && command.has_pair_reply();
+        let reply = command.execute(backend);
+        if flatten {
+            flatten_pairs(reply)
+        } else {
+            reply
+        }
+    }
+
+    /// Executes the command on behalf of the connection whose state is `session`, first
+    /// waiting for its keys if it is a blocking command.
+    ///
+    /// The client is registered as a waiter before the first attempt and stays registered
+    /// until the command is served or times out, so it keeps its place among the clients
+    /// blocked on the same keys. Dropping the returned future, e.g. when the client
+    /// disconnects, unregisters it.
+    ///
+    /// While the connection is in a transaction, the command is queued instead, unless it
+    /// is one of `MULTI`, `EXEC` and `DISCARD`.
+    pub async fn run(self, backend: &Backend, session: &mut Session) -> RespFrame {
+        if session.protocol() == Protocol::Resp2
+            && session.is_subscribed()
+            && !self.command.allowed_when_subscribed()
+        {
+            session.flag_transaction();
+            return CommandError::InvalidCommand(format!(
+                "Can't execute '{}': only (P|S)SUBSCRIBE / (P|S)UNSUBSCRIBE / PING / QUIT / \
+                 RESET are allowed in this context",
+                self.name
+            ))
+            .into();
+        }
+        if session.in_transaction() && !self.command.controls_transaction() {
+            return session.queue(self);
+        }
+        let mut command = match self.command.execute_session(backend, session) {
+            ControlFlow::Break(reply) => return reply,
+            ControlFlow::Continue(command) => command,
+        };
+        let Some(blocking) = command.as_blocking() else {
+            let flatten = session.protocol() == Protocol::Resp2 && command.has_pair_reply();
+            let reply = {
+                let _guard = self.scope.lock(backend);
+                command.execute(backend)
+            };
+            return if flatten { flatten_pairs(reply) } else { reply };
+        };
+        let waiter = backend.wait_for_keys(blocking.wait_keys());
+        {
+            let _guard = self.scope.lock(backend);
+            blocking.prepare(backend);
+        }
+        let deadline = blocking
+            .timeout()
+            .and_then(|timeout| Instant::now().checked_add(timeout));
+        loop {
+            let reply = {
+                let _guard = self.scope.lock(backend);
+                blocking.try_execute(backend)
+            };
+            if let Some(reply) = reply {
+                return reply;
             }
-            _ => {
-                return Err(CommandError::InvalidCommand(
-                    "Command must have a BulkString as the first argument".to_string(),
-                ))
+            match deadline {
+                Some(deadline) => {
+                    if timeout_at(deadline, waiter.ready()).await.is_err() {
+                        return blocking.timeout_reply();
+                    }
+                }
+                None => waiter.ready().await,
             }
         }
     }
-    Ok(())
+}
+
+/// Flattens an array of pairs into the array of their elements, as sent to RESP2 clients.
+fn flatten_pairs(frame: RespFrame) -> RespFrame {
+    match frame {
+        RespFrame::Array(array) => RespArray::new(
+            array
+                .0
+                .into_iter()
+                .flat_map(|pair| match pair {
+                    RespFrame::Array(pair) => pair.0,
+                    frame => vec![frame],
+                })
+                .collect::<Vec<_>>(),
+        )
+        .into(),
+        frame => frame,
+    }
+}
+
+/// Build the error returned for a command name that is not supported.
+///
+/// Like Redis, the message quotes the command name and the beginning of its arguments,
+/// each truncated to 128 bytes.
+fn unknown_command(frame: &RespArray) -> CommandError {
+    let quote = |frame: &RespFrame| match frame {
+        RespFrame::BulkString(s) => String::from_utf8_lossy(&s[..s.len().min(128)]).into_owned(),
+        _ => String::new(),
+    };
+    let name = frame.first().map(quote).unwrap_or_default();
+    let args = frame
+        .iter()
+        .skip(1)
+        .map(|arg| format!("'{}' ", quote(arg)))
+        .collect();
+    CommandError::UnknownCommand { name, args }
 }
 
 /// Extract arguments from a RESP array.
@@ -172,6 +683,102 @@ fn extract_args(value: RespArray, start: usize) -> Result<Vec<RespFrame>, Comman
     Ok(value.0.into_iter().skip(start).collect::<Vec<RespFrame>>())
 }
 
+/// Reply with a BulkString holding the value, or a null reply if there is none.
+fn optional_bulk_string(value: Option<Bytes>) -> RespFrame {
+    match value {
+        Some(value) => BulkString::new(value).into(),
+        None => RespFrame::Null(RespNull),
+    }
+}
+
+/// Publishes the keyspace event of a command that removed elements from `key`, followed by a
+/// `del` event if the key was left empty and removed along with them.
+fn notify_removed(backend: &Backend, class: EventClass, event: &str, key: &[u8]) {
+    backend.notify_keyspace_event(class, event, key);
+    if backend.notifies(EventClass::Generic) && !backend.contains_key(key) {
+        backend.notify_keyspace_event(EventClass::Generic, "del", key);
+    }
+}
+
+/// Runs `store`, which stores the result of a command at `destination` and returns its
+/// length. Publishes `event` if the result was stored, or `del` if it was empty and removed
+/// the key that was there.
+fn notify_store<E>(
+    backend: &Backend,
+    class: EventClass,
+    event: &str,
+    destination: &[u8],
+    store: impl FnOnce() -> Result<usize, E>,
+) -> Result<usize, E> {
+    let existed = backend.notifies(EventClass::Generic) && backend.contains_key(destination);
+    let ret = store();
+    match ret {
+        Ok(0) if existed => backend.notify_keyspace_event(EventClass::Generic, "del", destination),
+        Ok(0) | Err(_) => {}
+        Ok(_) => backend.notify_keyspace_event(class, event, destination),
+    }
+    ret
+}
+
+/// Publishes the events of a string written by `SET` and its variants: `set`, followed by
+/// `expire` if the write gave the key an expiry.
+fn notify_set(backend: &Backend, key: &[u8], expiry: SetExpiry) {
+    backend.notify_keyspace_event(EventClass::String, "set", key);
+    if let SetExpiry::At(_) = expiry {
+        backend.notify_keyspace_event(EventClass::Generic, "expire", key);
+    }
+}
+
+/// Parses a command whose only argument is a key.
+fn parse_key_arg(value: RespArray) -> Result<Bytes, CommandError> {
+    let mut args = extract_args(value, 1)?.into_iter();
+    match args.next() {
+        Some(RespFrame::BulkString(key)) => Ok(Bytes::from(key.0)),
+        _ => Err(CommandError::InvalidArguments("Invalid key".to_string())),
+    }
+}
+
+/// Parses a command whose arguments are all keys.
+fn parse_keys(value: RespArray) -> Result<Vec<Bytes>, CommandError> {
+    extract_args(value, 1)?
+        .into_iter()
+        .map(|arg| match arg {
+            RespFrame::BulkString(key) => Ok(Bytes::from(key.0)),
+            _ => Err(CommandError::InvalidArguments("Invalid key".to_string())),
+        })
+        .collect()
+}
+
+/// Parse an argument as a finite floating point number.
+fn parse_float(frame: RespFrame) -> Result<f64, CommandError> {
+    let value = match frame {
+        RespFrame::BulkString(s) => std::str::from_utf8(&s)
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .ok_or(CommandError::NotAFloat)?,
+        RespFrame::Double(d) => d,
+        _ => return Err(CommandError::NotAFloat),
+    };
+    if value.is_finite() {
+        Ok(value)
+    } else {
+        Err(CommandError::NotAFloat)
+    }
+}
+
+/// Parse an argument as a signed 64 bit integer.
+///
+/// Redis sends every argument as a BulkString, the integer is parsed from its text. Anything
+/// else, or text that is not an `i64` written the way Redis writes it, is a
+/// `CommandError::NotAnInteger`.
+fn parse_integer(frame: RespFrame) -> Result<i64, CommandError> {
+    match frame {
+        RespFrame::BulkString(s) => parse_i64(&s).ok_or(CommandError::NotAnInteger),
+        RespFrame::Integer(i) => Ok(i),
+        _ => Err(CommandError::NotAnInteger),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -198,6 +805,64 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_command_error_reply() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*1\r\n$3\r\nget\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let err = Command::try_from(frame).unwrap_err();
+        assert_eq!(
+            RespFrame::from(err),
+            SimpleError::new("ERR wrong number of arguments for 'get' command").into()
+        );
+        assert_eq!(
+            RespFrame::from(CommandError::WrongType),
+            SimpleError::new("WRONGTYPE Operation against a key holding the wrong kind of value")
+                .into()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_command_dispatch_is_case_insensitive() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*2\r\n$3\r\nGeT\r\n$5\r\nhello\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: Command = frame.try_into()?;
+        assert!(matches!(cmd, Command::Get(_)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_unknown_command() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$3\r\nxyz\r\n$1\r\na\r\n$1\r\nb\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let err = Command::try_from(frame).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "ERR unknown command 'xyz', with args beginning with: 'a' 'b' "
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_integer() {
+        assert_eq!(
+            parse_integer(RespFrame::BulkString("-42".into())).ok(),
+            Some(-42)
+        );
+        assert!(matches!(
+            parse_integer(RespFrame::BulkString("4x".into())),
+            Err(CommandError::NotAnInteger)
+        ));
+        assert!(parse_integer(RespFrame::BulkString("+4".into())).is_err());
+        assert_eq!(
+            parse_integer(RespFrame::BulkString("-9223372036854775808".into())).ok(),
+            Some(i64::MIN)
+        );
+    }
+
     #[test]
     fn test_command() -> Result<()> {
         let mut buf = BytesMut::new();
@@ -209,4 +874,125 @@ mod tests {
         assert_eq!(ret, RespFrame::Null(RespNull));
         Ok(())
     }
+
+    /// Runs each command and returns the keyspace events it published, as event and key.
+    fn keyspace_events(backend: &Backend, commands: &[&[&str]]) -> Vec<(String, String)> {
+        let (sender, mut messages) = tokio::sync::mpsc::unbounded_channel();
+        let subscriber = crate::Subscriber { id: 0, sender };
+        backend.psubscribe("__keyevent@0__:*".into(), &subscriber);
+        for args in commands {
+            let frame = RespArray::new(
+                args.iter()
+                    .map(|arg| BulkString::new(*arg).into())
+                    .collect::<Vec<RespFrame>>(),
+            );
+            Command::try_from(frame).unwrap().execute(backend);
+        }
+        backend.punsubscribe(b"__keyevent@0__:*", 0);
+        let mut events = Vec::new();
+        while let Ok(RespFrame::Push(push)) = messages.try_recv() {
+            let text = |frame: &RespFrame| match frame {
+                RespFrame::BulkString(s) => String::from_utf8_lossy(&s.0).to_string(),
+                _ => panic!("expected a bulk string"),
+            };
+            let channel = text(&push[2]);
+            let event = channel.trim_start_matches("__keyevent@0__:").to_string();
+            events.push((event, text(&push[3])));
+        }
+        events
+    }
+
+    fn events(expected: &[(&str, &str)]) -> Vec<(String, String)> {
+        expected
+            .iter()
+            .map(|(event, key)| (event.to_string(), key.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_keyspace_events() {
+        let backend = Backend::new();
+        assert!(keyspace_events(&backend, &[&["set", "k", "v"]]).is_empty());
+
+        backend
+            .config_set(&[(b"notify-keyspace-events".to_vec(), b"EA".to_vec())])
+            .unwrap();
+        let ret = keyspace_events(
+            &backend,
+            &[
+                &["set", "k", "v", "ex", "100"],
+                &["set", "k", "v", "nx"],
+                &["incr", "n"],
+                &["rename", "k", "k2"],
+                &["rpush", "l", "a"],
+                &["lmove", "l", "l2", "left", "right"],
+                &["hset", "h", "f", "v"],
+                &["hdel", "h", "missing"],
+                &["sadd", "s", "a", "b"],
+                &["sinterstore", "s2", "s", "missing"],
+                &["zadd", "z", "1", "a"],
+                &["zadd", "z", "2", "a"],
+                &["zpopmin", "z"],
+                &["xadd", "x", "maxlen", "1", "*", "f", "v"],
+                &["xadd", "x", "maxlen", "1", "*", "f", "v"],
+                &["xgroup", "create", "x", "g", "0"],
+                &["xreadgroup", "group", "g", "c", "streams", "x", ">"],
+                &["expire", "n", "0"],
+                &["del", "h", "missing"],
+            ],
+        );
+        assert_eq!(
+            ret,
+            events(&[
+                ("set", "k"),
+                ("expire", "k"),
+                ("incrby", "n"),
+                ("rename_from", "k"),
+                ("rename_to", "k2"),
+                ("rpush", "l"),
+                ("rpush", "l2"),
+                ("lpop", "l"),
+                ("del", "l"),
+                ("hset", "h"),
+                ("sadd", "s"),
+                ("zadd", "z"),
+                ("zadd", "z"),
+                ("zpopmin", "z"),
+                ("del", "z"),
+                ("xadd", "x"),
+                ("xadd", "x"),
+                ("xtrim", "x"),
+                ("xgroup-create", "x"),
+                ("xgroup-createconsumer", "x"),
+                ("del", "n"),
+                ("del", "h"),
+            ])
+        );
+
+        // the key was there, so storing an empty result removes it
+        backend
+            .config_set(&[(b"notify-keyspace-events".to_vec(), b"Egsxnm".to_vec())])
+            .unwrap();
+        let ret = keyspace_events(
+            &backend,
+            &[
+                &["sinterstore", "s", "missing"],
+                &["set", "new", "v", "px", "1"],
+                &["get", "absent"],
+            ],
+        );
+        assert_eq!(
+            ret,
+            events(&[
+                ("keymiss", "missing"),
+                ("del", "s"),
+                ("new", "new"),
+                ("expire", "new"),
+                ("keymiss", "absent"),
+            ])
+        );
+        std::thread::sleep(Duration::from_millis(5));
+        let ret = keyspace_events(&backend, &[&["get", "new"]]);
+        assert_eq!(ret, events(&[("expired", "new"), ("keymiss", "new")]));
+    }
 }
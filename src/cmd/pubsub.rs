@@ -0,0 +1,772 @@
+use super::list::bulk_string_args;
+use super::{optional_bulk_string, CommandError, CommandExecutor, Session, SessionCommand};
+use crate::slot::key_slot;
+use crate::{Backend, BulkString, RespArray, RespFrame, RespNull, RespPush, Subscriber};
+use bytes::Bytes;
+use std::collections::BTreeSet;
+use tokio::sync::mpsc;
+
+/// The channels and patterns a connection is subscribed to, and the queue of the messages
+/// delivered to it.
+#[derive(Debug)]
+pub(super) struct Subscriptions {
+    channels: BTreeSet<Bytes>,
+    patterns: BTreeSet<Bytes>,
+    shard_channels: BTreeSet<Bytes>,
+    subscriber: Subscriber,
+    messages: mpsc::UnboundedReceiver<RespFrame>,
+    /// The confirmations of subscription changes to send before the reply of the command.
+    pushes: Vec<RespFrame>,
+}
+
+/// `SUBSCRIBE channel [channel ...]`
+#[derive(Debug)]
+pub struct Subscribe {
+    channels: Vec<Bytes>,
+}
+
+/// `UNSUBSCRIBE [channel [channel ...]]`
+#[derive(Debug)]
+pub struct Unsubscribe {
+    channels: Vec<Bytes>,
+}
+
+/// `PSUBSCRIBE pattern [pattern ...]`
+#[derive(Debug)]
+pub struct PSubscribe {
+    patterns: Vec<Bytes>,
+}
+
+/// `PUNSUBSCRIBE [pattern [pattern ...]]`
+#[derive(Debug)]
+pub struct PUnsubscribe {
+    patterns: Vec<Bytes>,
+}
+
+/// `PUBLISH channel message`
+#[derive(Debug)]
+pub struct Publish {
+    channel: Bytes,
+    message: Bytes,
+}
+
+/// `SSUBSCRIBE shardchannel [shardchannel ...]`
+#[derive(Debug)]
+pub struct SSubscribe {
+    channels: Vec<Bytes>,
+}
+
+/// `SUNSUBSCRIBE [shardchannel [shardchannel ...]]`
+#[derive(Debug)]
+pub struct SUnsubscribe {
+    channels: Vec<Bytes>,
+}
+
+/// `SPUBLISH shardchannel message`
+#[derive(Debug)]
+pub struct SPublish {
+    channel: Bytes,
+    message: Bytes,
+}
+
+/// The `PUBSUB` command and its subcommands.
+#[derive(Debug)]
+pub struct PubSubCmd {
+    subcommand: PubSubSubcommand,
+}
+
+#[derive(Debug, PartialEq)]
+enum PubSubSubcommand {
+    /// `PUBSUB CHANNELS [pattern]`
+    Channels(Option<Bytes>),
+    /// `PUBSUB NUMSUB [channel [channel ...]]`
+    NumSub(Vec<Bytes>),
+    /// `PUBSUB NUMPAT`
+    NumPat,
+    /// `PUBSUB SHARDCHANNELS [pattern]`
+    ShardChannels(Option<Bytes>),
+    /// `PUBSUB SHARDNUMSUB [shardchannel [shardchannel ...]]`
+    ShardNumSub(Vec<Bytes>),
+}
+
+impl Subscriptions {
+    pub(super) fn new(id: u64) -> Self {
+        let (sender, messages) = mpsc::unbounded_channel();
+        Self {
+            channels: BTreeSet::new(),
+            patterns: BTreeSet::new(),
+            shard_channels: BTreeSet::new(),
+            subscriber: Subscriber { id, sender },
+            messages,
+            pushes: Vec::new(),
+        }
+    }
+
+    /// Returns the number of channels and patterns subscribed to.
+    fn count(&self) -> usize {
+        self.channels.len() + self.patterns.len()
+    }
+
+    /// Returns the number of shard channels subscribed to, counted apart from the others.
+    fn shard_count(&self) -> usize {
+        self.shard_channels.len()
+    }
+
+    /// Queues all the confirmations but the last one, which is returned as the reply of
+    /// the command.
+    fn reply(&mut self, mut confirmations: Vec<RespFrame>) -> RespFrame {
+        let reply = confirmations.pop().unwrap_or(RespFrame::Null(RespNull));
+        self.pushes.extend(confirmations);
+        reply
+    }
+}
+
+impl Session {
+    /// Returns `true` if the connection is subscribed to a channel, a pattern or a shard
+    /// channel.
+    pub fn is_subscribed(&self) -> bool {
+        self.subscriptions.count() + self.subscriptions.shard_count() > 0
+    }
+
+    /// Waits for the next message published to a channel the connection is subscribed to.
+    pub async fn next_message(&mut self) -> Option<RespFrame> {
+        self.subscriptions.messages.recv().await
+    }
+
+    /// Takes the confirmations of the subscription changes made by the last command, to be
+    /// sent before its reply.
+    pub fn take_pushes(&mut self) -> Vec<RespFrame> {
+        std::mem::take(&mut self.subscriptions.pushes)
+    }
+
+    /// Unsubscribes the connection from every channel, pattern and shard channel, e.g. once
+    /// it is closed.
+    pub fn unsubscribe_all(&mut self, backend: &Backend) {
+        let id = self.id();
+        for channel in std::mem::take(&mut self.subscriptions.channels) {
+            backend.unsubscribe(&channel, id);
+        }
+        for pattern in std::mem::take(&mut self.subscriptions.patterns) {
+            backend.punsubscribe(&pattern, id);
+        }
+        for channel in std::mem::take(&mut self.subscriptions.shard_channels) {
+            backend.sunsubscribe(&channel, id);
+        }
+    }
+}
+
+/// Builds the confirmation of a subscription change: its kind, the channel or pattern, and
+/// the number of subscriptions the connection has left.
+fn confirmation(kind: &str, name: Option<Bytes>, count: usize) -> RespFrame {
+    RespPush::new([
+        BulkString::new(kind).into(),
+        optional_bulk_string(name),
+        RespFrame::Integer(count as i64),
+    ])
+    .into()
+}
+
+/// Checks that the shard channels all hash to the same slot, as the shard owning it is the
+/// one serving the command.
+fn check_same_slot(channels: &[Bytes]) -> Result<(), CommandError> {
+    let mut slots = channels.iter().map(|channel| key_slot(channel));
+    match slots.next() {
+        Some(slot) if slots.any(|other| other != slot) => Err(CommandError::CrossSlot),
+        _ => Ok(()),
+    }
+}
+
+/// Runs a subscription command on a session of its own, which is unsubscribed right away
+/// since there is no connection to deliver the messages to.
+fn execute_detached(cmd: impl SessionCommand, backend: &Backend) -> RespFrame {
+    let mut session = Session::new();
+    let reply = cmd.execute_session(backend, &mut session);
+    session.unsubscribe_all(backend);
+    reply
+}
+
+impl SessionCommand for Subscribe {
+    /// Confirms each subscription, the last confirmation being the reply.
+    fn execute_session(self, backend: &Backend, session: &mut Session) -> RespFrame {
+        let subscriptions = &mut session.subscriptions;
+        let confirmations = self
+            .channels
+            .into_iter()
+            .map(|channel| {
+                if subscriptions.channels.insert(channel.clone()) {
+                    backend.subscribe(channel.clone(), &subscriptions.subscriber);
+                }
+                confirmation("subscribe", Some(channel), subscriptions.count())
+            })
+            .collect();
+        subscriptions.reply(confirmations)
+    }
+}
+
+impl SessionCommand for Unsubscribe {
+    /// Confirms each unsubscription, from every channel if none is given, the last
+    /// confirmation being the reply.
+    fn execute_session(self, backend: &Backend, session: &mut Session) -> RespFrame {
+        let subscriptions = &mut session.subscriptions;
+        let channels = match self.channels.is_empty() {
+            true => subscriptions.channels.iter().cloned().collect(),
+            false => self.channels,
+        };
+        if channels.is_empty() {
+            let reply = confirmation("unsubscribe", None, subscriptions.count());
+            return subscriptions.reply(vec![reply]);
+        }
+        let confirmations = channels
+            .into_iter()
+            .map(|channel| {
+                if subscriptions.channels.remove(&channel) {
+                    backend.unsubscribe(&channel, subscriptions.subscriber.id);
+                }
+                confirmation("unsubscribe", Some(channel), subscriptions.count())
+            })
+            .collect();
+        subscriptions.reply(confirmations)
+    }
+}
+
+impl SessionCommand for PSubscribe {
+    /// Confirms each subscription, the last confirmation being the reply.
+    fn execute_session(self, backend: &Backend, session: &mut Session) -> RespFrame {
+        let subscriptions = &mut session.subscriptions;
+        let confirmations = self
+            .patterns
+            .into_iter()
+            .map(|pattern| {
+                if subscriptions.patterns.insert(pattern.clone()) {
+                    backend.psubscribe(pattern.clone(), &subscriptions.subscriber);
+                }
+                confirmation("psubscribe", Some(pattern), subscriptions.count())
+            })
+            .collect();
+        subscriptions.reply(confirmations)
+    }
+}
+
+impl SessionCommand for PUnsubscribe {
+    /// Confirms each unsubscription, from every pattern if none is given, the last
+    /// confirmation being the reply.
+    fn execute_session(self, backend: &Backend, session: &mut Session) -> RespFrame {
+        let subscriptions = &mut session.subscriptions;
+        let patterns = match self.patterns.is_empty() {
+            true => subscriptions.patterns.iter().cloned().collect(),
+            false => self.patterns,
+        };
+        if patterns.is_empty() {
+            let reply = confirmation("punsubscribe", None, subscriptions.count());
+            return subscriptions.reply(vec![reply]);
+        }
+        let confirmations = patterns
+            .into_iter()
+            .map(|pattern| {
+                if subscriptions.patterns.remove(&pattern) {
+                    backend.punsubscribe(&pattern, subscriptions.subscriber.id);
+                }
+                confirmation("punsubscribe", Some(pattern), subscriptions.count())
+            })
+            .collect();
+        subscriptions.reply(confirmations)
+    }
+}
+
+impl SessionCommand for SSubscribe {
+    /// Confirms each subscription, the last confirmation being the reply.
+    fn execute_session(self, backend: &Backend, session: &mut Session) -> RespFrame {
+        let subscriptions = &mut session.subscriptions;
+        let confirmations = self
+            .channels
+            .into_iter()
+            .map(|channel| {
+                if subscriptions.shard_channels.insert(channel.clone()) {
+                    backend.ssubscribe(channel.clone(), &subscriptions.subscriber);
+                }
+                confirmation("ssubscribe", Some(channel), subscriptions.shard_count())
+            })
+            .collect();
+        subscriptions.reply(confirmations)
+    }
+}
+
+impl SessionCommand for SUnsubscribe {
+    /// Confirms each unsubscription, from every shard channel if none is given, the last
+    /// confirmation being the reply.
+    fn execute_session(self, backend: &Backend, session: &mut Session) -> RespFrame {
+        let subscriptions = &mut session.subscriptions;
+        let channels = match self.channels.is_empty() {
+            true => subscriptions.shard_channels.iter().cloned().collect(),
+            false => self.channels,
+        };
+        if channels.is_empty() {
+            let reply = confirmation("sunsubscribe", None, subscriptions.shard_count());
+            return subscriptions.reply(vec![reply]);
+        }
+        let confirmations = channels
+            .into_iter()
+            .map(|channel| {
+                if subscriptions.shard_channels.remove(&channel) {
+                    backend.sunsubscribe(&channel, subscriptions.subscriber.id);
+                }
+                confirmation("sunsubscribe", Some(channel), subscriptions.shard_count())
+            })
+            .collect();
+        subscriptions.reply(confirmations)
+    }
+}
+
+impl CommandExecutor for Subscribe {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        execute_detached(self, backend)
+    }
+}
+
+impl CommandExecutor for Unsubscribe {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        execute_detached(self, backend)
+    }
+}
+
+impl CommandExecutor for PSubscribe {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        execute_detached(self, backend)
+    }
+}
+
+impl CommandExecutor for PUnsubscribe {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        execute_detached(self, backend)
+    }
+}
+
+impl CommandExecutor for SSubscribe {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        execute_detached(self, backend)
+    }
+}
+
+impl CommandExecutor for SUnsubscribe {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        execute_detached(self, backend)
+    }
+}
+
+impl CommandExecutor for Publish {
+    /// Returns the number of clients the message was delivered to.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.publish(&self.channel, &self.message) as i64)
+    }
+}
+
+impl CommandExecutor for SPublish {
+    /// Returns the number of clients the message was delivered to.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.spublish(&self.channel, &self.message) as i64)
+    }
+}
+
+/// Replies with the channels as an array.
+fn channels_reply(channels: Vec<Bytes>) -> RespFrame {
+    RespArray::new(
+        channels
+            .into_iter()
+            .map(|channel| BulkString::new(channel).into())
+            .collect::<Vec<RespFrame>>(),
+    )
+    .into()
+}
+
+/// Replies with each channel followed by its number of subscribers, in the order of the
+/// arguments and repeating the channels given more than once, as Redis does.
+fn numsub_reply(channels: Vec<Bytes>, numsub: impl Fn(&[u8]) -> usize) -> RespFrame {
+    RespArray::new(
+        channels
+            .into_iter()
+            .flat_map(|channel| {
+                let count = numsub(&channel);
+                [
+                    BulkString::new(channel).into(),
+                    RespFrame::Integer(count as i64),
+                ]
+            })
+            .collect::<Vec<RespFrame>>(),
+    )
+    .into()
+}
+
+impl CommandExecutor for PubSubCmd {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match self.subcommand {
+            PubSubSubcommand::Channels(pattern) => {
+                channels_reply(backend.pubsub_channels(pattern.as_deref()))
+            }
+            PubSubSubcommand::NumSub(channels) => {
+                numsub_reply(channels, |channel| backend.pubsub_numsub(channel))
+            }
+            PubSubSubcommand::NumPat => RespFrame::Integer(backend.pubsub_numpat() as i64),
+            PubSubSubcommand::ShardChannels(pattern) => {
+                channels_reply(backend.pubsub_shardchannels(pattern.as_deref()))
+            }
+            PubSubSubcommand::ShardNumSub(channels) => {
+                numsub_reply(channels, |channel| backend.pubsub_shardnumsub(channel))
+            }
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Subscribe {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(Subscribe {
+            channels: bulk_string_args(value)?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for Unsubscribe {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(Unsubscribe {
+            channels: bulk_string_args(value)?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for PSubscribe {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(PSubscribe {
+            patterns: bulk_string_args(value)?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for PUnsubscribe {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(PUnsubscribe {
+            patterns: bulk_string_args(value)?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for Publish {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        match <[Bytes; 2]>::try_from(bulk_string_args(value)?) {
+            Ok([channel, message]) => Ok(Publish { channel, message }),
+            Err(_) => Err(CommandError::WrongArity("publish".to_string())),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for SSubscribe {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let channels = bulk_string_args(value)?;
+        check_same_slot(&channels)?;
+        Ok(SSubscribe { channels })
+    }
+}
+
+impl TryFrom<RespArray> for SUnsubscribe {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let channels = bulk_string_args(value)?;
+        check_same_slot(&channels)?;
+        Ok(SUnsubscribe { channels })
+    }
+}
+
+impl TryFrom<RespArray> for SPublish {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        match <[Bytes; 2]>::try_from(bulk_string_args(value)?) {
+            Ok([channel, message]) => Ok(SPublish { channel, message }),
+            Err(_) => Err(CommandError::WrongArity("spublish".to_string())),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for PubSubCmd {
+    type Error = CommandError;
+    /// Converts a RESP array into a `PubSubCmd` command.
+    ///
+    /// The first argument selects the subcommand: `CHANNELS`, `NUMSUB`, `NUMPAT`,
+    /// `SHARDCHANNELS` or `SHARDNUMSUB`.
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = bulk_string_args(value)?;
+        if args.is_empty() {
+            return Err(CommandError::WrongArity("pubsub".to_string()));
+        }
+        let name = args.remove(0).to_ascii_lowercase();
+        let subcommand = match (name.as_slice(), args.len()) {
+            (b"channels", 0 | 1) => PubSubSubcommand::Channels(args.pop()),
+            (b"numsub", _) => PubSubSubcommand::NumSub(args),
+            (b"numpat", 0) => PubSubSubcommand::NumPat,
+            (b"shardchannels", 0 | 1) => PubSubSubcommand::ShardChannels(args.pop()),
+            (b"shardnumsub", _) => PubSubSubcommand::ShardNumSub(args),
+            (b"channels" | b"numpat" | b"shardchannels", _) => {
+                return Err(CommandError::WrongArity(format!(
+                    "pubsub|{}",
+                    String::from_utf8_lossy(&name)
+                )))
+            }
+            _ => {
+                return Err(CommandError::InvalidArguments(format!(
+                    "unknown subcommand '{}'. Try PUBSUB HELP.",
+                    String::from_utf8_lossy(&name)
+                )))
+            }
+        };
+        Ok(PubSubCmd { subcommand })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::{CommandRequest, Protocol};
+
+    fn request(args: &[&str]) -> CommandRequest {
+        let frame = RespArray::new(
+            args.iter()
+                .map(|arg| BulkString::new(*arg).into())
+                .collect::<Vec<RespFrame>>(),
+        );
+        CommandRequest::try_from(RespFrame::Array(frame)).unwrap()
+    }
+
+    fn confirmation_of(kind: &str, name: &str, count: i64) -> RespFrame {
+        confirmation(kind, Some(Bytes::from(name.to_string())), count as usize)
+    }
+
+    #[tokio::test]
+    async fn test_subscriptions() {
+        let backend = Backend::new();
+        let mut session = Session::new();
+        let ret = request(&["subscribe", "a", "b", "a"])
+            .run(&backend, &mut session)
+            .await;
+        assert_eq!(ret, confirmation_of("subscribe", "a", 2));
+        assert_eq!(
+            session.take_pushes(),
+            vec![
+                confirmation_of("subscribe", "a", 1),
+                confirmation_of("subscribe", "b", 2),
+            ]
+        );
+        let ret = request(&["psubscribe", "a*"])
+            .run(&backend, &mut session)
+            .await;
+        assert_eq!(ret, confirmation_of("psubscribe", "a*", 3));
+
+        let ret = request(&["publish", "a", "hi"]).execute(&backend);
+        assert_eq!(ret, RespFrame::Integer(2));
+        let message = session.next_message().await.unwrap();
+        assert_eq!(
+            message,
+            RespPush::new([
+                BulkString::new("message").into(),
+                BulkString::new("a").into(),
+                BulkString::new("hi").into(),
+            ])
+            .into()
+        );
+
+        let ret = request(&["unsubscribe"]).run(&backend, &mut session).await;
+        assert_eq!(ret, confirmation_of("unsubscribe", "b", 1));
+        assert_eq!(
+            session.take_pushes(),
+            vec![confirmation_of("unsubscribe", "a", 2)]
+        );
+        let ret = request(&["unsubscribe"]).run(&backend, &mut session).await;
+        assert_eq!(ret, confirmation("unsubscribe", None, 1));
+        assert_eq!(backend.pubsub_numpat(), 1);
+        session.unsubscribe_all(&backend);
+        assert!(!session.is_subscribed());
+        assert_eq!(backend.pubsub_numpat(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_subscribed_mode_in_resp2() {
+        let backend = Backend::new();
+        let mut session = Session::new();
+        request(&["subscribe", "a"])
+            .run(&backend, &mut session)
+            .await;
+        let ret = request(&["get", "key"]).run(&backend, &mut session).await;
+        assert_eq!(
+            ret,
+            CommandError::InvalidCommand(
+                "Can't execute 'get': only (P|S)SUBSCRIBE / (P|S)UNSUBSCRIBE / PING / QUIT / \
+                 RESET are allowed in this context"
+                    .to_string()
+            )
+            .into()
+        );
+        let ret = request(&["ping"]).run(&backend, &mut session).await;
+        assert_eq!(
+            ret,
+            RespArray::new([BulkString::new("pong").into(), BulkString::new("").into()]).into()
+        );
+
+        // RESP3 clients can run any command
+        let mut session = Session::new();
+        request(&["hello", "3"]).run(&backend, &mut session).await;
+        request(&["subscribe", "a"])
+            .run(&backend, &mut session)
+            .await;
+        let ret = request(&["get", "key"]).run(&backend, &mut session).await;
+        assert_eq!(ret, RespFrame::Null(RespNull));
+        assert_eq!(session.protocol(), Protocol::Resp3);
+        let ret = request(&["ping", "hi"]).run(&backend, &mut session).await;
+        assert_eq!(ret, BulkString::new("hi").into());
+    }
+
+    #[test]
+    fn test_pubsub() {
+        let backend = Backend::new();
+        // without a connection, the subscriptions are dropped right away
+        let ret = request(&["subscribe", "a"]).execute(&backend);
+        assert_eq!(ret, confirmation_of("subscribe", "a", 1));
+        assert!(backend.pubsub_channels(None).is_empty());
+
+        let mut session = Session::new();
+        Subscribe::try_from(RespArray::new([
+            BulkString::new("subscribe").into(),
+            BulkString::new("news").into(),
+            BulkString::new("sport").into(),
+        ]))
+        .unwrap()
+        .execute_session(&backend, &mut session);
+        let ret = request(&["pubsub", "channels", "n*"]).execute(&backend);
+        assert_eq!(ret, RespArray::new([BulkString::new("news").into()]).into());
+        let ret = request(&["pubsub", "numsub", "news", "other"]).execute(&backend);
+        assert_eq!(
+            ret,
+            RespArray::new([
+                BulkString::new("news").into(),
+                RespFrame::Integer(1),
+                BulkString::new("other").into(),
+                RespFrame::Integer(0),
+            ])
+            .into()
+        );
+        // the channels keep the order of the arguments, duplicates included
+        let ret = request(&["pubsub", "numsub", "sport", "news", "sport"]).execute(&backend);
+        assert_eq!(
+            ret,
+            RespArray::new([
+                BulkString::new("sport").into(),
+                RespFrame::Integer(1),
+                BulkString::new("news").into(),
+                RespFrame::Integer(1),
+                BulkString::new("sport").into(),
+                RespFrame::Integer(1),
+            ])
+            .into()
+        );
+        let ret = request(&["pubsub", "numpat"]).execute(&backend);
+        assert_eq!(ret, RespFrame::Integer(0));
+
+        let err = PubSubCmd::try_from(RespArray::new([
+            BulkString::new("pubsub").into(),
+            BulkString::new("numpat").into(),
+            BulkString::new("x").into(),
+        ]))
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "ERR wrong number of arguments for 'pubsub|numpat' command"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_shard_subscriptions() {
+        let backend = Backend::new();
+        let mut session = Session::new();
+        request(&["subscribe", "a"])
+            .run(&backend, &mut session)
+            .await;
+        let ret = request(&["ssubscribe", "{a}1", "{a}2"])
+            .run(&backend, &mut session)
+            .await;
+        // shard channels are counted apart from the other subscriptions
+        assert_eq!(ret, confirmation_of("ssubscribe", "{a}2", 2));
+        assert_eq!(
+            session.take_pushes(),
+            vec![confirmation_of("ssubscribe", "{a}1", 1)]
+        );
+        let err = SSubscribe::try_from(RespArray::new([
+            BulkString::new("ssubscribe").into(),
+            BulkString::new("a").into(),
+            BulkString::new("b").into(),
+        ]))
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "CROSSSLOT Keys in request don't hash to the same slot"
+        );
+
+        let ret = request(&["spublish", "{a}1", "hi"]).execute(&backend);
+        assert_eq!(ret, RespFrame::Integer(1));
+        assert_eq!(
+            session.next_message().await.unwrap(),
+            RespPush::new([
+                BulkString::new("smessage").into(),
+                BulkString::new("{a}1").into(),
+                BulkString::new("hi").into(),
+            ])
+            .into()
+        );
+        let ret = request(&["publish", "{a}1", "hi"]).execute(&backend);
+        assert_eq!(ret, RespFrame::Integer(0));
+
+        let ret = request(&["pubsub", "shardchannels"]).execute(&backend);
+        assert_eq!(
+            ret,
+            RespArray::new([
+                BulkString::new("{a}1").into(),
+                BulkString::new("{a}2").into(),
+            ])
+            .into()
+        );
+        let ret = request(&["pubsub", "shardnumsub", "{a}2", "a", "{a}2"]).execute(&backend);
+        assert_eq!(
+            ret,
+            RespArray::new([
+                BulkString::new("{a}2").into(),
+                RespFrame::Integer(1),
+                BulkString::new("a").into(),
+                RespFrame::Integer(0),
+                BulkString::new("{a}2").into(),
+                RespFrame::Integer(1),
+            ])
+            .into()
+        );
+
+        let ret = request(&["sunsubscribe"]).run(&backend, &mut session).await;
+        assert_eq!(ret, confirmation_of("sunsubscribe", "{a}2", 0));
+        assert_eq!(
+            session.take_pushes(),
+            vec![confirmation_of("sunsubscribe", "{a}1", 1)]
+        );
+        let ret = request(&["sunsubscribe"]).run(&backend, &mut session).await;
+        assert_eq!(ret, confirmation("sunsubscribe", None, 0));
+        assert!(session.is_subscribed());
+        assert!(backend.pubsub_shardchannels(None).is_empty());
+
+        // a connection subscribed to shard channels only is in subscribed mode too
+        let mut session = Session::new();
+        request(&["ssubscribe", "a"])
+            .run(&backend, &mut session)
+            .await;
+        let ret = request(&["get", "a"]).run(&backend, &mut session).await;
+        assert!(matches!(ret, RespFrame::Error(_)));
+        session.unsubscribe_all(&backend);
+        assert!(backend.pubsub_shardchannels(None).is_empty());
+    }
+}
@@ -0,0 +1,318 @@
+use super::{extract_args, lookup_command, CommandError, CommandExecutor, COMMAND_TABLE, RESP_OK};
+use crate::{Backend, BulkString, RespArray, RespFrame, RespMap, RespNull};
+
+/// The `COMMAND` command and its subcommands.
+#[derive(Debug)]
+pub struct CommandCmd {
+    subcommand: CommandSubcommand,
+}
+
+/// The `CONFIG` command and its subcommands.
+#[derive(Debug)]
+pub struct ConfigCmd {
+    subcommand: ConfigSubcommand,
+}
+
+#[derive(Debug, PartialEq)]
+enum ConfigSubcommand {
+    /// `CONFIG GET parameter [parameter ...]`
+    Get(Vec<Vec<u8>>),
+    /// `CONFIG SET parameter value [parameter value ...]`
+    Set(Vec<(Vec<u8>, Vec<u8>)>),
+}
+
+#[derive(Debug, PartialEq)]
+enum CommandSubcommand {
+    /// `COMMAND`
+    All,
+    /// `COMMAND COUNT`
+    Count,
+    /// `COMMAND INFO [command-name ...]`
+    Info(Vec<Vec<u8>>),
+    /// `COMMAND DOCS [command-name ...]`
+    Docs(Vec<Vec<u8>>),
+    /// `COMMAND GETKEYS command [arg ...]`
+    GetKeys(RespArray),
+}
+
+impl CommandExecutor for CommandCmd {
+    fn execute(self, _backend: &Backend) -> RespFrame {
+        match self.subcommand {
+            CommandSubcommand::All => RespArray::new(
+                COMMAND_TABLE
+                    .iter()
+                    .map(|spec| spec.info())
+                    .collect::<Vec<_>>(),
+            )
+            .into(),
+            CommandSubcommand::Count => RespFrame::Integer(COMMAND_TABLE.len() as i64),
+            CommandSubcommand::Info(names) if names.is_empty() => RespArray::new(
+                COMMAND_TABLE
+                    .iter()
+                    .map(|spec| spec.info())
+                    .collect::<Vec<_>>(),
+            )
+            .into(),
+            CommandSubcommand::Info(names) => RespArray::new(
+                names
+                    .iter()
+                    .map(|name| match lookup_command(name) {
+                        Some(spec) => spec.info(),
+                        None => RespFrame::Null(RespNull),
+                    })
+                    .collect::<Vec<_>>(),
+            )
+            .into(),
+            CommandSubcommand::Docs(names) => {
+                let mut docs = RespMap::new();
+                let specs: Vec<_> = if names.is_empty() {
+                    COMMAND_TABLE.iter().collect()
+                } else {
+                    names
+                        .iter()
+                        .filter_map(|name| lookup_command(name))
+                        .collect()
+                };
+                for spec in specs {
+                    docs.insert(spec.name.into(), spec.docs());
+                }
+                docs.into()
+            }
+            CommandSubcommand::GetKeys(args) => get_keys(args),
+        }
+    }
+}
+
+impl CommandExecutor for ConfigCmd {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match self.subcommand {
+            ConfigSubcommand::Get(patterns) => {
+                let mut params = RespMap::new();
+                for pattern in patterns {
+                    for (name, value) in backend.config_get(&pattern) {
+                        params.insert(name.into(), BulkString::new(value).into());
+                    }
+                }
+                params.into()
+            }
+            ConfigSubcommand::Set(params) => match backend.config_set(&params) {
+                Ok(()) => RESP_OK.clone(),
+                Err(e) => e.into(),
+            },
+        }
+    }
+}
+
+/// Extracts the key arguments of a full command, as done by `COMMAND GETKEYS`.
+fn get_keys(args: RespArray) -> RespFrame {
+    let spec = match args.first() {
+        Some(RespFrame::BulkString(name)) => lookup_command(name),
+        _ => None,
+    };
+    let Some(spec) = spec else {
+        return CommandError::InvalidArguments("Invalid command specified".to_string()).into();
+    };
+    if !spec.check_arity(args.len()) {
+        return CommandError::InvalidArguments(
+            "Invalid number of arguments specified for command".to_string(),
+        )
+        .into();
+    }
+    let keys = spec.keys(&args);
+    if keys.is_empty() {
+        return CommandError::InvalidArguments("The command has no key arguments".to_string())
+            .into();
+    }
+    RespArray::new(keys.into_iter().cloned().collect::<Vec<_>>()).into()
+}
+
+impl TryFrom<RespArray> for CommandCmd {
+    type Error = CommandError;
+    /// Converts a RESP array into a `CommandCmd` command.
+    ///
+    /// The first argument, if any, selects the subcommand: `COUNT`, `INFO`, `DOCS` or
+    /// `GETKEYS`. Without argument, information about every command is returned.
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        let subcommand = match args.next() {
+            None => CommandSubcommand::All,
+            Some(RespFrame::BulkString(name)) => {
+                let name = name.to_ascii_lowercase();
+                let rest: Vec<RespFrame> = args.collect();
+                match name.as_slice() {
+                    b"count" if rest.is_empty() => CommandSubcommand::Count,
+                    b"info" => CommandSubcommand::Info(bulk_strings(rest)?),
+                    b"docs" => CommandSubcommand::Docs(bulk_strings(rest)?),
+                    b"getkeys" if !rest.is_empty() => {
+                        CommandSubcommand::GetKeys(RespArray::new(rest))
+                    }
+                    b"count" | b"getkeys" => {
+                        return Err(CommandError::WrongArity(format!(
+                            "command|{}",
+                            String::from_utf8_lossy(&name)
+                        )))
+                    }
+                    _ => {
+                        return Err(CommandError::InvalidArguments(format!(
+                            "unknown subcommand '{}'. Try COMMAND HELP.",
+                            String::from_utf8_lossy(&name)
+                        )))
+                    }
+                }
+            }
+            Some(_) => return Err(CommandError::SyntaxError),
+        };
+        Ok(CommandCmd { subcommand })
+    }
+}
+
+impl TryFrom<RespArray> for ConfigCmd {
+    type Error = CommandError;
+    /// Converts a RESP array into a `ConfigCmd` command.
+    ///
+    /// The first argument selects the subcommand: `GET` or `SET`.
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = bulk_strings(extract_args(value, 1)?)?.into_iter();
+        let name = args.next().unwrap_or_default().to_ascii_lowercase();
+        let rest: Vec<Vec<u8>> = args.collect();
+        let subcommand = match name.as_slice() {
+            b"get" if !rest.is_empty() => ConfigSubcommand::Get(rest),
+            b"set" if !rest.is_empty() && rest.len().is_multiple_of(2) => {
+                let mut rest = rest.into_iter();
+                let mut params = Vec::new();
+                while let (Some(name), Some(value)) = (rest.next(), rest.next()) {
+                    params.push((name, value));
+                }
+                ConfigSubcommand::Set(params)
+            }
+            b"get" | b"set" => {
+                return Err(CommandError::WrongArity(format!(
+                    "config|{}",
+                    String::from_utf8_lossy(&name)
+                )))
+            }
+            _ => {
+                return Err(CommandError::InvalidArguments(format!(
+                    "unknown subcommand '{}'. Try CONFIG HELP.",
+                    String::from_utf8_lossy(&name)
+                )))
+            }
+        };
+        Ok(ConfigCmd { subcommand })
+    }
+}
+
+fn bulk_strings(frames: Vec<RespFrame>) -> Result<Vec<Vec<u8>>, CommandError> {
+    frames
+        .into_iter()
+        .map(|frame| match frame {
+            RespFrame::BulkString(BulkString(s)) => Ok(s),
+            _ => Err(CommandError::SyntaxError),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cmd::Command, RespDecode, SimpleError};
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    fn command(buf: &[u8]) -> Result<Command> {
+        let mut buf = BytesMut::from(buf);
+        let frame = RespArray::decode(&mut buf)?;
+        Ok(frame.try_into()?)
+    }
+
+    #[test]
+    fn test_command_count() -> Result<()> {
+        let ret = command(b"*2\r\n$7\r\ncommand\r\n$5\r\ncount\r\n")?.execute(&Backend::new());
+        assert_eq!(ret, RespFrame::Integer(COMMAND_TABLE.len() as i64));
+        Ok(())
+    }
+
+    #[test]
+    fn test_command_info() -> Result<()> {
+        let ret = command(b"*4\r\n$7\r\ncommand\r\n$4\r\ninfo\r\n$3\r\nGET\r\n$3\r\nfoo\r\n")?
+            .execute(&Backend::new());
+        let RespFrame::Array(infos) = ret else {
+            panic!("expected an array");
+        };
+        assert_eq!(infos.len(), 2);
+        let RespFrame::Array(ref get) = infos[0] else {
+            panic!("expected an array");
+        };
+        assert_eq!(get[0], RespFrame::BulkString("get".into()));
+        assert_eq!(get[1], RespFrame::Integer(2));
+        assert_eq!(infos[1], RespFrame::Null(RespNull));
+        Ok(())
+    }
+
+    #[test]
+    fn test_command_getkeys() -> Result<()> {
+        let backend = Backend::new();
+        let ret = command(
+            b"*5\r\n$7\r\ncommand\r\n$7\r\ngetkeys\r\n$3\r\nset\r\n$1\r\nk\r\n$1\r\nv\r\n",
+        )?
+        .execute(&backend);
+        assert_eq!(ret, RespArray::new([b"k".into()]).into());
+
+        let ret = command(
+            b"*5\r\n$7\r\ncommand\r\n$7\r\ngetkeys\r\n$3\r\nget\r\n$1\r\nk\r\n$1\r\nx\r\n",
+        )?
+        .execute(&backend);
+        assert_eq!(
+            ret,
+            SimpleError::new("ERR Invalid number of arguments specified for command").into()
+        );
+
+        let ret = command(b"*3\r\n$7\r\ncommand\r\n$7\r\ngetkeys\r\n$7\r\ncommand\r\n")?
+            .execute(&backend);
+        assert_eq!(
+            ret,
+            SimpleError::new("ERR The command has no key arguments").into()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_command_docs() -> Result<()> {
+        let ret = command(b"*3\r\n$7\r\ncommand\r\n$4\r\ndocs\r\n$3\r\nget\r\n")?
+            .execute(&Backend::new());
+        let RespFrame::Map(docs) = ret else {
+            panic!("expected a map");
+        };
+        assert!(docs.contains_key(b"get".as_slice()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_config() -> Result<()> {
+        let backend = Backend::new();
+        let ret = command(
+            b"*4\r\n$6\r\nconfig\r\n$3\r\nset\r\n$22\r\nnotify-keyspace-events\r\n$3\r\nKEA\r\n",
+        )?
+        .execute(&backend);
+        assert_eq!(ret, RESP_OK.clone());
+
+        let ret =
+            command(b"*3\r\n$6\r\nconfig\r\n$3\r\nGET\r\n$8\r\nnotify-*\r\n")?.execute(&backend);
+        let mut expected = RespMap::new();
+        expected.insert(
+            "notify-keyspace-events".into(),
+            BulkString::new("AKE").into(),
+        );
+        assert_eq!(ret, expected.into());
+
+        let ret = command(b"*3\r\n$6\r\nconfig\r\n$3\r\nget\r\n$3\r\nfoo\r\n")?.execute(&backend);
+        assert_eq!(ret, RespMap::new().into());
+
+        let err = command(b"*3\r\n$6\r\nconfig\r\n$3\r\nset\r\n$3\r\nfoo\r\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "ERR wrong number of arguments for 'config|set' command"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,834 @@
+use super::hmap::{bulk_string_array, parse_key_and_args};
+use super::list::parse_numkeys;
+use super::{
+    notify_removed, notify_store, optional_bulk_string, parse_integer, parse_key_arg, parse_keys,
+    CommandError, CommandExecutor,
+};
+use crate::{Backend, BulkString, EventClass, RespArray, RespFrame, RespSet};
+use bytes::Bytes;
+
+#[derive(Debug)]
+pub struct SAdd {
+    key: Bytes,
+    members: Vec<Bytes>,
+}
+
+#[derive(Debug)]
+pub struct SRem {
+    key: Bytes,
+    members: Vec<Bytes>,
+}
+
+#[derive(Debug)]
+pub struct SMembers {
+    key: Bytes,
+}
+
+#[derive(Debug)]
+pub struct SIsMember {
+    key: Bytes,
+    member: Bytes,
+}
+
+#[derive(Debug)]
+pub struct SMIsMember {
+    key: Bytes,
+    members: Vec<Bytes>,
+}
+
+#[derive(Debug)]
+pub struct SCard {
+    key: Bytes,
+}
+
+#[derive(Debug)]
+pub struct SPop {
+    key: Bytes,
+    count: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct SRandMember {
+    key: Bytes,
+    count: Option<i64>,
+}
+
+#[derive(Debug)]
+pub struct SInter {
+    keys: Vec<Bytes>,
+}
+
+#[derive(Debug)]
+pub struct SUnion {
+    keys: Vec<Bytes>,
+}
+
+#[derive(Debug)]
+pub struct SDiff {
+    keys: Vec<Bytes>,
+}
+
+#[derive(Debug)]
+pub struct SInterStore {
+    destination: Bytes,
+    keys: Vec<Bytes>,
+}
+
+#[derive(Debug)]
+pub struct SUnionStore {
+    destination: Bytes,
+    keys: Vec<Bytes>,
+}
+
+#[derive(Debug)]
+pub struct SDiffStore {
+    destination: Bytes,
+    keys: Vec<Bytes>,
+}
+
+/// `SINTERCARD numkeys key [key ...] [LIMIT limit]`
+#[derive(Debug)]
+pub struct SInterCard {
+    keys: Vec<Bytes>,
+    limit: usize,
+}
+
+#[derive(Debug)]
+pub struct SMove {
+    source: Bytes,
+    destination: Bytes,
+    member: Bytes,
+}
+
+impl CommandExecutor for SAdd {
+    /// Returns the number of members that were added.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.sadd(&self.key, self.members) {
+            Ok(added) => {
+                if added > 0 {
+                    backend.notify_keyspace_event(EventClass::Set, "sadd", &self.key);
+                }
+                RespFrame::Integer(added as i64)
+            }
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for SRem {
+    /// Returns the number of members that were removed.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.srem(&self.key, &self.members) {
+            Ok(removed) => {
+                if removed > 0 {
+                    notify_removed(backend, EventClass::Set, "srem", &self.key);
+                }
+                RespFrame::Integer(removed as i64)
+            }
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for SMembers {
+    /// Returns the members as a set, sent as an array to RESP2 clients.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.smembers(&self.key) {
+            Ok(members) => bulk_string_set(members),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for SIsMember {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.sismember(&self.key, &self.member) {
+            Ok(exists) => RespFrame::Integer(exists as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for SMIsMember {
+    /// Returns `1` or `0` for each member, whether it belongs to the set.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.smismember(&self.key, &self.members) {
+            Ok(exists) => RespArray::new(
+                exists
+                    .into_iter()
+                    .map(|exists| RespFrame::Integer(exists as i64))
+                    .collect::<Vec<_>>(),
+            )
+            .into(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for SCard {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.scard(&self.key) {
+            Ok(len) => RespFrame::Integer(len as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for SPop {
+    /// Without a count, returns a single popped member or a null reply. With a count,
+    /// returns a set of the popped members.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let popped = backend.spop(&self.key, self.count.unwrap_or(1));
+        if popped.as_ref().is_ok_and(|members| !members.is_empty()) {
+            notify_removed(backend, EventClass::Set, "spop", &self.key);
+        }
+        match popped {
+            Ok(members) if self.count.is_some() => bulk_string_set(members),
+            Ok(members) => optional_bulk_string(members.into_iter().next()),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for SRandMember {
+    /// Without a count, returns a single random member or a null reply. With a count,
+    /// returns an array of members, distinct if the count is positive and possibly
+    /// repeated if it is negative.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.srandmember(&self.key, self.count.unwrap_or(1)) {
+            Ok(members) if self.count.is_some() => bulk_string_array(members),
+            Ok(members) => optional_bulk_string(members.into_iter().next()),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for SInter {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.sinter(&self.keys) {
+            Ok(members) => bulk_string_set(members),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for SUnion {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.sunion(&self.keys) {
+            Ok(members) => bulk_string_set(members),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for SDiff {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.sdiff(&self.keys) {
+            Ok(members) => bulk_string_set(members),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for SInterStore {
+    /// Returns the number of members of the stored set.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let destination = self.destination.clone();
+        let stored = notify_store(
+            backend,
+            EventClass::Set,
+            "sinterstore",
+            &destination,
+            || backend.sinterstore(self.destination, &self.keys),
+        );
+        match stored {
+            Ok(len) => RespFrame::Integer(len as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for SUnionStore {
+    /// Returns the number of members of the stored set.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let destination = self.destination.clone();
+        let stored = notify_store(
+            backend,
+            EventClass::Set,
+            "sunionstore",
+            &destination,
+            || backend.sunionstore(self.destination, &self.keys),
+        );
+        match stored {
+            Ok(len) => RespFrame::Integer(len as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for SDiffStore {
+    /// Returns the number of members of the stored set.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let destination = self.destination.clone();
+        let stored = notify_store(backend, EventClass::Set, "sdiffstore", &destination, || {
+            backend.sdiffstore(self.destination, &self.keys)
+        });
+        match stored {
+            Ok(len) => RespFrame::Integer(len as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for SInterCard {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.sintercard(&self.keys, self.limit) {
+            Ok(len) => RespFrame::Integer(len as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for SMove {
+    /// Returns `1` if the member was moved, `0` if it is not in the source set.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.smove(&self.source, &self.destination, self.member) {
+            Ok(moved) => {
+                if moved {
+                    notify_removed(backend, EventClass::Set, "srem", &self.source);
+                    backend.notify_keyspace_event(EventClass::Set, "sadd", &self.destination);
+                }
+                RespFrame::Integer(moved as i64)
+            }
+            Err(e) => e.into(),
+        }
+    }
+}
+
+/// Builds a set reply of members.
+pub(super) fn bulk_string_set(members: Vec<Bytes>) -> RespFrame {
+    RespSet::new(
+        members
+            .into_iter()
+            .map(|member| BulkString::new(member).into())
+            .collect::<Vec<_>>(),
+    )
+    .into()
+}
+
+/// Parses a key followed by one or more members.
+fn parse_key_members(value: RespArray, name: &str) -> Result<(Bytes, Vec<Bytes>), CommandError> {
+    let (key, members) = parse_key_and_args(value)?;
+    if members.is_empty() {
+        return Err(CommandError::WrongArity(name.to_string()));
+    }
+    Ok((key, members))
+}
+
+/// Parses a key followed by an optional count, as taken by `SPOP` and `SRANDMEMBER`.
+fn parse_key_count(value: RespArray) -> Result<(Bytes, Option<i64>), CommandError> {
+    let (key, args) = parse_key_and_args(value)?;
+    let mut args = args.into_iter();
+    let count = args
+        .next()
+        .map(|count| parse_integer(RespFrame::BulkString(BulkString::new(count))))
+        .transpose()?;
+    if args.next().is_some() {
+        return Err(CommandError::SyntaxError);
+    }
+    Ok((key, count))
+}
+
+impl TryFrom<RespArray> for SAdd {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, members) = parse_key_members(value, "sadd")?;
+        Ok(SAdd { key, members })
+    }
+}
+
+impl TryFrom<RespArray> for SRem {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, members) = parse_key_members(value, "srem")?;
+        Ok(SRem { key, members })
+    }
+}
+
+impl TryFrom<RespArray> for SMembers {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let key = parse_key_arg(value)?;
+        Ok(SMembers { key })
+    }
+}
+
+impl TryFrom<RespArray> for SIsMember {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, args) = parse_key_and_args(value)?;
+        match <[Bytes; 1]>::try_from(args) {
+            Ok([member]) => Ok(SIsMember { key, member }),
+            Err(_) => Err(CommandError::SyntaxError),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for SMIsMember {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, members) = parse_key_members(value, "smismember")?;
+        Ok(SMIsMember { key, members })
+    }
+}
+
+impl TryFrom<RespArray> for SCard {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let key = parse_key_arg(value)?;
+        Ok(SCard { key })
+    }
+}
+
+impl TryFrom<RespArray> for SPop {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, count) = parse_key_count(value)?;
+        let count = count
+            .map(|count| {
+                usize::try_from(count).map_err(|_| {
+                    CommandError::InvalidArguments(
+                        "value is out of range, must be positive".to_string(),
+                    )
+                })
+            })
+            .transpose()?;
+        Ok(SPop { key, count })
+    }
+}
+
+impl TryFrom<RespArray> for SRandMember {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, count) = parse_key_count(value)?;
+        if count == Some(i64::MIN) {
+            return Err(CommandError::InvalidArguments(
+                "value is out of range".to_string(),
+            ));
+        }
+        Ok(SRandMember { key, count })
+    }
+}
+
+impl TryFrom<RespArray> for SInter {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let keys = parse_keys(value)?;
+        Ok(SInter { keys })
+    }
+}
+
+impl TryFrom<RespArray> for SUnion {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let keys = parse_keys(value)?;
+        Ok(SUnion { keys })
+    }
+}
+
+impl TryFrom<RespArray> for SDiff {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let keys = parse_keys(value)?;
+        Ok(SDiff { keys })
+    }
+}
+
+impl TryFrom<RespArray> for SInterStore {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (destination, keys) = parse_key_members(value, "sinterstore")?;
+        Ok(SInterStore { destination, keys })
+    }
+}
+
+impl TryFrom<RespArray> for SUnionStore {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (destination, keys) = parse_key_members(value, "sunionstore")?;
+        Ok(SUnionStore { destination, keys })
+    }
+}
+
+impl TryFrom<RespArray> for SDiffStore {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (destination, keys) = parse_key_members(value, "sdiffstore")?;
+        Ok(SDiffStore { destination, keys })
+    }
+}
+
+impl TryFrom<RespArray> for SInterCard {
+    type Error = CommandError;
+    /// Converts a RESP array into a `SInterCard` command. A limit of `0`, the default,
+    /// means no limit.
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (numkeys, args) = parse_key_and_args(value)?;
+        let mut args = std::iter::once(numkeys).chain(args);
+        let keys = parse_numkeys(&mut args)?;
+        let limit = match args.next() {
+            None => 0,
+            Some(option) if option.eq_ignore_ascii_case(b"limit") => {
+                let limit = args.next().ok_or(CommandError::SyntaxError)?;
+                usize::try_from(parse_integer(RespFrame::BulkString(BulkString::new(
+                    limit,
+                )))?)
+                .map_err(|_| {
+                    CommandError::InvalidArguments("LIMIT can't be negative".to_string())
+                })?
+            }
+            Some(_) => return Err(CommandError::SyntaxError),
+        };
+        if args.next().is_some() {
+            return Err(CommandError::SyntaxError);
+        }
+        Ok(SInterCard { keys, limit })
+    }
+}
+
+impl TryFrom<RespArray> for SMove {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (source, args) = parse_key_and_args(value)?;
+        match <[Bytes; 2]>::try_from(args) {
+            Ok([destination, member]) => Ok(SMove {
+                source,
+                destination,
+                member,
+            }),
+            Err(_) => Err(CommandError::SyntaxError),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ExpireCondition, KeyExpiry, RespDecode, RespNull};
+    use anyhow::Result;
+    use bytes::BytesMut;
+    use std::collections::HashSet;
+
+    fn members(members: &[&'static str]) -> Vec<Bytes> {
+        members
+            .iter()
+            .map(|member| Bytes::from_static(member.as_bytes()))
+            .collect()
+    }
+
+    /// Returns the members of a set or array reply, in any order.
+    fn reply_members(frame: RespFrame) -> Vec<Bytes> {
+        let frames = match frame {
+            RespFrame::Set(set) => set.0,
+            RespFrame::Array(array) => array.0,
+            frame => panic!("unexpected reply {:?}", frame),
+        };
+        let mut members = frames
+            .into_iter()
+            .map(|frame| match frame {
+                RespFrame::BulkString(member) => Bytes::from(member.0),
+                frame => panic!("unexpected member {:?}", frame),
+            })
+            .collect::<Vec<_>>();
+        members.sort();
+        members
+    }
+
+    #[test]
+    fn test_spop_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$4\r\nspop\r\n$3\r\nset\r\n$2\r\n-1\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let err = SPop::try_from(frame).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "ERR value is out of range, must be positive"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_sadd_srem_smembers() {
+        let backend = Backend::new();
+        let ret = SAdd {
+            key: "set".into(),
+            members: members(&["a", "b", "a", "c"]),
+        }
+        .execute(&backend);
+        assert_eq!(ret, RespFrame::Integer(3));
+        let ret = SMembers { key: "set".into() }.execute(&backend);
+        assert!(matches!(ret, RespFrame::Set(_)));
+        assert_eq!(reply_members(ret), members(&["a", "b", "c"]));
+        // RESP2 clients get an array
+        let ret = SMembers { key: "set".into() }
+            .execute(&backend)
+            .into_resp2();
+        assert!(matches!(ret, RespFrame::Array(_)));
+
+        let ret = SIsMember {
+            key: "set".into(),
+            member: "b".into(),
+        }
+        .execute(&backend);
+        assert_eq!(ret, RespFrame::Integer(1));
+        let ret = SMIsMember {
+            key: "set".into(),
+            members: members(&["a", "x"]),
+        }
+        .execute(&backend);
+        assert_eq!(
+            ret,
+            RespArray::new([RespFrame::Integer(1), RespFrame::Integer(0)]).into()
+        );
+
+        let ret = SRem {
+            key: "set".into(),
+            members: members(&["a", "b", "c", "x"]),
+        }
+        .execute(&backend);
+        assert_eq!(ret, RespFrame::Integer(3));
+        assert!(!backend.contains_key(b"set"));
+        assert_eq!(
+            SCard { key: "set".into() }.execute(&backend),
+            RespFrame::Integer(0)
+        );
+        let ret = SMIsMember {
+            key: "set".into(),
+            members: members(&["a"]),
+        }
+        .execute(&backend);
+        assert_eq!(ret, RespArray::new([RespFrame::Integer(0)]).into());
+
+        backend.set("string".into(), "value".into());
+        let ret = SAdd {
+            key: "string".into(),
+            members: members(&["a"]),
+        }
+        .execute(&backend);
+        assert_eq!(ret, CommandError::WrongType.into());
+    }
+
+    #[test]
+    fn test_spop() {
+        let backend = Backend::new();
+        backend
+            .sadd(&"set".into(), members(&["a", "b", "c", "d"]))
+            .unwrap();
+        let ret = SPop {
+            key: "set".into(),
+            count: None,
+        }
+        .execute(&backend);
+        let RespFrame::BulkString(popped) = ret else {
+            panic!("SPOP must reply with a member");
+        };
+        assert!(!backend.sismember(b"set", &popped).unwrap());
+
+        let ret = SPop {
+            key: "set".into(),
+            count: Some(10),
+        }
+        .execute(&backend);
+        let popped = reply_members(ret);
+        assert_eq!(popped.len(), 3);
+        assert!(!backend.contains_key(b"set"));
+
+        let ret = SPop {
+            key: "set".into(),
+            count: None,
+        }
+        .execute(&backend);
+        assert_eq!(ret, RespFrame::Null(RespNull));
+        let ret = SPop {
+            key: "set".into(),
+            count: Some(2),
+        }
+        .execute(&backend);
+        assert_eq!(ret, RespSet::new(Vec::new()).into());
+    }
+
+    #[test]
+    fn test_srandmember_counts() {
+        let backend = Backend::new();
+        backend
+            .sadd(&"set".into(), members(&["a", "b", "c"]))
+            .unwrap();
+        let srandmember = |count| {
+            SRandMember {
+                key: "set".into(),
+                count,
+            }
+            .execute(&backend)
+        };
+        let distinct = reply_members(srandmember(Some(2)));
+        assert_eq!(distinct.len(), 2);
+        assert_eq!(distinct.iter().collect::<HashSet<_>>().len(), 2);
+        assert_eq!(
+            reply_members(srandmember(Some(5))),
+            members(&["a", "b", "c"])
+        );
+
+        // a negative count allows repeated members
+        let repeated = reply_members(srandmember(Some(-10)));
+        assert_eq!(repeated.len(), 10);
+        assert!(repeated
+            .iter()
+            .all(|member| backend.sismember(b"set", member).unwrap()));
+        assert_eq!(backend.scard(b"set").unwrap(), 3);
+
+        assert!(matches!(srandmember(None), RespFrame::BulkString(_)));
+        assert_eq!(srandmember(Some(0)), RespArray::new([]).into());
+        backend.del(b"set");
+        assert_eq!(srandmember(Some(-3)), RespArray::new([]).into());
+    }
+
+    fn keys(keys: &[&'static str]) -> Vec<Bytes> {
+        members(keys)
+    }
+
+    #[test]
+    fn test_sintercard_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*6\r\n$10\r\nsintercard\r\n$1\r\n2\r\n$1\r\na\r\n$1\r\nb\r\n$5\r\nLIMIT\r\n$1\r\n3\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd = SInterCard::try_from(frame)?;
+        assert_eq!(cmd.keys, keys(&["a", "b"]));
+        assert_eq!(cmd.limit, 3);
+
+        buf.extend_from_slice(
+            b"*6\r\n$10\r\nsintercard\r\n$1\r\n2\r\n$1\r\na\r\n$1\r\nb\r\n$5\r\nlimit\r\n$2\r\n-1\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let err = SInterCard::try_from(frame).unwrap_err();
+        assert_eq!(err.to_string(), "ERR LIMIT can't be negative");
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_algebra() {
+        let backend = Backend::new();
+        backend
+            .sadd(&"s1".into(), members(&["a", "b", "c", "d"]))
+            .unwrap();
+        backend
+            .sadd(&"s2".into(), members(&["c", "d", "e"]))
+            .unwrap();
+        backend
+            .sadd(&"s3".into(), members(&["a", "c", "e"]))
+            .unwrap();
+
+        let ret = SInter {
+            keys: keys(&["s1", "s2", "s3"]),
+        }
+        .execute(&backend);
+        assert_eq!(reply_members(ret), members(&["c"]));
+        let ret = SInter {
+            keys: keys(&["s1", "missing"]),
+        }
+        .execute(&backend);
+        assert_eq!(ret, RespSet::new(Vec::new()).into());
+        let ret = SUnion {
+            keys: keys(&["s1", "s2", "missing"]),
+        }
+        .execute(&backend);
+        assert_eq!(reply_members(ret), members(&["a", "b", "c", "d", "e"]));
+        let ret = SDiff {
+            keys: keys(&["s1", "s2", "s3"]),
+        }
+        .execute(&backend);
+        assert_eq!(reply_members(ret), members(&["b"]));
+
+        let ret = SInterCard {
+            keys: keys(&["s1", "s2"]),
+            limit: 0,
+        }
+        .execute(&backend);
+        assert_eq!(ret, RespFrame::Integer(2));
+        let ret = SInterCard {
+            keys: keys(&["s1", "s2"]),
+            limit: 1,
+        }
+        .execute(&backend);
+        assert_eq!(ret, RespFrame::Integer(1));
+
+        backend.set("string".into(), "value".into());
+        let ret = SInter {
+            keys: keys(&["missing", "string"]),
+        }
+        .execute(&backend);
+        assert_eq!(ret, CommandError::WrongType.into());
+    }
+
+    #[test]
+    fn test_set_algebra_store() {
+        let backend = Backend::new();
+        backend
+            .sadd(&"s1".into(), members(&["a", "b", "c"]))
+            .unwrap();
+        backend
+            .sadd(&"s2".into(), members(&["b", "c", "d"]))
+            .unwrap();
+        backend.set("dest".into(), "value".into());
+        backend.expire_at(b"dest", u64::MAX, ExpireCondition::Always);
+
+        let ret = SInterStore {
+            destination: "dest".into(),
+            keys: keys(&["s1", "s2"]),
+        }
+        .execute(&backend);
+        assert_eq!(ret, RespFrame::Integer(2));
+        assert_eq!(backend.scard(b"dest").unwrap(), 2);
+        assert_eq!(backend.expiry(b"dest"), KeyExpiry::Persistent);
+
+        // the destination may be one of the sources
+        let ret = SUnionStore {
+            destination: "s1".into(),
+            keys: keys(&["s1", "s2"]),
+        }
+        .execute(&backend);
+        assert_eq!(ret, RespFrame::Integer(4));
+        let ret = SDiffStore {
+            destination: "dest".into(),
+            keys: keys(&["s2", "s1"]),
+        }
+        .execute(&backend);
+        assert_eq!(ret, RespFrame::Integer(0));
+        assert!(!backend.contains_key(b"dest"));
+    }
+
+    #[test]
+    fn test_smove() {
+        let backend = Backend::new();
+        backend.sadd(&"src".into(), members(&["a", "b"])).unwrap();
+        let smove = |source: &'static str, destination: &'static str, member: &'static str| {
+            SMove {
+                source: source.into(),
+                destination: destination.into(),
+                member: member.into(),
+            }
+            .execute(&backend)
+        };
+        assert_eq!(smove("src", "dst", "a"), RespFrame::Integer(1));
+        assert_eq!(smove("src", "dst", "a"), RespFrame::Integer(0));
+        assert_eq!(smove("src", "src", "b"), RespFrame::Integer(1));
+        assert_eq!(backend.smembers(b"dst").unwrap(), members(&["a"]));
+        assert_eq!(smove("src", "dst", "b"), RespFrame::Integer(1));
+        assert!(!backend.contains_key(b"src"));
+
+        backend.set("string".into(), "value".into());
+        assert_eq!(smove("dst", "string", "a"), CommandError::WrongType.into());
+        assert!(backend.sismember(b"dst", b"a").unwrap());
+    }
+}
@@ -0,0 +1,2190 @@
+use super::hmap::parse_key_and_args;
+use super::list::bulk_string_args;
+use super::{parse_integer, BlockingCommand, CommandError, CommandExecutor, RESP_OK};
+use crate::{
+    Backend, BulkString, ClaimOptions, ConsumerInfo, EventClass, GroupInfo, PendingFilter,
+    PendingSummary, RespArray, RespFrame, RespMap, RespNull, RespNullArray, StreamFields, StreamId,
+    StreamInfo, StreamTrim, TrimStrategy, XAddId,
+};
+use bytes::Bytes;
+use std::iter::Peekable;
+use std::time::Duration;
+use std::vec;
+
+/// `XADD key [NOMKSTREAM] [<MAXLEN | MINID> [= | ~] threshold [LIMIT count]]
+/// <* | id> field value [field value ...]`
+#[derive(Debug)]
+pub struct XAdd {
+    key: Bytes,
+    create: bool,
+    trim: Option<StreamTrim>,
+    id: XAddId,
+    fields: StreamFields,
+}
+
+/// `XRANGE key start end [COUNT count]`
+#[derive(Debug)]
+pub struct XRange {
+    key: Bytes,
+    start: StreamId,
+    end: StreamId,
+    count: Option<usize>,
+}
+
+/// `XREVRANGE key end start [COUNT count]`
+#[derive(Debug)]
+pub struct XRevRange {
+    key: Bytes,
+    start: StreamId,
+    end: StreamId,
+    count: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct XLen {
+    key: Bytes,
+}
+
+/// `XTRIM key <MAXLEN | MINID> [= | ~] threshold [LIMIT count]`
+#[derive(Debug)]
+pub struct XTrim {
+    key: Bytes,
+    trim: StreamTrim,
+}
+
+#[derive(Debug)]
+pub struct XDel {
+    key: Bytes,
+    ids: Vec<StreamId>,
+}
+
+/// `XREAD [COUNT count] [BLOCK milliseconds] STREAMS key [key ...] id [id ...]`
+#[derive(Debug)]
+pub struct XRead {
+    count: Option<usize>,
+    block: bool,
+    timeout: Option<Duration>,
+    keys: Vec<Bytes>,
+    /// The ID after which each stream is read, `None` for `$` until it is resolved to the
+    /// last ID of the stream.
+    ids: Vec<Option<StreamId>>,
+}
+
+/// The `XGROUP` command and its subcommands.
+#[derive(Debug)]
+pub struct XGroup {
+    key: Bytes,
+    group: Bytes,
+    subcommand: XGroupSubcommand,
+}
+
+#[derive(Debug, PartialEq)]
+enum XGroupSubcommand {
+    /// `XGROUP CREATE key group <id | $> [MKSTREAM] [ENTRIESREAD entries-read]`
+    Create {
+        id: Option<StreamId>,
+        mkstream: bool,
+        entries_read: Option<u64>,
+    },
+    /// `XGROUP SETID key group <id | $> [ENTRIESREAD entries-read]`
+    SetId {
+        id: Option<StreamId>,
+        entries_read: Option<u64>,
+    },
+    /// `XGROUP DESTROY key group`
+    Destroy,
+    /// `XGROUP CREATECONSUMER key group consumer`
+    CreateConsumer(Bytes),
+    /// `XGROUP DELCONSUMER key group consumer`
+    DelConsumer(Bytes),
+}
+
+/// `XREADGROUP GROUP group consumer [COUNT count] [BLOCK milliseconds] [NOACK]
+/// STREAMS key [key ...] id [id ...]`
+#[derive(Debug)]
+pub struct XReadGroup {
+    group: Bytes,
+    consumer: Bytes,
+    count: Option<usize>,
+    block: bool,
+    timeout: Option<Duration>,
+    noack: bool,
+    keys: Vec<Bytes>,
+    /// The ID after which the history of the consumer is read from each stream, `None`
+    /// for `>` to read the entries never delivered to the group.
+    ids: Vec<Option<StreamId>>,
+}
+
+/// `XACK key group id [id ...]`
+#[derive(Debug)]
+pub struct XAck {
+    key: Bytes,
+    group: Bytes,
+    ids: Vec<StreamId>,
+}
+
+/// `XPENDING key group [[IDLE min-idle-time] start end count [consumer]]`
+#[derive(Debug)]
+pub struct XPending {
+    key: Bytes,
+    group: Bytes,
+    /// The entries to list, or `None` for a summary of the pending entries.
+    filter: Option<PendingFilter>,
+}
+
+/// `XCLAIM key group consumer min-idle-time id [id ...] [IDLE ms]
+/// [TIME unix-time-milliseconds] [RETRYCOUNT count] [FORCE] [JUSTID] [LASTID lastid]`
+#[derive(Debug)]
+pub struct XClaim {
+    key: Bytes,
+    group: Bytes,
+    consumer: Bytes,
+    min_idle: u64,
+    ids: Vec<StreamId>,
+    options: ClaimOptions,
+}
+
+/// `XAUTOCLAIM key group consumer min-idle-time start [COUNT count] [JUSTID]`
+#[derive(Debug)]
+pub struct XAutoClaim {
+    key: Bytes,
+    group: Bytes,
+    consumer: Bytes,
+    min_idle: u64,
+    start: StreamId,
+    count: usize,
+    just_id: bool,
+}
+
+/// The `XINFO` command and its subcommands.
+#[derive(Debug)]
+pub struct XInfo {
+    key: Bytes,
+    subcommand: XInfoSubcommand,
+}
+
+#[derive(Debug, PartialEq)]
+enum XInfoSubcommand {
+    /// `XINFO STREAM key`
+    Stream,
+    /// `XINFO GROUPS key`
+    Groups,
+    /// `XINFO CONSUMERS key group`
+    Consumers(Bytes),
+}
+
+impl CommandExecutor for XAdd {
+    /// Returns the ID of the added entry, or a null reply if the stream does not exist and
+    /// `NOMKSTREAM` was given.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        // the length before the addition tells whether trimming evicted entries
+        let len = match self.trim {
+            Some(_) if backend.notifies(EventClass::Stream) && backend.contains_key(&self.key) => {
+                backend.xlen(&self.key).ok()
+            }
+            _ => None,
+        };
+        match backend.xadd(&self.key, self.id, self.fields, self.trim, self.create) {
+            Ok(Some(id)) => {
+                backend.notify_keyspace_event(EventClass::Stream, "xadd", &self.key);
+                if len.is_some_and(|len| backend.xlen(&self.key).is_ok_and(|new| new <= len)) {
+                    backend.notify_keyspace_event(EventClass::Stream, "xtrim", &self.key);
+                }
+                BulkString::new(id.to_string()).into()
+            }
+            Ok(None) => RespFrame::Null(RespNull),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for XRange {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        range(backend, &self.key, self.start, self.end, false, self.count)
+    }
+}
+
+impl CommandExecutor for XRevRange {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        range(backend, &self.key, self.start, self.end, true, self.count)
+    }
+}
+
+impl CommandExecutor for XLen {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.xlen(&self.key) {
+            Ok(len) => RespFrame::Integer(len as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for XTrim {
+    /// Returns the number of entries evicted.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.xtrim(&self.key, &self.trim) {
+            Ok(evicted) => {
+                if evicted > 0 {
+                    backend.notify_keyspace_event(EventClass::Stream, "xtrim", &self.key);
+                }
+                RespFrame::Integer(evicted as i64)
+            }
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for XDel {
+    /// Returns the number of entries deleted.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.xdel(&self.key, &self.ids) {
+            Ok(removed) => {
+                if removed > 0 {
+                    backend.notify_keyspace_event(EventClass::Stream, "xdel", &self.key);
+                }
+                RespFrame::Integer(removed as i64)
+            }
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl XRead {
+    /// Returns `true` if `BLOCK` was given.
+    pub(super) fn blocks(&self) -> bool {
+        self.block
+    }
+}
+
+impl BlockingCommand for XRead {
+    fn wait_keys(&self) -> &[Bytes] {
+        &self.keys
+    }
+
+    fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Resolves `$` to the last ID of the stream, so that only the entries added from now
+    /// on are read.
+    fn prepare(&mut self, backend: &Backend) {
+        for (key, id) in self.keys.iter().zip(self.ids.iter_mut()) {
+            if id.is_none() {
+                *id = Some(backend.xlast_id(key).unwrap_or_default());
+            }
+        }
+    }
+
+    /// Returns the new entries of each stream having some, along with its key.
+    fn try_execute(&self, backend: &Backend) -> Option<RespFrame> {
+        let streams = self
+            .keys
+            .iter()
+            .zip(&self.ids)
+            .map(|(key, id)| (key.clone(), id.unwrap_or(StreamId::MAX)))
+            .collect::<Vec<_>>();
+        match backend.xread(&streams, self.count) {
+            Ok(streams) if streams.is_empty() => None,
+            Ok(streams) => Some(streams_reply(streams)),
+            Err(e) => Some(e.into()),
+        }
+    }
+}
+
+impl CommandExecutor for XRead {
+    fn execute(mut self, backend: &Backend) -> RespFrame {
+        self.prepare(backend);
+        self.try_execute(backend)
+            .unwrap_or_else(|| self.timeout_reply())
+    }
+}
+
+/// Creates `consumer` in the group ahead of the commands creating it implicitly, only to
+/// publish its creation, as they don't tell whether it already existed.
+fn notify_consumer_created(backend: &Backend, key: &Bytes, group: &[u8], consumer: &Bytes) {
+    if !backend.notifies(EventClass::Stream) {
+        return;
+    }
+    if let Ok(Some(true)) = backend.xgroup_createconsumer(key, group, consumer) {
+        backend.notify_keyspace_event(EventClass::Stream, "xgroup-createconsumer", key);
+    }
+}
+
+/// The error replied when the group of a stream does not exist.
+fn no_such_key_or_group(key: &[u8], group: &[u8]) -> CommandError {
+    CommandError::NoGroup(format!(
+        "No such key '{}' or consumer group '{}'",
+        String::from_utf8_lossy(key),
+        String::from_utf8_lossy(group)
+    ))
+}
+
+/// The error replied when the group of an existing stream does not exist.
+fn no_such_group(key: &[u8], group: &[u8]) -> CommandError {
+    CommandError::NoGroup(format!(
+        "No such consumer group '{}' for key name '{}'",
+        String::from_utf8_lossy(group),
+        String::from_utf8_lossy(key)
+    ))
+}
+
+impl CommandExecutor for XGroup {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let (event, zero_is_noop) = match self.subcommand {
+            XGroupSubcommand::Create { .. } => ("xgroup-create", false),
+            XGroupSubcommand::SetId { .. } => ("xgroup-setid", false),
+            XGroupSubcommand::Destroy => ("xgroup-destroy", true),
+            XGroupSubcommand::CreateConsumer(_) => ("xgroup-createconsumer", true),
+            XGroupSubcommand::DelConsumer(_) => ("xgroup-delconsumer", false),
+        };
+        let ret =
+            match self.subcommand {
+                XGroupSubcommand::Create {
+                    id,
+                    mkstream,
+                    entries_read,
+                } => backend
+                    .xgroup_create(&self.key, self.group.clone(), id, entries_read, mkstream)
+                    .map(|created| match created {
+                        Some(true) => Ok(RESP_OK.clone()),
+                        Some(false) => Err(CommandError::BusyGroup),
+                        None => Err(xgroup_missing_key()),
+                    }),
+                XGroupSubcommand::SetId { id, entries_read } => backend
+                    .xgroup_setid(&self.key, &self.group, id, entries_read)
+                    .map(|set| match set {
+                        true => Ok(RESP_OK.clone()),
+                        false => Err(xgroup_missing(backend, &self.key, &self.group)),
+                    }),
+                XGroupSubcommand::Destroy => backend.xgroup_destroy(&self.key, &self.group).map(
+                    |destroyed| match destroyed {
+                        false if !backend.contains_key(&self.key) => Err(xgroup_missing_key()),
+                        destroyed => Ok(RespFrame::Integer(destroyed as i64)),
+                    },
+                ),
+                XGroupSubcommand::CreateConsumer(consumer) => backend
+                    .xgroup_createconsumer(&self.key, &self.group, &consumer)
+                    .map(|created| match created {
+                        Some(created) => Ok(RespFrame::Integer(created as i64)),
+                        None => Err(xgroup_missing(backend, &self.key, &self.group)),
+                    }),
+                XGroupSubcommand::DelConsumer(consumer) => backend
+                    .xgroup_delconsumer(&self.key, &self.group, &consumer)
+                    .map(|pending| match pending {
+                        Some(pending) => Ok(RespFrame::Integer(pending as i64)),
+                        None => Err(xgroup_missing(backend, &self.key, &self.group)),
+                    }),
+            };
+        match ret {
+            Ok(Ok(frame)) => {
+                // destroying a missing group or creating an existing consumer is no change
+                if !(zero_is_noop && frame == RespFrame::Integer(0)) {
+                    backend.notify_keyspace_event(EventClass::Stream, event, &self.key);
+                }
+                frame
+            }
+            Ok(Err(e)) | Err(e) => e.into(),
+        }
+    }
+}
+
+fn xgroup_missing_key() -> CommandError {
+    CommandError::InvalidArguments(
+        "The XGROUP subcommand requires the key to exist. Note that for CREATE you may want \
+         to use the MKSTREAM option to create an empty stream automatically."
+            .to_string(),
+    )
+}
+
+/// The error replied by `XGROUP` when either the key or the group does not exist.
+fn xgroup_missing(backend: &Backend, key: &[u8], group: &[u8]) -> CommandError {
+    if backend.contains_key(key) {
+        no_such_group(key, group)
+    } else {
+        xgroup_missing_key()
+    }
+}
+
+impl XReadGroup {
+    /// Returns `true` if `BLOCK` was given.
+    pub(super) fn blocks(&self) -> bool {
+        self.block
+    }
+}
+
+impl BlockingCommand for XReadGroup {
+    fn wait_keys(&self) -> &[Bytes] {
+        &self.keys
+    }
+
+    fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Returns the entries read from each stream along with its key, or an error if the
+    /// group does not exist, e.g. because it was destroyed while the client was blocked.
+    fn try_execute(&self, backend: &Backend) -> Option<RespFrame> {
+        for key in &self.keys {
+            match backend.xgroup_exists(key, &self.group) {
+                Ok(true) => {}
+                Ok(false) => {
+                    return Some(
+                        CommandError::NoGroup(format!(
+                            "No such key '{}' or consumer group '{}' in XREADGROUP with GROUP \
+                             option",
+                            String::from_utf8_lossy(key),
+                            String::from_utf8_lossy(&self.group)
+                        ))
+                        .into(),
+                    )
+                }
+                Err(e) => return Some(e.into()),
+            }
+        }
+        for key in &self.keys {
+            notify_consumer_created(backend, key, &self.group, &self.consumer);
+        }
+        let streams = self
+            .keys
+            .iter()
+            .cloned()
+            .zip(self.ids.iter().copied())
+            .collect::<Vec<_>>();
+        match backend.xreadgroup(
+            &self.group,
+            &self.consumer,
+            &streams,
+            self.count,
+            self.noack,
+        ) {
+            Ok(streams) if streams.is_empty() => None,
+            Ok(streams) => Some(streams_reply(streams)),
+            Err(e) => Some(e.into()),
+        }
+    }
+}
+
+impl CommandExecutor for XReadGroup {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        self.try_execute(backend)
+            .unwrap_or_else(|| self.timeout_reply())
+    }
+}
+
+impl CommandExecutor for XAck {
+    /// Returns the number of entries acknowledged.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.xack(&self.key, &self.group, &self.ids) {
+            Ok(acked) => RespFrame::Integer(acked as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for XPending {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let Some(filter) = self.filter else {
+            return match backend.xpending_summary(&self.key, &self.group) {
+                Ok(Some(summary)) => pending_summary_reply(summary),
+                Ok(None) => no_such_key_or_group(&self.key, &self.group).into(),
+                Err(e) => e.into(),
+            };
+        };
+        match backend.xpending(&self.key, &self.group, &filter) {
+            Ok(Some(pending)) => RespArray::new(
+                pending
+                    .into_iter()
+                    .map(|info| {
+                        RespArray::new([
+                            BulkString::new(info.id.to_string()).into(),
+                            BulkString::new(info.consumer).into(),
+                            RespFrame::Integer(info.idle as i64),
+                            RespFrame::Integer(info.delivery_count as i64),
+                        ])
+                        .into()
+                    })
+                    .collect::<Vec<RespFrame>>(),
+            )
+            .into(),
+            Ok(None) => no_such_key_or_group(&self.key, &self.group).into(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+/// Builds the reply of `XPENDING` without range: the number of pending entries, the
+/// lowest and greatest pending IDs, and the number of entries pending for each consumer.
+fn pending_summary_reply(summary: PendingSummary) -> RespFrame {
+    let Some((first, last)) = summary.bounds else {
+        return RespArray::new([
+            RespFrame::Integer(0),
+            RespFrame::Null(RespNull),
+            RespFrame::Null(RespNull),
+            RespFrame::NullArray(RespNullArray),
+        ])
+        .into();
+    };
+    let consumers = summary
+        .consumers
+        .into_iter()
+        .map(|(name, count)| {
+            RespArray::new([
+                BulkString::new(name).into(),
+                BulkString::new(count.to_string()).into(),
+            ])
+            .into()
+        })
+        .collect::<Vec<RespFrame>>();
+    RespArray::new([
+        RespFrame::Integer(summary.count as i64),
+        BulkString::new(first.to_string()).into(),
+        BulkString::new(last.to_string()).into(),
+        RespArray::new(consumers).into(),
+    ])
+    .into()
+}
+
+fn ids_reply(ids: impl IntoIterator<Item = StreamId>) -> RespFrame {
+    RespArray::new(
+        ids.into_iter()
+            .map(|id| BulkString::new(id.to_string()).into())
+            .collect::<Vec<RespFrame>>(),
+    )
+    .into()
+}
+
+impl CommandExecutor for XClaim {
+    /// Returns the claimed entries, or only their IDs if `JUSTID` was given.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        notify_consumer_created(backend, &self.key, &self.group, &self.consumer);
+        match backend.xclaim(
+            &self.key,
+            &self.group,
+            &self.consumer,
+            self.min_idle,
+            &self.ids,
+            &self.options,
+        ) {
+            Ok(Some(claimed)) if self.options.just_id => {
+                ids_reply(claimed.into_iter().map(|(id, _)| id))
+            }
+            Ok(Some(claimed)) => entries_reply(claimed),
+            Ok(None) => no_such_key_or_group(&self.key, &self.group).into(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for XAutoClaim {
+    /// Returns the ID to resume the scan from, `0-0` once complete, the claimed entries, or
+    /// only their IDs if `JUSTID` was given, and the IDs of the pending entries found
+    /// deleted from the stream.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        notify_consumer_created(backend, &self.key, &self.group, &self.consumer);
+        match backend.xautoclaim(
+            &self.key,
+            &self.group,
+            &self.consumer,
+            self.min_idle,
+            self.start,
+            self.count,
+            self.just_id,
+        ) {
+            Ok(Some(claim)) => {
+                let claimed = if self.just_id {
+                    ids_reply(claim.claimed.into_iter().map(|(id, _)| id))
+                } else {
+                    entries_reply(claim.claimed)
+                };
+                RespArray::new([
+                    BulkString::new(claim.next.to_string()).into(),
+                    claimed,
+                    ids_reply(claim.deleted),
+                ])
+                .into()
+            }
+            Ok(None) => no_such_key_or_group(&self.key, &self.group).into(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for XInfo {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let ret = match self.subcommand {
+            XInfoSubcommand::Stream => backend
+                .xinfo_stream(&self.key)
+                .map(|info| info.map(stream_info_reply)),
+            XInfoSubcommand::Groups => backend.xinfo_groups(&self.key).map(|groups| {
+                groups.map(|groups| {
+                    RespArray::new(groups.into_iter().map(group_info_reply).collect::<Vec<_>>())
+                        .into()
+                })
+            }),
+            XInfoSubcommand::Consumers(group) => match backend.xinfo_consumers(&self.key, &group) {
+                Ok(None) if backend.contains_key(&self.key) => {
+                    return no_such_group(&self.key, &group).into()
+                }
+                ret => ret.map(|consumers| {
+                    consumers.map(|consumers| {
+                        RespArray::new(
+                            consumers
+                                .into_iter()
+                                .map(consumer_info_reply)
+                                .collect::<Vec<_>>(),
+                        )
+                        .into()
+                    })
+                }),
+            },
+        };
+        match ret {
+            Ok(Some(frame)) => frame,
+            Ok(None) => CommandError::NoSuchKey.into(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+fn stream_info_reply(info: StreamInfo) -> RespFrame {
+    let entry = |entry: Option<(StreamId, StreamFields)>| match entry {
+        Some((id, fields)) => entry_reply(id, Some(fields)),
+        None => RespFrame::Null(RespNull),
+    };
+    let mut map = RespMap::new();
+    map.insert("length".into(), RespFrame::Integer(info.length as i64));
+    map.insert(
+        "last-generated-id".into(),
+        BulkString::new(info.last_generated_id.to_string()).into(),
+    );
+    map.insert(
+        "max-deleted-entry-id".into(),
+        BulkString::new(info.max_deleted_id.to_string()).into(),
+    );
+    map.insert(
+        "entries-added".into(),
+        RespFrame::Integer(info.entries_added as i64),
+    );
+    map.insert(
+        "recorded-first-entry-id".into(),
+        BulkString::new(info.recorded_first_id.to_string()).into(),
+    );
+    map.insert("groups".into(), RespFrame::Integer(info.groups as i64));
+    map.insert("first-entry".into(), entry(info.first_entry));
+    map.insert("last-entry".into(), entry(info.last_entry));
+    map.into()
+}
+
+/// Replies with an integer, or a null reply if the value is unknown.
+fn optional_integer(value: Option<u64>) -> RespFrame {
+    match value {
+        Some(value) => RespFrame::Integer(value as i64),
+        None => RespFrame::Null(RespNull),
+    }
+}
+
+fn group_info_reply(info: GroupInfo) -> RespFrame {
+    let mut map = RespMap::new();
+    map.insert("name".into(), BulkString::new(info.name).into());
+    map.insert(
+        "consumers".into(),
+        RespFrame::Integer(info.consumers as i64),
+    );
+    map.insert("pending".into(), RespFrame::Integer(info.pending as i64));
+    map.insert(
+        "last-delivered-id".into(),
+        BulkString::new(info.last_delivered_id.to_string()).into(),
+    );
+    map.insert("entries-read".into(), optional_integer(info.entries_read));
+    map.insert("lag".into(), optional_integer(info.lag));
+    map.into()
+}
+
+fn consumer_info_reply(info: ConsumerInfo) -> RespFrame {
+    let mut map = RespMap::new();
+    map.insert("name".into(), BulkString::new(info.name).into());
+    map.insert("pending".into(), RespFrame::Integer(info.pending as i64));
+    map.insert("idle".into(), RespFrame::Integer(info.idle as i64));
+    // -1 for a consumer which never had an entry delivered
+    map.insert(
+        "inactive".into(),
+        RespFrame::Integer(info.inactive.map_or(-1, |inactive| inactive as i64)),
+    );
+    map.into()
+}
+
+fn range(
+    backend: &Backend,
+    key: &[u8],
+    start: StreamId,
+    end: StreamId,
+    rev: bool,
+    count: Option<usize>,
+) -> RespFrame {
+    if count == Some(0) {
+        return RespFrame::NullArray(RespNullArray);
+    }
+    match backend.xrange(key, start, end, rev, count) {
+        Ok(entries) => entries_reply(entries),
+        Err(e) => e.into(),
+    }
+}
+
+/// Builds the reply of an entry: its ID followed by an array of its fields and values, or
+/// a null reply in place of the array if the entry was deleted.
+pub(super) fn entry_reply(id: StreamId, fields: Option<StreamFields>) -> RespFrame {
+    let fields = match fields {
+        Some(fields) => RespArray::new(
+            fields
+                .into_iter()
+                .flat_map(|(field, value)| {
+                    [BulkString::new(field).into(), BulkString::new(value).into()]
+                })
+                .collect::<Vec<RespFrame>>(),
+        )
+        .into(),
+        None => RespFrame::Null(RespNull),
+    };
+    RespArray::new([BulkString::new(id.to_string()).into(), fields]).into()
+}
+
+pub(super) fn entries_reply<F: Into<Option<StreamFields>>>(
+    entries: Vec<(StreamId, F)>,
+) -> RespFrame {
+    RespArray::new(
+        entries
+            .into_iter()
+            .map(|(id, fields)| entry_reply(id, fields.into()))
+            .collect::<Vec<_>>(),
+    )
+    .into()
+}
+
+/// Builds the reply of `XREAD` and `XREADGROUP`: an array of the key of each stream
+/// followed by its entries.
+pub(super) fn streams_reply<F: Into<Option<StreamFields>>>(
+    streams: Vec<(Bytes, Vec<(StreamId, F)>)>,
+) -> RespFrame {
+    RespArray::new(
+        streams
+            .into_iter()
+            .map(|(key, entries)| {
+                RespArray::new([BulkString::new(key).into(), entries_reply(entries)]).into()
+            })
+            .collect::<Vec<RespFrame>>(),
+    )
+    .into()
+}
+
+fn invalid_id() -> CommandError {
+    CommandError::InvalidArguments(
+        "Invalid stream ID specified as stream command argument".to_string(),
+    )
+}
+
+/// Parses an ID written `ms-seq`, or `ms` alone in which case the sequence number is
+/// `missing_seq`.
+pub(super) fn parse_stream_id(arg: &[u8], missing_seq: u64) -> Result<StreamId, CommandError> {
+    let number = |part: &[u8]| {
+        std::str::from_utf8(part)
+            .ok()
+            .and_then(|part| part.parse::<u64>().ok())
+            .ok_or_else(invalid_id)
+    };
+    match arg.iter().position(|&b| b == b'-') {
+        Some(dash) => Ok(StreamId {
+            ms: number(&arg[..dash])?,
+            seq: number(&arg[dash + 1..])?,
+        }),
+        None => Ok(StreamId {
+            ms: number(arg)?,
+            seq: missing_seq,
+        }),
+    }
+}
+
+/// Parses the start of a range of IDs: `-` for the lowest ID, or an ID prefixed with `(`
+/// to exclude it.
+pub(super) fn parse_range_start(arg: &[u8]) -> Result<StreamId, CommandError> {
+    match arg {
+        b"-" => Ok(StreamId::MIN),
+        [b'(', id @ ..] => parse_stream_id(id, 0)?.next().ok_or_else(|| {
+            CommandError::InvalidArguments("invalid start ID for the interval".to_string())
+        }),
+        id => parse_stream_id(id, 0),
+    }
+}
+
+/// Parses the end of a range of IDs: `+` for the greatest ID, or an ID prefixed with `(`
+/// to exclude it.
+pub(super) fn parse_range_end(arg: &[u8]) -> Result<StreamId, CommandError> {
+    match arg {
+        b"+" => Ok(StreamId::MAX),
+        [b'(', id @ ..] => parse_stream_id(id, u64::MAX)?.prev().ok_or_else(|| {
+            CommandError::InvalidArguments("invalid end ID for the interval".to_string())
+        }),
+        id => parse_stream_id(id, u64::MAX),
+    }
+}
+
+fn integer(arg: Bytes) -> Result<i64, CommandError> {
+    parse_integer(RespFrame::BulkString(BulkString::new(arg)))
+}
+
+/// Parses the `COUNT` of a range. A negative count is taken as `0`.
+fn parse_range_count(args: Vec<Bytes>) -> Result<Option<usize>, CommandError> {
+    match <[Bytes; 2]>::try_from(args) {
+        Ok([option, count]) if option.eq_ignore_ascii_case(b"count") => {
+            Ok(Some(integer(count)?.max(0) as usize))
+        }
+        Ok(_) => Err(CommandError::SyntaxError),
+        Err(args) if args.is_empty() => Ok(None),
+        Err(_) => Err(CommandError::SyntaxError),
+    }
+}
+
+/// Parses the options shared by `XADD` and `XTRIM`, in any order, up to the first
+/// argument which is not one of them.
+///
+/// Returns whether `NOMKSTREAM` was given, which only `XADD` accepts, and how to trim the
+/// stream if at all.
+fn parse_trim_options(
+    args: &mut Peekable<vec::IntoIter<Bytes>>,
+    xadd: bool,
+) -> Result<(bool, Option<StreamTrim>), CommandError> {
+    let mut no_mkstream = false;
+    let mut strategy = None;
+    let mut approximate = false;
+    let mut limit = None;
+    while let Some(option) = args.peek() {
+        let option = option.to_ascii_lowercase();
+        match option.as_slice() {
+            b"nomkstream" if xadd => no_mkstream = true,
+            b"maxlen" | b"minid" => {
+                args.next();
+                match args.peek().map(|arg| arg.as_ref()) {
+                    Some(b"~") => {
+                        approximate = true;
+                        args.next();
+                    }
+                    Some(b"=") => {
+                        args.next();
+                    }
+                    _ => {}
+                }
+                let threshold = args.peek().cloned().ok_or(CommandError::SyntaxError)?;
+                let parsed = if option == b"maxlen" {
+                    let max_len = integer(threshold)?;
+                    TrimStrategy::MaxLen(usize::try_from(max_len).map_err(|_| {
+                        CommandError::InvalidArguments(
+                            "The MAXLEN argument must be >= 0.".to_string(),
+                        )
+                    })?)
+                } else {
+                    TrimStrategy::MinId(parse_stream_id(&threshold, 0)?)
+                };
+                if strategy.is_some_and(|strategy| {
+                    std::mem::discriminant(&strategy) != std::mem::discriminant(&parsed)
+                }) {
+                    return Err(CommandError::InvalidArguments(
+                        "syntax error, MAXLEN and MINID options at the same time are not \
+                         compatible"
+                            .to_string(),
+                    ));
+                }
+                strategy = Some(parsed);
+            }
+            b"limit" => {
+                args.next();
+                let count = integer(args.peek().cloned().ok_or(CommandError::SyntaxError)?)?;
+                limit = Some(usize::try_from(count).map_err(|_| {
+                    CommandError::InvalidArguments("The LIMIT argument must be >= 0.".to_string())
+                })?);
+            }
+            _ if xadd => break,
+            _ => return Err(CommandError::SyntaxError),
+        }
+        args.next();
+    }
+    if limit.is_some() && !approximate {
+        return Err(CommandError::InvalidArguments(
+            "syntax error, LIMIT cannot be used without the special ~ option".to_string(),
+        ));
+    }
+    let trim = strategy.map(|strategy| StreamTrim {
+        strategy,
+        approximate,
+        limit,
+    });
+    Ok((no_mkstream, trim))
+}
+
+impl TryFrom<RespArray> for XAdd {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, args) = parse_key_and_args(value)?;
+        let mut args = args.into_iter().peekable();
+        let (no_mkstream, trim) = parse_trim_options(&mut args, true)?;
+        let id = args
+            .next()
+            .ok_or_else(|| CommandError::WrongArity("xadd".to_string()))?;
+        let id = match id.as_ref() {
+            b"*" => XAddId::Auto,
+            [ms @ .., b'-', b'*'] if !ms.contains(&b'-') => {
+                XAddId::AutoSeq(parse_stream_id(ms, 0)?.ms)
+            }
+            id => match parse_stream_id(id, 0)? {
+                StreamId::MIN => {
+                    return Err(CommandError::InvalidArguments(
+                        "The ID specified in XADD must be greater than 0-0".to_string(),
+                    ))
+                }
+                id => XAddId::Explicit(id),
+            },
+        };
+        let args = args.collect::<Vec<_>>();
+        if args.is_empty() || args.len() % 2 != 0 {
+            return Err(CommandError::WrongArity("xadd".to_string()));
+        }
+        let fields = args
+            .chunks_exact(2)
+            .map(|pair| (pair[0].clone(), pair[1].clone()))
+            .collect();
+        Ok(XAdd {
+            key,
+            create: !no_mkstream,
+            trim,
+            id,
+            fields,
+        })
+    }
+}
+
+/// Parses a key followed by a range of IDs, bounds first if `rev` is `false`, and an
+/// optional count.
+fn parse_range(value: RespArray, rev: bool) -> Result<XRange, CommandError> {
+    let (key, mut args) = parse_key_and_args(value)?;
+    if args.len() < 2 {
+        return Err(CommandError::SyntaxError);
+    }
+    let rest = args.split_off(2);
+    let (start, end) = if rev {
+        (&args[1], &args[0])
+    } else {
+        (&args[0], &args[1])
+    };
+    Ok(XRange {
+        key,
+        start: parse_range_start(start)?,
+        end: parse_range_end(end)?,
+        count: parse_range_count(rest)?,
+    })
+}
+
+impl TryFrom<RespArray> for XRange {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        parse_range(value, false)
+    }
+}
+
+impl TryFrom<RespArray> for XRevRange {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let XRange {
+            key,
+            start,
+            end,
+            count,
+        } = parse_range(value, true)?;
+        Ok(XRevRange {
+            key,
+            start,
+            end,
+            count,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for XLen {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, args) = parse_key_and_args(value)?;
+        if !args.is_empty() {
+            return Err(CommandError::WrongArity("xlen".to_string()));
+        }
+        Ok(XLen { key })
+    }
+}
+
+impl TryFrom<RespArray> for XTrim {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, args) = parse_key_and_args(value)?;
+        let mut args = args.into_iter().peekable();
+        match parse_trim_options(&mut args, false)? {
+            (_, Some(trim)) => Ok(XTrim { key, trim }),
+            (_, None) => Err(CommandError::SyntaxError),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for XDel {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, args) = parse_key_and_args(value)?;
+        let ids = args
+            .iter()
+            .map(|id| parse_stream_id(id, 0))
+            .collect::<Result<_, _>>()?;
+        Ok(XDel { key, ids })
+    }
+}
+
+/// Splits the arguments following `STREAMS` into the keys and the IDs given for them.
+pub(super) fn parse_streams(
+    args: Vec<Bytes>,
+    name: &str,
+) -> Result<(Vec<Bytes>, Vec<Bytes>), CommandError> {
+    if args.is_empty() || !args.len().is_multiple_of(2) {
+        return Err(CommandError::InvalidArguments(format!(
+            "Unbalanced '{}' list of streams: for each stream key an ID or '$' must be \
+             specified.",
+            name
+        )));
+    }
+    let mut keys = args;
+    let ids = keys.split_off(keys.len() / 2);
+    Ok((keys, ids))
+}
+
+/// Parses the `BLOCK` timeout in milliseconds. `0` means waiting forever and is returned
+/// as `None`.
+pub(super) fn parse_block_timeout(arg: Bytes) -> Result<Option<Duration>, CommandError> {
+    let timeout = integer(arg).map_err(|_| {
+        CommandError::InvalidArguments("timeout is not an integer or out of range".to_string())
+    })?;
+    match u64::try_from(timeout) {
+        Ok(0) => Ok(None),
+        Ok(timeout) => Ok(Some(Duration::from_millis(timeout))),
+        Err(_) => Err(CommandError::InvalidArguments(
+            "timeout is negative".to_string(),
+        )),
+    }
+}
+
+/// The arguments shared by `XREAD` and `XREADGROUP`.
+struct ReadArgs {
+    count: Option<usize>,
+    block: bool,
+    timeout: Option<Duration>,
+    /// `GROUP group consumer`, only accepted by `XREADGROUP`.
+    group: Option<(Bytes, Bytes)>,
+    noack: bool,
+    keys: Vec<Bytes>,
+    ids: Vec<Bytes>,
+}
+
+/// Parses the options of `XREAD`, or `XREADGROUP` if `group` is `true`, in any order, up
+/// to `STREAMS` and the keys and IDs following it.
+fn parse_read_args(value: RespArray, group: bool) -> Result<ReadArgs, CommandError> {
+    let mut args = bulk_string_args(value)?.into_iter();
+    let mut read = ReadArgs {
+        count: None,
+        block: false,
+        timeout: None,
+        group: None,
+        noack: false,
+        keys: Vec::new(),
+        ids: Vec::new(),
+    };
+    let mut next = || args.next().ok_or(CommandError::SyntaxError);
+    loop {
+        let option = next()?.to_ascii_lowercase();
+        match option.as_slice() {
+            b"count" => {
+                let n = integer(next()?)?;
+                read.count = usize::try_from(n).ok().filter(|&n| n > 0);
+            }
+            b"block" => {
+                read.block = true;
+                read.timeout = parse_block_timeout(next()?)?;
+            }
+            b"group" if group => read.group = Some((next()?, next()?)),
+            b"noack" if group => read.noack = true,
+            b"streams" => break,
+            _ => return Err(CommandError::SyntaxError),
+        }
+    }
+    let name = if group { "xreadgroup" } else { "xread" };
+    (read.keys, read.ids) = parse_streams(args.collect(), name)?;
+    Ok(read)
+}
+
+impl TryFrom<RespArray> for XRead {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let read = parse_read_args(value, false)?;
+        let ids = read
+            .ids
+            .iter()
+            .map(|id| match id.as_ref() {
+                b"$" => Ok(None),
+                id => parse_stream_id(id, 0).map(Some),
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(XRead {
+            count: read.count,
+            block: read.block,
+            timeout: read.timeout,
+            keys: read.keys,
+            ids,
+        })
+    }
+}
+
+/// Parses the ID given to `XGROUP CREATE` or `XGROUP SETID`, `None` for `$`.
+fn parse_group_id(arg: &[u8]) -> Result<Option<StreamId>, CommandError> {
+    match arg {
+        b"$" => Ok(None),
+        id => parse_stream_id(id, 0).map(Some),
+    }
+}
+
+/// Parses the options of `XGROUP CREATE`, or `XGROUP SETID` which doesn't accept
+/// `MKSTREAM`.
+///
+/// Returns whether `MKSTREAM` was given, and the number of entries read by the group if
+/// known.
+fn parse_group_options(
+    options: &[Bytes],
+    create: bool,
+) -> Result<(bool, Option<u64>), CommandError> {
+    let mut mkstream = false;
+    let mut entries_read = None;
+    let mut options = options.iter();
+    while let Some(option) = options.next() {
+        if create && option.eq_ignore_ascii_case(b"mkstream") {
+            mkstream = true;
+        } else if option.eq_ignore_ascii_case(b"entriesread") {
+            let n = integer(options.next().cloned().ok_or(CommandError::SyntaxError)?)?;
+            entries_read = match n {
+                -1 => None,
+                n => Some(u64::try_from(n).map_err(|_| {
+                    CommandError::InvalidArguments(
+                        "value for ENTRIESREAD must be positive or -1".to_string(),
+                    )
+                })?),
+            };
+        } else {
+            return Err(CommandError::SyntaxError);
+        }
+    }
+    Ok((mkstream, entries_read))
+}
+
+impl TryFrom<RespArray> for XGroup {
+    type Error = CommandError;
+    /// Converts a RESP array into an `XGroup` command.
+    ///
+    /// The first argument selects the subcommand: `CREATE`, `SETID`, `DESTROY`,
+    /// `CREATECONSUMER` or `DELCONSUMER`, followed by the key and the group.
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = bulk_string_args(value)?;
+        if args.is_empty() {
+            return Err(CommandError::WrongArity("xgroup".to_string()));
+        }
+        let name = args.remove(0).to_ascii_lowercase();
+        let (key, group, subcommand) = match (name.as_slice(), args.as_slice()) {
+            (b"create", [key, group, id, options @ ..]) => {
+                let (mkstream, entries_read) = parse_group_options(options, true)?;
+                let subcommand = XGroupSubcommand::Create {
+                    id: parse_group_id(id)?,
+                    mkstream,
+                    entries_read,
+                };
+                (key, group, subcommand)
+            }
+            (b"setid", [key, group, id, options @ ..]) => {
+                let (_, entries_read) = parse_group_options(options, false)?;
+                let subcommand = XGroupSubcommand::SetId {
+                    id: parse_group_id(id)?,
+                    entries_read,
+                };
+                (key, group, subcommand)
+            }
+            (b"destroy", [key, group]) => (key, group, XGroupSubcommand::Destroy),
+            (b"createconsumer", [key, group, consumer]) => (
+                key,
+                group,
+                XGroupSubcommand::CreateConsumer(consumer.clone()),
+            ),
+            (b"delconsumer", [key, group, consumer]) => {
+                (key, group, XGroupSubcommand::DelConsumer(consumer.clone()))
+            }
+            (b"create" | b"setid" | b"destroy" | b"createconsumer" | b"delconsumer", _) => {
+                return Err(CommandError::WrongArity(format!(
+                    "xgroup|{}",
+                    String::from_utf8_lossy(&name)
+                )))
+            }
+            _ => {
+                return Err(CommandError::InvalidArguments(format!(
+                    "unknown subcommand '{}'. Try XGROUP HELP.",
+                    String::from_utf8_lossy(&name)
+                )))
+            }
+        };
+        Ok(XGroup {
+            key: key.clone(),
+            group: group.clone(),
+            subcommand,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for XReadGroup {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let read = parse_read_args(value, true)?;
+        let Some((group, consumer)) = read.group else {
+            return Err(CommandError::InvalidArguments(
+                "Missing GROUP option for XREADGROUP".to_string(),
+            ));
+        };
+        let ids = read
+            .ids
+            .iter()
+            .map(|id| match id.as_ref() {
+                b">" => Ok(None),
+                b"$" => Err(CommandError::InvalidArguments(
+                    "The $ ID is meaningless in the context of XREADGROUP: you want to read the \
+                     history of this consumer by specifying a proper ID, or use the > ID to get \
+                     new messages. The $ ID would just return an empty result set."
+                        .to_string(),
+                )),
+                id => parse_stream_id(id, 0).map(Some),
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(XReadGroup {
+            group,
+            consumer,
+            count: read.count,
+            block: read.block,
+            timeout: read.timeout,
+            noack: read.noack,
+            keys: read.keys,
+            ids,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for XAck {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, mut args) = parse_key_and_args(value)?;
+        if args.len() < 2 {
+            return Err(CommandError::WrongArity("xack".to_string()));
+        }
+        let group = args.remove(0);
+        let ids = args
+            .iter()
+            .map(|id| parse_stream_id(id, 0))
+            .collect::<Result<_, _>>()?;
+        Ok(XAck { key, group, ids })
+    }
+}
+
+/// Parses the range of `XPENDING`: `start end count [consumer]`. A negative count is taken
+/// as `0`.
+fn parse_pending_filter(args: &[Bytes], min_idle: u64) -> Result<PendingFilter, CommandError> {
+    match args {
+        [start, end, count, consumer @ ..] if consumer.len() <= 1 => Ok(PendingFilter {
+            start: parse_range_start(start)?,
+            end: parse_range_end(end)?,
+            count: integer(count.clone())?.max(0) as usize,
+            consumer: consumer.first().cloned(),
+            min_idle,
+        }),
+        _ => Err(CommandError::SyntaxError),
+    }
+}
+
+impl TryFrom<RespArray> for XPending {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, mut args) = parse_key_and_args(value)?;
+        if args.is_empty() {
+            return Err(CommandError::WrongArity("xpending".to_string()));
+        }
+        let group = args.remove(0);
+        let filter = match args.as_slice() {
+            [] => None,
+            [option, min_idle, range @ ..] if option.eq_ignore_ascii_case(b"idle") => {
+                let min_idle = integer(min_idle.clone())?.max(0) as u64;
+                Some(parse_pending_filter(range, min_idle)?)
+            }
+            range => Some(parse_pending_filter(range, 0)?),
+        };
+        Ok(XPending { key, group, filter })
+    }
+}
+
+/// Parses the minimum idle time of `XCLAIM` or `XAUTOCLAIM`. A negative time is taken as
+/// `0`.
+fn parse_min_idle(arg: Option<Bytes>, name: &str) -> Result<u64, CommandError> {
+    let min_idle = arg
+        .ok_or_else(|| CommandError::WrongArity(name.to_ascii_lowercase()))
+        .and_then(|arg| {
+            integer(arg).map_err(|_| {
+                CommandError::InvalidArguments(format!(
+                    "Invalid min-idle-time argument for {}",
+                    name
+                ))
+            })
+        })?;
+    Ok(min_idle.max(0) as u64)
+}
+
+impl TryFrom<RespArray> for XClaim {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, args) = parse_key_and_args(value)?;
+        let mut args = args.into_iter().peekable();
+        let arity = || CommandError::WrongArity("xclaim".to_string());
+        let group = args.next().ok_or_else(arity)?;
+        let consumer = args.next().ok_or_else(arity)?;
+        let min_idle = parse_min_idle(args.next(), "XCLAIM")?;
+        // the IDs go up to the first argument which is not an ID
+        let mut ids = Vec::new();
+        while let Some(id) = args.peek().and_then(|id| parse_stream_id(id, 0).ok()) {
+            ids.push(id);
+            args.next();
+        }
+        let mut options = ClaimOptions::default();
+        while let Some(option) = args.next() {
+            let mut value = |name: &str| {
+                args.next()
+                    .ok_or(CommandError::SyntaxError)
+                    .and_then(|arg| {
+                        integer(arg).map_err(|_| {
+                            CommandError::InvalidArguments(format!(
+                                "Invalid {} option argument for XCLAIM",
+                                name
+                            ))
+                        })
+                    })
+            };
+            match option.to_ascii_lowercase().as_slice() {
+                b"force" => options.force = true,
+                b"justid" => options.just_id = true,
+                // the last of IDLE and TIME wins
+                b"idle" => {
+                    options.idle = Some(value("IDLE")?.max(0) as u64);
+                    options.time = None;
+                }
+                b"time" => {
+                    options.time = u64::try_from(value("TIME")?).ok();
+                    options.idle = None;
+                }
+                b"retrycount" => options.retry_count = Some(value("RETRYCOUNT")?.max(0) as u64),
+                b"lastid" => {
+                    let id = args.next().ok_or(CommandError::SyntaxError)?;
+                    options.last_id = Some(parse_stream_id(&id, 0)?);
+                }
+                _ => {
+                    return Err(CommandError::InvalidArguments(format!(
+                        "Unrecognized XCLAIM option '{}'",
+                        String::from_utf8_lossy(&option)
+                    )))
+                }
+            }
+        }
+        Ok(XClaim {
+            key,
+            group,
+            consumer,
+            min_idle,
+            ids,
+            options,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for XAutoClaim {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, args) = parse_key_and_args(value)?;
+        let mut args = args.into_iter();
+        let arity = || CommandError::WrongArity("xautoclaim".to_string());
+        let group = args.next().ok_or_else(arity)?;
+        let consumer = args.next().ok_or_else(arity)?;
+        let min_idle = parse_min_idle(args.next(), "XAUTOCLAIM")?;
+        let start = parse_range_start(&args.next().ok_or_else(arity)?)?;
+        let mut count = 100;
+        let mut just_id = false;
+        while let Some(option) = args.next() {
+            if option.eq_ignore_ascii_case(b"count") {
+                let n = integer(args.next().ok_or(CommandError::SyntaxError)?)?;
+                // up to ten times the count of pending entries are scanned
+                count = usize::try_from(n)
+                    .ok()
+                    .filter(|&n| n > 0 && n <= usize::MAX / 10)
+                    .ok_or_else(|| {
+                        CommandError::InvalidArguments("COUNT must be > 0".to_string())
+                    })?;
+            } else if option.eq_ignore_ascii_case(b"justid") {
+                just_id = true;
+            } else {
+                return Err(CommandError::SyntaxError);
+            }
+        }
+        Ok(XAutoClaim {
+            key,
+            group,
+            consumer,
+            min_idle,
+            start,
+            count,
+            just_id,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for XInfo {
+    type Error = CommandError;
+    /// Converts a RESP array into an `XInfo` command.
+    ///
+    /// The first argument selects the subcommand: `STREAM`, `GROUPS` or `CONSUMERS`,
+    /// followed by the key.
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = bulk_string_args(value)?;
+        if args.is_empty() {
+            return Err(CommandError::WrongArity("xinfo".to_string()));
+        }
+        let name = args.remove(0).to_ascii_lowercase();
+        let (key, subcommand) = match (name.as_slice(), args.as_slice()) {
+            (b"stream", [key]) => (key, XInfoSubcommand::Stream),
+            (b"stream", [_, _, ..]) => return Err(CommandError::SyntaxError),
+            (b"groups", [key]) => (key, XInfoSubcommand::Groups),
+            (b"consumers", [key, group]) => (key, XInfoSubcommand::Consumers(group.clone())),
+            (b"stream" | b"groups" | b"consumers", _) => {
+                return Err(CommandError::WrongArity(format!(
+                    "xinfo|{}",
+                    String::from_utf8_lossy(&name)
+                )))
+            }
+            _ => {
+                return Err(CommandError::InvalidArguments(format!(
+                    "unknown subcommand '{}'. Try XINFO HELP.",
+                    String::from_utf8_lossy(&name)
+                )))
+            }
+        };
+        Ok(XInfo {
+            key: key.clone(),
+            subcommand,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::{CommandRequest, Session};
+
+    fn args(args: &[&str]) -> RespArray {
+        RespArray::new(
+            args.iter()
+                .map(|arg| BulkString::new(*arg).into())
+                .collect::<Vec<RespFrame>>(),
+        )
+    }
+
+    fn request(args_: &[&str]) -> CommandRequest {
+        CommandRequest::try_from(RespFrame::Array(args(args_))).unwrap()
+    }
+
+    fn bulk(s: &str) -> RespFrame {
+        BulkString::new(s).into()
+    }
+
+    fn id(ms: u64, seq: u64) -> StreamId {
+        StreamId { ms, seq }
+    }
+
+    fn entry(id: &str, fields: &[&str]) -> RespFrame {
+        let fields = fields.iter().map(|field| bulk(field)).collect::<Vec<_>>();
+        RespArray::new([bulk(id), RespArray::new(fields).into()]).into()
+    }
+
+    fn xadd(backend: &Backend, cmd: &[&str]) -> RespFrame {
+        XAdd::try_from(args(cmd)).unwrap().execute(backend)
+    }
+
+    #[test]
+    fn test_parse_stream_ids() {
+        assert_eq!(parse_stream_id(b"5-3", 0).unwrap(), id(5, 3));
+        assert_eq!(parse_stream_id(b"5", 7).unwrap(), id(5, 7));
+        let err = parse_stream_id(b"5-x", 0).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "ERR Invalid stream ID specified as stream command argument"
+        );
+        assert!(parse_stream_id(b"-5", 0).is_err());
+        assert_eq!(parse_range_start(b"(5").unwrap(), id(5, 1));
+        assert_eq!(parse_range_end(b"5").unwrap(), id(5, u64::MAX));
+        assert_eq!(parse_range_end(b"(5-0").unwrap(), id(4, u64::MAX));
+        let err = parse_range_end(b"(0-0").unwrap_err();
+        assert_eq!(err.to_string(), "ERR invalid end ID for the interval");
+    }
+
+    #[test]
+    fn test_xadd_from_resp_array() {
+        let cmd = XAdd::try_from(args(&[
+            "xadd",
+            "s",
+            "NOMKSTREAM",
+            "maxlen",
+            "~",
+            "10",
+            "limit",
+            "5",
+            "7-*",
+            "f",
+            "v",
+        ]))
+        .unwrap();
+        assert!(!cmd.create);
+        assert_eq!(
+            cmd.trim,
+            Some(StreamTrim {
+                strategy: TrimStrategy::MaxLen(10),
+                approximate: true,
+                limit: Some(5),
+            })
+        );
+        assert_eq!(cmd.id, XAddId::AutoSeq(7));
+        assert_eq!(cmd.fields, vec![(Bytes::from("f"), Bytes::from("v"))]);
+
+        let err = XAdd::try_from(args(&["xadd", "s", "0-0", "f", "v"])).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "ERR The ID specified in XADD must be greater than 0-0"
+        );
+        let err = XAdd::try_from(args(&["xadd", "s", "*", "f"])).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "ERR wrong number of arguments for 'xadd' command"
+        );
+        let err = XAdd::try_from(args(&[
+            "xadd", "s", "maxlen", "5", "limit", "5", "*", "f", "v",
+        ]))
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "ERR syntax error, LIMIT cannot be used without the special ~ option"
+        );
+        let err = XTrim::try_from(args(&["xtrim", "s", "maxlen", "-1"])).unwrap_err();
+        assert_eq!(err.to_string(), "ERR The MAXLEN argument must be >= 0.");
+        assert!(matches!(
+            XTrim::try_from(args(&["xtrim", "s", "nomkstream", "maxlen", "1"])),
+            Err(CommandError::SyntaxError)
+        ));
+    }
+
+    #[test]
+    fn test_xadd_and_ranges() {
+        let backend = Backend::new();
+        assert_eq!(
+            xadd(&backend, &["xadd", "s", "nomkstream", "*", "f", "v"]),
+            RespFrame::Null(RespNull)
+        );
+        assert_eq!(
+            xadd(&backend, &["xadd", "s", "1-1", "a", "1", "b", "2"]),
+            bulk("1-1")
+        );
+        assert_eq!(xadd(&backend, &["xadd", "s", "1-*", "a", "2"]), bulk("1-2"));
+        assert_eq!(xadd(&backend, &["xadd", "s", "3", "a", "3"]), bulk("3-0"));
+        let err = xadd(&backend, &["xadd", "s", "2-*", "a", "4"]);
+        assert!(matches!(err, RespFrame::Error(_)));
+
+        let xrange = |cmd: &[&str]| XRange::try_from(args(cmd)).unwrap().execute(&backend);
+        assert_eq!(
+            xrange(&["xrange", "s", "-", "+"]),
+            RespArray::new([
+                entry("1-1", &["a", "1", "b", "2"]),
+                entry("1-2", &["a", "2"]),
+                entry("3-0", &["a", "3"]),
+            ])
+            .into()
+        );
+        assert_eq!(
+            xrange(&["xrange", "s", "(1-1", "1"]),
+            RespArray::new([entry("1-2", &["a", "2"])]).into()
+        );
+        assert_eq!(
+            xrange(&["xrange", "s", "-", "+", "count", "0"]),
+            RespFrame::NullArray(RespNullArray)
+        );
+        let ret = XRevRange::try_from(args(&["xrevrange", "s", "+", "-", "COUNT", "1"]))
+            .unwrap()
+            .execute(&backend);
+        assert_eq!(ret, RespArray::new([entry("3-0", &["a", "3"])]).into());
+
+        let ret = XDel::try_from(args(&["xdel", "s", "1-2", "9-9"]))
+            .unwrap()
+            .execute(&backend);
+        assert_eq!(ret, RespFrame::Integer(1));
+        let ret = XTrim::try_from(args(&["xtrim", "s", "minid", "=", "2"]))
+            .unwrap()
+            .execute(&backend);
+        assert_eq!(ret, RespFrame::Integer(1));
+        let ret = XLen::try_from(args(&["xlen", "s"]))
+            .unwrap()
+            .execute(&backend);
+        assert_eq!(ret, RespFrame::Integer(1));
+    }
+
+    #[test]
+    fn test_xread_from_resp_array() {
+        let cmd = XRead::try_from(args(&[
+            "xread", "count", "2", "block", "0", "streams", "a", "b", "$", "1",
+        ]))
+        .unwrap();
+        assert_eq!(cmd.count, Some(2));
+        assert!(cmd.block);
+        assert_eq!(cmd.timeout, None);
+        assert_eq!(cmd.keys, vec![Bytes::from("a"), Bytes::from("b")]);
+        assert_eq!(cmd.ids, vec![None, Some(id(1, 0))]);
+
+        let err = XRead::try_from(args(&["xread", "streams", "a", "b", "0"])).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "ERR Unbalanced 'xread' list of streams: for each stream key an ID or '$' must be specified."
+        );
+        let err =
+            XRead::try_from(args(&["xread", "block", "-1", "streams", "a", "0"])).unwrap_err();
+        assert_eq!(err.to_string(), "ERR timeout is negative");
+    }
+
+    #[tokio::test]
+    async fn test_xread() {
+        let backend = Backend::new();
+        xadd(&backend, &["xadd", "a", "1", "f", "1"]);
+        xadd(&backend, &["xadd", "b", "1", "f", "2"]);
+        let ret = request(&["xread", "streams", "a", "b", "c", "0", "1", "0"])
+            .run(&backend, &mut Session::new())
+            .await;
+        assert_eq!(
+            ret,
+            RespArray::new([RespFrame::from(RespArray::new([
+                bulk("a"),
+                RespArray::new([entry("1-0", &["f", "1"])]).into(),
+            ]))])
+            .into()
+        );
+        // without BLOCK, `$` never returns anything
+        let ret = request(&["xread", "streams", "a", "$"])
+            .run(&backend, &mut Session::new())
+            .await;
+        assert_eq!(ret, RespFrame::NullArray(RespNullArray));
+        let ret = request(&["xread", "block", "10", "streams", "a", "$"])
+            .run(&backend, &mut Session::new())
+            .await;
+        assert_eq!(ret, RespFrame::NullArray(RespNullArray));
+    }
+
+    #[tokio::test]
+    async fn test_blocked_readers_are_all_woken_by_xadd() {
+        let backend = Backend::new();
+        xadd(&backend, &["xadd", "s", "1", "f", "old"]);
+        let readers = (0..2)
+            .map(|_| {
+                tokio::spawn({
+                    let backend = backend.clone();
+                    async move {
+                        request(&["xread", "block", "0", "streams", "s", "$"])
+                            .run(&backend, &mut Session::new())
+                            .await
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        request(&["xadd", "s", "2", "f", "new"])
+            .run(&backend, &mut Session::new())
+            .await;
+        for reader in readers {
+            assert_eq!(
+                reader.await.unwrap(),
+                RespArray::new([RespFrame::from(RespArray::new([
+                    bulk("s"),
+                    RespArray::new([entry("2-0", &["f", "new"])]).into(),
+                ]))])
+                .into()
+            );
+        }
+    }
+
+    fn run(backend: &Backend, cmd: &[&str]) -> RespFrame {
+        CommandRequest::try_from(RespFrame::Array(args(cmd)))
+            .unwrap()
+            .execute(backend)
+    }
+
+    fn error(frame: RespFrame) -> String {
+        match frame {
+            RespFrame::Error(e) => e.0,
+            frame => panic!("expected an error, got {:?}", frame),
+        }
+    }
+
+    #[test]
+    fn test_xgroup_from_resp_array() {
+        let cmd = XGroup::try_from(args(&[
+            "xgroup",
+            "CREATE",
+            "s",
+            "g",
+            "$",
+            "mkstream",
+            "entriesread",
+            "3",
+        ]))
+        .unwrap();
+        assert_eq!(
+            cmd.subcommand,
+            XGroupSubcommand::Create {
+                id: None,
+                mkstream: true,
+                entries_read: Some(3),
+            }
+        );
+        let cmd = XGroup::try_from(args(&["xgroup", "setid", "s", "g", "5"])).unwrap();
+        assert_eq!(
+            cmd.subcommand,
+            XGroupSubcommand::SetId {
+                id: Some(id(5, 0)),
+                entries_read: None,
+            }
+        );
+        assert!(matches!(
+            XGroup::try_from(args(&["xgroup", "setid", "s", "g", "5", "mkstream"])),
+            Err(CommandError::SyntaxError)
+        ));
+        let err = XGroup::try_from(args(&["xgroup", "destroy", "s"])).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "ERR wrong number of arguments for 'xgroup|destroy' command"
+        );
+        let err = XGroup::try_from(args(&["xgroup", "foo", "s", "g"])).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "ERR unknown subcommand 'foo'. Try XGROUP HELP."
+        );
+    }
+
+    #[test]
+    fn test_xgroup() {
+        let backend = Backend::new();
+        let ret = run(&backend, &["xgroup", "create", "s", "g", "$"]);
+        assert!(error(ret).starts_with("ERR The XGROUP subcommand requires the key to exist."));
+        assert_eq!(
+            run(&backend, &["xgroup", "create", "s", "g", "$", "mkstream"]),
+            RESP_OK.clone()
+        );
+        assert_eq!(
+            error(run(&backend, &["xgroup", "create", "s", "g", "0"])),
+            "BUSYGROUP Consumer Group name already exists"
+        );
+        assert_eq!(
+            error(run(&backend, &["xgroup", "setid", "s", "x", "0"])),
+            "NOGROUP No such consumer group 'x' for key name 's'"
+        );
+        assert_eq!(
+            run(&backend, &["xgroup", "createconsumer", "s", "g", "c"]),
+            RespFrame::Integer(1)
+        );
+        assert_eq!(
+            run(&backend, &["xgroup", "createconsumer", "s", "g", "c"]),
+            RespFrame::Integer(0)
+        );
+        assert_eq!(
+            run(&backend, &["xgroup", "delconsumer", "s", "g", "c"]),
+            RespFrame::Integer(0)
+        );
+        assert_eq!(
+            run(&backend, &["xgroup", "destroy", "s", "g"]),
+            RespFrame::Integer(1)
+        );
+        assert_eq!(
+            run(&backend, &["xgroup", "destroy", "s", "g"]),
+            RespFrame::Integer(0)
+        );
+    }
+
+    #[test]
+    fn test_xreadgroup_from_resp_array() {
+        let cmd = XReadGroup::try_from(args(&[
+            "xreadgroup",
+            "group",
+            "g",
+            "c",
+            "noack",
+            "count",
+            "2",
+            "streams",
+            "a",
+            "b",
+            ">",
+            "1",
+        ]))
+        .unwrap();
+        assert_eq!(
+            (cmd.group.as_ref(), cmd.consumer.as_ref()),
+            (&b"g"[..], &b"c"[..])
+        );
+        assert!(cmd.noack);
+        assert!(!cmd.block);
+        assert_eq!(cmd.count, Some(2));
+        assert_eq!(cmd.ids, vec![None, Some(id(1, 0))]);
+
+        let err = XReadGroup::try_from(args(&["xreadgroup", "streams", "a", ">"])).unwrap_err();
+        assert_eq!(err.to_string(), "ERR Missing GROUP option for XREADGROUP");
+        let err = XReadGroup::try_from(args(&[
+            "xreadgroup",
+            "group",
+            "g",
+            "c",
+            "streams",
+            "a",
+            "$",
+        ]))
+        .unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("ERR The $ ID is meaningless in the context of XREADGROUP"));
+        assert!(matches!(
+            XRead::try_from(args(&["xread", "noack", "streams", "a", "0"])),
+            Err(CommandError::SyntaxError)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_xreadgroup_and_pending() {
+        let backend = Backend::new();
+        let ret = run(
+            &backend,
+            &["xreadgroup", "group", "g", "c", "streams", "s", ">"],
+        );
+        assert_eq!(
+            error(ret),
+            "NOGROUP No such key 's' or consumer group 'g' in XREADGROUP with GROUP option"
+        );
+        run(&backend, &["xgroup", "create", "s", "g", "$", "mkstream"]);
+        xadd(&backend, &["xadd", "s", "1", "f", "1"]);
+        xadd(&backend, &["xadd", "s", "2", "f", "2"]);
+
+        let ret = run(
+            &backend,
+            &["xreadgroup", "group", "g", "c", "streams", "s", ">"],
+        );
+        assert_eq!(
+            ret,
+            RespArray::new([RespFrame::from(RespArray::new([
+                bulk("s"),
+                RespArray::new([entry("1-0", &["f", "1"]), entry("2-0", &["f", "2"])]).into(),
+            ]))])
+            .into()
+        );
+        let ret = run(
+            &backend,
+            &["xreadgroup", "group", "g", "c", "streams", "s", ">"],
+        );
+        assert_eq!(ret, RespFrame::NullArray(RespNullArray));
+        // the history is replied even when empty, deleted entries having null fields
+        run(&backend, &["xdel", "s", "1"]);
+        let ret = run(
+            &backend,
+            &["xreadgroup", "group", "g", "c", "streams", "s", "0"],
+        );
+        assert_eq!(
+            ret,
+            RespArray::new([RespFrame::from(RespArray::new([
+                bulk("s"),
+                RespArray::new([
+                    RespFrame::from(RespArray::new([bulk("1-0"), RespFrame::Null(RespNull)])),
+                    entry("2-0", &["f", "2"]),
+                ])
+                .into(),
+            ]))])
+            .into()
+        );
+
+        let ret = run(&backend, &["xpending", "s", "g"]);
+        assert_eq!(
+            ret,
+            RespArray::new([
+                RespFrame::Integer(2),
+                bulk("1-0"),
+                bulk("2-0"),
+                RespArray::new([RespFrame::from(RespArray::new([bulk("c"), bulk("2")]))]).into(),
+            ])
+            .into()
+        );
+        assert_eq!(
+            run(&backend, &["xack", "s", "g", "1", "2", "3"]),
+            RespFrame::Integer(2)
+        );
+        let ret = run(
+            &backend,
+            &["xreadgroup", "group", "g", "c", "streams", "s", "0"],
+        );
+        assert_eq!(
+            ret,
+            RespArray::new([RespFrame::from(RespArray::new([
+                bulk("s"),
+                RespArray::new(Vec::<RespFrame>::new()).into(),
+            ]))])
+            .into()
+        );
+        assert_eq!(
+            run(&backend, &["xpending", "s", "g"]),
+            RespArray::new([
+                RespFrame::Integer(0),
+                RespFrame::Null(RespNull),
+                RespFrame::Null(RespNull),
+                RespFrame::NullArray(RespNullArray),
+            ])
+            .into()
+        );
+        assert_eq!(
+            error(run(&backend, &["xpending", "s", "x"])),
+            "NOGROUP No such key 's' or consumer group 'x'"
+        );
+    }
+
+    #[test]
+    fn test_xpending_from_resp_array() {
+        let cmd = XPending::try_from(args(&[
+            "xpending", "s", "g", "IDLE", "10", "-", "(5-0", "-1", "c",
+        ]))
+        .unwrap();
+        assert_eq!(
+            cmd.filter,
+            Some(PendingFilter {
+                start: StreamId::MIN,
+                end: id(4, u64::MAX),
+                count: 0,
+                consumer: Some("c".into()),
+                min_idle: 10,
+            })
+        );
+        assert!(matches!(
+            XPending::try_from(args(&["xpending", "s", "g", "-", "+"])),
+            Err(CommandError::SyntaxError)
+        ));
+        assert!(matches!(
+            XPending::try_from(args(&["xpending", "s", "g", "idle", "10"])),
+            Err(CommandError::SyntaxError)
+        ));
+    }
+
+    #[test]
+    fn test_xclaim_and_xautoclaim() {
+        let backend = Backend::new();
+        run(&backend, &["xgroup", "create", "s", "g", "$", "mkstream"]);
+        xadd(&backend, &["xadd", "s", "1", "f", "1"]);
+        xadd(&backend, &["xadd", "s", "2", "f", "2"]);
+        run(
+            &backend,
+            &["xreadgroup", "group", "g", "alice", "streams", "s", ">"],
+        );
+
+        let cmd = XClaim::try_from(args(&[
+            "xclaim",
+            "s",
+            "g",
+            "bob",
+            "0",
+            "1",
+            "2",
+            "justid",
+            "idle",
+            "5",
+            "time",
+            "-1",
+            "retrycount",
+            "3",
+        ]))
+        .unwrap();
+        assert_eq!(cmd.ids, vec![id(1, 0), id(2, 0)]);
+        assert_eq!(
+            cmd.options,
+            ClaimOptions {
+                just_id: true,
+                retry_count: Some(3),
+                ..Default::default()
+            }
+        );
+        let err =
+            XClaim::try_from(args(&["xclaim", "s", "g", "bob", "0", "1", "foo"])).unwrap_err();
+        assert_eq!(err.to_string(), "ERR Unrecognized XCLAIM option 'foo'");
+        let err = XClaim::try_from(args(&["xclaim", "s", "g", "bob", "x", "1"])).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "ERR Invalid min-idle-time argument for XCLAIM"
+        );
+
+        assert_eq!(
+            cmd.execute(&backend),
+            RespArray::new([bulk("1-0"), bulk("2-0")]).into()
+        );
+        let ret = run(&backend, &["xclaim", "s", "g", "alice", "0", "2"]);
+        assert_eq!(ret, RespArray::new([entry("2-0", &["f", "2"])]).into());
+        let ret = run(&backend, &["xclaim", "s", "g", "alice", "3600000", "1"]);
+        assert_eq!(ret, RespArray::new(Vec::<RespFrame>::new()).into());
+        assert_eq!(
+            error(run(&backend, &["xclaim", "s", "x", "alice", "0", "1"])),
+            "NOGROUP No such key 's' or consumer group 'x'"
+        );
+
+        let err =
+            XAutoClaim::try_from(args(&["xautoclaim", "s", "g", "c", "0", "0", "count", "0"]))
+                .unwrap_err();
+        assert_eq!(err.to_string(), "ERR COUNT must be > 0");
+        run(&backend, &["xdel", "s", "1"]);
+        let ret = run(&backend, &["xautoclaim", "s", "g", "carol", "0", "-"]);
+        assert_eq!(
+            ret,
+            RespArray::new([
+                bulk("0-0"),
+                RespArray::new([entry("2-0", &["f", "2"])]).into(),
+                RespArray::new([bulk("1-0")]).into(),
+            ])
+            .into()
+        );
+    }
+
+    #[test]
+    fn test_xinfo() {
+        let backend = Backend::new();
+        assert_eq!(
+            error(run(&backend, &["xinfo", "stream", "s"])),
+            "ERR no such key"
+        );
+        let err = XInfo::try_from(args(&["xinfo", "consumers", "s"])).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "ERR wrong number of arguments for 'xinfo|consumers' command"
+        );
+        xadd(&backend, &["xadd", "s", "1", "f", "1"]);
+        xadd(&backend, &["xadd", "s", "2", "f", "2"]);
+        run(&backend, &["xgroup", "create", "s", "g", "0"]);
+        run(
+            &backend,
+            &[
+                "xreadgroup",
+                "group",
+                "g",
+                "c",
+                "count",
+                "1",
+                "streams",
+                "s",
+                ">",
+            ],
+        );
+
+        let mut expected = RespMap::new();
+        expected.insert("length".into(), RespFrame::Integer(2));
+        expected.insert("last-generated-id".into(), bulk("2-0"));
+        expected.insert("max-deleted-entry-id".into(), bulk("0-0"));
+        expected.insert("entries-added".into(), RespFrame::Integer(2));
+        expected.insert("recorded-first-entry-id".into(), bulk("1-0"));
+        expected.insert("groups".into(), RespFrame::Integer(1));
+        expected.insert("first-entry".into(), entry("1-0", &["f", "1"]));
+        expected.insert("last-entry".into(), entry("2-0", &["f", "2"]));
+        assert_eq!(run(&backend, &["xinfo", "stream", "s"]), expected.into());
+
+        let mut expected = RespMap::new();
+        expected.insert("name".into(), bulk("g"));
+        expected.insert("consumers".into(), RespFrame::Integer(1));
+        expected.insert("pending".into(), RespFrame::Integer(1));
+        expected.insert("last-delivered-id".into(), bulk("1-0"));
+        expected.insert("entries-read".into(), RespFrame::Integer(1));
+        expected.insert("lag".into(), RespFrame::Integer(1));
+        assert_eq!(
+            run(&backend, &["xinfo", "groups", "s"]),
+            RespArray::new([RespFrame::from(expected)]).into()
+        );
+
+        let RespFrame::Array(consumers) = run(&backend, &["xinfo", "consumers", "s", "g"]) else {
+            panic!("expected an array");
+        };
+        let RespFrame::Map(consumer) = &consumers[0] else {
+            panic!("expected a map");
+        };
+        assert_eq!(consumer.get(&b"name"[..]), Some(&bulk("c")));
+        assert_eq!(consumer.get(&b"pending"[..]), Some(&RespFrame::Integer(1)));
+        assert_eq!(
+            error(run(&backend, &["xinfo", "consumers", "s", "x"])),
+            "NOGROUP No such consumer group 'x' for key name 's'"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_blocked_xreadgroup() {
+        let backend = Backend::new();
+        run(&backend, &["xgroup", "create", "s", "g", "$", "mkstream"]);
+        let readers = ["alice", "bob"]
+            .into_iter()
+            .map(|consumer| {
+                tokio::spawn({
+                    let backend = backend.clone();
+                    async move {
+                        request(&[
+                            "xreadgroup",
+                            "group",
+                            "g",
+                            consumer,
+                            "block",
+                            "0",
+                            "streams",
+                            "s",
+                            ">",
+                        ])
+                        .run(&backend, &mut Session::new())
+                        .await
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        request(&["xadd", "s", "1", "f", "new"])
+            .run(&backend, &mut Session::new())
+            .await;
+        // the entry is delivered once, the other reader keeps waiting until the group is
+        // destroyed
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        request(&["xgroup", "destroy", "s", "g"])
+            .run(&backend, &mut Session::new())
+            .await;
+        let mut replies = Vec::new();
+        for reader in readers {
+            replies.push(reader.await.unwrap());
+        }
+        let delivered = RespFrame::from(RespArray::new([RespFrame::from(RespArray::new([
+            bulk("s"),
+            RespArray::new([entry("1-0", &["f", "new"])]).into(),
+        ]))]));
+        assert_eq!(
+            replies.iter().filter(|&reply| *reply == delivered).count(),
+            1
+        );
+        assert!(replies
+            .iter()
+            .any(|reply| matches!(reply, RespFrame::Error(e) if e.0.starts_with("NOGROUP"))));
+    }
+}
@@ -0,0 +1,2223 @@
+use super::{
+    Append, BLMPop, BLMove, BLPop, BRPop, BZMPop, BZPopMax, BZPopMin, Command, CommandCmd,
+    CommandError, ConfigCmd, CopyCmd, Decr, DecrBy, Del, Discard, Exec, Exists, Expire, ExpireAt,
+    ExpireTime, Get, GetDel, GetEx, GetRange, GetSet, HDel, HExists, HGet, HGetAll, HIncrBy,
+    HIncrByFloat, HKeys, HLen, HMGet, HMSet, HRandField, HScan, HSet, HSetNx, HStrLen, HVals,
+    Hello, Incr, IncrBy, IncrByFloat, Keys, LIndex, LInsert, LLen, LMPop, LMove, LPop, LPos, LPush,
+    LPushX, LRange, LRem, LSet, LTrim, MGet, MSet, MSetNx, Multi, PExpire, PExpireAt, PExpireTime,
+    PSetEx, PSubscribe, PTtl, PUnsubscribe, Persist, Ping, PubSubCmd, Publish, RPop, RPush, RPushX,
+    Rename, RenameNx, SAdd, SCard, SDiff, SDiffStore, SInter, SInterCard, SInterStore, SIsMember,
+    SMIsMember, SMembers, SMove, SPop, SPublish, SRandMember, SRem, SSubscribe, SUnion,
+    SUnionStore, SUnsubscribe, Scan, Set, SetEx, SetNx, SetRange, StrLen, Subscribe, Touch, Ttl,
+    Type, Unlink, Unsubscribe, XAck, XAdd, XAutoClaim, XClaim, XDel, XGroup, XInfo, XLen, XPending,
+    XRange, XRead, XReadGroup, XRevRange, XTrim, ZAdd, ZCard, ZCount, ZDiff, ZDiffStore, ZIncrBy,
+    ZInter, ZInterStore, ZMPop, ZMScore, ZPopMax, ZPopMin, ZRandMember, ZRange, ZRangeStore, ZRank,
+    ZRem, ZRemRangeByLex, ZRemRangeByRank, ZRemRangeByScore, ZRevRank, ZScore, ZUnion, ZUnionStore,
+};
+use crate::{BulkString, RespArray, RespFrame, RespMap, SimpleString};
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+
+/// Flags describing the behavior of a command, reported by `COMMAND INFO`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandFlag {
+    /// The command may modify the keyspace.
+    Write,
+    /// The command never modifies the keyspace.
+    ReadOnly,
+    /// The command may increase memory usage.
+    DenyOom,
+    /// The command is an administrative command.
+    Admin,
+    /// The command is related to pub/sub.
+    PubSub,
+    /// The command may block the client.
+    Blocking,
+    /// The command is allowed while the server is loading data.
+    Loading,
+    /// The command is allowed while a replica has stale data.
+    Stale,
+    /// The command runs in O(1) or O(log N) time.
+    Fast,
+    /// Some keys of the command can't be located by `first_key`, `last_key` and `step`.
+    MovableKeys,
+}
+
+impl CommandFlag {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CommandFlag::Write => "write",
+            CommandFlag::ReadOnly => "readonly",
+            CommandFlag::DenyOom => "denyoom",
+            CommandFlag::Admin => "admin",
+            CommandFlag::PubSub => "pubsub",
+            CommandFlag::Blocking => "blocking",
+            CommandFlag::Loading => "loading",
+            CommandFlag::Stale => "stale",
+            CommandFlag::Fast => "fast",
+            CommandFlag::MovableKeys => "movablekeys",
+        }
+    }
+}
+
+/// An entry of the command table.
+///
+/// `arity` follows the Redis convention: it counts the command name itself, and a negative
+/// value `-N` means "at least N". `first_key`, `last_key` and `step` locate the key arguments
+/// (a negative `last_key` counts from the end, `0` means the command takes no keys).
+#[derive(Debug)]
+pub struct CommandSpec {
+    pub name: &'static str,
+    pub arity: i64,
+    pub flags: &'static [CommandFlag],
+    pub first_key: i64,
+    pub last_key: i64,
+    pub step: i64,
+    pub group: &'static str,
+    pub since: &'static str,
+    pub summary: &'static str,
+    pub complexity: &'static str,
+    pub(crate) parse: fn(RespArray) -> Result<Command, CommandError>,
+}
+
+/// Converts a RESP array into a `Command` through the `TryFrom` implementation of `T`.
+fn parse<T>(frame: RespArray) -> Result<Command, CommandError>
+where
+    T: TryFrom<RespArray, Error = CommandError> + Into<Command>,
+{
+    Ok(T::try_from(frame)?.into())
+}
+
+use CommandFlag::*;
+
+/// Every command supported by the server.
+pub static COMMAND_TABLE: &[CommandSpec] = &[
+    CommandSpec {
+        name: "get",
+        arity: 2,
+        flags: &[ReadOnly, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "string",
+        since: "1.0.0",
+        summary: "Returns the string value of a key.",
+        complexity: "O(1)",
+        parse: parse::<Get>,
+    },
+    CommandSpec {
+        name: "set",
+        arity: -3,
+        flags: &[Write, DenyOom],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "string",
+        since: "1.0.0",
+        summary: "Sets the string value of a key, ignoring its type. The key is created if it doesn't exist.",
+        complexity: "O(1)",
+        parse: parse::<Set>,
+    },
+    CommandSpec {
+        name: "append",
+        arity: 3,
+        flags: &[Write, DenyOom, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "string",
+        since: "2.0.0",
+        summary: "Appends a string to the value of a key. Creates the key if it doesn't exist.",
+        complexity: "O(1). The amortized time complexity is O(1) assuming the appended value is small and the already present value is of any size, since the dynamic string library used by Redis will double the free space available on every reallocation.",
+        parse: parse::<Append>,
+    },
+    CommandSpec {
+        name: "strlen",
+        arity: 2,
+        flags: &[ReadOnly, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "string",
+        since: "2.2.0",
+        summary: "Returns the length of a string value.",
+        complexity: "O(1)",
+        parse: parse::<StrLen>,
+    },
+    CommandSpec {
+        name: "getrange",
+        arity: 4,
+        flags: &[ReadOnly],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "string",
+        since: "2.4.0",
+        summary: "Returns a substring of the string stored at a key.",
+        complexity: "O(N) where N is the length of the returned string. The complexity is ultimately determined by the returned length, but because creating a substring from an existing string is very cheap, it can be considered O(1) for small strings.",
+        parse: parse::<GetRange>,
+    },
+    CommandSpec {
+        name: "setrange",
+        arity: 4,
+        flags: &[Write, DenyOom],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "string",
+        since: "2.2.0",
+        summary: "Overwrites a part of a string value with another by an offset. Creates the key if it doesn't exist.",
+        complexity: "O(1), not counting the time taken to copy the new string in place. Usually, this string is very small so the amortized complexity is O(1). Otherwise, complexity is O(M) with M being the length of the value argument.",
+        parse: parse::<SetRange>,
+    },
+    CommandSpec {
+        name: "getset",
+        arity: 3,
+        flags: &[Write, DenyOom, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "string",
+        since: "1.0.0",
+        summary: "Returns the previous string value of a key after setting it to a new value.",
+        complexity: "O(1)",
+        parse: parse::<GetSet>,
+    },
+    CommandSpec {
+        name: "getdel",
+        arity: 2,
+        flags: &[Write, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "string",
+        since: "6.2.0",
+        summary: "Returns the string value of a key after deleting the key.",
+        complexity: "O(1)",
+        parse: parse::<GetDel>,
+    },
+    CommandSpec {
+        name: "getex",
+        arity: -2,
+        flags: &[Write, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "string",
+        since: "6.2.0",
+        summary: "Returns the string value of a key after setting its expiration time.",
+        complexity: "O(1)",
+        parse: parse::<GetEx>,
+    },
+    CommandSpec {
+        name: "setnx",
+        arity: 3,
+        flags: &[Write, DenyOom, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "string",
+        since: "1.0.0",
+        summary: "Set the string value of a key only when the key doesn't exist.",
+        complexity: "O(1)",
+        parse: parse::<SetNx>,
+    },
+    CommandSpec {
+        name: "setex",
+        arity: 4,
+        flags: &[Write, DenyOom],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "string",
+        since: "2.0.0",
+        summary: "Sets the string value and expiration time of a key. Creates the key if it doesn't exist.",
+        complexity: "O(1)",
+        parse: parse::<SetEx>,
+    },
+    CommandSpec {
+        name: "psetex",
+        arity: 4,
+        flags: &[Write, DenyOom],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "string",
+        since: "2.6.0",
+        summary: "Sets both string value and expiration time in milliseconds of a key. The key is created if it doesn't exist.",
+        complexity: "O(1)",
+        parse: parse::<PSetEx>,
+    },
+    CommandSpec {
+        name: "incr",
+        arity: 2,
+        flags: &[Write, DenyOom, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "string",
+        since: "1.0.0",
+        summary: "Increments the integer value of a key by one. Uses 0 as initial value if the key doesn't exist.",
+        complexity: "O(1)",
+        parse: parse::<Incr>,
+    },
+    CommandSpec {
+        name: "decr",
+        arity: 2,
+        flags: &[Write, DenyOom, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "string",
+        since: "1.0.0",
+        summary: "Decrements the integer value of a key by one. Uses 0 as initial value if the key doesn't exist.",
+        complexity: "O(1)",
+        parse: parse::<Decr>,
+    },
+    CommandSpec {
+        name: "incrby",
+        arity: 3,
+        flags: &[Write, DenyOom, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "string",
+        since: "1.0.0",
+        summary: "Increments the integer value of a key by a number. Uses 0 as initial value if the key doesn't exist.",
+        complexity: "O(1)",
+        parse: parse::<IncrBy>,
+    },
+    CommandSpec {
+        name: "decrby",
+        arity: 3,
+        flags: &[Write, DenyOom, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "string",
+        since: "1.0.0",
+        summary: "Decrements a number from the integer value of a key. Uses 0 as initial value if the key doesn't exist.",
+        complexity: "O(1)",
+        parse: parse::<DecrBy>,
+    },
+    CommandSpec {
+        name: "incrbyfloat",
+        arity: 3,
+        flags: &[Write, DenyOom, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "string",
+        since: "2.6.0",
+        summary: "Increment the floating point value of a key by a number. Uses 0 as initial value if the key doesn't exist.",
+        complexity: "O(1)",
+        parse: parse::<IncrByFloat>,
+    },
+    CommandSpec {
+        name: "mget",
+        arity: -2,
+        flags: &[ReadOnly, Fast],
+        first_key: 1,
+        last_key: -1,
+        step: 1,
+        group: "string",
+        since: "1.0.0",
+        summary: "Atomically returns the string values of one or more keys.",
+        complexity: "O(N) where N is the number of keys to retrieve.",
+        parse: parse::<MGet>,
+    },
+    CommandSpec {
+        name: "mset",
+        arity: -3,
+        flags: &[Write, DenyOom],
+        first_key: 1,
+        last_key: -1,
+        step: 2,
+        group: "string",
+        since: "1.0.1",
+        summary: "Atomically creates or modifies the string values of one or more keys.",
+        complexity: "O(N) where N is the number of keys to set.",
+        parse: parse::<MSet>,
+    },
+    CommandSpec {
+        name: "msetnx",
+        arity: -3,
+        flags: &[Write, DenyOom],
+        first_key: 1,
+        last_key: -1,
+        step: 2,
+        group: "string",
+        since: "1.0.1",
+        summary: "Atomically modifies the string values of one or more keys only when all keys don't exist.",
+        complexity: "O(N) where N is the number of keys to set.",
+        parse: parse::<MSetNx>,
+    },
+    CommandSpec {
+        name: "hget",
+        arity: 3,
+        flags: &[ReadOnly, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "hash",
+        since: "2.0.0",
+        summary: "Returns the value of a field in a hash.",
+        complexity: "O(1)",
+        parse: parse::<HGet>,
+    },
+    CommandSpec {
+        name: "hset",
+        arity: -4,
+        flags: &[Write, DenyOom, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "hash",
+        since: "2.0.0",
+        summary: "Creates or modifies the value of a field in a hash.",
+        complexity: "O(1) for each field/value pair added, so O(N) to add N field/value pairs when the command is called with multiple field/value pairs.",
+        parse: parse::<HSet>,
+    },
+    CommandSpec {
+        name: "hgetall",
+        arity: 2,
+        flags: &[ReadOnly],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "hash",
+        since: "2.0.0",
+        summary: "Returns all fields and values in a hash.",
+        complexity: "O(N) where N is the size of the hash.",
+        parse: parse::<HGetAll>,
+    },
+    CommandSpec {
+        name: "hdel",
+        arity: -3,
+        flags: &[Write, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "hash",
+        since: "2.0.0",
+        summary: "Deletes one or more fields and their values from a hash. Deletes the hash if no fields remain.",
+        complexity: "O(N) where N is the number of fields to be removed.",
+        parse: parse::<HDel>,
+    },
+    CommandSpec {
+        name: "hexists",
+        arity: 3,
+        flags: &[ReadOnly, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "hash",
+        since: "2.0.0",
+        summary: "Determines whether a field exists in a hash.",
+        complexity: "O(1)",
+        parse: parse::<HExists>,
+    },
+    CommandSpec {
+        name: "hlen",
+        arity: 2,
+        flags: &[ReadOnly, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "hash",
+        since: "2.0.0",
+        summary: "Returns the number of fields in a hash.",
+        complexity: "O(1)",
+        parse: parse::<HLen>,
+    },
+    CommandSpec {
+        name: "hkeys",
+        arity: 2,
+        flags: &[ReadOnly],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "hash",
+        since: "2.0.0",
+        summary: "Returns all fields in a hash.",
+        complexity: "O(N) where N is the size of the hash.",
+        parse: parse::<HKeys>,
+    },
+    CommandSpec {
+        name: "hvals",
+        arity: 2,
+        flags: &[ReadOnly],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "hash",
+        since: "2.0.0",
+        summary: "Returns all values in a hash.",
+        complexity: "O(N) where N is the size of the hash.",
+        parse: parse::<HVals>,
+    },
+    CommandSpec {
+        name: "hmget",
+        arity: -3,
+        flags: &[ReadOnly, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "hash",
+        since: "2.0.0",
+        summary: "Returns the values of all fields in a hash.",
+        complexity: "O(N) where N is the number of fields being requested.",
+        parse: parse::<HMGet>,
+    },
+    CommandSpec {
+        name: "hmset",
+        arity: -4,
+        flags: &[Write, DenyOom, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "hash",
+        since: "2.0.0",
+        summary: "Sets the values of multiple fields.",
+        complexity: "O(N) where N is the number of fields being set.",
+        parse: parse::<HMSet>,
+    },
+    CommandSpec {
+        name: "hsetnx",
+        arity: 4,
+        flags: &[Write, DenyOom, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "hash",
+        since: "2.0.0",
+        summary: "Sets the value of a field in a hash only when the field doesn't exist.",
+        complexity: "O(1)",
+        parse: parse::<HSetNx>,
+    },
+    CommandSpec {
+        name: "hstrlen",
+        arity: 3,
+        flags: &[ReadOnly, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "hash",
+        since: "3.2.0",
+        summary: "Returns the length of the value of a field.",
+        complexity: "O(1)",
+        parse: parse::<HStrLen>,
+    },
+    CommandSpec {
+        name: "hincrby",
+        arity: 4,
+        flags: &[Write, DenyOom, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "hash",
+        since: "2.0.0",
+        summary: "Increments the integer value of a field in a hash by a number. Uses 0 as initial value if the field doesn't exist.",
+        complexity: "O(1)",
+        parse: parse::<HIncrBy>,
+    },
+    CommandSpec {
+        name: "hincrbyfloat",
+        arity: 4,
+        flags: &[Write, DenyOom, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "hash",
+        since: "2.6.0",
+        summary: "Increments the floating point value of a field by a number. Uses 0 as initial value if the field doesn't exist.",
+        complexity: "O(1)",
+        parse: parse::<HIncrByFloat>,
+    },
+    CommandSpec {
+        name: "hrandfield",
+        arity: -2,
+        flags: &[ReadOnly],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "hash",
+        since: "6.2.0",
+        summary: "Returns one or more random fields from a hash.",
+        complexity: "O(N) where N is the number of fields returned",
+        parse: parse::<HRandField>,
+    },
+    CommandSpec {
+        name: "hscan",
+        arity: -3,
+        flags: &[ReadOnly],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "hash",
+        since: "2.8.0",
+        summary: "Iterates over fields and values of a hash.",
+        complexity: "O(N) for every call, where N is the number of fields in the hash.",
+        parse: parse::<HScan>,
+    },
+    CommandSpec {
+        name: "lpush",
+        arity: -3,
+        flags: &[Write, DenyOom, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "list",
+        since: "1.0.0",
+        summary: "Prepends one or more elements to a list. Creates the key if it doesn't exist.",
+        complexity: "O(1) for each element added, so O(N) to add N elements when the command is called with multiple arguments.",
+        parse: parse::<LPush>,
+    },
+    CommandSpec {
+        name: "rpush",
+        arity: -3,
+        flags: &[Write, DenyOom, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "list",
+        since: "1.0.0",
+        summary: "Appends one or more elements to a list. Creates the key if it doesn't exist.",
+        complexity: "O(1) for each element added, so O(N) to add N elements when the command is called with multiple arguments.",
+        parse: parse::<RPush>,
+    },
+    CommandSpec {
+        name: "lpushx",
+        arity: -3,
+        flags: &[Write, DenyOom, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "list",
+        since: "2.2.0",
+        summary: "Prepends one or more elements to a list only when the list exists.",
+        complexity: "O(1) for each element added, so O(N) to add N elements when the command is called with multiple arguments.",
+        parse: parse::<LPushX>,
+    },
+    CommandSpec {
+        name: "rpushx",
+        arity: -3,
+        flags: &[Write, DenyOom, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "list",
+        since: "2.2.0",
+        summary: "Appends an element to a list only when the list exists.",
+        complexity: "O(1) for each element added, so O(N) to add N elements when the command is called with multiple arguments.",
+        parse: parse::<RPushX>,
+    },
+    CommandSpec {
+        name: "lpop",
+        arity: -2,
+        flags: &[Write, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "list",
+        since: "1.0.0",
+        summary: "Returns the first elements in a list after removing it. Deletes the list if the last element was popped.",
+        complexity: "O(N) where N is the number of elements returned",
+        parse: parse::<LPop>,
+    },
+    CommandSpec {
+        name: "rpop",
+        arity: -2,
+        flags: &[Write, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "list",
+        since: "1.0.0",
+        summary: "Returns and removes the last elements of a list. Deletes the list if the last element was popped.",
+        complexity: "O(N) where N is the number of elements returned",
+        parse: parse::<RPop>,
+    },
+    CommandSpec {
+        name: "llen",
+        arity: 2,
+        flags: &[ReadOnly, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "list",
+        since: "1.0.0",
+        summary: "Returns the length of a list.",
+        complexity: "O(1)",
+        parse: parse::<LLen>,
+    },
+    CommandSpec {
+        name: "lrange",
+        arity: 4,
+        flags: &[ReadOnly],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "list",
+        since: "1.0.0",
+        summary: "Returns a range of elements from a list.",
+        complexity: "O(S+N) where S is the distance of start offset from HEAD for small lists, from nearest end (HEAD or TAIL) for large lists; and N is the number of elements in the specified range.",
+        parse: parse::<LRange>,
+    },
+    CommandSpec {
+        name: "lindex",
+        arity: 3,
+        flags: &[ReadOnly],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "list",
+        since: "1.0.0",
+        summary: "Returns an element from a list by its index.",
+        complexity: "O(N) where N is the number of elements to traverse to get to the element at index. This makes asking for the first or the last element of the list O(1).",
+        parse: parse::<LIndex>,
+    },
+    CommandSpec {
+        name: "lset",
+        arity: 4,
+        flags: &[Write, DenyOom],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "list",
+        since: "1.0.0",
+        summary: "Sets the value of an element in a list by its index.",
+        complexity: "O(N) where N is the length of the list. Setting either the first or the last element of the list is O(1).",
+        parse: parse::<LSet>,
+    },
+    CommandSpec {
+        name: "linsert",
+        arity: 5,
+        flags: &[Write, DenyOom],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "list",
+        since: "2.2.0",
+        summary: "Inserts an element before or after another element in a list.",
+        complexity: "O(N) where N is the number of elements to traverse before seeing the value pivot. This means that inserting somewhere on the left end on the list (head) can be considered O(1) and inserting somewhere on the right end (tail) is O(N).",
+        parse: parse::<LInsert>,
+    },
+    CommandSpec {
+        name: "lrem",
+        arity: 4,
+        flags: &[Write],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "list",
+        since: "1.0.0",
+        summary: "Removes elements from a list. Deletes the list if the last element was removed.",
+        complexity: "O(N+M) where N is the length of the list and M is the number of elements removed.",
+        parse: parse::<LRem>,
+    },
+    CommandSpec {
+        name: "ltrim",
+        arity: 4,
+        flags: &[Write],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "list",
+        since: "1.0.0",
+        summary: "Removes elements from both ends a list. Deletes the list if all elements were trimmed.",
+        complexity: "O(N) where N is the number of elements to be removed by the operation.",
+        parse: parse::<LTrim>,
+    },
+    CommandSpec {
+        name: "lpos",
+        arity: -3,
+        flags: &[ReadOnly],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "list",
+        since: "6.0.6",
+        summary: "Returns the index of matching elements in a list.",
+        complexity: "O(N) where N is the number of elements in the list, for the average case. When searching for elements near the head or the tail of the list, or when the MAXLEN option is provided, the command may run in constant time.",
+        parse: parse::<LPos>,
+    },
+    CommandSpec {
+        name: "lmove",
+        arity: 5,
+        flags: &[Write, DenyOom],
+        first_key: 1,
+        last_key: 2,
+        step: 1,
+        group: "list",
+        since: "6.2.0",
+        summary: "Returns an element after popping it from one list and pushing it to another. Deletes the list if the last element was moved.",
+        complexity: "O(1)",
+        parse: parse::<LMove>,
+    },
+    CommandSpec {
+        name: "lmpop",
+        arity: -4,
+        flags: &[Write, MovableKeys],
+        first_key: 0,
+        last_key: 0,
+        step: 0,
+        group: "list",
+        since: "7.0.0",
+        summary: "Returns multiple elements from a list after removing them. Deletes the list if the last element was popped.",
+        complexity: "O(N+M) where N is the number of provided keys and M is the number of elements returned.",
+        parse: parse::<LMPop>,
+    },
+    CommandSpec {
+        name: "blpop",
+        arity: -3,
+        flags: &[Write, Blocking],
+        first_key: 1,
+        last_key: -2,
+        step: 1,
+        group: "list",
+        since: "2.0.0",
+        summary: "Removes and returns the first element in a list. Blocks until an element is available otherwise. Deletes the list if the last element was popped.",
+        complexity: "O(N) where N is the number of provided keys.",
+        parse: parse::<BLPop>,
+    },
+    CommandSpec {
+        name: "brpop",
+        arity: -3,
+        flags: &[Write, Blocking],
+        first_key: 1,
+        last_key: -2,
+        step: 1,
+        group: "list",
+        since: "2.0.0",
+        summary: "Removes and returns the last element in a list. Blocks until an element is available otherwise. Deletes the list if the last element was popped.",
+        complexity: "O(N) where N is the number of provided keys.",
+        parse: parse::<BRPop>,
+    },
+    CommandSpec {
+        name: "blmove",
+        arity: 6,
+        flags: &[Write, DenyOom, Blocking],
+        first_key: 1,
+        last_key: 2,
+        step: 1,
+        group: "list",
+        since: "6.2.0",
+        summary: "Pops an element from a list, pushes it to another list and returns it. Blocks until an element is available otherwise. Deletes the list if the last element was moved.",
+        complexity: "O(1)",
+        parse: parse::<BLMove>,
+    },
+    CommandSpec {
+        name: "blmpop",
+        arity: -5,
+        flags: &[Write, Blocking, MovableKeys],
+        first_key: 0,
+        last_key: 0,
+        step: 0,
+        group: "list",
+        since: "7.0.0",
+        summary: "Pops the first element from one of multiple lists. Blocks until an element is available otherwise. Deletes the list if the last element was popped.",
+        complexity: "O(N+M) where N is the number of provided keys and M is the number of elements returned.",
+        parse: parse::<BLMPop>,
+    },
+    CommandSpec {
+        name: "sadd",
+        arity: -3,
+        flags: &[Write, DenyOom, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "set",
+        since: "1.0.0",
+        summary: "Adds one or more members to a set. Creates the key if it doesn't exist.",
+        complexity: "O(1) for each element added, so O(N) to add N elements when the command is called with multiple arguments.",
+        parse: parse::<SAdd>,
+    },
+    CommandSpec {
+        name: "srem",
+        arity: -3,
+        flags: &[Write, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "set",
+        since: "1.0.0",
+        summary: "Removes one or more members from a set. Deletes the set if the last member was removed.",
+        complexity: "O(N) where N is the number of members to be removed.",
+        parse: parse::<SRem>,
+    },
+    CommandSpec {
+        name: "smembers",
+        arity: 2,
+        flags: &[ReadOnly],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "set",
+        since: "1.0.0",
+        summary: "Returns all members of a set.",
+        complexity: "O(N) where N is the set cardinality.",
+        parse: parse::<SMembers>,
+    },
+    CommandSpec {
+        name: "sismember",
+        arity: 3,
+        flags: &[ReadOnly, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "set",
+        since: "1.0.0",
+        summary: "Determines whether a member belongs to a set.",
+        complexity: "O(1)",
+        parse: parse::<SIsMember>,
+    },
+    CommandSpec {
+        name: "smismember",
+        arity: -3,
+        flags: &[ReadOnly, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "set",
+        since: "6.2.0",
+        summary: "Determines whether multiple members belong to a set.",
+        complexity: "O(N) where N is the number of elements being checked for membership",
+        parse: parse::<SMIsMember>,
+    },
+    CommandSpec {
+        name: "scard",
+        arity: 2,
+        flags: &[ReadOnly, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "set",
+        since: "1.0.0",
+        summary: "Returns the number of members in a set.",
+        complexity: "O(1)",
+        parse: parse::<SCard>,
+    },
+    CommandSpec {
+        name: "spop",
+        arity: -2,
+        flags: &[Write, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "set",
+        since: "1.0.0",
+        summary: "Returns one or more random members from a set after removing them. Deletes the set if the last member was popped.",
+        complexity: "Without the count argument O(1), otherwise O(N) where N is the value of the passed count.",
+        parse: parse::<SPop>,
+    },
+    CommandSpec {
+        name: "srandmember",
+        arity: -2,
+        flags: &[ReadOnly],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "set",
+        since: "1.0.0",
+        summary: "Get one or multiple random members from a set",
+        complexity: "Without the count argument O(1), otherwise O(N) where N is the absolute value of the passed count.",
+        parse: parse::<SRandMember>,
+    },
+    CommandSpec {
+        name: "sinter",
+        arity: -2,
+        flags: &[ReadOnly],
+        first_key: 1,
+        last_key: -1,
+        step: 1,
+        group: "set",
+        since: "1.0.0",
+        summary: "Returns the intersect of multiple sets.",
+        complexity: "O(N*M) worst case where N is the cardinality of the smallest set and M is the number of sets.",
+        parse: parse::<SInter>,
+    },
+    CommandSpec {
+        name: "sinterstore",
+        arity: -3,
+        flags: &[Write, DenyOom],
+        first_key: 1,
+        last_key: -1,
+        step: 1,
+        group: "set",
+        since: "1.0.0",
+        summary: "Stores the intersect of multiple sets in a key.",
+        complexity: "O(N*M) worst case where N is the cardinality of the smallest set and M is the number of sets.",
+        parse: parse::<SInterStore>,
+    },
+    CommandSpec {
+        name: "sintercard",
+        arity: -3,
+        flags: &[ReadOnly, MovableKeys],
+        first_key: 0,
+        last_key: 0,
+        step: 0,
+        group: "set",
+        since: "7.0.0",
+        summary: "Returns the number of members of the intersect of multiple sets.",
+        complexity: "O(N*M) worst case where N is the cardinality of the smallest set and M is the number of sets.",
+        parse: parse::<SInterCard>,
+    },
+    CommandSpec {
+        name: "sunion",
+        arity: -2,
+        flags: &[ReadOnly],
+        first_key: 1,
+        last_key: -1,
+        step: 1,
+        group: "set",
+        since: "1.0.0",
+        summary: "Returns the union of multiple sets.",
+        complexity: "O(N) where N is the total number of elements in all given sets.",
+        parse: parse::<SUnion>,
+    },
+    CommandSpec {
+        name: "sunionstore",
+        arity: -3,
+        flags: &[Write, DenyOom],
+        first_key: 1,
+        last_key: -1,
+        step: 1,
+        group: "set",
+        since: "1.0.0",
+        summary: "Stores the union of multiple sets in a key.",
+        complexity: "O(N) where N is the total number of elements in all given sets.",
+        parse: parse::<SUnionStore>,
+    },
+    CommandSpec {
+        name: "sdiff",
+        arity: -2,
+        flags: &[ReadOnly],
+        first_key: 1,
+        last_key: -1,
+        step: 1,
+        group: "set",
+        since: "1.0.0",
+        summary: "Returns the difference of multiple sets.",
+        complexity: "O(N) where N is the total number of elements in all given sets.",
+        parse: parse::<SDiff>,
+    },
+    CommandSpec {
+        name: "sdiffstore",
+        arity: -3,
+        flags: &[Write, DenyOom],
+        first_key: 1,
+        last_key: -1,
+        step: 1,
+        group: "set",
+        since: "1.0.0",
+        summary: "Stores the difference of multiple sets in a key.",
+        complexity: "O(N) where N is the total number of elements in all given sets.",
+        parse: parse::<SDiffStore>,
+    },
+    CommandSpec {
+        name: "smove",
+        arity: 4,
+        flags: &[Write, Fast],
+        first_key: 1,
+        last_key: 2,
+        step: 1,
+        group: "set",
+        since: "1.0.0",
+        summary: "Moves a member from one set to another.",
+        complexity: "O(1)",
+        parse: parse::<SMove>,
+    },
+    CommandSpec {
+        name: "zadd",
+        arity: -4,
+        flags: &[Write, DenyOom, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "sorted-set",
+        since: "1.2.0",
+        summary: "Adds one or more members to a sorted set, or updates their scores. Creates the key if it doesn't exist.",
+        complexity: "O(log(N)) for each item added, where N is the number of elements in the sorted set.",
+        parse: parse::<ZAdd>,
+    },
+    CommandSpec {
+        name: "zrem",
+        arity: -3,
+        flags: &[Write, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "sorted-set",
+        since: "1.2.0",
+        summary: "Removes one or more members from a sorted set. Deletes the sorted set if all members were removed.",
+        complexity: "O(M*log(N)) with N being the number of elements in the sorted set and M the number of elements to be removed.",
+        parse: parse::<ZRem>,
+    },
+    CommandSpec {
+        name: "zscore",
+        arity: 3,
+        flags: &[ReadOnly, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "sorted-set",
+        since: "1.2.0",
+        summary: "Returns the score of a member in a sorted set.",
+        complexity: "O(1)",
+        parse: parse::<ZScore>,
+    },
+    CommandSpec {
+        name: "zmscore",
+        arity: -3,
+        flags: &[ReadOnly, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "sorted-set",
+        since: "6.2.0",
+        summary: "Returns the score of one or more members in a sorted set.",
+        complexity: "O(N) where N is the number of members being requested.",
+        parse: parse::<ZMScore>,
+    },
+    CommandSpec {
+        name: "zincrby",
+        arity: 4,
+        flags: &[Write, DenyOom, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "sorted-set",
+        since: "1.2.0",
+        summary: "Increments the score of a member in a sorted set.",
+        complexity: "O(log(N)) where N is the number of elements in the sorted set.",
+        parse: parse::<ZIncrBy>,
+    },
+    CommandSpec {
+        name: "zcard",
+        arity: 2,
+        flags: &[ReadOnly, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "sorted-set",
+        since: "1.2.0",
+        summary: "Returns the number of members in a sorted set.",
+        complexity: "O(1)",
+        parse: parse::<ZCard>,
+    },
+    CommandSpec {
+        name: "zcount",
+        arity: 4,
+        flags: &[ReadOnly, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "sorted-set",
+        since: "2.0.0",
+        summary: "Returns the count of members in a sorted set that have scores within a range.",
+        complexity: "O(log(N)) with N being the number of elements in the sorted set.",
+        parse: parse::<ZCount>,
+    },
+    CommandSpec {
+        name: "zrank",
+        arity: -3,
+        flags: &[ReadOnly, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "sorted-set",
+        since: "2.0.0",
+        summary: "Returns the index of a member in a sorted set ordered by ascending scores.",
+        complexity: "O(log(N))",
+        parse: parse::<ZRank>,
+    },
+    CommandSpec {
+        name: "zrevrank",
+        arity: -3,
+        flags: &[ReadOnly, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "sorted-set",
+        since: "2.0.0",
+        summary: "Returns the index of a member in a sorted set ordered by descending scores.",
+        complexity: "O(log(N))",
+        parse: parse::<ZRevRank>,
+    },
+    CommandSpec {
+        name: "zrange",
+        arity: -4,
+        flags: &[ReadOnly],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "sorted-set",
+        since: "1.2.0",
+        summary: "Returns members in a sorted set within a range of indexes.",
+        complexity: "O(log(N)+M) with N being the number of elements in the sorted set and M the number of elements returned.",
+        parse: parse::<ZRange>,
+    },
+    CommandSpec {
+        name: "zunionstore",
+        arity: -4,
+        flags: &[Write, DenyOom, MovableKeys],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "sorted-set",
+        since: "2.0.0",
+        summary: "Stores the union of multiple sorted sets in a key.",
+        complexity: "O(N)+O(M log(M)) with N being the sum of the sizes of the input sorted sets, and M being the number of elements in the resulting sorted set.",
+        parse: parse::<ZUnionStore>,
+    },
+    CommandSpec {
+        name: "zinterstore",
+        arity: -4,
+        flags: &[Write, DenyOom, MovableKeys],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "sorted-set",
+        since: "2.0.0",
+        summary: "Stores the intersect of multiple sorted sets in a key.",
+        complexity: "O(N*K)+O(M*log(M)) worst case with N being the smallest input sorted set, K being the number of input sorted sets and M being the number of elements in the resulting sorted set.",
+        parse: parse::<ZInterStore>,
+    },
+    CommandSpec {
+        name: "zdiffstore",
+        arity: -4,
+        flags: &[Write, DenyOom, MovableKeys],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "sorted-set",
+        since: "6.2.0",
+        summary: "Stores the difference of multiple sorted sets in a key.",
+        complexity: "O(L + (N-K)log(N)) worst case where L is the total number of elements in all the sets, N is the size of the first set, and K is the size of the result set.",
+        parse: parse::<ZDiffStore>,
+    },
+    CommandSpec {
+        name: "zunion",
+        arity: -3,
+        flags: &[ReadOnly, MovableKeys],
+        first_key: 0,
+        last_key: 0,
+        step: 0,
+        group: "sorted-set",
+        since: "6.2.0",
+        summary: "Returns the union of multiple sorted sets.",
+        complexity: "O(N)+O(M*log(M)) with N being the sum of the sizes of the input sorted sets, and M being the number of elements in the resulting sorted set.",
+        parse: parse::<ZUnion>,
+    },
+    CommandSpec {
+        name: "zinter",
+        arity: -3,
+        flags: &[ReadOnly, MovableKeys],
+        first_key: 0,
+        last_key: 0,
+        step: 0,
+        group: "sorted-set",
+        since: "6.2.0",
+        summary: "Returns the intersect of multiple sorted sets.",
+        complexity: "O(N*K)+O(M*log(M)) worst case with N being the smallest input sorted set, K being the number of input sorted sets and M being the number of elements in the resulting sorted set.",
+        parse: parse::<ZInter>,
+    },
+    CommandSpec {
+        name: "zdiff",
+        arity: -3,
+        flags: &[ReadOnly, MovableKeys],
+        first_key: 0,
+        last_key: 0,
+        step: 0,
+        group: "sorted-set",
+        since: "6.2.0",
+        summary: "Returns the difference between multiple sorted sets.",
+        complexity: "O(L + (N-K)log(N)) worst case where L is the total number of elements in all the sets, N is the size of the first set, and K is the size of the result set.",
+        parse: parse::<ZDiff>,
+    },
+    CommandSpec {
+        name: "zremrangebyscore",
+        arity: 4,
+        flags: &[Write],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "sorted-set",
+        since: "1.2.0",
+        summary: "Removes members in a sorted set within a range of scores. Deletes the sorted set if all members were removed.",
+        complexity: "O(log(N)+M) with N being the number of elements in the sorted set and M the number of elements removed by the operation.",
+        parse: parse::<ZRemRangeByScore>,
+    },
+    CommandSpec {
+        name: "zremrangebyrank",
+        arity: 4,
+        flags: &[Write],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "sorted-set",
+        since: "2.0.0",
+        summary: "Removes members in a sorted set within a range of indexes. Deletes the sorted set if all members were removed.",
+        complexity: "O(log(N)+M) with N being the number of elements in the sorted set and M the number of elements removed by the operation.",
+        parse: parse::<ZRemRangeByRank>,
+    },
+    CommandSpec {
+        name: "zremrangebylex",
+        arity: 4,
+        flags: &[Write],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "sorted-set",
+        since: "2.8.9",
+        summary: "Removes members in a sorted set within a lexicographical range. Deletes the sorted set if all members were removed.",
+        complexity: "O(log(N)+M) with N being the number of elements in the sorted set and M the number of elements removed by the operation.",
+        parse: parse::<ZRemRangeByLex>,
+    },
+    CommandSpec {
+        name: "zpopmin",
+        arity: -2,
+        flags: &[Write, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "sorted-set",
+        since: "5.0.0",
+        summary: "Returns the lowest-scoring members from a sorted set after removing them. Deletes the sorted set if the last member was popped.",
+        complexity: "O(log(N)*M) with N being the number of elements in the sorted set, and M being the number of elements popped.",
+        parse: parse::<ZPopMin>,
+    },
+    CommandSpec {
+        name: "zpopmax",
+        arity: -2,
+        flags: &[Write, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "sorted-set",
+        since: "5.0.0",
+        summary: "Returns the highest-scoring members from a sorted set after removing them. Deletes the sorted set if the last member was popped.",
+        complexity: "O(log(N)*M) with N being the number of elements in the sorted set, and M being the number of elements popped.",
+        parse: parse::<ZPopMax>,
+    },
+    CommandSpec {
+        name: "zrandmember",
+        arity: -2,
+        flags: &[ReadOnly],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "sorted-set",
+        since: "6.2.0",
+        summary: "Returns one or more random members from a sorted set.",
+        complexity: "O(N) where N is the number of members returned",
+        parse: parse::<ZRandMember>,
+    },
+    CommandSpec {
+        name: "zrangestore",
+        arity: -5,
+        flags: &[Write, DenyOom],
+        first_key: 1,
+        last_key: 2,
+        step: 1,
+        group: "sorted-set",
+        since: "6.2.0",
+        summary: "Stores a range of members from sorted set in a key.",
+        complexity: "O(log(N)+M) with N being the number of elements in the sorted set and M the number of elements stored into the destination key.",
+        parse: parse::<ZRangeStore>,
+    },
+    CommandSpec {
+        name: "zmpop",
+        arity: -4,
+        flags: &[Write, MovableKeys],
+        first_key: 0,
+        last_key: 0,
+        step: 0,
+        group: "sorted-set",
+        since: "7.0.0",
+        summary: "Returns the highest- or lowest-scoring members from one or more sorted sets after removing them. Deletes the sorted set if the last member was popped.",
+        complexity: "O(K) + O(M*log(N)) where K is the number of provided keys, N being the number of elements in the sorted set, and M being the number of elements popped.",
+        parse: parse::<ZMPop>,
+    },
+    CommandSpec {
+        name: "bzpopmin",
+        arity: -3,
+        flags: &[Write, Blocking, Fast],
+        first_key: 1,
+        last_key: -2,
+        step: 1,
+        group: "sorted-set",
+        since: "5.0.0",
+        summary: "Removes and returns the member with the lowest score from one or more sorted sets. Blocks until a member is available otherwise. Deletes the sorted set if the last element was popped.",
+        complexity: "O(log(N)) with N being the number of elements in the sorted set.",
+        parse: parse::<BZPopMin>,
+    },
+    CommandSpec {
+        name: "bzpopmax",
+        arity: -3,
+        flags: &[Write, Blocking, Fast],
+        first_key: 1,
+        last_key: -2,
+        step: 1,
+        group: "sorted-set",
+        since: "5.0.0",
+        summary: "Removes and returns the member with the highest score from one or more sorted sets. Blocks until a member available otherwise. Deletes the sorted set if the last element was popped.",
+        complexity: "O(log(N)) with N being the number of elements in the sorted set.",
+        parse: parse::<BZPopMax>,
+    },
+    CommandSpec {
+        name: "bzmpop",
+        arity: -5,
+        flags: &[Write, Blocking, MovableKeys],
+        first_key: 0,
+        last_key: 0,
+        step: 0,
+        group: "sorted-set",
+        since: "7.0.0",
+        summary: "Removes and returns a member by score from one or more sorted sets. Blocks until a member is available otherwise. Deletes the sorted set if the last element was popped.",
+        complexity: "O(K) + O(M*log(N)) where K is the number of provided keys, N being the number of elements in the sorted set, and M being the number of elements popped.",
+        parse: parse::<BZMPop>,
+    },
+    CommandSpec {
+        name: "xadd",
+        arity: -5,
+        flags: &[Write, DenyOom, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "stream",
+        since: "5.0.0",
+        summary: "Appends a new message to a stream. Creates the key if it doesn't exist.",
+        complexity: "O(1) when adding a new entry, O(N) when trimming where N being the number of entries evicted.",
+        parse: parse::<XAdd>,
+    },
+    CommandSpec {
+        name: "xrange",
+        arity: -4,
+        flags: &[ReadOnly],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "stream",
+        since: "5.0.0",
+        summary: "Returns the messages from a stream within a range of IDs.",
+        complexity: "O(N) with N being the number of elements being returned. If N is constant (e.g. always asking for the first 10 elements with COUNT), you can consider it O(1).",
+        parse: parse::<XRange>,
+    },
+    CommandSpec {
+        name: "xrevrange",
+        arity: -4,
+        flags: &[ReadOnly],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "stream",
+        since: "5.0.0",
+        summary: "Returns the messages from a stream within a range of IDs in reverse order.",
+        complexity: "O(N) with N being the number of elements returned. If N is constant (e.g. always asking for the first 10 elements with COUNT), you can consider it O(1).",
+        parse: parse::<XRevRange>,
+    },
+    CommandSpec {
+        name: "xlen",
+        arity: 2,
+        flags: &[ReadOnly, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "stream",
+        since: "5.0.0",
+        summary: "Return the number of messages in a stream.",
+        complexity: "O(1)",
+        parse: parse::<XLen>,
+    },
+    CommandSpec {
+        name: "xtrim",
+        arity: -4,
+        flags: &[Write],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "stream",
+        since: "5.0.0",
+        summary: "Deletes messages from the beginning of a stream.",
+        complexity: "O(N), with N being the number of evicted entries. Constant times are very small however, since entries are organized in macro nodes containing multiple entries that can be released with a single deallocation.",
+        parse: parse::<XTrim>,
+    },
+    CommandSpec {
+        name: "xdel",
+        arity: -3,
+        flags: &[Write, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "stream",
+        since: "5.0.0",
+        summary: "Returns the number of messages after removing them from a stream.",
+        complexity: "O(1) for each single item to delete in the stream, regardless of the stream size.",
+        parse: parse::<XDel>,
+    },
+    CommandSpec {
+        name: "xread",
+        arity: -4,
+        flags: &[ReadOnly, Blocking, MovableKeys],
+        first_key: 0,
+        last_key: 0,
+        step: 0,
+        group: "stream",
+        since: "5.0.0",
+        summary: "Returns messages from multiple streams with IDs greater than the ones requested. Blocks until a message is available otherwise.",
+        complexity: "For each stream mentioned: O(N) with N being the number of elements being returned, it means that XREAD-ing with a fixed COUNT is O(1). Note that when the BLOCK option is used, XADD will pay O(M) time in order to serve the M clients blocked on the stream getting new data.",
+        parse: parse::<XRead>,
+    },
+    CommandSpec {
+        name: "xgroup",
+        arity: -2,
+        flags: &[Write],
+        first_key: 2,
+        last_key: 2,
+        step: 1,
+        group: "stream",
+        since: "5.0.0",
+        summary: "A container for consumer groups commands.",
+        complexity: "Depends on subcommand.",
+        parse: parse::<XGroup>,
+    },
+    CommandSpec {
+        name: "xreadgroup",
+        arity: -7,
+        flags: &[Write, Blocking, MovableKeys],
+        first_key: 0,
+        last_key: 0,
+        step: 0,
+        group: "stream",
+        since: "5.0.0",
+        summary: "Returns new or historical messages from a stream for a consumer in a group. Blocks until a message is available otherwise.",
+        complexity: "For each stream mentioned: O(M) with M being the number of elements returned. If M is constant (e.g. always asking for the first 10 elements with COUNT), you can consider it O(1). On the other side when XREADGROUP blocks, XADD will pay the O(N) time in order to serve the N clients blocked on the stream getting new data.",
+        parse: parse::<XReadGroup>,
+    },
+    CommandSpec {
+        name: "xack",
+        arity: -4,
+        flags: &[Write, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "stream",
+        since: "5.0.0",
+        summary: "Returns the number of messages that were successfully acknowledged by the consumer group member of a stream.",
+        complexity: "O(1) for each message ID processed.",
+        parse: parse::<XAck>,
+    },
+    CommandSpec {
+        name: "xpending",
+        arity: -3,
+        flags: &[ReadOnly],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "stream",
+        since: "5.0.0",
+        summary: "Returns the information and entries from a stream consumer group's pending entries list.",
+        complexity: "O(N) with N being the number of elements returned, so asking for a small fixed number of entries per call is O(1). O(M), where M is the total number of entries scanned when used with the IDLE filter. When the command returns just the summary and the list of consumers is small, it runs in O(1) time; otherwise, an additional O(N) time for iterating every consumer.",
+        parse: parse::<XPending>,
+    },
+    CommandSpec {
+        name: "xclaim",
+        arity: -6,
+        flags: &[Write, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "stream",
+        since: "5.0.0",
+        summary: "Changes, or acquires, ownership of a message in a consumer group, as if the message was delivered a consumer group member.",
+        complexity: "O(log N) with N being the number of messages in the PEL of the consumer group.",
+        parse: parse::<XClaim>,
+    },
+    CommandSpec {
+        name: "xautoclaim",
+        arity: -6,
+        flags: &[Write, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "stream",
+        since: "6.2.0",
+        summary: "Changes, or acquires, ownership of messages in a consumer group, as if the messages were delivered to as consumer group member.",
+        complexity: "O(1) if COUNT is small.",
+        parse: parse::<XAutoClaim>,
+    },
+    CommandSpec {
+        name: "xinfo",
+        arity: -2,
+        flags: &[ReadOnly],
+        first_key: 2,
+        last_key: 2,
+        step: 1,
+        group: "stream",
+        since: "5.0.0",
+        summary: "A container for stream introspection commands.",
+        complexity: "Depends on subcommand.",
+        parse: parse::<XInfo>,
+    },
+    CommandSpec {
+        name: "expire",
+        arity: -3,
+        flags: &[Write, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "generic",
+        since: "1.0.0",
+        summary: "Sets the expiration time of a key in seconds.",
+        complexity: "O(1)",
+        parse: parse::<Expire>,
+    },
+    CommandSpec {
+        name: "pexpire",
+        arity: -3,
+        flags: &[Write, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "generic",
+        since: "2.6.0",
+        summary: "Sets the expiration time of a key in milliseconds.",
+        complexity: "O(1)",
+        parse: parse::<PExpire>,
+    },
+    CommandSpec {
+        name: "expireat",
+        arity: -3,
+        flags: &[Write, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "generic",
+        since: "1.2.0",
+        summary: "Sets the expiration time of a key to a Unix timestamp.",
+        complexity: "O(1)",
+        parse: parse::<ExpireAt>,
+    },
+    CommandSpec {
+        name: "pexpireat",
+        arity: -3,
+        flags: &[Write, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "generic",
+        since: "2.6.0",
+        summary: "Sets the expiration time of a key to a Unix milliseconds timestamp.",
+        complexity: "O(1)",
+        parse: parse::<PExpireAt>,
+    },
+    CommandSpec {
+        name: "ttl",
+        arity: 2,
+        flags: &[ReadOnly, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "generic",
+        since: "1.0.0",
+        summary: "Returns the expiration time in seconds of a key.",
+        complexity: "O(1)",
+        parse: parse::<Ttl>,
+    },
+    CommandSpec {
+        name: "pttl",
+        arity: 2,
+        flags: &[ReadOnly, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "generic",
+        since: "2.6.0",
+        summary: "Returns the expiration time in milliseconds of a key.",
+        complexity: "O(1)",
+        parse: parse::<PTtl>,
+    },
+    CommandSpec {
+        name: "expiretime",
+        arity: 2,
+        flags: &[ReadOnly, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "generic",
+        since: "7.0.0",
+        summary: "Returns the expiration time of a key as a Unix timestamp.",
+        complexity: "O(1)",
+        parse: parse::<ExpireTime>,
+    },
+    CommandSpec {
+        name: "pexpiretime",
+        arity: 2,
+        flags: &[ReadOnly, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "generic",
+        since: "7.0.0",
+        summary: "Returns the expiration time of a key as a Unix milliseconds timestamp.",
+        complexity: "O(1)",
+        parse: parse::<PExpireTime>,
+    },
+    CommandSpec {
+        name: "persist",
+        arity: 2,
+        flags: &[Write, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "generic",
+        since: "2.2.0",
+        summary: "Removes the expiration time of a key.",
+        complexity: "O(1)",
+        parse: parse::<Persist>,
+    },
+    CommandSpec {
+        name: "del",
+        arity: -2,
+        flags: &[Write],
+        first_key: 1,
+        last_key: -1,
+        step: 1,
+        group: "generic",
+        since: "1.0.0",
+        summary: "Deletes one or more keys.",
+        complexity: "O(N) where N is the number of keys that will be removed. When a key to remove holds a value other than a string, the individual complexity for this key is O(M) where M is the number of elements in the list, set, sorted set or hash. Removing a single key that holds a string value is O(1).",
+        parse: parse::<Del>,
+    },
+    CommandSpec {
+        name: "unlink",
+        arity: -2,
+        flags: &[Write, Fast],
+        first_key: 1,
+        last_key: -1,
+        step: 1,
+        group: "generic",
+        since: "4.0.0",
+        summary: "Asynchronously deletes one or more keys.",
+        complexity: "O(1) for each key removed regardless of its size. Then the command does O(N) work in a different thread in order to reclaim memory, where N is the number of allocations the deleted objects where composed of.",
+        parse: parse::<Unlink>,
+    },
+    CommandSpec {
+        name: "exists",
+        arity: -2,
+        flags: &[ReadOnly, Fast],
+        first_key: 1,
+        last_key: -1,
+        step: 1,
+        group: "generic",
+        since: "1.0.0",
+        summary: "Determines whether one or more keys exist.",
+        complexity: "O(N) where N is the number of keys to check.",
+        parse: parse::<Exists>,
+    },
+    CommandSpec {
+        name: "touch",
+        arity: -2,
+        flags: &[ReadOnly, Fast],
+        first_key: 1,
+        last_key: -1,
+        step: 1,
+        group: "generic",
+        since: "3.2.1",
+        summary: "Returns the number of existing keys out of those specified after updating the time they were last accessed.",
+        complexity: "O(N) where N is the number of keys that will be touched.",
+        parse: parse::<Touch>,
+    },
+    CommandSpec {
+        name: "type",
+        arity: 2,
+        flags: &[ReadOnly, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "generic",
+        since: "1.0.0",
+        summary: "Determines the type of value stored at a key.",
+        complexity: "O(1)",
+        parse: parse::<Type>,
+    },
+    CommandSpec {
+        name: "rename",
+        arity: 3,
+        flags: &[Write],
+        first_key: 1,
+        last_key: 2,
+        step: 1,
+        group: "generic",
+        since: "1.0.0",
+        summary: "Renames a key and overwrites the destination.",
+        complexity: "O(1)",
+        parse: parse::<Rename>,
+    },
+    CommandSpec {
+        name: "renamenx",
+        arity: 3,
+        flags: &[Write, Fast],
+        first_key: 1,
+        last_key: 2,
+        step: 1,
+        group: "generic",
+        since: "1.0.0",
+        summary: "Renames a key only when the target key name doesn't exist.",
+        complexity: "O(1)",
+        parse: parse::<RenameNx>,
+    },
+    CommandSpec {
+        name: "copy",
+        arity: -3,
+        flags: &[Write, DenyOom],
+        first_key: 1,
+        last_key: 2,
+        step: 1,
+        group: "generic",
+        since: "6.2.0",
+        summary: "Copies the value of a key to a new key.",
+        complexity: "O(N) worst case for collections, where N is the number of nested items. O(1) for string values.",
+        parse: parse::<CopyCmd>,
+    },
+    CommandSpec {
+        name: "keys",
+        arity: 2,
+        flags: &[ReadOnly],
+        first_key: 0,
+        last_key: 0,
+        step: 0,
+        group: "generic",
+        since: "1.0.0",
+        summary: "Returns all key names that match a pattern.",
+        complexity: "O(N) with N being the number of keys in the database, under the assumption that the key names in the database and the given pattern have limited length.",
+        parse: parse::<Keys>,
+    },
+    CommandSpec {
+        name: "scan",
+        arity: -2,
+        flags: &[ReadOnly],
+        first_key: 0,
+        last_key: 0,
+        step: 0,
+        group: "generic",
+        since: "2.8.0",
+        summary: "Iterates over the key names in the database.",
+        complexity: "O(N) for every call, where N is the number of keys in the database.",
+        parse: parse::<Scan>,
+    },
+    CommandSpec {
+        name: "hello",
+        arity: -1,
+        flags: &[Fast, Loading, Stale],
+        first_key: 0,
+        last_key: 0,
+        step: 0,
+        group: "connection",
+        since: "6.0.0",
+        summary: "Handshakes with the Redis server.",
+        complexity: "O(1)",
+        parse: parse::<Hello>,
+    },
+    CommandSpec {
+        name: "ping",
+        arity: -1,
+        flags: &[Fast],
+        first_key: 0,
+        last_key: 0,
+        step: 0,
+        group: "connection",
+        since: "1.0.0",
+        summary: "Returns the server's liveliness response.",
+        complexity: "O(1)",
+        parse: parse::<Ping>,
+    },
+    CommandSpec {
+        name: "subscribe",
+        arity: -2,
+        flags: &[PubSub, Loading, Stale],
+        first_key: 0,
+        last_key: 0,
+        step: 0,
+        group: "pubsub",
+        since: "2.0.0",
+        summary: "Listens for messages published to channels.",
+        complexity: "O(N) where N is the number of channels to subscribe to.",
+        parse: parse::<Subscribe>,
+    },
+    CommandSpec {
+        name: "unsubscribe",
+        arity: -1,
+        flags: &[PubSub, Loading, Stale],
+        first_key: 0,
+        last_key: 0,
+        step: 0,
+        group: "pubsub",
+        since: "2.0.0",
+        summary: "Stops listening to messages posted to channels.",
+        complexity: "O(N) where N is the number of channels to unsubscribe.",
+        parse: parse::<Unsubscribe>,
+    },
+    CommandSpec {
+        name: "psubscribe",
+        arity: -2,
+        flags: &[PubSub, Loading, Stale],
+        first_key: 0,
+        last_key: 0,
+        step: 0,
+        group: "pubsub",
+        since: "2.0.0",
+        summary: "Listens for messages published to channels that match one or more patterns.",
+        complexity: "O(N) where N is the number of patterns to subscribe to.",
+        parse: parse::<PSubscribe>,
+    },
+    CommandSpec {
+        name: "punsubscribe",
+        arity: -1,
+        flags: &[PubSub, Loading, Stale],
+        first_key: 0,
+        last_key: 0,
+        step: 0,
+        group: "pubsub",
+        since: "2.0.0",
+        summary: "Stops listening to messages published to channels that match one or more patterns.",
+        complexity: "O(N) where N is the number of patterns to unsubscribe.",
+        parse: parse::<PUnsubscribe>,
+    },
+    CommandSpec {
+        name: "publish",
+        arity: 3,
+        flags: &[PubSub, Loading, Stale, Fast],
+        first_key: 0,
+        last_key: 0,
+        step: 0,
+        group: "pubsub",
+        since: "2.0.0",
+        summary: "Posts a message to a channel.",
+        complexity: "O(N+M) where N is the number of clients subscribed to the receiving channel and M is the total number of subscribed patterns (by any client).",
+        parse: parse::<Publish>,
+    },
+    CommandSpec {
+        name: "pubsub",
+        arity: -2,
+        flags: &[PubSub, Loading, Stale],
+        first_key: 0,
+        last_key: 0,
+        step: 0,
+        group: "pubsub",
+        since: "2.8.0",
+        summary: "A container for Pub/Sub commands.",
+        complexity: "Depends on subcommand.",
+        parse: parse::<PubSubCmd>,
+    },
+    CommandSpec {
+        name: "ssubscribe",
+        arity: -2,
+        flags: &[PubSub, Loading, Stale],
+        first_key: 1,
+        last_key: -1,
+        step: 1,
+        group: "pubsub",
+        since: "7.0.0",
+        summary: "Listens for messages published to shard channels.",
+        complexity: "O(N) where N is the number of shard channels to subscribe to.",
+        parse: parse::<SSubscribe>,
+    },
+    CommandSpec {
+        name: "sunsubscribe",
+        arity: -1,
+        flags: &[PubSub, Loading, Stale],
+        first_key: 1,
+        last_key: -1,
+        step: 1,
+        group: "pubsub",
+        since: "7.0.0",
+        summary: "Stops listening to messages posted to shard channels.",
+        complexity: "O(N) where N is the number of shard channels to unsubscribe.",
+        parse: parse::<SUnsubscribe>,
+    },
+    CommandSpec {
+        name: "spublish",
+        arity: 3,
+        flags: &[PubSub, Loading, Stale, Fast],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        group: "pubsub",
+        since: "7.0.0",
+        summary: "Posts a message to a shard channel.",
+        complexity: "O(N) where N is the number of clients subscribed to the receiving shard channel.",
+        parse: parse::<SPublish>,
+    },
+    CommandSpec {
+        name: "multi",
+        arity: 1,
+        flags: &[Loading, Stale, Fast],
+        first_key: 0,
+        last_key: 0,
+        step: 0,
+        group: "transactions",
+        since: "1.2.0",
+        summary: "Starts a transaction.",
+        complexity: "O(1)",
+        parse: parse::<Multi>,
+    },
+    CommandSpec {
+        name: "exec",
+        arity: 1,
+        flags: &[Loading, Stale],
+        first_key: 0,
+        last_key: 0,
+        step: 0,
+        group: "transactions",
+        since: "1.2.0",
+        summary: "Executes all commands in a transaction.",
+        complexity: "Depends on commands in the transaction",
+        parse: parse::<Exec>,
+    },
+    CommandSpec {
+        name: "discard",
+        arity: 1,
+        flags: &[Loading, Stale, Fast],
+        first_key: 0,
+        last_key: 0,
+        step: 0,
+        group: "transactions",
+        since: "2.0.0",
+        summary: "Discards a transaction.",
+        complexity: "O(N), when N is the number of queued commands",
+        parse: parse::<Discard>,
+    },
+    CommandSpec {
+        name: "command",
+        arity: -1,
+        flags: &[Loading, Stale],
+        first_key: 0,
+        last_key: 0,
+        step: 0,
+        group: "server",
+        since: "2.8.13",
+        summary: "Returns detailed information about all commands.",
+        complexity: "O(N) where N is the total number of Redis commands",
+        parse: parse::<CommandCmd>,
+    },
+    CommandSpec {
+        name: "config",
+        arity: -2,
+        flags: &[Admin, Loading, Stale],
+        first_key: 0,
+        last_key: 0,
+        step: 0,
+        group: "server",
+        since: "2.0.0",
+        summary: "A container for server configuration commands.",
+        complexity: "Depends on subcommand.",
+        parse: parse::<ConfigCmd>,
+    },
+];
+
+lazy_static! {
+    static ref COMMANDS_BY_NAME: HashMap<&'static str, &'static CommandSpec> =
+        COMMAND_TABLE.iter().map(|spec| (spec.name, spec)).collect();
+}
+
+/// Returns the keys of `XREAD`, which are the first half of the arguments following
+/// `STREAMS`, the other half being their IDs.
+fn stream_keys(args: &RespArray) -> Vec<&RespFrame> {
+    let streams = args.iter().position(
+        |arg| matches!(arg, RespFrame::BulkString(arg) if arg.eq_ignore_ascii_case(b"streams")),
+    );
+    match streams {
+        Some(streams) => {
+            let numkeys = (args.len() - streams - 1) / 2;
+            args.iter().skip(streams + 1).take(numkeys).collect()
+        }
+        None => Vec::new(),
+    }
+}
+
+/// Looks up a command by name, case-insensitively.
+pub fn lookup_command(name: &[u8]) -> Option<&'static CommandSpec> {
+    let name = name.to_ascii_lowercase();
+    std::str::from_utf8(&name)
+        .ok()
+        .and_then(|name| COMMANDS_BY_NAME.get(name).copied())
+}
+
+impl CommandSpec {
+    /// Returns `true` if a command array of `len` elements, name included, satisfies the
+    /// arity of this command.
+    pub fn check_arity(&self, len: usize) -> bool {
+        let len = len as i64;
+        if self.arity >= 0 {
+            len == self.arity
+        } else {
+            len >= -self.arity
+        }
+    }
+
+    /// Returns the key arguments of a command array, as located by the key specs.
+    pub fn keys<'a>(&self, args: &'a RespArray) -> Vec<&'a RespFrame> {
+        let mut keys = self.movable_keys(args);
+        if self.first_key <= 0 || self.step <= 0 {
+            return keys;
+        }
+        let last = if self.last_key < 0 {
+            args.len() as i64 + self.last_key
+        } else {
+            self.last_key
+        };
+        keys.extend(
+            (self.first_key..=last.min(args.len() as i64 - 1))
+                .step_by(self.step as usize)
+                .map(|i| &args[i as usize]),
+        );
+        keys
+    }
+
+    /// Returns the keys of a command flagged `MovableKeys`, which are preceded by their
+    /// number, or for the stream commands follow `STREAMS`.
+    fn movable_keys<'a>(&self, args: &'a RespArray) -> Vec<&'a RespFrame> {
+        if !self.has_flag(MovableKeys) {
+            return Vec::new();
+        }
+        let numkeys_index = match self.name {
+            "xread" | "xreadgroup" => return stream_keys(args),
+            "lmpop" => 1,
+            "blmpop" => 2,
+            "sintercard" | "zunion" | "zinter" | "zdiff" | "zmpop" => 1,
+            "zunionstore" | "zinterstore" | "zdiffstore" | "bzmpop" => 2,
+            _ => return Vec::new(),
+        };
+        let numkeys = match args.get(numkeys_index) {
+            Some(RespFrame::BulkString(numkeys)) => std::str::from_utf8(numkeys)
+                .ok()
+                .and_then(|numkeys| numkeys.parse().ok())
+                .unwrap_or_default(),
+            _ => 0,
+        };
+        args.iter().skip(numkeys_index + 1).take(numkeys).collect()
+    }
+
+    /// Returns `true` if the command has the given flag.
+    pub fn has_flag(&self, flag: CommandFlag) -> bool {
+        self.flags.contains(&flag)
+    }
+
+    /// Builds the `COMMAND INFO` reply of this command.
+    ///
+    /// The reply is an array of name, arity, flags, first key, last key, step, ACL
+    /// categories, tips, key specifications and subcommands, as sent by Redis 7.
+    pub(crate) fn info(&self) -> RespFrame {
+        let flags = self
+            .flags
+            .iter()
+            .map(|flag| SimpleString::new(flag.as_str()).into())
+            .collect::<Vec<_>>();
+        let mut categories = vec![format!("@{}", self.group)];
+        if self.has_flag(Write) {
+            categories.push("@write".to_string());
+        } else if self.has_flag(ReadOnly) {
+            categories.push("@read".to_string());
+        }
+        if self.has_flag(Admin) {
+            categories.push("@admin".to_string());
+            categories.push("@dangerous".to_string());
+        }
+        if self.has_flag(PubSub) {
+            categories.push("@pubsub".to_string());
+        }
+        if self.has_flag(Blocking) {
+            categories.push("@blocking".to_string());
+        }
+        categories.push(
+            if self.has_flag(Fast) {
+                "@fast"
+            } else {
+                "@slow"
+            }
+            .to_string(),
+        );
+        let categories = categories
+            .into_iter()
+            .map(|category| SimpleString::new(category).into())
+            .collect::<Vec<_>>();
+        RespArray::new([
+            BulkString::new(self.name).into(),
+            self.arity.into(),
+            RespArray::new(flags).into(),
+            self.first_key.into(),
+            self.last_key.into(),
+            self.step.into(),
+            RespArray::new(categories).into(),
+            RespArray::new([]).into(),
+            RespArray::new([]).into(),
+            RespArray::new([]).into(),
+        ])
+        .into()
+    }
+
+    /// Builds the `COMMAND DOCS` reply of this command.
+    pub(crate) fn docs(&self) -> RespFrame {
+        let mut docs = RespMap::new();
+        docs.insert("summary".into(), BulkString::new(self.summary).into());
+        docs.insert("since".into(), BulkString::new(self.since).into());
+        docs.insert("group".into(), BulkString::new(self.group).into());
+        docs.insert("complexity".into(), BulkString::new(self.complexity).into());
+        docs.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_table_names_are_unique_and_lowercase() {
+        assert_eq!(COMMANDS_BY_NAME.len(), COMMAND_TABLE.len());
+        for spec in COMMAND_TABLE {
+            assert_eq!(spec.name, spec.name.to_ascii_lowercase());
+        }
+    }
+
+    #[test]
+    fn test_lookup_and_arity() {
+        let spec = lookup_command(b"GET").unwrap();
+        assert_eq!(spec.name, "get");
+        assert!(spec.check_arity(2));
+        assert!(!spec.check_arity(3));
+
+        let spec = lookup_command(b"set").unwrap();
+        assert!(!spec.check_arity(2));
+        assert!(spec.check_arity(3));
+        assert!(spec.check_arity(6));
+
+        assert!(lookup_command(b"nosuchcommand").is_none());
+    }
+
+    #[test]
+    fn test_keys() {
+        let spec = lookup_command(b"get").unwrap();
+        let args = RespArray::new([b"get".into(), b"hello".into()]);
+        assert_eq!(spec.keys(&args), vec![&RespFrame::from(b"hello")]);
+
+        let spec = lookup_command(b"command").unwrap();
+        let args = RespArray::new([b"command".into()]);
+        assert!(spec.keys(&args).is_empty());
+
+        let spec = lookup_command(b"lmpop").unwrap();
+        let args = RespArray::new([
+            b"lmpop".into(),
+            b"2".into(),
+            b"a".into(),
+            b"b".into(),
+            b"left".into(),
+        ]);
+        assert_eq!(
+            spec.keys(&args),
+            vec![&RespFrame::from(b"a"), &RespFrame::from(b"b")]
+        );
+    }
+}
@@ -0,0 +1,301 @@
+use super::{
+    CommandError, CommandExecutor, CommandRequest, LockScope, Session, SessionCommand, RESP_OK,
+};
+use crate::{Backend, KeyLockGuard, RespArray, RespFrame, SimpleString};
+
+/// The commands a connection queued since `MULTI`, run all at once by `EXEC`.
+#[derive(Debug, Default)]
+pub(super) struct Transaction {
+    commands: Vec<CommandRequest>,
+    /// Set when a command could not be queued, which makes `EXEC` discard the transaction.
+    failed: bool,
+}
+
+/// `MULTI`
+#[derive(Debug)]
+pub struct Multi;
+
+/// `EXEC`
+#[derive(Debug)]
+pub struct Exec;
+
+/// `DISCARD`
+#[derive(Debug)]
+pub struct Discard;
+
+impl Session {
+    /// Returns `true` if the connection is queueing commands since `MULTI`.
+    pub fn in_transaction(&self) -> bool {
+        self.transaction.is_some()
+    }
+
+    /// Marks the transaction of the connection, if any, as failed, e.g. because a command
+    /// could not be parsed, so that `EXEC` discards it instead of running it.
+    pub fn flag_transaction(&mut self) {
+        if let Some(transaction) = &mut self.transaction {
+            transaction.failed = true;
+        }
+    }
+
+    /// Queues a command of the transaction, to be run by `EXEC`.
+    pub(super) fn queue(&mut self, request: CommandRequest) -> RespFrame {
+        match &mut self.transaction {
+            Some(transaction) => {
+                transaction.commands.push(request);
+                SimpleString::new("QUEUED").into()
+            }
+            None => unreachable!("queueing a command outside of a transaction"),
+        }
+    }
+}
+
+/// Locks what the queued commands access for the whole of `EXEC`, so that no other client
+/// runs a command in between: the whole keyspace if one of them scans it, e.g. `KEYS`, and
+/// the keys of all of them otherwise.
+fn lock_transaction<'a>(backend: &'a Backend, commands: &[CommandRequest]) -> KeyLockGuard<'a> {
+    let scopes = commands.iter().map(|request| &request.scope);
+    LockScope {
+        keys: scopes
+            .clone()
+            .flat_map(|scope| scope.keys.clone())
+            .collect(),
+        write: scopes.clone().any(|scope| scope.write),
+        keyspace: scopes.clone().any(|scope| scope.keyspace),
+    }
+    .lock(backend)
+}
+
+impl SessionCommand for Multi {
+    /// Starts queueing the commands of the connection.
+    fn execute_session(self, _backend: &Backend, session: &mut Session) -> RespFrame {
+        if session.in_transaction() {
+            return CommandError::InvalidCommand("MULTI calls can not be nested".to_string())
+                .into();
+        }
+        session.transaction = Some(Transaction::default());
+        RESP_OK.clone()
+    }
+}
+
+impl SessionCommand for Exec {
+    /// Runs the queued commands one after the other while holding the locks of all of them,
+    /// and returns the array of their replies.
+    ///
+    /// Blocking commands don't wait, and reply as if their timeout expired when they would
+    /// have to. Nothing is run if a command failed to be queued.
+    fn execute_session(self, backend: &Backend, session: &mut Session) -> RespFrame {
+        let Some(transaction) = session.transaction.take() else {
+            return CommandError::InvalidCommand("EXEC without MULTI".to_string()).into();
+        };
+        if transaction.failed {
+            return CommandError::ExecAbort.into();
+        }
+        let _guard = lock_transaction(backend, &transaction.commands);
+        let replies = transaction
+            .commands
+            .into_iter()
+            .map(|request| request.execute_queued(backend, session))
+            .collect::<Vec<_>>();
+        RespArray::new(replies).into()
+    }
+}
+
+impl SessionCommand for Discard {
+    /// Drops the queued commands and leaves the transaction.
+    fn execute_session(self, _backend: &Backend, session: &mut Session) -> RespFrame {
+        match session.transaction.take() {
+            Some(_) => RESP_OK.clone(),
+            None => CommandError::InvalidCommand("DISCARD without MULTI".to_string()).into(),
+        }
+    }
+}
+
+impl CommandExecutor for Multi {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        self.execute_session(backend, &mut Session::new())
+    }
+}
+
+impl CommandExecutor for Exec {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        self.execute_session(backend, &mut Session::new())
+    }
+}
+
+impl CommandExecutor for Discard {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        self.execute_session(backend, &mut Session::new())
+    }
+}
+
+impl TryFrom<RespArray> for Multi {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        match value.len() {
+            1 => Ok(Multi),
+            _ => Err(CommandError::WrongArity("multi".to_string())),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Exec {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        match value.len() {
+            1 => Ok(Exec),
+            _ => Err(CommandError::WrongArity("exec".to_string())),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Discard {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        match value.len() {
+            1 => Ok(Discard),
+            _ => Err(CommandError::WrongArity("discard".to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::Protocol;
+    use crate::{BulkString, RespNull, RespNullArray};
+
+    fn request(args: &[&str]) -> CommandRequest {
+        let frame = RespArray::new(
+            args.iter()
+                .map(|arg| BulkString::new(*arg).into())
+                .collect::<Vec<RespFrame>>(),
+        );
+        CommandRequest::try_from(RespFrame::Array(frame)).unwrap()
+    }
+
+    async fn run(backend: &Backend, session: &mut Session, args: &[&str]) -> RespFrame {
+        request(args).run(backend, session).await
+    }
+
+    fn queued() -> RespFrame {
+        SimpleString::new("QUEUED").into()
+    }
+
+    #[tokio::test]
+    async fn test_transaction() {
+        let backend = Backend::new();
+        let mut session = Session::new();
+        assert_eq!(
+            run(&backend, &mut session, &["multi"]).await,
+            RESP_OK.clone()
+        );
+        assert!(session.in_transaction());
+        assert_eq!(
+            run(&backend, &mut session, &["multi"]).await,
+            CommandError::InvalidCommand("MULTI calls can not be nested".to_string()).into()
+        );
+        for args in [
+            &["set", "a", "1"][..],
+            &["incr", "a"],
+            &["lpush", "a", "x"],
+            &["get", "a"],
+            &["blpop", "list", "0"],
+            &["keys", "*"],
+        ] {
+            assert_eq!(run(&backend, &mut session, args).await, queued());
+        }
+        // nothing runs before EXEC
+        assert!(!backend.contains_key(b"a"));
+
+        // a command failing at runtime doesn't stop the others, and blocking commands
+        // don't wait
+        assert_eq!(
+            run(&backend, &mut session, &["exec"]).await,
+            RespArray::new([
+                RESP_OK.clone(),
+                RespFrame::Integer(2),
+                CommandError::WrongType.into(),
+                BulkString::new("2").into(),
+                RespFrame::NullArray(RespNullArray),
+                RespArray::new([BulkString::new("a").into()]).into(),
+            ])
+            .into()
+        );
+        assert!(!session.in_transaction());
+        assert_eq!(
+            run(&backend, &mut session, &["get", "a"]).await,
+            BulkString::new("2").into()
+        );
+
+        // an empty transaction
+        run(&backend, &mut session, &["multi"]).await;
+        assert_eq!(
+            run(&backend, &mut session, &["exec"]).await,
+            RespArray::new([]).into()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_exec_flattens_pairs_in_resp2() {
+        let backend = Backend::new();
+        request(&["zadd", "z", "1", "a"]).execute(&backend);
+        let mut session = Session::new();
+        run(&backend, &mut session, &["multi"]).await;
+        run(
+            &backend,
+            &mut session,
+            &["zrange", "z", "0", "-1", "withscores"],
+        )
+        .await;
+        let RespFrame::Array(replies) = run(&backend, &mut session, &["exec"]).await else {
+            panic!("expected an array");
+        };
+        let RespFrame::Array(members) = &replies[0] else {
+            panic!("expected an array");
+        };
+        assert_eq!(session.protocol(), Protocol::Resp2);
+        assert_eq!(members.len(), 2);
+        assert_eq!(members[0], BulkString::new("a").into());
+    }
+
+    #[tokio::test]
+    async fn test_exec_abort_and_discard() {
+        let backend = Backend::new();
+        let mut session = Session::new();
+        assert_eq!(
+            run(&backend, &mut session, &["exec"]).await,
+            CommandError::InvalidCommand("EXEC without MULTI".to_string()).into()
+        );
+        assert_eq!(
+            run(&backend, &mut session, &["discard"]).await,
+            CommandError::InvalidCommand("DISCARD without MULTI".to_string()).into()
+        );
+
+        // a command that fails to parse discards the whole transaction
+        run(&backend, &mut session, &["multi"]).await;
+        run(&backend, &mut session, &["set", "a", "1"]).await;
+        session.flag_transaction();
+        assert_eq!(
+            run(&backend, &mut session, &["exec"]).await,
+            CommandError::ExecAbort.into()
+        );
+        assert!(!session.in_transaction());
+        assert!(!backend.contains_key(b"a"));
+
+        run(&backend, &mut session, &["multi"]).await;
+        run(&backend, &mut session, &["set", "a", "1"]).await;
+        assert_eq!(
+            run(&backend, &mut session, &["discard"]).await,
+            RESP_OK.clone()
+        );
+        assert!(!session.in_transaction());
+        assert_eq!(
+            run(&backend, &mut session, &["get", "a"]).await,
+            RespFrame::Null(RespNull)
+        );
+
+        // outside of a transaction, flagging it is a no-op
+        session.flag_transaction();
+        assert!(!session.in_transaction());
+    }
+}
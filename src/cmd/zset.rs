@@ -0,0 +1,1785 @@
+use super::hmap::parse_key_and_args;
+use super::list::{
+    bulk_string_args, parse_keys_timeout, parse_mpop_count, parse_numkeys, parse_timeout,
+};
+use super::{
+    notify_removed, notify_store, optional_bulk_string, parse_integer, parse_key_arg,
+    BlockingCommand, CommandError, CommandExecutor,
+};
+use crate::{
+    Aggregate, Backend, BulkString, EventClass, LexBound, LexRange, RespArray, RespFrame, RespNull,
+    RespNullArray, ScoreRange, ScoredMembers, SetCondition, ZAddComparison, ZAddOptions, ZRangeBy,
+    ZSetEnd,
+};
+use bytes::Bytes;
+use std::time::Duration;
+
+/// `ZADD key [NX | XX] [GT | LT] [CH] [INCR] score member [score member ...]`
+#[derive(Debug)]
+pub struct ZAdd {
+    key: Bytes,
+    options: ZAddOptions,
+    incr: bool,
+    members: Vec<(f64, Bytes)>,
+}
+
+#[derive(Debug)]
+pub struct ZRem {
+    key: Bytes,
+    members: Vec<Bytes>,
+}
+
+#[derive(Debug)]
+pub struct ZScore {
+    key: Bytes,
+    member: Bytes,
+}
+
+#[derive(Debug)]
+pub struct ZMScore {
+    key: Bytes,
+    members: Vec<Bytes>,
+}
+
+#[derive(Debug)]
+pub struct ZIncrBy {
+    key: Bytes,
+    increment: f64,
+    member: Bytes,
+}
+
+#[derive(Debug)]
+pub struct ZCard {
+    key: Bytes,
+}
+
+#[derive(Debug)]
+pub struct ZCount {
+    key: Bytes,
+    range: ScoreRange,
+}
+
+/// `ZRANK key member [WITHSCORE]`
+#[derive(Debug)]
+pub struct ZRank {
+    key: Bytes,
+    member: Bytes,
+    with_score: bool,
+}
+
+/// `ZREVRANK key member [WITHSCORE]`
+#[derive(Debug)]
+pub struct ZRevRank {
+    key: Bytes,
+    member: Bytes,
+    with_score: bool,
+}
+
+/// `ZRANGE key start stop [BYSCORE | BYLEX] [REV] [LIMIT offset count] [WITHSCORES]`
+#[derive(Debug)]
+pub struct ZRange {
+    key: Bytes,
+    args: ZRangeArgs,
+}
+
+/// What `ZRANGE` and `ZRANGESTORE` select and in which order.
+#[derive(Debug)]
+struct ZRangeArgs {
+    range: ZRangeBy,
+    rev: bool,
+    offset: usize,
+    count: usize,
+    with_scores: bool,
+}
+
+/// `ZUNIONSTORE destination numkeys key [key ...] [WEIGHTS weight [weight ...]]
+/// [AGGREGATE <SUM | MIN | MAX>]`
+#[derive(Debug)]
+pub struct ZUnionStore {
+    destination: Bytes,
+    keys: Vec<Bytes>,
+    weights: Vec<f64>,
+    aggregate: Aggregate,
+}
+
+/// `ZINTERSTORE destination numkeys key [key ...] [WEIGHTS weight [weight ...]]
+/// [AGGREGATE <SUM | MIN | MAX>]`
+#[derive(Debug)]
+pub struct ZInterStore {
+    destination: Bytes,
+    keys: Vec<Bytes>,
+    weights: Vec<f64>,
+    aggregate: Aggregate,
+}
+
+/// `ZDIFFSTORE destination numkeys key [key ...]`
+#[derive(Debug)]
+pub struct ZDiffStore {
+    destination: Bytes,
+    keys: Vec<Bytes>,
+}
+
+/// `ZUNION numkeys key [key ...] [WEIGHTS weight [weight ...]]
+/// [AGGREGATE <SUM | MIN | MAX>] [WITHSCORES]`
+#[derive(Debug)]
+pub struct ZUnion {
+    keys: Vec<Bytes>,
+    weights: Vec<f64>,
+    aggregate: Aggregate,
+    with_scores: bool,
+}
+
+/// `ZINTER numkeys key [key ...] [WEIGHTS weight [weight ...]]
+/// [AGGREGATE <SUM | MIN | MAX>] [WITHSCORES]`
+#[derive(Debug)]
+pub struct ZInter {
+    keys: Vec<Bytes>,
+    weights: Vec<f64>,
+    aggregate: Aggregate,
+    with_scores: bool,
+}
+
+/// `ZDIFF numkeys key [key ...] [WITHSCORES]`
+#[derive(Debug)]
+pub struct ZDiff {
+    keys: Vec<Bytes>,
+    with_scores: bool,
+}
+
+#[derive(Debug)]
+pub struct ZRemRangeByScore {
+    key: Bytes,
+    range: ZRangeBy,
+}
+
+#[derive(Debug)]
+pub struct ZRemRangeByRank {
+    key: Bytes,
+    range: ZRangeBy,
+}
+
+#[derive(Debug)]
+pub struct ZRemRangeByLex {
+    key: Bytes,
+    range: ZRangeBy,
+}
+
+#[derive(Debug)]
+pub struct ZPopMin {
+    key: Bytes,
+    count: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct ZPopMax {
+    key: Bytes,
+    count: Option<usize>,
+}
+
+/// `ZRANDMEMBER key [count [WITHSCORES]]`
+#[derive(Debug)]
+pub struct ZRandMember {
+    key: Bytes,
+    count: Option<i64>,
+    with_scores: bool,
+}
+
+/// `ZRANGESTORE dst src min max [BYSCORE | BYLEX] [REV] [LIMIT offset count]`
+#[derive(Debug)]
+pub struct ZRangeStore {
+    destination: Bytes,
+    source: Bytes,
+    args: ZRangeArgs,
+}
+
+/// `ZMPOP numkeys key [key ...] <MIN | MAX> [COUNT count]`
+#[derive(Debug)]
+pub struct ZMPop {
+    keys: Vec<Bytes>,
+    end: ZSetEnd,
+    count: usize,
+}
+
+#[derive(Debug)]
+pub struct BZPopMin {
+    keys: Vec<Bytes>,
+    timeout: Option<Duration>,
+}
+
+#[derive(Debug)]
+pub struct BZPopMax {
+    keys: Vec<Bytes>,
+    timeout: Option<Duration>,
+}
+
+/// `BZMPOP timeout numkeys key [key ...] <MIN | MAX> [COUNT count]`
+#[derive(Debug)]
+pub struct BZMPop {
+    keys: Vec<Bytes>,
+    end: ZSetEnd,
+    count: usize,
+    timeout: Option<Duration>,
+}
+
+impl CommandExecutor for ZAdd {
+    /// Returns the number of members added, or changed with `CH`. With `INCR`, returns the
+    /// new score of the member, or a null reply if the options prevented the update.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        if self.incr {
+            let (increment, member) = self.members.into_iter().next().unwrap_or_default();
+            return match backend.zincrby(&self.key, member, increment, self.options) {
+                Ok(score) => {
+                    if score.is_some() {
+                        backend.notify_keyspace_event(EventClass::ZSet, "zincr", &self.key);
+                    }
+                    optional_score(score)
+                }
+                Err(e) => e.into(),
+            };
+        }
+        match backend.zadd_counts(&self.key, self.members, self.options) {
+            Ok((added, updated)) => {
+                if added + updated > 0 {
+                    backend.notify_keyspace_event(EventClass::ZSet, "zadd", &self.key);
+                }
+                let count = if self.options.changed {
+                    added + updated
+                } else {
+                    added
+                };
+                RespFrame::Integer(count as i64)
+            }
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for ZRem {
+    /// Returns the number of members that were removed.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.zrem(&self.key, &self.members) {
+            Ok(removed) => {
+                if removed > 0 {
+                    notify_removed(backend, EventClass::ZSet, "zrem", &self.key);
+                }
+                RespFrame::Integer(removed as i64)
+            }
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for ZScore {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.zscore(&self.key, &self.member) {
+            Ok(score) => optional_score(score),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for ZMScore {
+    /// Returns the score of each member, or a null reply for the missing ones.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.zmscore(&self.key, &self.members) {
+            Ok(scores) => {
+                RespArray::new(scores.into_iter().map(optional_score).collect::<Vec<_>>()).into()
+            }
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for ZIncrBy {
+    /// Returns the new score of the member.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.zincrby(
+            &self.key,
+            self.member,
+            self.increment,
+            ZAddOptions::default(),
+        ) {
+            Ok(score) => {
+                backend.notify_keyspace_event(EventClass::ZSet, "zincr", &self.key);
+                optional_score(score)
+            }
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for ZCard {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.zcard(&self.key) {
+            Ok(len) => RespFrame::Integer(len as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for ZCount {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.zcount(&self.key, &self.range) {
+            Ok(count) => RespFrame::Integer(count as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for ZRank {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        rank(backend, &self.key, &self.member, false, self.with_score)
+    }
+}
+
+impl CommandExecutor for ZRevRank {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        rank(backend, &self.key, &self.member, true, self.with_score)
+    }
+}
+
+impl CommandExecutor for ZRange {
+    /// Returns the selected members, each paired with its score with `WITHSCORES`.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let args = self.args;
+        match backend.zrange(&self.key, &args.range, args.rev, args.offset, args.count) {
+            Ok(members) => scored_members_reply(members, args.with_scores),
+            Err(e) => e.into(),
+        }
+    }
+
+    fn has_pair_reply(&self) -> bool {
+        self.args.with_scores
+    }
+}
+
+impl CommandExecutor for ZUnionStore {
+    /// Returns the number of members of the stored sorted set.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.zunion(&self.keys, &self.weights, self.aggregate) {
+            Ok(members) => store(backend, "zunionstore", self.destination, members),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for ZInterStore {
+    /// Returns the number of members of the stored sorted set.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.zinter(&self.keys, &self.weights, self.aggregate) {
+            Ok(members) => store(backend, "zinterstore", self.destination, members),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for ZDiffStore {
+    /// Returns the number of members of the stored sorted set.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.zdiff(&self.keys) {
+            Ok(members) => store(backend, "zdiffstore", self.destination, members),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for ZUnion {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.zunion(&self.keys, &self.weights, self.aggregate) {
+            Ok(members) => scored_members_reply(members, self.with_scores),
+            Err(e) => e.into(),
+        }
+    }
+
+    fn has_pair_reply(&self) -> bool {
+        self.with_scores
+    }
+}
+
+impl CommandExecutor for ZInter {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.zinter(&self.keys, &self.weights, self.aggregate) {
+            Ok(members) => scored_members_reply(members, self.with_scores),
+            Err(e) => e.into(),
+        }
+    }
+
+    fn has_pair_reply(&self) -> bool {
+        self.with_scores
+    }
+}
+
+impl CommandExecutor for ZDiff {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.zdiff(&self.keys) {
+            Ok(members) => scored_members_reply(members, self.with_scores),
+            Err(e) => e.into(),
+        }
+    }
+
+    fn has_pair_reply(&self) -> bool {
+        self.with_scores
+    }
+}
+
+impl CommandExecutor for ZRemRangeByScore {
+    /// Returns the number of members that were removed.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        remove_range(backend, &self.key, &self.range)
+    }
+}
+
+impl CommandExecutor for ZRemRangeByRank {
+    /// Returns the number of members that were removed.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        remove_range(backend, &self.key, &self.range)
+    }
+}
+
+impl CommandExecutor for ZRemRangeByLex {
+    /// Returns the number of members that were removed.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        remove_range(backend, &self.key, &self.range)
+    }
+}
+
+impl CommandExecutor for ZPopMin {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        pop(backend, &self.key, ZSetEnd::Min, self.count)
+    }
+
+    fn has_pair_reply(&self) -> bool {
+        self.count.is_some()
+    }
+}
+
+impl CommandExecutor for ZPopMax {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        pop(backend, &self.key, ZSetEnd::Max, self.count)
+    }
+
+    fn has_pair_reply(&self) -> bool {
+        self.count.is_some()
+    }
+}
+
+impl CommandExecutor for ZRandMember {
+    /// Without a count, returns a single random member or a null reply. With a count,
+    /// returns an array of members, distinct if the count is positive and possibly
+    /// repeated if it is negative, each paired with its score with `WITHSCORES`.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.zrandmember(&self.key, self.count.unwrap_or(1)) {
+            Ok(members) if self.count.is_some() => scored_members_reply(members, self.with_scores),
+            Ok(members) => {
+                optional_bulk_string(members.into_iter().next().map(|(member, _)| member))
+            }
+            Err(e) => e.into(),
+        }
+    }
+
+    fn has_pair_reply(&self) -> bool {
+        self.with_scores
+    }
+}
+
+impl CommandExecutor for ZRangeStore {
+    /// Returns the number of members of the stored sorted set.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let args = self.args;
+        let destination = self.destination.clone();
+        let stored = notify_store(
+            backend,
+            EventClass::ZSet,
+            "zrangestore",
+            &destination,
+            || {
+                backend.zrangestore(
+                    self.destination,
+                    &self.source,
+                    &args.range,
+                    args.rev,
+                    args.offset,
+                    args.count,
+                )
+            },
+        );
+        match stored {
+            Ok(len) => RespFrame::Integer(len as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for ZMPop {
+    /// Returns the key the members were popped from and an array of the members paired
+    /// with their score, or a null reply.
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.zmpop(&self.keys, self.end, self.count) {
+            Ok(popped) => {
+                if let Some((key, _)) = &popped {
+                    notify_removed(backend, EventClass::ZSet, pop_event(self.end), key);
+                }
+                key_members_reply(popped)
+            }
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl BlockingCommand for BZPopMin {
+    fn wait_keys(&self) -> &[Bytes] {
+        &self.keys
+    }
+
+    fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Returns the key, the popped member and its score.
+    fn try_execute(&self, backend: &Backend) -> Option<RespFrame> {
+        blocking_pop(backend, &self.keys, ZSetEnd::Min)
+    }
+}
+
+impl BlockingCommand for BZPopMax {
+    fn wait_keys(&self) -> &[Bytes] {
+        &self.keys
+    }
+
+    fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Returns the key, the popped member and its score.
+    fn try_execute(&self, backend: &Backend) -> Option<RespFrame> {
+        blocking_pop(backend, &self.keys, ZSetEnd::Max)
+    }
+}
+
+impl BlockingCommand for BZMPop {
+    fn wait_keys(&self) -> &[Bytes] {
+        &self.keys
+    }
+
+    fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Returns the key the members were popped from and an array of the members paired
+    /// with their score.
+    fn try_execute(&self, backend: &Backend) -> Option<RespFrame> {
+        match backend.zmpop(&self.keys, self.end, self.count) {
+            Ok(None) => None,
+            Ok(popped) => {
+                if let Some((key, _)) = &popped {
+                    notify_removed(backend, EventClass::ZSet, pop_event(self.end), key);
+                }
+                Some(key_members_reply(popped))
+            }
+            Err(e) => Some(e.into()),
+        }
+    }
+}
+
+impl CommandExecutor for BZPopMin {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        self.try_execute(backend)
+            .unwrap_or_else(|| self.timeout_reply())
+    }
+}
+
+impl CommandExecutor for BZPopMax {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        self.try_execute(backend)
+            .unwrap_or_else(|| self.timeout_reply())
+    }
+}
+
+impl CommandExecutor for BZMPop {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        self.try_execute(backend)
+            .unwrap_or_else(|| self.timeout_reply())
+    }
+}
+
+/// Stores the result of the command `event` at `destination`.
+fn store(backend: &Backend, event: &str, destination: Bytes, members: ScoredMembers) -> RespFrame {
+    let key = destination.clone();
+    let stored = notify_store(backend, EventClass::ZSet, event, &key, || {
+        Ok::<_, CommandError>(backend.zstore(destination, members.into_iter()))
+    });
+    match stored {
+        Ok(len) => RespFrame::Integer(len as i64),
+        Err(e) => e.into(),
+    }
+}
+
+fn remove_range(backend: &Backend, key: &Bytes, range: &ZRangeBy) -> RespFrame {
+    match backend.zremrange(key, range) {
+        Ok(removed) => {
+            if removed > 0 {
+                let event = match range {
+                    ZRangeBy::Rank(..) => "zremrangebyrank",
+                    ZRangeBy::Score(_) => "zremrangebyscore",
+                    ZRangeBy::Lex(_) => "zremrangebylex",
+                };
+                notify_removed(backend, EventClass::ZSet, event, key);
+            }
+            RespFrame::Integer(removed as i64)
+        }
+        Err(e) => e.into(),
+    }
+}
+
+fn pop_event(end: ZSetEnd) -> &'static str {
+    match end {
+        ZSetEnd::Min => "zpopmin",
+        ZSetEnd::Max => "zpopmax",
+    }
+}
+
+/// Replies to `ZPOPMIN` and `ZPOPMAX`: without a count, the popped member and its score,
+/// and with a count, an array of such pairs.
+fn pop(backend: &Backend, key: &Bytes, end: ZSetEnd, count: Option<usize>) -> RespFrame {
+    let popped = backend.zpop(key, end, count.unwrap_or(1));
+    if popped
+        .as_ref()
+        .is_ok_and(|popped| popped.as_ref().is_some_and(|members| !members.is_empty()))
+    {
+        notify_removed(backend, EventClass::ZSet, pop_event(end), key);
+    }
+    match popped {
+        Ok(popped) if count.is_some() => scored_members_reply(popped.unwrap_or_default(), true),
+        Ok(popped) => RespArray::new(
+            popped
+                .into_iter()
+                .flatten()
+                .flat_map(|(member, score)| {
+                    [BulkString::new(member).into(), RespFrame::Double(score)]
+                })
+                .collect::<Vec<_>>(),
+        )
+        .into(),
+        Err(e) => e.into(),
+    }
+}
+
+fn blocking_pop(backend: &Backend, keys: &[Bytes], end: ZSetEnd) -> Option<RespFrame> {
+    match backend.zmpop(keys, end, 1) {
+        Ok(Some((key, members))) => {
+            let (member, score) = members.into_iter().next()?;
+            notify_removed(backend, EventClass::ZSet, pop_event(end), &key);
+            Some(
+                RespArray::new([
+                    BulkString::new(key).into(),
+                    BulkString::new(member).into(),
+                    RespFrame::Double(score),
+                ])
+                .into(),
+            )
+        }
+        Ok(None) => None,
+        Err(e) => Some(e.into()),
+    }
+}
+
+/// Builds the reply of the commands popping from one of several keys: the key followed by
+/// an array of the popped members paired with their score, or a null reply.
+fn key_members_reply(popped: Option<(Bytes, ScoredMembers)>) -> RespFrame {
+    match popped {
+        Some((key, members)) => RespArray::new([
+            BulkString::new(key).into(),
+            scored_members_reply(members, true),
+        ])
+        .into(),
+        None => RespFrame::NullArray(RespNullArray),
+    }
+}
+
+/// Replies with the rank of a member, along with its score with `WITHSCORE`.
+fn rank(backend: &Backend, key: &[u8], member: &[u8], rev: bool, with_score: bool) -> RespFrame {
+    match (backend.zrank(key, member, rev), with_score) {
+        (Ok(Some((rank, score))), true) => {
+            RespArray::new([RespFrame::Integer(rank as i64), RespFrame::Double(score)]).into()
+        }
+        (Ok(Some((rank, _))), false) => RespFrame::Integer(rank as i64),
+        (Ok(None), true) => RespFrame::NullArray(RespNullArray),
+        (Ok(None), false) => RespFrame::Null(RespNull),
+        (Err(e), _) => e.into(),
+    }
+}
+
+fn optional_score(score: Option<f64>) -> RespFrame {
+    match score {
+        Some(score) => RespFrame::Double(score),
+        None => RespFrame::Null(RespNull),
+    }
+}
+
+/// Builds the reply of members, as pairs of member and score if `with_scores` is set.
+///
+/// The pairs are flattened for RESP2 clients, see `CommandExecutor::has_pair_reply`.
+pub(super) fn scored_members_reply(members: ScoredMembers, with_scores: bool) -> RespFrame {
+    RespArray::new(
+        members
+            .into_iter()
+            .map(|(member, score)| {
+                if with_scores {
+                    RespArray::new([BulkString::new(member).into(), RespFrame::Double(score)])
+                        .into()
+                } else {
+                    BulkString::new(member).into()
+                }
+            })
+            .collect::<Vec<_>>(),
+    )
+    .into()
+}
+
+/// Parses a score, which may be infinite but not NaN.
+pub(super) fn parse_score(arg: &[u8]) -> Result<f64, CommandError> {
+    std::str::from_utf8(arg)
+        .ok()
+        .and_then(|score| score.parse::<f64>().ok())
+        .filter(|score| !score.is_nan())
+        .ok_or(CommandError::NotAFloat)
+}
+
+/// Parses the bounds of a score range, each excluded when prefixed with `(`.
+pub(super) fn parse_score_range(min: &[u8], max: &[u8]) -> Result<ScoreRange, CommandError> {
+    let bound = |arg: &[u8]| {
+        let (arg, exclusive) = match arg.strip_prefix(b"(") {
+            Some(arg) => (arg, true),
+            None => (arg, false),
+        };
+        parse_score(arg)
+            .map(|score| (score, exclusive))
+            .map_err(|_| CommandError::InvalidArguments("min or max is not a float".to_string()))
+    };
+    let (min, min_exclusive) = bound(min)?;
+    let (max, max_exclusive) = bound(max)?;
+    Ok(ScoreRange {
+        min,
+        min_exclusive,
+        max,
+        max_exclusive,
+    })
+}
+
+/// Parses the bounds of a lex range: `-`, `+`, `[member` or `(member`.
+pub(super) fn parse_lex_range(min: &Bytes, max: &Bytes) -> Result<LexRange, CommandError> {
+    let bound = |arg: &Bytes| match arg.first() {
+        Some(b'-') if arg.len() == 1 => Ok(LexBound::Min),
+        Some(b'+') if arg.len() == 1 => Ok(LexBound::Max),
+        Some(b'[') => Ok(LexBound::Inclusive(arg.slice(1..))),
+        Some(b'(') => Ok(LexBound::Exclusive(arg.slice(1..))),
+        _ => Err(CommandError::InvalidArguments(
+            "min or max not valid string range item".to_string(),
+        )),
+    };
+    Ok(LexRange {
+        min: bound(min)?,
+        max: bound(max)?,
+    })
+}
+
+fn integer(arg: Bytes) -> Result<i64, CommandError> {
+    parse_integer(RespFrame::BulkString(BulkString::new(arg)))
+}
+
+/// Parses a key followed by one or more members.
+fn parse_key_members(value: RespArray, name: &str) -> Result<(Bytes, Vec<Bytes>), CommandError> {
+    let (key, members) = parse_key_and_args(value)?;
+    if members.is_empty() {
+        return Err(CommandError::WrongArity(name.to_string()));
+    }
+    Ok((key, members))
+}
+
+/// Parses a key, a member and the optional `WITHSCORE`, as taken by `ZRANK` and
+/// `ZREVRANK`.
+fn parse_rank(value: RespArray) -> Result<(Bytes, Bytes, bool), CommandError> {
+    let (key, args) = parse_key_and_args(value)?;
+    let mut args = args.into_iter();
+    let member = args.next().ok_or(CommandError::SyntaxError)?;
+    let with_score = match args.next() {
+        None => false,
+        Some(option) if option.eq_ignore_ascii_case(b"withscore") => true,
+        Some(_) => return Err(CommandError::SyntaxError),
+    };
+    if args.next().is_some() {
+        return Err(CommandError::SyntaxError);
+    }
+    Ok((key, member, with_score))
+}
+
+/// Parses `MIN` or `MAX`.
+fn parse_zset_end(arg: &[u8]) -> Result<ZSetEnd, CommandError> {
+    if arg.eq_ignore_ascii_case(b"min") {
+        Ok(ZSetEnd::Min)
+    } else if arg.eq_ignore_ascii_case(b"max") {
+        Ok(ZSetEnd::Max)
+    } else {
+        Err(CommandError::SyntaxError)
+    }
+}
+
+/// The inputs and options of `ZUNION`, `ZINTER`, `ZDIFF` and their `STORE` variants.
+struct SetOperation {
+    keys: Vec<Bytes>,
+    weights: Vec<f64>,
+    aggregate: Aggregate,
+    with_scores: bool,
+}
+
+impl SetOperation {
+    /// Parses `numkeys key [key ...]` followed by the options. `WEIGHTS` and `AGGREGATE`
+    /// are only accepted if `weighted`, and `WITHSCORES` only if `with_scores` is allowed.
+    fn parse(
+        args: Vec<Bytes>,
+        weighted: bool,
+        with_scores_allowed: bool,
+    ) -> Result<Self, CommandError> {
+        let mut args = args.into_iter();
+        let keys = parse_numkeys(&mut args)?;
+        let mut operation = SetOperation {
+            weights: Vec::new(),
+            aggregate: Aggregate::default(),
+            with_scores: false,
+            keys,
+        };
+        while let Some(option) = args.next() {
+            if weighted && option.eq_ignore_ascii_case(b"weights") {
+                operation.weights = (0..operation.keys.len())
+                    .map(|_| {
+                        let weight = args.next().ok_or(CommandError::SyntaxError)?;
+                        parse_score(&weight).map_err(|_| {
+                            CommandError::InvalidArguments(
+                                "weight value is not a float".to_string(),
+                            )
+                        })
+                    })
+                    .collect::<Result<_, _>>()?;
+            } else if weighted && option.eq_ignore_ascii_case(b"aggregate") {
+                let aggregate = args.next().ok_or(CommandError::SyntaxError)?;
+                operation.aggregate = if aggregate.eq_ignore_ascii_case(b"sum") {
+                    Aggregate::Sum
+                } else if aggregate.eq_ignore_ascii_case(b"min") {
+                    Aggregate::Min
+                } else if aggregate.eq_ignore_ascii_case(b"max") {
+                    Aggregate::Max
+                } else {
+                    return Err(CommandError::SyntaxError);
+                };
+            } else if with_scores_allowed && option.eq_ignore_ascii_case(b"withscores") {
+                operation.with_scores = true;
+            } else {
+                return Err(CommandError::SyntaxError);
+            }
+        }
+        Ok(operation)
+    }
+}
+
+/// Parses a destination followed by the arguments of a set operation.
+fn parse_store(value: RespArray, weighted: bool) -> Result<(Bytes, SetOperation), CommandError> {
+    let (destination, args) = parse_key_and_args(value)?;
+    Ok((destination, SetOperation::parse(args, weighted, false)?))
+}
+
+/// Parses a key followed by two bounds, as taken by the `ZREMRANGEBY` commands.
+fn parse_key_bounds(value: RespArray) -> Result<(Bytes, Bytes, Bytes), CommandError> {
+    let (key, args) = parse_key_and_args(value)?;
+    match <[Bytes; 2]>::try_from(args) {
+        Ok([min, max]) => Ok((key, min, max)),
+        Err(_) => Err(CommandError::SyntaxError),
+    }
+}
+
+/// Parses a key followed by an optional count which must be positive, as taken by
+/// `ZPOPMIN` and `ZPOPMAX`.
+fn parse_pop_count(value: RespArray) -> Result<(Bytes, Option<usize>), CommandError> {
+    let (key, args) = parse_key_and_args(value)?;
+    let mut args = args.into_iter();
+    let count = args
+        .next()
+        .map(|count| {
+            usize::try_from(integer(count)?).map_err(|_| {
+                CommandError::InvalidArguments(
+                    "value is out of range, must be positive".to_string(),
+                )
+            })
+        })
+        .transpose()?;
+    if args.next().is_some() {
+        return Err(CommandError::SyntaxError);
+    }
+    Ok((key, count))
+}
+
+impl TryFrom<RespArray> for ZAdd {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, args) = parse_key_and_args(value)?;
+        let mut options = ZAddOptions::default();
+        let mut incr = false;
+        let mut args = args.into_iter().peekable();
+        while let Some(option) = args.peek() {
+            if option.eq_ignore_ascii_case(b"nx") {
+                options.condition = SetCondition::Nx;
+            } else if option.eq_ignore_ascii_case(b"xx") {
+                options.condition = SetCondition::Xx;
+            } else if option.eq_ignore_ascii_case(b"gt") {
+                options.comparison = Some(ZAddComparison::Gt);
+            } else if option.eq_ignore_ascii_case(b"lt") {
+                options.comparison = Some(ZAddComparison::Lt);
+            } else if option.eq_ignore_ascii_case(b"ch") {
+                options.changed = true;
+            } else if option.eq_ignore_ascii_case(b"incr") {
+                incr = true;
+            } else {
+                break;
+            }
+            args.next();
+        }
+        let args = args.collect::<Vec<_>>();
+        if args.is_empty() || args.len() % 2 != 0 {
+            return Err(CommandError::SyntaxError);
+        }
+        let members = args
+            .chunks(2)
+            .map(|pair| Ok((parse_score(&pair[0])?, pair[1].clone())))
+            .collect::<Result<Vec<_>, CommandError>>()?;
+        if options.condition == SetCondition::Nx && options.comparison.is_some() {
+            return Err(CommandError::InvalidArguments(
+                "GT, LT, and/or NX options at the same time are not compatible".to_string(),
+            ));
+        }
+        if incr && members.len() > 1 {
+            return Err(CommandError::InvalidArguments(
+                "INCR option supports a single increment-element pair".to_string(),
+            ));
+        }
+        Ok(ZAdd {
+            key,
+            options,
+            incr,
+            members,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZRem {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, members) = parse_key_members(value, "zrem")?;
+        Ok(ZRem { key, members })
+    }
+}
+
+impl TryFrom<RespArray> for ZScore {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, args) = parse_key_and_args(value)?;
+        match <[Bytes; 1]>::try_from(args) {
+            Ok([member]) => Ok(ZScore { key, member }),
+            Err(_) => Err(CommandError::SyntaxError),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for ZMScore {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, members) = parse_key_members(value, "zmscore")?;
+        Ok(ZMScore { key, members })
+    }
+}
+
+impl TryFrom<RespArray> for ZIncrBy {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, args) = parse_key_and_args(value)?;
+        match <[Bytes; 2]>::try_from(args) {
+            Ok([increment, member]) => Ok(ZIncrBy {
+                key,
+                increment: parse_score(&increment)?,
+                member,
+            }),
+            Err(_) => Err(CommandError::SyntaxError),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for ZCard {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let key = parse_key_arg(value)?;
+        Ok(ZCard { key })
+    }
+}
+
+impl TryFrom<RespArray> for ZCount {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, args) = parse_key_and_args(value)?;
+        match <[Bytes; 2]>::try_from(args) {
+            Ok([min, max]) => Ok(ZCount {
+                key,
+                range: parse_score_range(&min, &max)?,
+            }),
+            Err(_) => Err(CommandError::SyntaxError),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for ZRank {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, member, with_score) = parse_rank(value)?;
+        Ok(ZRank {
+            key,
+            member,
+            with_score,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZRevRank {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, member, with_score) = parse_rank(value)?;
+        Ok(ZRevRank {
+            key,
+            member,
+            with_score,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZRange {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, args) = parse_key_and_args(value)?;
+        let args = ZRangeArgs::parse(args)?;
+        Ok(ZRange { key, args })
+    }
+}
+
+impl TryFrom<RespArray> for ZUnionStore {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (destination, operation) = parse_store(value, true)?;
+        Ok(ZUnionStore {
+            destination,
+            keys: operation.keys,
+            weights: operation.weights,
+            aggregate: operation.aggregate,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZInterStore {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (destination, operation) = parse_store(value, true)?;
+        Ok(ZInterStore {
+            destination,
+            keys: operation.keys,
+            weights: operation.weights,
+            aggregate: operation.aggregate,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZDiffStore {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (destination, operation) = parse_store(value, false)?;
+        Ok(ZDiffStore {
+            destination,
+            keys: operation.keys,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZUnion {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let operation = SetOperation::parse(bulk_string_args(value)?, true, true)?;
+        Ok(ZUnion {
+            keys: operation.keys,
+            weights: operation.weights,
+            aggregate: operation.aggregate,
+            with_scores: operation.with_scores,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZInter {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let operation = SetOperation::parse(bulk_string_args(value)?, true, true)?;
+        Ok(ZInter {
+            keys: operation.keys,
+            weights: operation.weights,
+            aggregate: operation.aggregate,
+            with_scores: operation.with_scores,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZDiff {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let operation = SetOperation::parse(bulk_string_args(value)?, false, true)?;
+        Ok(ZDiff {
+            keys: operation.keys,
+            with_scores: operation.with_scores,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZRemRangeByScore {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, min, max) = parse_key_bounds(value)?;
+        let range = ZRangeBy::Score(parse_score_range(&min, &max)?);
+        Ok(ZRemRangeByScore { key, range })
+    }
+}
+
+impl TryFrom<RespArray> for ZRemRangeByRank {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, start, stop) = parse_key_bounds(value)?;
+        let range = ZRangeBy::Rank(integer(start)?, integer(stop)?);
+        Ok(ZRemRangeByRank { key, range })
+    }
+}
+
+impl TryFrom<RespArray> for ZRemRangeByLex {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, min, max) = parse_key_bounds(value)?;
+        let range = ZRangeBy::Lex(parse_lex_range(&min, &max)?);
+        Ok(ZRemRangeByLex { key, range })
+    }
+}
+
+impl TryFrom<RespArray> for ZPopMin {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, count) = parse_pop_count(value)?;
+        Ok(ZPopMin { key, count })
+    }
+}
+
+impl TryFrom<RespArray> for ZPopMax {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, count) = parse_pop_count(value)?;
+        Ok(ZPopMax { key, count })
+    }
+}
+
+impl TryFrom<RespArray> for ZRandMember {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, args) = parse_key_and_args(value)?;
+        let mut args = args.into_iter();
+        let count = args.next().map(integer).transpose()?;
+        let with_scores = match args.next() {
+            None => false,
+            Some(option) if option.eq_ignore_ascii_case(b"withscores") => true,
+            Some(_) => return Err(CommandError::SyntaxError),
+        };
+        if args.next().is_some() {
+            return Err(CommandError::SyntaxError);
+        }
+        if count == Some(i64::MIN) {
+            return Err(CommandError::InvalidArguments(
+                "value is out of range".to_string(),
+            ));
+        }
+        Ok(ZRandMember {
+            key,
+            count,
+            with_scores,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZRangeStore {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (destination, args) = parse_key_and_args(value)?;
+        let mut args = args.into_iter();
+        let source = args.next().ok_or(CommandError::SyntaxError)?;
+        let args = ZRangeArgs::parse(args.collect())?;
+        if args.with_scores {
+            return Err(CommandError::SyntaxError);
+        }
+        Ok(ZRangeStore {
+            destination,
+            source,
+            args,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZMPop {
+    type Error = CommandError;
+    /// Converts a RESP array into a `ZMPop` command.
+    ///
+    /// The arguments are `numkeys`, the keys, `MIN` or `MAX` and optionally
+    /// `COUNT count`.
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = bulk_string_args(value)?.into_iter();
+        let keys = parse_numkeys(&mut args)?;
+        let end = parse_zset_end(&args.next().ok_or(CommandError::SyntaxError)?)?;
+        let count = parse_mpop_count(args)?;
+        Ok(ZMPop { keys, end, count })
+    }
+}
+
+impl TryFrom<RespArray> for BZPopMin {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (keys, timeout) = parse_keys_timeout(value)?;
+        Ok(BZPopMin { keys, timeout })
+    }
+}
+
+impl TryFrom<RespArray> for BZPopMax {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (keys, timeout) = parse_keys_timeout(value)?;
+        Ok(BZPopMax { keys, timeout })
+    }
+}
+
+impl TryFrom<RespArray> for BZMPop {
+    type Error = CommandError;
+    /// Converts a RESP array into a `BZMPop` command.
+    ///
+    /// The arguments are those of `ZMPOP`, preceded by the timeout.
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = bulk_string_args(value)?.into_iter();
+        let timeout = parse_timeout(&args.next().ok_or(CommandError::SyntaxError)?)?;
+        let keys = parse_numkeys(&mut args)?;
+        let end = parse_zset_end(&args.next().ok_or(CommandError::SyntaxError)?)?;
+        let count = parse_mpop_count(args)?;
+        Ok(BZMPop {
+            keys,
+            end,
+            count,
+            timeout,
+        })
+    }
+}
+
+impl ZRangeArgs {
+    /// Parses the arguments of `ZRANGE` following the key.
+    ///
+    /// With `REV`, the bounds of a score or lex range are given from the upper one. A
+    /// negative `LIMIT` offset selects nothing and a negative count selects everything past
+    /// the offset.
+    fn parse(args: Vec<Bytes>) -> Result<Self, CommandError> {
+        let mut args = args.into_iter();
+        let (Some(start), Some(stop)) = (args.next(), args.next()) else {
+            return Err(CommandError::SyntaxError);
+        };
+        let (mut by_score, mut by_lex, mut rev, mut with_scores) = (false, false, false, false);
+        let mut limit = None;
+        while let Some(option) = args.next() {
+            if option.eq_ignore_ascii_case(b"byscore") {
+                by_score = true;
+            } else if option.eq_ignore_ascii_case(b"bylex") {
+                by_lex = true;
+            } else if option.eq_ignore_ascii_case(b"rev") {
+                rev = true;
+            } else if option.eq_ignore_ascii_case(b"withscores") {
+                with_scores = true;
+            } else if option.eq_ignore_ascii_case(b"limit") {
+                let (Some(offset), Some(count)) = (args.next(), args.next()) else {
+                    return Err(CommandError::SyntaxError);
+                };
+                limit = Some((integer(offset)?, integer(count)?));
+            } else {
+                return Err(CommandError::SyntaxError);
+            }
+        }
+        if by_score && by_lex {
+            return Err(CommandError::SyntaxError);
+        }
+        if limit.is_some() && !by_score && !by_lex {
+            return Err(CommandError::InvalidArguments(
+                "syntax error, LIMIT is only supported in combination with either BYSCORE or BYLEX"
+                    .to_string(),
+            ));
+        }
+        if with_scores && by_lex {
+            return Err(CommandError::InvalidArguments(
+                "syntax error, WITHSCORES not supported in combination with BYLEX".to_string(),
+            ));
+        }
+        let (min, max) = if rev {
+            (&stop, &start)
+        } else {
+            (&start, &stop)
+        };
+        let range = if by_score {
+            ZRangeBy::Score(parse_score_range(min, max)?)
+        } else if by_lex {
+            ZRangeBy::Lex(parse_lex_range(min, max)?)
+        } else {
+            // ranks are given in the order of the reply, so they are never swapped
+            ZRangeBy::Rank(integer(start)?, integer(stop)?)
+        };
+        let (offset, count) = match limit {
+            None => (0, usize::MAX),
+            Some((offset, _)) if offset < 0 => (0, 0),
+            Some((offset, count)) => (
+                offset as usize,
+                usize::try_from(count).unwrap_or(usize::MAX),
+            ),
+        };
+        Ok(ZRangeArgs {
+            range,
+            rev,
+            offset,
+            count,
+            with_scores,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::{CommandRequest, Session};
+    use crate::RespFrame;
+
+    fn args(args: &[&str]) -> RespArray {
+        RespArray::new(
+            args.iter()
+                .map(|arg| BulkString::new(*arg).into())
+                .collect::<Vec<RespFrame>>(),
+        )
+    }
+
+    fn members(frame: RespFrame) -> Vec<RespFrame> {
+        match frame {
+            RespFrame::Array(array) => array.0,
+            frame => panic!("unexpected reply {:?}", frame),
+        }
+    }
+
+    fn bulk(s: &str) -> RespFrame {
+        BulkString::new(s).into()
+    }
+
+    fn leaderboard() -> Backend {
+        let backend = Backend::new();
+        ZAdd::try_from(args(&["zadd", "z", "1", "a", "2", "b", "2", "c", "3", "d"]))
+            .unwrap()
+            .execute(&backend);
+        backend
+    }
+
+    #[test]
+    fn test_zadd_from_resp_array() {
+        let cmd = ZAdd::try_from(args(&["zadd", "z", "xx", "GT", "ch", "-inf", "a"])).unwrap();
+        assert_eq!(cmd.options.condition, SetCondition::Xx);
+        assert_eq!(cmd.options.comparison, Some(ZAddComparison::Gt));
+        assert!(cmd.options.changed);
+        assert_eq!(cmd.members, vec![(f64::NEG_INFINITY, Bytes::from("a"))]);
+
+        let err = ZAdd::try_from(args(&["zadd", "z", "nx", "lt", "1", "a"])).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "ERR GT, LT, and/or NX options at the same time are not compatible"
+        );
+        let err = ZAdd::try_from(args(&["zadd", "z", "incr", "1", "a", "2", "b"])).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "ERR INCR option supports a single increment-element pair"
+        );
+        assert!(matches!(
+            ZAdd::try_from(args(&["zadd", "z", "1", "a", "2"])),
+            Err(CommandError::SyntaxError)
+        ));
+        assert!(matches!(
+            ZAdd::try_from(args(&["zadd", "z", "nan", "a"])),
+            Err(CommandError::NotAFloat)
+        ));
+    }
+
+    #[test]
+    fn test_zadd_incr_and_scores() {
+        let backend = leaderboard();
+        let ret = ZAdd::try_from(args(&["zadd", "z", "incr", "2.5", "a"]))
+            .unwrap()
+            .execute(&backend);
+        assert_eq!(ret, RespFrame::Double(3.5));
+        let ret = ZAdd::try_from(args(&["zadd", "z", "nx", "incr", "1", "a"]))
+            .unwrap()
+            .execute(&backend);
+        assert_eq!(ret, RespFrame::Null(RespNull));
+        let ret = ZIncrBy::try_from(args(&["zincrby", "z", "-1", "b"]))
+            .unwrap()
+            .execute(&backend);
+        assert_eq!(ret, RespFrame::Double(1.0));
+
+        let ret = ZMScore::try_from(args(&["zmscore", "z", "a", "x"]))
+            .unwrap()
+            .execute(&backend);
+        assert_eq!(
+            members(ret),
+            vec![RespFrame::Double(3.5), RespFrame::Null(RespNull)]
+        );
+        let ret = ZCount::try_from(args(&["zcount", "z", "(1", "+inf"]))
+            .unwrap()
+            .execute(&backend);
+        assert_eq!(ret, RespFrame::Integer(3));
+        let ret = ZRem::try_from(args(&["zrem", "z", "a", "x"]))
+            .unwrap()
+            .execute(&backend);
+        assert_eq!(ret, RespFrame::Integer(1));
+        let ret = ZCard::try_from(args(&["zcard", "z"]))
+            .unwrap()
+            .execute(&backend);
+        assert_eq!(ret, RespFrame::Integer(3));
+    }
+
+    #[test]
+    fn test_zrank() {
+        let backend = leaderboard();
+        let zrank = |cmd: &[&str]| match cmd[0] {
+            "zrank" => ZRank::try_from(args(cmd)).unwrap().execute(&backend),
+            _ => ZRevRank::try_from(args(cmd)).unwrap().execute(&backend),
+        };
+        assert_eq!(zrank(&["zrank", "z", "c"]), RespFrame::Integer(2));
+        assert_eq!(zrank(&["zrevrank", "z", "c"]), RespFrame::Integer(1));
+        assert_eq!(
+            zrank(&["zrevrank", "z", "d", "withscore"]),
+            RespArray::new([RespFrame::Integer(0), RespFrame::Double(3.0)]).into()
+        );
+        assert_eq!(zrank(&["zrank", "z", "x"]), RespFrame::Null(RespNull));
+        assert_eq!(
+            zrank(&["zrank", "z", "x", "withscore"]),
+            RespFrame::NullArray(RespNullArray)
+        );
+    }
+
+    #[test]
+    fn test_zrange() {
+        let backend = leaderboard();
+        let zrange = |cmd: &[&str]| ZRange::try_from(args(cmd)).unwrap().execute(&backend);
+        assert_eq!(
+            members(zrange(&["zrange", "z", "0", "-3"])),
+            vec![bulk("a"), bulk("b")]
+        );
+        assert_eq!(
+            members(zrange(&["zrange", "z", "0", "1", "rev"])),
+            vec![bulk("d"), bulk("c")]
+        );
+        assert_eq!(
+            members(zrange(&[
+                "zrange", "z", "(1", "3", "byscore", "limit", "1", "-1"
+            ])),
+            vec![bulk("c"), bulk("d")]
+        );
+        // with REV, the upper bound comes first
+        assert_eq!(
+            members(zrange(&[
+                "zrange",
+                "z",
+                "+inf",
+                "2",
+                "byscore",
+                "rev",
+                "withscores"
+            ])),
+            vec![
+                RespArray::new([bulk("d"), RespFrame::Double(3.0)]).into(),
+                RespArray::new([bulk("c"), RespFrame::Double(2.0)]).into(),
+                RespArray::new([bulk("b"), RespFrame::Double(2.0)]).into(),
+            ]
+        );
+        assert_eq!(
+            members(zrange(&["zrange", "z", "[b", "(d", "bylex"])),
+            vec![bulk("b"), bulk("c")]
+        );
+        assert!(members(zrange(&[
+            "zrange", "z", "-", "+", "bylex", "limit", "-1", "2"
+        ]))
+        .is_empty());
+
+        let err =
+            ZRange::try_from(args(&["zrange", "z", "0", "1", "limit", "0", "1"])).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "ERR syntax error, LIMIT is only supported in combination with either BYSCORE or BYLEX"
+        );
+        let err = ZRange::try_from(args(&["zrange", "z", "a", "b", "bylex"])).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "ERR min or max not valid string range item"
+        );
+        let err = ZRange::try_from(args(&["zrange", "z", "a", "b", "byscore"])).unwrap_err();
+        assert_eq!(err.to_string(), "ERR min or max is not a float");
+    }
+
+    #[tokio::test]
+    async fn test_zrange_withscores_resp2() -> anyhow::Result<()> {
+        let backend = leaderboard();
+        let request = CommandRequest::try_from(RespFrame::Array(args(&[
+            "zrange",
+            "z",
+            "0",
+            "0",
+            "withscores",
+        ])))?;
+        let reply = request.run(&backend, &mut Session::new()).await;
+        // RESP2 clients get the pairs flattened, the score is made a bulk string on the wire
+        assert_eq!(members(reply), vec![bulk("a"), RespFrame::Double(1.0)]);
+        Ok(())
+    }
+
+    fn scored(member: &str, score: f64) -> RespFrame {
+        RespArray::new([bulk(member), RespFrame::Double(score)]).into()
+    }
+
+    fn request(args_: &[&str]) -> CommandRequest {
+        CommandRequest::try_from(RespFrame::Array(args(args_))).unwrap()
+    }
+
+    #[test]
+    fn test_set_operation_from_resp_array() {
+        let cmd = ZUnionStore::try_from(args(&[
+            "zunionstore",
+            "dst",
+            "2",
+            "a",
+            "b",
+            "weights",
+            "2",
+            "-inf",
+            "aggregate",
+            "MAX",
+        ]))
+        .unwrap();
+        assert_eq!(cmd.keys, vec![Bytes::from("a"), Bytes::from("b")]);
+        assert_eq!(cmd.weights, vec![2.0, f64::NEG_INFINITY]);
+        assert_eq!(cmd.aggregate, Aggregate::Max);
+
+        let err =
+            ZInter::try_from(args(&["zinter", "2", "a", "b", "weights", "1", "x"])).unwrap_err();
+        assert_eq!(err.to_string(), "ERR weight value is not a float");
+        assert!(matches!(
+            ZInter::try_from(args(&["zinter", "2", "a", "b", "weights", "1"])),
+            Err(CommandError::SyntaxError)
+        ));
+        assert!(matches!(
+            ZDiff::try_from(args(&["zdiff", "1", "a", "aggregate", "sum"])),
+            Err(CommandError::SyntaxError)
+        ));
+        assert!(matches!(
+            ZInterStore::try_from(args(&["zinterstore", "dst", "1", "a", "withscores"])),
+            Err(CommandError::SyntaxError)
+        ));
+        assert!(matches!(
+            ZRangeStore::try_from(args(&["zrangestore", "dst", "z", "0", "1", "withscores"])),
+            Err(CommandError::SyntaxError)
+        ));
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let backend = leaderboard();
+        ZAdd::try_from(args(&["zadd", "y", "10", "a", "20", "x"]))
+            .unwrap()
+            .execute(&backend);
+        let run = |cmd: &[&str]| match cmd[0] {
+            "zunion" => ZUnion::try_from(args(cmd)).unwrap().execute(&backend),
+            "zinter" => ZInter::try_from(args(cmd)).unwrap().execute(&backend),
+            "zdiff" => ZDiff::try_from(args(cmd)).unwrap().execute(&backend),
+            "zinterstore" => ZInterStore::try_from(args(cmd)).unwrap().execute(&backend),
+            _ => ZDiffStore::try_from(args(cmd)).unwrap().execute(&backend),
+        };
+        assert_eq!(
+            members(run(&["zinter", "2", "z", "y", "withscores"])),
+            vec![scored("a", 11.0)]
+        );
+        assert_eq!(
+            members(run(&[
+                "zunion",
+                "2",
+                "z",
+                "y",
+                "weights",
+                "1",
+                "0.5",
+                "aggregate",
+                "min",
+                "withscores",
+            ])),
+            vec![
+                scored("a", 1.0),
+                scored("b", 2.0),
+                scored("c", 2.0),
+                scored("d", 3.0),
+                scored("x", 10.0),
+            ]
+        );
+        assert_eq!(
+            members(run(&["zdiff", "2", "z", "y"])),
+            vec![bulk("b"), bulk("c"), bulk("d")]
+        );
+
+        assert_eq!(
+            run(&["zinterstore", "dst", "2", "z", "y", "aggregate", "max"]),
+            RespFrame::Integer(1)
+        );
+        assert_eq!(backend.zscore(b"dst", b"a").unwrap(), Some(10.0));
+        // an empty result deletes the destination
+        assert_eq!(
+            run(&["zdiffstore", "dst", "2", "z", "z"]),
+            RespFrame::Integer(0)
+        );
+        assert!(!backend.contains_key(b"dst"));
+    }
+
+    #[test]
+    fn test_zremrange() {
+        let backend = leaderboard();
+        let ret = ZRemRangeByScore::try_from(args(&["zremrangebyscore", "z", "-inf", "(2"]))
+            .unwrap()
+            .execute(&backend);
+        assert_eq!(ret, RespFrame::Integer(1));
+        let ret = ZRemRangeByLex::try_from(args(&["zremrangebylex", "z", "(b", "+"]))
+            .unwrap()
+            .execute(&backend);
+        assert_eq!(ret, RespFrame::Integer(2));
+        let ret = ZRemRangeByRank::try_from(args(&["zremrangebyrank", "z", "0", "-1"]))
+            .unwrap()
+            .execute(&backend);
+        assert_eq!(ret, RespFrame::Integer(1));
+        assert!(!backend.contains_key(b"z"));
+        assert!(matches!(
+            ZRemRangeByRank::try_from(args(&["zremrangebyrank", "z", "0", "x"])),
+            Err(CommandError::NotAnInteger)
+        ));
+    }
+
+    #[test]
+    fn test_zpop_and_zmpop() {
+        let backend = leaderboard();
+        let ret = ZPopMin::try_from(args(&["zpopmin", "z"]))
+            .unwrap()
+            .execute(&backend);
+        assert_eq!(members(ret), vec![bulk("a"), RespFrame::Double(1.0)]);
+        let ret = ZPopMax::try_from(args(&["zpopmax", "z", "2"]))
+            .unwrap()
+            .execute(&backend);
+        assert_eq!(members(ret), vec![scored("d", 3.0), scored("c", 2.0)]);
+        let ret = ZMPop::try_from(args(&["zmpop", "2", "x", "z", "min", "count", "5"]))
+            .unwrap()
+            .execute(&backend);
+        assert_eq!(
+            ret,
+            RespArray::new([bulk("z"), RespArray::new([scored("b", 2.0)]).into()]).into()
+        );
+        let ret = ZPopMin::try_from(args(&["zpopmin", "z"]))
+            .unwrap()
+            .execute(&backend);
+        assert!(members(ret).is_empty());
+        let ret = ZMPop::try_from(args(&["zmpop", "1", "z", "max"]))
+            .unwrap()
+            .execute(&backend);
+        assert_eq!(ret, RespFrame::NullArray(RespNullArray));
+
+        let err = ZPopMin::try_from(args(&["zpopmin", "z", "-1"])).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "ERR value is out of range, must be positive"
+        );
+        assert!(matches!(
+            ZMPop::try_from(args(&["zmpop", "1", "z", "middle"])),
+            Err(CommandError::SyntaxError)
+        ));
+    }
+
+    #[test]
+    fn test_zrandmember_and_zrangestore() {
+        let backend = leaderboard();
+        let zrandmember =
+            |cmd: &[&str]| ZRandMember::try_from(args(cmd)).unwrap().execute(&backend);
+        assert!(matches!(
+            zrandmember(&["zrandmember", "z"]),
+            RespFrame::BulkString(_)
+        ));
+        assert_eq!(members(zrandmember(&["zrandmember", "z", "10"])).len(), 4);
+        let ret = members(zrandmember(&["zrandmember", "z", "-6", "withscores"]));
+        assert_eq!(ret.len(), 6);
+        assert!(ret.iter().all(|pair| members(pair.clone()).len() == 2));
+        assert_eq!(
+            zrandmember(&["zrandmember", "missing"]),
+            RespFrame::Null(RespNull)
+        );
+
+        let ret = ZRangeStore::try_from(args(&[
+            "zrangestore",
+            "dst",
+            "z",
+            "(3",
+            "-inf",
+            "byscore",
+            "rev",
+            "limit",
+            "0",
+            "2",
+        ]))
+        .unwrap()
+        .execute(&backend);
+        assert_eq!(ret, RespFrame::Integer(2));
+        assert_eq!(backend.zscore(b"dst", b"c").unwrap(), Some(2.0));
+        assert_eq!(backend.zscore(b"dst", b"b").unwrap(), Some(2.0));
+    }
+
+    #[tokio::test]
+    async fn test_blocking_zpop_is_woken_by_zadd() {
+        let backend = Backend::new();
+        let ret = request(&["bzpopmax", "z", "0.01"])
+            .run(&backend, &mut Session::new())
+            .await;
+        assert_eq!(ret, RespFrame::NullArray(RespNullArray));
+
+        let blocked = tokio::spawn({
+            let backend = backend.clone();
+            async move {
+                request(&["bzpopmin", "other", "z", "0"])
+                    .run(&backend, &mut Session::new())
+                    .await
+            }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        request(&["zadd", "z", "2", "b", "1", "a"])
+            .run(&backend, &mut Session::new())
+            .await;
+        assert_eq!(
+            members(blocked.await.unwrap()),
+            vec![bulk("z"), bulk("a"), RespFrame::Double(1.0)]
+        );
+
+        let ret = request(&["bzmpop", "0", "1", "z", "max", "count", "3"])
+            .run(&backend, &mut Session::new())
+            .await;
+        assert_eq!(
+            ret,
+            RespArray::new([bulk("z"), RespArray::new([scored("b", 2.0)]).into()]).into()
+        );
+    }
+}
@@ -0,0 +1,163 @@
+//! Redis-compatible glob-style pattern matching, as used by `KEYS`, `SCAN MATCH` and
+//! `PSUBSCRIBE`.
+
+/// Returns `true` if `string` matches the glob `pattern`.
+///
+/// The supported syntax is the one of Redis:
+///
+/// * `?` matches any single byte.
+/// * `*` matches any sequence of bytes, including the empty one.
+/// * `[abc]` matches one of the listed bytes, `[^abc]` any byte not listed, and `[a-z]` a
+///   range of bytes. An unterminated class extends to the end of the pattern.
+/// * `\x` matches `x` literally, both outside and inside a class.
+///
+/// Every token other than `*` matches exactly one byte, so on a mismatch it is enough to
+/// backtrack to the last `*` and let it swallow one more byte. This keeps matching linear in
+/// the pattern length for every position of the string, instead of the exponential worst
+/// case of a recursive matcher.
+pub(crate) fn glob_match(pattern: &[u8], string: &[u8]) -> bool {
+    let mut p = 0;
+    let mut s = 0;
+    // position in the pattern after the last `*`, and in the string where it stopped
+    let mut backtrack: Option<(usize, usize)> = None;
+    while s < string.len() {
+        if pattern.get(p) == Some(&b'*') {
+            while pattern.get(p) == Some(&b'*') {
+                p += 1;
+            }
+            if p == pattern.len() {
+                return true;
+            }
+            backtrack = Some((p, s));
+            continue;
+        }
+        if let Some(next) = match_token(pattern, p, string[s]) {
+            p = next;
+            s += 1;
+            continue;
+        }
+        match backtrack {
+            Some((star_p, star_s)) => {
+                p = star_p;
+                s = star_s + 1;
+                backtrack = Some((star_p, s));
+            }
+            None => return false,
+        }
+    }
+    pattern[p..].iter().all(|&c| c == b'*')
+}
+
+/// Matches the token starting at `pattern[p]`, which is not `*`, against the byte `c`.
+///
+/// Returns the position of the next token on a match.
+fn match_token(pattern: &[u8], p: usize, c: u8) -> Option<usize> {
+    match *pattern.get(p)? {
+        b'?' => Some(p + 1),
+        b'\\' if p + 1 < pattern.len() => (pattern[p + 1] == c).then_some(p + 2),
+        b'[' => match_class(pattern, p + 1, c),
+        literal => (literal == c).then_some(p + 1),
+    }
+}
+
+/// Matches the class whose body starts at `pattern[p]` against the byte `c`.
+///
+/// Returns the position after the closing `]` on a match.
+fn match_class(pattern: &[u8], mut p: usize, c: u8) -> Option<usize> {
+    let negate = pattern.get(p) == Some(&b'^');
+    if negate {
+        p += 1;
+    }
+    let mut matched = false;
+    while p < pattern.len() && pattern[p] != b']' {
+        if pattern[p] == b'\\' && p + 1 < pattern.len() {
+            matched |= pattern[p + 1] == c;
+            p += 2;
+        } else if p + 2 < pattern.len() && pattern[p + 1] == b'-' {
+            let (start, end) = (pattern[p], pattern[p + 2]);
+            let (start, end) = if start > end {
+                (end, start)
+            } else {
+                (start, end)
+            };
+            matched |= (start..=end).contains(&c);
+            p += 3;
+        } else {
+            matched |= pattern[p] == c;
+            p += 1;
+        }
+    }
+    // skip the closing bracket, an unterminated class ends with the pattern
+    let next = (p + 1).min(pattern.len());
+    (matched != negate).then_some(next)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(pattern: &str, string: &str) -> bool {
+        glob_match(pattern.as_bytes(), string.as_bytes())
+    }
+
+    #[test]
+    fn test_literal_and_wildcards() {
+        assert!(matches("", ""));
+        assert!(!matches("", "a"));
+        assert!(matches("hello", "hello"));
+        assert!(!matches("hello", "hell"));
+        assert!(matches("h?llo", "hallo"));
+        assert!(!matches("h?llo", "hllo"));
+        assert!(matches("*", ""));
+        assert!(matches("h*llo", "hllo"));
+        assert!(matches("h*llo", "heeeello"));
+        assert!(matches("*llo", "hello"));
+        assert!(matches("he**", "hello"));
+        assert!(matches("*a*b*c*", "xxaxxbxxcxx"));
+        assert!(!matches("*a*b*c*", "xxaxxcxxbxx"));
+        assert!(matches("user:*:name", "user:1:2:name"));
+    }
+
+    #[test]
+    fn test_classes() {
+        assert!(matches("h[ae]llo", "hello"));
+        assert!(matches("h[ae]llo", "hallo"));
+        assert!(!matches("h[ae]llo", "hillo"));
+        assert!(matches("h[^e]llo", "hallo"));
+        assert!(!matches("h[^e]llo", "hello"));
+        assert!(matches("h[a-b]llo", "hbllo"));
+        assert!(!matches("h[a-b]llo", "hcllo"));
+        // reversed ranges are accepted
+        assert!(matches("h[b-a]llo", "hallo"));
+        assert!(!matches("[]", "a"));
+        // an unterminated class extends to the end of the pattern
+        assert!(matches("a[bc", "ab"));
+        assert!(matches("[\\]]", "]"));
+        assert!(matches("[a\\-z]", "-"));
+        assert!(!matches("[a\\-z]", "b"));
+    }
+
+    #[test]
+    fn test_escapes() {
+        assert!(matches("h\\*llo", "h*llo"));
+        assert!(!matches("h\\*llo", "hello"));
+        assert!(matches("h\\?llo", "h?llo"));
+        assert!(!matches("h\\?llo", "hallo"));
+        assert!(matches("\\[a]", "[a]"));
+        // a trailing backslash matches itself
+        assert!(matches("a\\", "a\\"));
+    }
+
+    #[test]
+    fn test_binary() {
+        assert!(glob_match(b"\xff*", b"\xff\x00\r\n"));
+        assert!(glob_match(b"?", b"\x00"));
+    }
+
+    #[test]
+    fn test_many_stars_do_not_blow_up() {
+        let pattern = "a*".repeat(30) + "b";
+        let string = "a".repeat(100);
+        assert!(!matches(&pattern, &string));
+    }
+}
@@ -1,7 +1,9 @@
 mod backend;
 pub mod cmd;
+mod glob;
 pub mod network;
 mod resp;
+mod slot;
 
 pub use backend::*;
 pub use resp::*;
@@ -1,12 +1,13 @@
 use anyhow::Result;
 use futures::SinkExt;
+use std::collections::VecDeque;
 use tokio::net::TcpStream;
 use tokio_stream::StreamExt;
 use tokio_util::codec::{Decoder, Encoder, Framed};
-use tracing::info;
+use tracing::{info, warn};
 
 use crate::{
-    cmd::{Command, CommandExecutor},
+    cmd::{CommandRequest, Session},
     Backend, RespDecode, RespEncode, RespError, RespFrame,
 };
 
@@ -21,50 +22,114 @@ struct RedisRequest {
 
 #[derive(Debug)]
 struct RedisResponse {
+    /// The confirmations of subscription changes, sent before the reply.
+    pushes: Vec<RespFrame>,
     frame: RespFrame,
 }
 
 pub async fn stream_handler(stream: TcpStream, backend: Backend) -> Result<()> {
+    let mut session = Session::new();
+    let ret = serve_connection(stream, &backend, &mut session).await;
+    // the subscriptions of the connection end with it
+    session.unsubscribe_all(&backend);
+    ret
+}
+
+/// Serves the requests of a connection until it is closed, and forwards to it the messages
+/// published to the channels it is subscribed to.
+async fn serve_connection(
+    stream: TcpStream,
+    backend: &Backend,
+    session: &mut Session,
+) -> Result<()> {
     // how to get a frame from the stream?
     let mut framed = Framed::new(stream, RespFrameCodec);
+    // requests received while a blocking command was waiting for its reply
+    let mut pending = VecDeque::new();
     loop {
-        match framed.next().await {
-            Some(Ok(frame)) => {
-                info!("Received request: {:?}", frame);
-                // handle the frame
-                let request = RedisRequest {
-                    frame,
-                    backend: backend.clone(),
-                };
-                let response = request_handler(request).await?;
-                info!("Received response: {:?}", response);
-                // send the response to the stream
-                framed.send(response.frame).await?;
+        let frame = match pending.pop_front() {
+            Some(frame) => frame,
+            // messages are only sent between replies, never in the middle of one
+            None => tokio::select! {
+                frame = framed.next() => match frame {
+                    Some(Ok(frame)) => frame,
+                    Some(Err(e)) => return Err(e),
+                    None => return Ok(()),
+                },
+                Some(message) = session.next_message() => {
+                    framed.send(session.encode_reply(message)).await?;
+                    continue;
+                }
+            },
+        };
+        info!("Received request: {:?}", frame);
+        // handle the frame
+        let request = RedisRequest {
+            frame,
+            backend: backend.clone(),
+        };
+        let response = request_handler(request, session);
+        tokio::pin!(response);
+        // keep reading the stream while the response is pending, so that a client
+        // disconnecting while blocked is noticed and its request dropped
+        let response = loop {
+            tokio::select! {
+                biased;
+                response = &mut response => break response,
+                frame = framed.next() => match frame {
+                    Some(Ok(frame)) => pending.push_back(frame),
+                    Some(Err(e)) => return Err(e),
+                    None => return Ok(()),
+                },
             }
-            Some(Err(e)) => return Err(e),
-
-            None => return Ok(()),
+        };
+        info!("Received response: {:?}", response);
+        // send the response to the stream
+        for push in response.pushes {
+            framed.feed(push).await?;
         }
+        framed.send(response.frame).await?;
     }
 }
 
 /// Handles a single Redis request by executing the command and returning the response.
 ///
+/// A request that can't be turned into a command, e.g. an unknown option or a wrong number
+/// of arguments, is answered with an error reply. The connection stays open: only protocol
+/// errors raised by the codec close it.
+///
 /// # Parameters
 ///
 /// * `request`: A `RedisRequest` struct containing the incoming request frame and the backend to execute the command.
+/// * `session`: The state of the connection the request was received on.
 ///
 /// # Returns
 ///
-/// * `Result<RedisResponse, anyhow::Error>`: On success, returns a `RedisResponse` containing the response frame.
-///   On error, returns an `anyhow::Error` containing the error details.
-async fn request_handler(request: RedisRequest) -> Result<RedisResponse, anyhow::Error> {
+/// * `RedisResponse`: The response frame, either the command's reply or an error reply,
+///   along with the confirmations of subscription changes, encoded for the protocol of the
+///   connection.
+async fn request_handler(request: RedisRequest, session: &mut Session) -> RedisResponse {
     let (frame, backend) = (request.frame, request.backend);
-    // let cmd: Command = frame.try_into()?;
-    let cmd = Command::try_from(frame)?;
-    info!("Executing command: {:?}", cmd);
-    let frame = cmd.execute(&backend);
-    Ok(RedisResponse { frame })
+    let frame = match CommandRequest::try_from(frame) {
+        Ok(request) => {
+            info!("Executing command: {:?}", request);
+            request.run(&backend, session).await
+        }
+        Err(e) => {
+            warn!("Invalid command: {}", e);
+            // a transaction with a command that can't be queued is discarded by EXEC
+            session.flag_transaction();
+            e.into()
+        }
+    };
+    RedisResponse {
+        pushes: session
+            .take_pushes()
+            .into_iter()
+            .map(|push| session.encode_reply(push))
+            .collect(),
+        frame: session.encode_reply(frame),
+    }
 }
 
 impl Encoder<RespFrame> for RespFrameCodec {
@@ -101,6 +166,10 @@ impl Decoder for RespFrameCodec {
     ///
     /// * `Result<Option<RespFrame>>`: On success, returns `Ok(Some(frame))`. If the input is incomplete, returns `Ok(None)`. On error, returns a `Self::Error`.
     fn decode(&mut self, src: &mut bytes::BytesMut) -> Result<Option<RespFrame>> {
+        // the codec is asked for another frame once the previous one is consumed
+        if src.is_empty() {
+            return Ok(None);
+        }
         match RespFrame::decode(src) {
             Ok(frame) => Ok(Some(frame)),
             Err(RespError::NotComplete) => Ok(None),
@@ -108,3 +177,203 @@ impl Decoder for RespFrameCodec {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BulkString, RespArray};
+    use std::time::Duration;
+    use tokio::net::TcpListener;
+
+    fn command(args: &[&str]) -> RespFrame {
+        RespArray::new(
+            args.iter()
+                .map(|arg| BulkString::new(*arg).into())
+                .collect::<Vec<RespFrame>>(),
+        )
+        .into()
+    }
+
+    /// Serves `backend` on a local port and returns the address to connect to.
+    async fn serve(backend: &Backend) -> Result<std::net::SocketAddr> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let backend = backend.clone();
+        tokio::spawn(async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                tokio::spawn(stream_handler(stream, backend.clone()));
+            }
+        });
+        Ok(addr)
+    }
+
+    #[tokio::test]
+    async fn test_disconnect_unblocks_client() -> Result<()> {
+        let backend = Backend::new();
+        let addr = serve(&backend).await?;
+
+        let mut client = Framed::new(TcpStream::connect(addr).await?, RespFrameCodec);
+        client.send(command(&["BLPOP", "list", "0"])).await?;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        drop(client);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        // the value is not handed over to the disconnected client
+        let mut client = Framed::new(TcpStream::connect(addr).await?, RespFrameCodec);
+        client.send(command(&["RPUSH", "list", "a"])).await?;
+        assert_eq!(
+            client.next().await.transpose()?,
+            Some(RespFrame::Integer(1))
+        );
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(backend.llen(b"list")?, 1);
+
+        // requests sent while blocked are answered once the blocking command is
+        client.send(command(&["BLPOP", "empty", "0.05"])).await?;
+        client.send(command(&["LLEN", "list"])).await?;
+        assert_eq!(
+            client.next().await.transpose()?,
+            Some(RespFrame::NullArray(crate::RespNullArray))
+        );
+        assert_eq!(
+            client.next().await.transpose()?,
+            Some(RespFrame::Integer(1))
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_replies_follow_negotiated_protocol() -> Result<()> {
+        let backend = Backend::new();
+        backend.sadd(&"set".into(), vec!["a".into()])?;
+        let addr = serve(&backend).await?;
+        let mut client = Framed::new(TcpStream::connect(addr).await?, RespFrameCodec);
+
+        client.send(command(&["SMEMBERS", "set"])).await?;
+        assert_eq!(
+            client.next().await.transpose()?,
+            Some(RespArray::new([BulkString::new("a").into()]).into())
+        );
+
+        client.send(command(&["HELLO", "3"])).await?;
+        assert!(matches!(
+            client.next().await.transpose()?,
+            Some(RespFrame::Map(_))
+        ));
+        client.send(command(&["SMEMBERS", "set"])).await?;
+        assert_eq!(
+            client.next().await.transpose()?,
+            Some(crate::RespSet::new([BulkString::new("a").into()]).into())
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_messages_are_pushed_to_subscribers() -> Result<()> {
+        let backend = Backend::new();
+        let addr = serve(&backend).await?;
+        let mut publisher = Framed::new(TcpStream::connect(addr).await?, RespFrameCodec);
+        let mut resp2 = Framed::new(TcpStream::connect(addr).await?, RespFrameCodec);
+        let mut resp3 = Framed::new(TcpStream::connect(addr).await?, RespFrameCodec);
+
+        resp2.send(command(&["SUBSCRIBE", "a", "b"])).await?;
+        for (channel, count) in [("a", 1), ("b", 2)] {
+            assert_eq!(
+                resp2.next().await.transpose()?,
+                Some(
+                    RespArray::new([
+                        BulkString::new("subscribe").into(),
+                        BulkString::new(channel).into(),
+                        RespFrame::Integer(count),
+                    ])
+                    .into()
+                )
+            );
+        }
+        resp3.send(command(&["HELLO", "3"])).await?;
+        resp3.next().await.transpose()?;
+        resp3.send(command(&["PSUBSCRIBE", "a*"])).await?;
+        assert!(matches!(
+            resp3.next().await.transpose()?,
+            Some(RespFrame::Push(_))
+        ));
+
+        publisher.send(command(&["PUBLISH", "a", "hi"])).await?;
+        assert_eq!(
+            publisher.next().await.transpose()?,
+            Some(RespFrame::Integer(2))
+        );
+        assert_eq!(
+            resp2.next().await.transpose()?,
+            Some(command(&["message", "a", "hi"]))
+        );
+        assert_eq!(
+            resp3.next().await.transpose()?,
+            Some(
+                crate::RespPush::new([
+                    BulkString::new("pmessage").into(),
+                    BulkString::new("a*").into(),
+                    BulkString::new("a").into(),
+                    BulkString::new("hi").into(),
+                ])
+                .into()
+            )
+        );
+
+        // a RESP3 subscriber can still run regular commands
+        resp3.send(command(&["LLEN", "list"])).await?;
+        assert_eq!(resp3.next().await.transpose()?, Some(RespFrame::Integer(0)));
+
+        // closed connections drop their subscriptions
+        drop(resp2);
+        drop(resp3);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(backend.pubsub_channels(None).is_empty());
+        assert_eq!(backend.pubsub_numpat(), 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_transactions() -> Result<()> {
+        let backend = Backend::new();
+        let addr = serve(&backend).await?;
+        let mut client = Framed::new(TcpStream::connect(addr).await?, RespFrameCodec);
+        let mut other = Framed::new(TcpStream::connect(addr).await?, RespFrameCodec);
+        let queued = Some(crate::SimpleString::new("QUEUED").into());
+
+        client.send(command(&["MULTI"])).await?;
+        client.next().await.transpose()?;
+        client.send(command(&["INCR", "a"])).await?;
+        assert_eq!(client.next().await.transpose()?, queued);
+        client.send(command(&["INCR"])).await?;
+        assert!(matches!(
+            client.next().await.transpose()?,
+            Some(RespFrame::Error(_))
+        ));
+        client.send(command(&["EXEC"])).await?;
+        assert_eq!(
+            client.next().await.transpose()?,
+            Some(
+                crate::SimpleError::new(
+                    "EXECABORT Transaction discarded because of previous errors."
+                )
+                .into()
+            )
+        );
+
+        // the commands of other clients don't wait for EXEC
+        client.send(command(&["MULTI"])).await?;
+        client.next().await.transpose()?;
+        client.send(command(&["INCR", "a"])).await?;
+        assert_eq!(client.next().await.transpose()?, queued);
+        other.send(command(&["INCR", "a"])).await?;
+        assert_eq!(other.next().await.transpose()?, Some(RespFrame::Integer(1)));
+        client.send(command(&["EXEC"])).await?;
+        assert_eq!(
+            client.next().await.transpose()?,
+            Some(RespArray::new([RespFrame::Integer(2)]).into())
+        );
+        Ok(())
+    }
+}
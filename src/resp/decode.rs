@@ -2,9 +2,9 @@ use crate::{
     BulkString, RespArray, RespDecode, RespError, RespFrame, RespNull, RespNullArray,
     RespNullBulkString, SimpleError, SimpleString,
 };
-use bytes::{Buf, BytesMut};
+use bytes::{Buf, Bytes, BytesMut};
 
-use super::{RespMap, RespSet};
+use super::{RespMap, RespPush, RespSet};
 
 const CRLF: &[u8] = b"\r\n";
 const CRLF_LEN: usize = CRLF.len();
@@ -68,6 +68,10 @@ impl RespDecode for RespFrame {
                 let frame = RespSet::decode(buf)?;
                 Ok(frame.into())
             }
+            Some(b'>') => {
+                let frame = RespPush::decode(buf)?;
+                Ok(frame.into())
+            }
             _ => Err(RespError::InvalidFrameType(format!(
                 "expect_length: unknown frame type: {:?}",
                 buf
@@ -80,6 +84,7 @@ impl RespDecode for RespFrame {
         match iter.peek() {
             Some(b'*') => RespArray::expect_length(buf),
             Some(b'~') => RespSet::expect_length(buf),
+            Some(b'>') => RespPush::expect_length(buf),
             Some(b'%') => RespMap::expect_length(buf),
             Some(b'$') => BulkString::expect_length(buf),
             Some(b':') => i64::expect_length(buf),
@@ -277,9 +282,18 @@ impl RespDecode for RespMap {
         buf.advance(end + CRLF_LEN);
         let mut frames = RespMap::new();
         for _ in 0..len {
-            let key = SimpleString::decode(buf)?;
+            let key = match RespFrame::decode(buf)? {
+                RespFrame::SimpleString(s) => Bytes::from(s.0),
+                RespFrame::BulkString(s) => Bytes::from(s.0),
+                frame => {
+                    return Err(RespError::InvalidFrameType(format!(
+                        "expect: SimpleString or BulkString map key, got: {:?}",
+                        frame
+                    )))
+                }
+            };
             let value = RespFrame::decode(buf)?;
-            frames.insert(key.0, value);
+            frames.insert(key, value);
         }
 
         Ok(frames)
@@ -314,6 +328,29 @@ impl RespDecode for RespSet {
     }
 }
 
+// ><number-of-elements>\r\n<element-1>...<element-n>
+impl RespDecode for RespPush {
+    const PREFIX: &'static str = ">";
+    fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
+        let (end, len) = parse_length(buf, Self::PREFIX)?;
+        let total_length = calc_total_length(buf, end, len, Self::PREFIX)?;
+        if buf.len() < total_length {
+            return Err(RespError::NotComplete);
+        }
+        buf.advance(end + CRLF_LEN);
+        let mut frames = Vec::new();
+        for _ in 0..len {
+            frames.push(RespFrame::decode(buf)?);
+        }
+        Ok(RespPush::new(frames))
+    }
+
+    fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
+        let (end, len) = parse_length(buf, Self::PREFIX)?;
+        calc_total_length(buf, end, len, Self::PREFIX)
+    }
+}
+
 fn extract_fixed_data(
     buf: &mut BytesMut,
     expect: &str,
@@ -402,8 +439,8 @@ fn calc_total_length(buf: &[u8], end: usize, len: usize, prefix: &str) -> Result
     let mut total = end + CRLF_LEN;
     let mut data = &buf[total..];
     match prefix {
-        "*" | "~" => {
-            // find nth CRLF in the buffer, for array and set, we need to find 1 CRLF for each element
+        "*" | "~" | ">" => {
+            // find nth CRLF in the buffer, for array, set and push, we need to find 1 CRLF for each element
             for _ in 0..len {
                 let element_len = RespFrame::expect_length(data)?;
                 data = &data[element_len..];
@@ -414,7 +451,7 @@ fn calc_total_length(buf: &[u8], end: usize, len: usize, prefix: &str) -> Result
         "%" => {
             // find nth CRLF in the buffer, for map, we need to find 2 CRLF for each key-value pair
             for _ in 0..len {
-                let key_len = SimpleString::expect_length(data)?;
+                let key_len = RespFrame::expect_length(data)?;
                 data = &data[key_len..];
                 total += key_len;
 
@@ -589,11 +626,14 @@ mod tests {
         buf.extend_from_slice(b"%2\r\n+hello\r\n$5\r\nworld\r\n+foo\r\n$3\r\nbar\r\n");
         let frame = RespMap::decode(&mut buf)?;
         let mut map = RespMap::new();
-        map.insert(
-            "hello".to_string(),
-            BulkString::new(b"world".to_vec()).into(),
-        );
-        map.insert("foo".to_string(), BulkString::new(b"bar".to_vec()).into());
+        map.insert("hello".into(), BulkString::new(b"world".to_vec()).into());
+        map.insert("foo".into(), BulkString::new(b"bar".to_vec()).into());
+        assert_eq!(frame, map);
+
+        buf.extend_from_slice(b"%1\r\n$4\r\na\r\nb\r\n:1\r\n");
+        let frame = RespMap::decode(&mut buf)?;
+        let mut map = RespMap::new();
+        map.insert(Bytes::from_static(b"a\r\nb"), 1.into());
         assert_eq!(frame, map);
         Ok(())
     }
@@ -613,6 +653,21 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_push_decode() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b">2\r\n$7\r\nmessage\r\n:+1\r\n");
+        let frame = RespFrame::decode(&mut buf)?;
+        assert_eq!(
+            frame,
+            RespPush::new(vec![BulkString::new(b"message".to_vec()).into(), 1.into()]).into()
+        );
+
+        buf.extend_from_slice(b">2\r\n$7\r\nmessage\r\n");
+        assert_eq!(RespFrame::decode(&mut buf), Err(RespError::NotComplete));
+        Ok(())
+    }
+
     #[test]
     fn test_calc_array_length() -> Result<()> {
         let buf = b"*2\r\n$3\r\nset\r\n$5\r\nhello\r\n";
@@ -648,8 +703,8 @@ mod tests {
         buf.extend_from_slice(b"%2\r\n+\r\n$5\r\nworld\r\n+foo\r\n$3\r\nbar\r\n");
         let frame = RespMap::decode(&mut buf)?;
         let mut map = RespMap::new();
-        map.insert("".to_string(), BulkString::new(b"world".to_vec()).into());
-        map.insert("foo".to_string(), BulkString::new(b"bar".to_vec()).into());
+        map.insert("".into(), BulkString::new(b"world".to_vec()).into());
+        map.insert("foo".into(), BulkString::new(b"bar".to_vec()).into());
         assert_eq!(frame, map);
         Ok(())
     }
@@ -1,6 +1,6 @@
 use super::{
-    BulkString, RespArray, RespEncode, RespMap, RespNull, RespNullArray, RespNullBulkString,
-    RespSet, SimpleError, SimpleString,
+    BulkString, RespArray, RespEncode, RespFrame, RespMap, RespNull, RespNullArray,
+    RespNullBulkString, RespPush, RespSet, SimpleError, SimpleString,
 };
 
 const BUF_CAP: usize = 4096;
@@ -84,7 +84,10 @@ impl RespEncode for bool {
 impl RespEncode for f64 {
     fn encode(self) -> Vec<u8> {
         let mut buf = Vec::with_capacity(32);
-        let ret = if self.abs() > 1e+8 || self.abs() < 1e-8 {
+        let ret = if self.is_infinite() {
+            // the protocol spells infinities without a sign for the positive one
+            format!(",{}inf\r\n", if self < 0.0 { "-" } else { "" })
+        } else if self.abs() > 1e+8 || self.abs() < 1e-8 {
             format!(",{:+e}\r\n", self)
         } else {
             let sign = if self < 0.0 { "" } else { "+" };
@@ -96,13 +99,19 @@ impl RespEncode for f64 {
 }
 
 // Maps: %<number-of-entries>\r\n<key-1><value-1>...<key-n><value-n>
-// we only support string key which encode to SimpleString
+// keys are encoded as SimpleString, unless they are binary or contain CR/LF, which
+// need a BulkString
 impl RespEncode for RespMap {
     fn encode(self) -> Vec<u8> {
         let mut buf = Vec::with_capacity(BUF_CAP);
         buf.extend_from_slice(&format!("%{}\r\n", self.len()).into_bytes());
         for (k, v) in self.0 {
-            buf.extend_from_slice(&SimpleString::new(k).encode());
+            match std::str::from_utf8(&k) {
+                Ok(s) if !s.contains(['\r', '\n']) => {
+                    buf.extend_from_slice(&SimpleString::new(s).encode())
+                }
+                _ => buf.extend_from_slice(&BulkString::new(k.to_vec()).encode()),
+            }
             buf.extend_from_slice(&v.encode());
         }
         buf
@@ -121,9 +130,68 @@ impl RespEncode for RespSet {
     }
 }
 
+// Pushes: ><number-of-elements>\r\n<element-1>...<element-n>
+impl RespEncode for RespPush {
+    fn encode(self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(BUF_CAP);
+        buf.extend_from_slice(&format!(">{}\r\n", self.len()).into_bytes());
+        for v in self.0 {
+            buf.extend_from_slice(&v.encode());
+        }
+        buf
+    }
+}
+
+impl RespFrame {
+    /// Converts a frame into the closest frame RESP2 clients understand.
+    ///
+    /// RESP3 only types are replaced the way Redis replies to RESP2 clients: maps become
+    /// arrays of alternating keys and values, sets and pushes become arrays, nulls become
+    /// null bulk strings, booleans become the integers `1` and `0`, and doubles become bulk
+    /// strings.
+    pub fn into_resp2(self) -> RespFrame {
+        match self {
+            RespFrame::Array(array) => RespArray::new(
+                array
+                    .0
+                    .into_iter()
+                    .map(RespFrame::into_resp2)
+                    .collect::<Vec<_>>(),
+            )
+            .into(),
+            RespFrame::Map(map) => RespArray::new(
+                map.0
+                    .into_iter()
+                    .flat_map(|(key, value)| [BulkString::new(key).into(), value.into_resp2()])
+                    .collect::<Vec<_>>(),
+            )
+            .into(),
+            RespFrame::Set(set) => RespArray::new(
+                set.0
+                    .into_iter()
+                    .map(RespFrame::into_resp2)
+                    .collect::<Vec<_>>(),
+            )
+            .into(),
+            RespFrame::Push(push) => RespArray::new(
+                push.0
+                    .into_iter()
+                    .map(RespFrame::into_resp2)
+                    .collect::<Vec<_>>(),
+            )
+            .into(),
+            RespFrame::Null(_) => RespNullBulkString.into(),
+            RespFrame::Boolean(value) => RespFrame::Integer(value as i64),
+            RespFrame::Double(value) => BulkString::new(value.to_string()).into(),
+            frame => frame,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::RespFrame;
+    use bytes::Bytes;
 
     use super::*;
 
@@ -226,16 +294,19 @@ mod tests {
 
         let frame: RespFrame = (-1.23456e-8).into();
         assert_eq!(frame.encode(), b",-0.0000000123456\r\n");
+
+        let frame: RespFrame = f64::INFINITY.into();
+        assert_eq!(frame.encode(), b",inf\r\n");
+
+        let frame: RespFrame = f64::NEG_INFINITY.into();
+        assert_eq!(frame.encode(), b",-inf\r\n");
     }
 
     #[test]
     fn test_map_encode() {
         let mut map = RespMap::new();
-        map.insert(
-            "hello".to_string(),
-            BulkString::new("world".to_string()).into(),
-        );
-        map.insert("foo".to_string(), (-123456.789).into());
+        map.insert("hello".into(), BulkString::new("world".to_string()).into());
+        map.insert("foo".into(), (-123456.789).into());
         let frame: RespFrame = map.into();
         assert_eq!(
             frame.encode(),
@@ -243,6 +314,18 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_map_encode_binary_key() {
+        let mut map = RespMap::new();
+        map.insert(Bytes::from_static(b"a\r\nb"), 1.into());
+        map.insert(Bytes::from_static(b"\xff"), 2.into());
+        let frame: RespFrame = map.into();
+        assert_eq!(
+            frame.encode(),
+            b"%2\r\n$4\r\na\r\nb\r\n:+1\r\n$1\r\n\xff\r\n:+2\r\n"
+        );
+    }
+
     #[test]
     fn test_set_encode() {
         let frame: RespFrame = RespSet::new([
@@ -255,4 +338,37 @@ mod tests {
             b"~2\r\n*2\r\n:+1234\r\n#t\r\n$5\r\nworld\r\n"
         );
     }
+
+    #[test]
+    fn test_push_encode() {
+        let frame: RespFrame =
+            RespPush::new([BulkString::new("message").into(), 1234.into()]).into();
+        assert_eq!(frame.encode(), b">2\r\n$7\r\nmessage\r\n:+1234\r\n");
+        assert_eq!(
+            RespFrame::from(RespPush::new([RespNull.into()])).into_resp2(),
+            RespArray::new([RespNullBulkString.into()]).into()
+        );
+    }
+
+    #[test]
+    fn test_into_resp2() {
+        let mut map = RespMap::new();
+        map.insert("key".into(), RespSet::new([1.5.into(), true.into()]).into());
+        map.insert("null".into(), RespNull.into());
+        let frame: RespFrame = RespArray::new([map.into(), RespNullArray.into()]).into();
+        assert_eq!(
+            frame.into_resp2(),
+            RespArray::new([
+                RespArray::new([
+                    BulkString::new("key").into(),
+                    RespArray::new([BulkString::new("1.5").into(), 1.into()]).into(),
+                    BulkString::new("null").into(),
+                    RespNullBulkString.into(),
+                ])
+                .into(),
+                RespNullArray.into(),
+            ])
+            .into()
+        );
+    }
 }
@@ -1,7 +1,7 @@
 mod decode;
 mod encode;
 
-use bytes::BytesMut;
+use bytes::{Bytes, BytesMut};
 use enum_dispatch::enum_dispatch;
 use std::collections::BTreeMap;
 use std::ops::{Deref, DerefMut};
@@ -50,6 +50,7 @@ pub enum RespFrame {
     Double(f64),
     Map(RespMap),
     Set(RespSet),
+    Push(RespPush),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd)]
@@ -67,9 +68,11 @@ pub struct RespNullArray;
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd)]
 pub struct RespNullBulkString;
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
-pub struct RespMap(pub(crate) BTreeMap<String, RespFrame>);
+pub struct RespMap(pub(crate) BTreeMap<Bytes, RespFrame>);
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub struct RespSet(pub(crate) Vec<RespFrame>);
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct RespPush(pub(crate) Vec<RespFrame>);
 
 impl Deref for SimpleString {
     type Target = String;
@@ -104,7 +107,7 @@ impl Deref for RespArray {
 }
 
 impl Deref for RespMap {
-    type Target = BTreeMap<String, RespFrame>;
+    type Target = BTreeMap<Bytes, RespFrame>;
 
     fn deref(&self) -> &Self::Target {
         &self.0
@@ -131,6 +134,14 @@ impl DerefMut for RespSet {
     }
 }
 
+impl Deref for RespPush {
+    type Target = Vec<RespFrame>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
 impl SimpleString {
     pub fn new(s: impl Into<String>) -> Self {
         SimpleString(s.into())
@@ -167,6 +178,12 @@ impl RespSet {
     }
 }
 
+impl RespPush {
+    pub fn new(s: impl Into<Vec<RespFrame>>) -> Self {
+        RespPush(s.into())
+    }
+}
+
 impl Default for RespMap {
     fn default() -> Self {
         RespMap::new()
@@ -0,0 +1,56 @@
+//! Redis Cluster hash slots, as used to route shard channels.
+
+/// The number of hash slots keys and shard channels are distributed over.
+const SLOT_COUNT: u16 = 16384;
+
+/// Returns the hash slot of `key`: the CRC16 of the key modulo 16384.
+///
+/// If the key contains a hash tag, i.e. a non-empty substring between the first `{` and the
+/// next `}`, only the tag is hashed, so that related keys can be forced into the same slot.
+pub(crate) fn key_slot(key: &[u8]) -> u16 {
+    let hashed = key
+        .iter()
+        .position(|&b| b == b'{')
+        .and_then(|start| {
+            let tag = &key[start + 1..];
+            tag.iter().position(|&b| b == b'}').map(|end| &tag[..end])
+        })
+        .filter(|tag| !tag.is_empty())
+        .unwrap_or(key);
+    crc16(hashed) % SLOT_COUNT
+}
+
+/// The CRC16 variant used by Redis Cluster: XMODEM, with polynomial 0x1021 and no initial
+/// value, reflection or final xor.
+fn crc16(bytes: &[u8]) -> u16 {
+    bytes.iter().fold(0, |crc, &b| {
+        (0..8).fold(crc ^ ((b as u16) << 8), |crc, _| match crc & 0x8000 {
+            0 => crc << 1,
+            _ => (crc << 1) ^ 0x1021,
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crc16() {
+        assert_eq!(crc16(b"123456789"), 0x31c3);
+        assert_eq!(crc16(b""), 0);
+    }
+
+    #[test]
+    fn test_key_slot() {
+        assert_eq!(key_slot(b"foo"), 12182);
+        assert_eq!(key_slot(b"bar"), 5061);
+        assert_eq!(key_slot(b"{user1000}.following"), key_slot(b"user1000"));
+        assert_eq!(key_slot(b"{user1000}.followers"), key_slot(b"user1000"));
+        // only the first tag counts, and an empty one is ignored
+        assert_eq!(key_slot(b"foo{bar}{zap}"), key_slot(b"bar"));
+        assert_eq!(key_slot(b"foo{}{bar}"), crc16(b"foo{}{bar}") % SLOT_COUNT);
+        assert_eq!(key_slot(b"foo{{bar}}zap"), key_slot(b"{bar"));
+        assert_eq!(key_slot(b"foo{bar"), crc16(b"foo{bar") % SLOT_COUNT);
+    }
+}